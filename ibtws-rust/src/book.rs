@@ -0,0 +1,212 @@
+//! Order book maintenance for market depth streams.
+//!
+//! `UpdateMktDepth` and `UpdateMktDepthL2` events describe the book as a
+//! sequence of row operations: insert, update or delete the row at
+//! `position` on one side. `OrderBook` replays those operations so the
+//! current book can be read at any time.
+//!
+//! There is no C++ equivalent -- `EWrapper::updateMktDepth` leaves book
+//! maintenance to the application.
+
+use rust_decimal::Decimal;
+
+use crate::wrapper::IBEvent;
+
+// ============================================================================
+// BookLevel
+// ============================================================================
+
+/// One row of an order book side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookLevel {
+    pub price: f64,
+    pub size: Decimal,
+    /// Market maker (L2) or exchange (smart depth) quoting this row. Empty
+    /// for `UpdateMktDepth`.
+    pub market_maker: String,
+}
+
+// ============================================================================
+// OrderBook
+// ============================================================================
+
+/// Depth operation codes (C++ `updateMktDepth` `operation`).
+const INSERT: i32 = 0;
+const UPDATE: i32 = 1;
+const DELETE: i32 = 2;
+
+/// Side codes (C++ `updateMktDepth` `side`).
+const ASK: i32 = 0;
+const BID: i32 = 1;
+
+/// Order book rebuilt from the depth events of one `req_mkt_depth` request.
+///
+/// Rows are kept in the order TWS maintains them: bids best (highest)
+/// first, asks best (lowest) first.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let mut book = OrderBook::new();
+/// while let Some(event) = sub.recv().await {
+///     if book.apply(&event) {
+///         println!("spread {:?}", book.spread());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrderBook {
+    bids: Vec<BookLevel>,
+    asks: Vec<BookLevel>,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a depth event. Returns `false` for other events and for
+    /// unknown sides or operations, which leave the book unchanged.
+    ///
+    /// Positions past the end of a side are clamped: an insert or update
+    /// appends, a delete is ignored.
+    pub fn apply(&mut self, event: &IBEvent) -> bool {
+        let (position, market_maker, operation, side, price, size) = match event {
+            IBEvent::UpdateMktDepth { position, operation, side, price, size, .. } => {
+                (*position, "", *operation, *side, *price, *size)
+            }
+            IBEvent::UpdateMktDepthL2 {
+                position, market_maker, operation, side, price, size, ..
+            } => (*position, market_maker.as_str(), *operation, *side, *price, *size),
+            _ => return false,
+        };
+        let levels = match side {
+            BID => &mut self.bids,
+            ASK => &mut self.asks,
+            _ => return false,
+        };
+        let position = usize::try_from(position).unwrap_or(0).min(levels.len());
+        let level = || BookLevel { price, size, market_maker: market_maker.to_string() };
+        match operation {
+            INSERT => levels.insert(position, level()),
+            UPDATE if position < levels.len() => levels[position] = level(),
+            UPDATE => levels.push(level()),
+            DELETE if position < levels.len() => {
+                levels.remove(position);
+            }
+            DELETE => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// Bid rows, best first.
+    pub fn bids(&self) -> &[BookLevel] {
+        &self.bids
+    }
+
+    /// Ask rows, best first.
+    pub fn asks(&self) -> &[BookLevel] {
+        &self.asks
+    }
+
+    pub fn best_bid(&self) -> Option<&BookLevel> {
+        self.bids.first()
+    }
+
+    pub fn best_ask(&self) -> Option<&BookLevel> {
+        self.asks.first()
+    }
+
+    /// Best ask minus best bid, once both sides have a row.
+    pub fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+
+    /// Drop every row, e.g. after the depth request was re-sent.
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn depth(position: i32, operation: i32, side: i32, price: f64, size: i64) -> IBEvent {
+        IBEvent::UpdateMktDepth {
+            req_id: 1,
+            position,
+            operation,
+            side,
+            price,
+            size: Decimal::from(size),
+        }
+    }
+
+    fn prices(levels: &[BookLevel]) -> Vec<f64> {
+        levels.iter().map(|l| l.price).collect()
+    }
+
+    #[test]
+    fn insert_update_delete_sequence() {
+        let mut book = OrderBook::new();
+        assert_eq!(book.spread(), None);
+
+        assert!(book.apply(&depth(0, INSERT, BID, 100.0, 5)));
+        assert!(book.apply(&depth(0, INSERT, ASK, 100.5, 3)));
+        // A better bid pushes the old best down a row.
+        assert!(book.apply(&depth(0, INSERT, BID, 100.25, 2)));
+        assert!(book.apply(&depth(1, INSERT, ASK, 100.75, 8)));
+        assert_eq!(prices(book.bids()), [100.25, 100.0]);
+        assert_eq!(prices(book.asks()), [100.5, 100.75]);
+        assert_eq!(book.spread(), Some(0.25));
+
+        assert!(book.apply(&depth(1, UPDATE, BID, 100.0, 9)));
+        assert_eq!(book.bids()[1].size, Decimal::from(9));
+
+        assert!(book.apply(&depth(0, DELETE, ASK, 100.5, 3)));
+        assert_eq!(book.best_ask().unwrap().price, 100.75);
+        assert_eq!(book.spread(), Some(0.5));
+
+        // Out-of-range rows: update appends, delete is a no-op.
+        assert!(book.apply(&depth(5, UPDATE, ASK, 101.0, 1)));
+        assert!(book.apply(&depth(9, DELETE, BID, 0.0, 0)));
+        assert_eq!(prices(book.asks()), [100.75, 101.0]);
+        assert_eq!(book.bids().len(), 2);
+
+        // Unknown operation or side, and unrelated events, are ignored.
+        assert!(!book.apply(&depth(0, 7, BID, 1.0, 1)));
+        assert!(!book.apply(&depth(0, INSERT, 4, 1.0, 1)));
+        assert!(!book.apply(&IBEvent::ConnectionClosed));
+        assert_eq!(book.bids().len(), 2);
+    }
+
+    #[test]
+    fn l2_rows_keep_market_maker() {
+        let l2 = |position, operation, market_maker: &str, price| IBEvent::UpdateMktDepthL2 {
+            req_id: 1,
+            position,
+            market_maker: market_maker.to_string(),
+            operation,
+            side: BID,
+            price,
+            size: Decimal::from(100),
+            is_smart_depth: true,
+        };
+        let mut book = OrderBook::new();
+        book.apply(&l2(0, INSERT, "NSDQ", 50.0));
+        book.apply(&l2(1, INSERT, "ARCA", 49.99));
+        book.apply(&l2(0, UPDATE, "BATS", 50.01));
+
+        let makers: Vec<_> = book.bids().iter().map(|l| l.market_maker.as_str()).collect();
+        assert_eq!(makers, ["BATS", "ARCA"]);
+        assert_eq!(book.best_bid().unwrap().price, 50.01);
+        assert_eq!(book.best_ask(), None);
+    }
+}
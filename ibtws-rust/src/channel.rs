@@ -0,0 +1,335 @@
+//! Event channel between the reader task and the application.
+//!
+//! By default events are delivered through an unbounded tokio mpsc channel.
+//! A slow consumer then lets decoded events pile up without limit, e.g.
+//! during a historical data flood. [`MessageReader::spawn_bounded`] and
+//! [`IBClient::connect_bounded`] instead deliver through a fixed-capacity
+//! queue whose [`OverflowPolicy`] decides what happens when it is full.
+//!
+//! There is no C++ equivalent -- `EReader` queues messages without limit.
+//!
+//! [`MessageReader::spawn_bounded`]: crate::reader::MessageReader::spawn_bounded
+//! [`IBClient::connect_bounded`]: crate::client::IBClient::connect_bounded
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use tokio::sync::{mpsc, Notify};
+
+use crate::wrapper::IBEvent;
+
+// ============================================================================
+// OverflowPolicy
+// ============================================================================
+
+/// What a bounded event channel does with a new event when it is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Discard the oldest queued event to make room for the new one.
+    DropOldest,
+    /// Discard the new event.
+    #[default]
+    DropNewest,
+    /// Stop reading from the socket until the consumer makes room.
+    Block,
+}
+
+// ============================================================================
+// Bounded queue
+// ============================================================================
+
+pub(crate) struct Shared {
+    queue: Mutex<VecDeque<IBEvent>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+    /// Signalled when an event is queued or the last sender goes away.
+    readable: Notify,
+    /// Signalled when an event is taken or the receiver goes away.
+    writable: Notify,
+}
+
+impl Shared {
+    fn queue(&self) -> MutexGuard<'_, VecDeque<IBEvent>> {
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn record_drop(&self) {
+        let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::warn!(
+            capacity = self.capacity,
+            policy = ?self.policy,
+            dropped,
+            "event channel full, dropping event"
+        );
+    }
+}
+
+/// Receiving half of a bounded event channel.
+pub struct BoundedReceiver {
+    shared: Arc<Shared>,
+}
+
+impl BoundedReceiver {
+    /// Receive the next event.
+    ///
+    /// Returns `None` once the reader has stopped and every queued event
+    /// has been received.
+    pub async fn recv(&mut self) -> Option<IBEvent> {
+        loop {
+            if let Some(event) = self.shared.queue().pop_front() {
+                self.shared.writable.notify_one();
+                return Some(event);
+            }
+            if self.shared.senders.load(Ordering::Acquire) == 0 {
+                // A sender may have queued one last event before leaving.
+                return self.shared.queue().pop_front();
+            }
+            self.shared.readable.notified().await;
+        }
+    }
+
+    /// Number of events discarded because the channel was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for BoundedReceiver {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::Release);
+        self.shared.writable.notify_one();
+    }
+}
+
+/// Sending half of a bounded event channel.
+pub(crate) struct BoundedSender {
+    shared: Arc<Shared>,
+}
+
+impl BoundedSender {
+    /// Queue `event` according to the overflow policy. Returns `false` if
+    /// the receiver has been dropped.
+    async fn send(&self, event: IBEvent) -> bool {
+        let shared = &self.shared;
+        loop {
+            if !shared.receiver_alive.load(Ordering::Acquire) {
+                return false;
+            }
+            {
+                let mut queue = shared.queue();
+                if queue.len() < shared.capacity {
+                    queue.push_back(event);
+                    drop(queue);
+                    shared.readable.notify_one();
+                    return true;
+                }
+                match shared.policy {
+                    OverflowPolicy::DropNewest => {
+                        drop(queue);
+                        shared.record_drop();
+                        return true;
+                    }
+                    OverflowPolicy::DropOldest => {
+                        queue.pop_front();
+                        queue.push_back(event);
+                        drop(queue);
+                        shared.record_drop();
+                        shared.readable.notify_one();
+                        return true;
+                    }
+                    OverflowPolicy::Block => {}
+                }
+            }
+            shared.writable.notified().await;
+        }
+    }
+}
+
+impl Clone for BoundedSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for BoundedSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.readable.notify_one();
+        }
+    }
+}
+
+/// Create a bounded event channel holding at most `capacity` events.
+///
+/// `dropped` is incremented for every event discarded by the policy.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub(crate) fn bounded(
+    capacity: usize,
+    policy: OverflowPolicy,
+    dropped: Arc<AtomicU64>,
+) -> (BoundedSender, BoundedReceiver) {
+    assert!(capacity > 0, "event channel capacity must be non-zero");
+    let shared = Arc::new(Shared {
+        queue: Mutex::new(VecDeque::with_capacity(capacity)),
+        capacity,
+        policy,
+        dropped,
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+        readable: Notify::new(),
+        writable: Notify::new(),
+    });
+    (BoundedSender { shared: shared.clone() }, BoundedReceiver { shared })
+}
+
+// ============================================================================
+// EventSink
+// ============================================================================
+
+/// Where the reader task (or reconnect supervisor) delivers events.
+#[derive(Clone)]
+pub(crate) enum EventSink {
+    Unbounded(mpsc::UnboundedSender<IBEvent>),
+    Bounded(BoundedSender),
+}
+
+impl EventSink {
+    /// Deliver `event`. Returns `false` if the receiver has been dropped.
+    pub(crate) async fn send(&self, event: IBEvent) -> bool {
+        match self {
+            Self::Unbounded(tx) => tx.send(event).is_ok(),
+            Self::Bounded(tx) => tx.send(event).await,
+        }
+    }
+
+    /// A handle that does not keep the channel open.
+    pub(crate) fn downgrade(&self) -> WeakEventSink {
+        match self {
+            Self::Unbounded(tx) => WeakEventSink::Unbounded(tx.downgrade()),
+            Self::Bounded(tx) => WeakEventSink::Bounded(Arc::downgrade(&tx.shared)),
+        }
+    }
+}
+
+impl From<mpsc::UnboundedSender<IBEvent>> for EventSink {
+    fn from(tx: mpsc::UnboundedSender<IBEvent>) -> Self {
+        Self::Unbounded(tx)
+    }
+}
+
+/// Weak counterpart of [`EventSink`], held by `IBClient` and the keepalive
+/// watchdog so they don't keep the channel open after the reader stops.
+#[derive(Clone)]
+pub(crate) enum WeakEventSink {
+    Unbounded(mpsc::WeakUnboundedSender<IBEvent>),
+    Bounded(Weak<Shared>),
+}
+
+impl WeakEventSink {
+    /// The sink, if any strong sender is still alive.
+    pub(crate) fn upgrade(&self) -> Option<EventSink> {
+        match self {
+            Self::Unbounded(tx) => tx.upgrade().map(EventSink::Unbounded),
+            Self::Bounded(shared) => {
+                let shared = shared.upgrade()?;
+                shared
+                    .senders
+                    .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                        (n > 0).then_some(n + 1)
+                    })
+                    .ok()?;
+                Some(EventSink::Bounded(BoundedSender { shared }))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(time: i64) -> IBEvent {
+        IBEvent::CurrentTime { time }
+    }
+
+    fn time_of(event: Option<IBEvent>) -> i64 {
+        match event {
+            Some(IBEvent::CurrentTime { time }) => time,
+            other => panic!("expected CurrentTime, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn drop_newest_keeps_first_events() {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (tx, mut rx) = bounded(2, OverflowPolicy::DropNewest, dropped.clone());
+        for t in 1..=4 {
+            assert!(tx.send(event(t)).await);
+        }
+        drop(tx);
+        assert_eq!(time_of(rx.recv().await), 1);
+        assert_eq!(time_of(rx.recv().await), 2);
+        assert!(rx.recv().await.is_none());
+        assert_eq!(rx.dropped_events(), 2);
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_latest_events() {
+        let (tx, mut rx) = bounded(2, OverflowPolicy::DropOldest, Arc::default());
+        for t in 1..=4 {
+            assert!(tx.send(event(t)).await);
+        }
+        drop(tx);
+        assert_eq!(time_of(rx.recv().await), 3);
+        assert_eq!(time_of(rx.recv().await), 4);
+        assert!(rx.recv().await.is_none());
+        assert_eq!(rx.dropped_events(), 2);
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_room() {
+        let (tx, mut rx) = bounded(1, OverflowPolicy::Block, Arc::default());
+        let producer = tokio::spawn(async move {
+            for t in 1..=3 {
+                assert!(tx.send(event(t)).await);
+            }
+        });
+        for t in 1..=3 {
+            assert_eq!(time_of(rx.recv().await), t);
+        }
+        producer.await.unwrap();
+        assert!(rx.recv().await.is_none());
+        assert_eq!(rx.dropped_events(), 0);
+    }
+
+    #[tokio::test]
+    async fn send_fails_once_receiver_dropped() {
+        let (tx, rx) = bounded(1, OverflowPolicy::Block, Arc::default());
+        assert!(tx.send(event(1)).await);
+        drop(rx);
+        assert!(!tx.send(event(2)).await);
+    }
+
+    #[test]
+    fn weak_sink_upgrades_only_while_a_sender_lives() {
+        let (tx, _rx) = bounded(1, OverflowPolicy::DropNewest, Arc::default());
+        let sink = EventSink::Bounded(tx);
+        let weak = sink.downgrade();
+        assert!(weak.upgrade().is_some());
+        drop(sink);
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -7,37 +7,85 @@
 //! Replaces C++ `EClient` + `EClientSocket` + `EReader` with a single async
 //! struct backed by tokio.
 
-use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
-
-use tokio::sync::mpsc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
+
+#[cfg(feature = "xml")]
+use tokio::sync::OnceCell;
+use tokio::sync::{mpsc, watch, Mutex};
 use tokio::task::JoinHandle;
 
-use crate::encoder::MessageEncoder;
+use crate::channel::{self, BoundedReceiver, EventSink, OverflowPolicy, WeakEventSink};
+use crate::encoder::{ContractFields, MessageEncoder};
 use crate::errors::{IBApiError, Result};
-use crate::models::common::TagValue;
-use crate::models::contract::Contract;
-use crate::models::execution::ExecutionFilter;
+use crate::keepalive::{Heartbeat, Watchdog};
+use crate::models::account::{AccountSnapshot, AccountSummary, PortfolioItem, PositionRecord};
+use crate::models::bar::{
+    format_utc_date_time, Bar, HistoricalSchedule, HistoricalTickSeries, TickKind,
+};
+use crate::models::common::{NewsArticleContent, SmartComponent, TagValue};
+use crate::models::contract::{Contract, ContractDescription, ContractDetails};
+use crate::models::enums::{BarSize, WhatToShow};
+use crate::models::execution::{ExecutionFilter, ExecutionWithCommission};
+use crate::models::market_data::{DepthMktDataDescription, MarketSnapshot, OptionComputation};
 use crate::models::order::{Order, OrderCancel, OrderCondition};
 use crate::models::scanner::ScannerSubscription;
-use crate::protocol::{outgoing, server_version};
-use crate::reader::MessageReader;
-use crate::transport::{Transport, TransportWriter};
-use crate::wrapper::IBEvent;
+use crate::pending::{PendingRequests, Stream};
+use crate::protocol::{outgoing, server_version, Feature};
+use crate::reader::{AccountsCache, DepthFlags, MessageReader, OrderIds};
+use crate::reconnect::{
+    OrderTracker, ReconnectPolicy, ReplayKey, ReplayLog, Supervisor, TrackedOrder,
+};
+#[cfg(feature = "xml")]
+use crate::scanner_params::ScannerParameters;
+use crate::state::{ConnectionState, ConnectionStatus};
+use crate::subscription::{
+    CalcSubscription, CancelFn, HistoricalSubscription, MarketDataSubscription, NewsSubscription,
+    OrderHandle, PnlSubscription, SharedWriter, Subscription,
+};
+use crate::transport::{Transport, TransportConfig};
+use crate::wrapper::{IBEvent, ScannerDataItem};
 
 // ============================================================================
 // IBClient
 // ============================================================================
 
+/// Default deadline for correlated requests such as
+/// [`IBClient::req_contract_details_async`].
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default time [`IBClient::executions`] waits for commission reports that
+/// have not arrived by `ExecDetailsEnd`.
+pub const DEFAULT_COMMISSION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Ticks requested per page by [`IBClient::historical_ticks_all`], the most
+/// TWS returns for one `REQ_HISTORICAL_TICKS`.
+pub const HISTORICAL_TICKS_PAGE_SIZE: i32 = 1000;
+
+/// First request ID used by the `*_async` collectors and `subscribe_*`
+/// handles.
+///
+/// TWS reports errors for requests and orders in the same `id` field, so
+/// the IDs the client allocates for itself start far above both the order
+/// ID sequence and the caller's [`IBClient::next_req_id`] sequence.
+const COLLECTOR_ID_BASE: i32 = 1 << 30;
+
 /// Async IB TWS API client.
 ///
 /// Manages a single connection to TWS/Gateway. After calling `connect()`,
 /// the client spawns a background reader task that decodes incoming messages
 /// and sends them as `IBEvent`s through an mpsc channel.
 ///
+/// Request methods take `&self`; writes to the socket are serialized
+/// internally, so several tasks can send on the same connection. Give each
+/// task its own [`handle`](Self::handle).
+///
 /// ## Usage
 ///
 /// ```rust,ignore
-/// let (mut client, mut rx) = IBClient::connect("127.0.0.1", 4002, 0, None).await?;
+/// let (client, mut rx) = IBClient::connect("127.0.0.1", 4002, 0, None).await?;
 ///
 /// // Send requests
 /// client.req_current_time().await?;
@@ -52,13 +100,68 @@ use crate::wrapper::IBEvent;
 /// }
 /// ```
 pub struct IBClient {
-    writer: TransportWriter,
-    server_version: i32,
+    writer: SharedWriter,
+    server_version: Arc<AtomicI32>,
     tws_time: String,
+    optional_capabilities: String,
     client_id: i32,
-    next_req_id: AtomicI32,
-    connected: AtomicBool,
-    reader_handle: Option<JoinHandle<()>>,
+    next_req_id: Arc<AtomicI32>,
+    /// Request IDs for the client's own collectors and subscriptions, see
+    /// [`COLLECTOR_ID_BASE`].
+    collector_ids: Arc<AtomicI32>,
+    order_ids: OrderIds,
+    state: ConnectionStatus,
+    closing: Arc<AtomicBool>,
+    tasks: Arc<Tasks>,
+    heartbeat: Heartbeat,
+    accounts: AccountsCache,
+    events: WeakEventSink,
+    dropped_events: Arc<AtomicU64>,
+    pending: PendingRequests,
+    replay: ReplayLog,
+    /// `is_smart_depth` of each open `req_mkt_depth`, for `cancel_mkt_depth`.
+    depth_flags: DepthFlags,
+    /// Present only with [`connect_with_reconnect`](Self::connect_with_reconnect).
+    orders: Option<OrderTracker>,
+    request_timeout: Duration,
+    commission_timeout: Duration,
+    validate_orders: bool,
+    /// Catalog fetched by `scanner_parameters`, kept for the connection.
+    #[cfg(feature = "xml")]
+    scanner_params: Arc<OnceCell<ScannerParameters>>,
+}
+
+/// Background tasks of a connection, shared by all handles of a client.
+/// Dropping the last handle stops them.
+struct Tasks {
+    reader: StdMutex<Option<JoinHandle<()>>>,
+    keepalive: StdMutex<Option<JoinHandle<()>>>,
+    closing: Arc<AtomicBool>,
+}
+
+impl Tasks {
+    fn take_reader(&self) -> Option<JoinHandle<()>> {
+        self.reader.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Install a new keepalive task, aborting the previous one (if any).
+    fn replace_keepalive(&self, handle: Option<JoinHandle<()>>) {
+        let mut keepalive = self.keepalive.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(old) = std::mem::replace(&mut *keepalive, handle) {
+            old.abort();
+        }
+    }
+}
+
+impl Drop for Tasks {
+    fn drop(&mut self) {
+        // Can't do async in Drop, but we can abort the tasks
+        self.closing.store(true, Ordering::Relaxed);
+        self.replace_keepalive(None);
+        if let Some(handle) = self.take_reader() {
+            handle.abort();
+        }
+    }
 }
 
 impl IBClient {
@@ -75,10 +178,189 @@ impl IBClient {
         client_id: i32,
         optional_capabilities: Option<&str>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<IBEvent>)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let client = Self::connect_inner(
+            host,
+            port,
+            client_id,
+            optional_capabilities,
+            &TransportConfig::default(),
+            None,
+            tx.into(),
+            Arc::default(),
+        )
+        .await?;
+        Ok((client, rx))
+    }
+
+    /// Connect like [`connect`](Self::connect), with the connect and
+    /// handshake timeouts (and wire tap, if any) from `config`.
+    ///
+    /// Fails with `IBApiError::Timeout` if TWS accepts the connection but
+    /// does not complete the handshake within `config.handshake_timeout`.
+    pub async fn connect_with_config(
+        host: &str,
+        port: u16,
+        client_id: i32,
+        optional_capabilities: Option<&str>,
+        config: &TransportConfig,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<IBEvent>)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let client = Self::connect_inner(
+            host,
+            port,
+            client_id,
+            optional_capabilities,
+            config,
+            None,
+            tx.into(),
+            Arc::default(),
+        )
+        .await?;
+        Ok((client, rx))
+    }
+
+    /// Connect like [`connect`](Self::connect), then wait until TWS has sent
+    /// `NextValidId` and `ManagedAccounts`.
+    ///
+    /// The order ID sequence starts at the server's next valid order ID
+    /// (see [`next_order_id`](Self::next_order_id)) and
+    /// [`managed_accounts`](Self::managed_accounts) is filled in. The
+    /// returned receiver is positioned after both events; anything that
+    /// arrived before them is consumed (and logged at debug level).
+    ///
+    /// Fails with `IBApiError::Timeout` if either event is missing after
+    /// `timeout`, or `IBApiError::Disconnected` if the connection closes
+    /// first.
+    pub async fn connect_ready(
+        host: &str,
+        port: u16,
+        client_id: i32,
+        optional_capabilities: Option<&str>,
+        timeout: Duration,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<IBEvent>)> {
+        let (client, mut rx) =
+            Self::connect(host, port, client_id, optional_capabilities).await?;
+        let (mut next_valid_id, mut accounts) = (None, false);
+        let ready = async {
+            while next_valid_id.is_none() || !accounts {
+                match rx.recv().await {
+                    Some(IBEvent::NextValidId { order_id }) => next_valid_id = Some(order_id),
+                    Some(IBEvent::ManagedAccounts { .. }) => accounts = true,
+                    Some(IBEvent::ConnectionClosed) | None => {
+                        return Err(IBApiError::Disconnected(
+                            "connection closed before NextValidId and ManagedAccounts".into(),
+                        ))
+                    }
+                    Some(event) => tracing::debug!(%event, "event before connection ready"),
+                }
+            }
+            Ok(())
+        };
+        let result = tokio::time::timeout(timeout, ready).await.unwrap_or_else(|_| {
+            Err(IBApiError::Timeout(format!(
+                "NextValidId and ManagedAccounts not received within {timeout:?}"
+            )))
+        });
+        if let Err(e) = result {
+            client.disconnect().await;
+            return Err(e);
+        }
+        Ok((client, rx))
+    }
+
+    /// Connect like [`connect`](Self::connect), but deliver events through a
+    /// bounded channel holding at most `capacity` events.
+    ///
+    /// When the application falls behind, `policy` decides whether events
+    /// are dropped or the reader waits for room (see [`OverflowPolicy`]).
+    /// Dropped events are counted in [`dropped_events`](Self::dropped_events).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub async fn connect_bounded(
+        host: &str,
+        port: u16,
+        client_id: i32,
+        optional_capabilities: Option<&str>,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> Result<(Self, BoundedReceiver)> {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (tx, rx) = channel::bounded(capacity, policy, dropped.clone());
+        let client = Self::connect_inner(
+            host,
+            port,
+            client_id,
+            optional_capabilities,
+            &TransportConfig::default(),
+            None,
+            EventSink::Bounded(tx),
+            dropped,
+        )
+        .await?;
+        Ok((client, rx))
+    }
+
+    /// Connect like [`connect`](Self::connect), but survive server restarts
+    /// and dropped connections.
+    ///
+    /// When the connection closes without `disconnect()` being called, the
+    /// client reconnects according to `policy`: it re-runs the handshake and
+    /// START_API (so TWS sends a fresh `NextValidId`), emits
+    /// `IBEvent::ConnectionReestablished`, and re-sends the active market
+    /// data, positions, and account update subscriptions. Until the
+    /// `OpenOrderEnd` that closes TWS's replay of open orders, `OrderStatus`
+    /// events that merely repeat an order's last known status, filled and
+    /// remaining quantities are dropped; see [`order_state`](Self::order_state).
+    ///
+    /// Correlated requests that are in flight during the outage fail with
+    /// `IBApiError::Timeout`. If every attempt fails, `ConnectionClosed` is
+    /// emitted and the client stays disconnected.
+    pub async fn connect_with_reconnect(
+        host: &str,
+        port: u16,
+        client_id: i32,
+        policy: ReconnectPolicy,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<IBEvent>)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let client = Self::connect_inner(
+            host,
+            port,
+            client_id,
+            None,
+            &TransportConfig::default(),
+            Some(policy),
+            tx.into(),
+            Arc::default(),
+        )
+        .await?;
+        Ok((client, rx))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn connect_inner(
+        host: &str,
+        port: u16,
+        client_id: i32,
+        optional_capabilities: Option<&str>,
+        transport_config: &TransportConfig,
+        policy: Option<ReconnectPolicy>,
+        tx: EventSink,
+        dropped_events: Arc<AtomicU64>,
+    ) -> Result<Self> {
         // 1. TCP connect + V100+ handshake
-        let mut transport = Transport::connect(host, port, None).await?;
+        let state = ConnectionStatus::new(ConnectionState::Connecting);
+        let mut transport = Transport::connect_observed(host, port, transport_config, || {
+            state.set(ConnectionState::Handshaking);
+        })
+        .await?;
         let server_version = transport.server_version();
         let tws_time = transport.tws_time().to_string();
+        let sent_capabilities = optional_capabilities.unwrap_or_default();
+        let negotiated_capabilities =
+            transport.optional_capabilities().unwrap_or(sent_capabilities).to_string();
 
         tracing::info!(
             server_version,
@@ -94,21 +376,83 @@ impl IBClient {
         // 3. Split transport into reader/writer halves
         let (transport_reader, transport_writer) = transport.into_split();
 
-        // 4. Spawn the reader task
-        let reader = MessageReader::new(transport_reader, server_version);
-        let (rx, reader_handle) = reader.spawn();
+        let writer = Arc::new(Mutex::new(transport_writer));
+        let server_version = Arc::new(AtomicI32::new(server_version));
+        state.set(ConnectionState::Connected);
+        let closing = Arc::new(AtomicBool::new(false));
+        let pending = PendingRequests::new();
+        let replay = ReplayLog::default();
+        let heartbeat = Heartbeat::default();
+        let accounts = AccountsCache::default();
+        let order_ids = OrderIds::default();
+        let depth_flags = DepthFlags::default();
+        let orders = policy.is_some().then(OrderTracker::default);
+        let events = tx.downgrade();
+
+        // 4. Spawn the reader task, routing correlated responses to `pending`
+        let reader_handle = match policy {
+            Some(policy) => Supervisor {
+                host: host.to_string(),
+                port,
+                client_id,
+                optional_capabilities: optional_capabilities.map(str::to_string),
+                transport_config: transport_config.clone(),
+                policy,
+                writer: writer.clone(),
+                pending: pending.clone(),
+                replay: replay.clone(),
+                heartbeat: heartbeat.clone(),
+                accounts: accounts.clone(),
+                order_ids: order_ids.clone(),
+                depth_flags: depth_flags.clone(),
+                orders: orders.clone().unwrap_or_default(),
+                server_version: server_version.clone(),
+                state: state.clone(),
+                closing: closing.clone(),
+            }
+            .spawn_with(transport_reader, tx),
+            None => MessageReader::new(transport_reader, server_version.load(Ordering::Relaxed))
+                .with_pending(pending.clone())
+                .with_heartbeat(heartbeat.clone())
+                .with_accounts(accounts.clone())
+                .with_order_ids(order_ids.clone())
+                .with_depth_flags(depth_flags.clone())
+                .with_state(state.clone())
+                .spawn_with(tx),
+        };
 
         let client = Self {
-            writer: transport_writer,
+            writer,
             server_version,
             tws_time,
+            optional_capabilities: negotiated_capabilities,
             client_id,
-            next_req_id: AtomicI32::new(1),
-            connected: AtomicBool::new(true),
-            reader_handle: Some(reader_handle),
+            next_req_id: Arc::new(AtomicI32::new(1)),
+            collector_ids: Arc::new(AtomicI32::new(COLLECTOR_ID_BASE)),
+            order_ids,
+            state,
+            tasks: Arc::new(Tasks {
+                reader: StdMutex::new(Some(reader_handle)),
+                keepalive: StdMutex::new(None),
+                closing: closing.clone(),
+            }),
+            closing,
+            heartbeat,
+            accounts,
+            events,
+            dropped_events,
+            pending,
+            replay,
+            depth_flags,
+            orders,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            commission_timeout: DEFAULT_COMMISSION_TIMEOUT,
+            validate_orders: false,
+            #[cfg(feature = "xml")]
+            scanner_params: Arc::default(),
         };
 
-        Ok((client, rx))
+        Ok(client)
     }
 
     // ========================================================================
@@ -117,31 +461,96 @@ impl IBClient {
 
     /// Negotiated server version.
     pub fn server_version(&self) -> i32 {
-        self.server_version
+        self.server_version.load(Ordering::Relaxed)
+    }
+
+    /// Whether the connected server supports `feature`, e.g.
+    /// `client.supports(Feature::TickByTick)`.
+    pub fn supports(&self, feature: Feature) -> bool {
+        self.server_version() >= feature.min_server_version()
     }
 
-    /// TWS connection time string from handshake.
+    /// TWS connection time string from the initial handshake.
     pub fn tws_time(&self) -> &str {
         &self.tws_time
     }
 
+    /// Optional capabilities in effect for this connection: those the
+    /// server acknowledged in the handshake, or, if it echoed none (as
+    /// standard TWS builds do), those passed to `connect`.
+    pub fn optional_capabilities(&self) -> &str {
+        &self.optional_capabilities
+    }
+
     /// Client ID used for this connection.
     pub fn client_id(&self) -> i32 {
         self.client_id
     }
 
-    /// Whether the client is currently connected.
+    /// Whether the client is currently connected, i.e. the
+    /// [`connection_state`](Self::connection_state) is `Connected`.
     pub fn is_connected(&self) -> bool {
-        self.connected.load(Ordering::Relaxed)
+        self.state.is_connected()
+    }
+
+    /// Current state of the connection.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.state.get()
+    }
+
+    /// Receiver that observes every [`ConnectionState`] transition:
+    /// `disconnect`, a server close, the keepalive timeout and, with
+    /// [`connect_with_reconnect`](Self::connect_with_reconnect), each
+    /// reconnect attempt.
+    ///
+    /// The receiver starts at the current state, marked as seen; call
+    /// `changed().await` to wait for the next transition.
+    pub fn watch_connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Accounts managed by this login, from the `ManagedAccounts` list TWS
+    /// sends right after connecting.
+    ///
+    /// Empty until that message has been read. The event is still delivered
+    /// to the event channel as well.
+    pub fn managed_accounts(&self) -> Vec<String> {
+        self.accounts.get()
+    }
+
+    /// Last known state of the order with permanent ID `perm_id`, from the
+    /// most recent `OrderStatus` for it.
+    ///
+    /// Orders are tracked only by clients created with
+    /// [`connect_with_reconnect`](Self::connect_with_reconnect), which use
+    /// this state to drop the statuses TWS replays after a reconnect.
+    /// Always `None` for other clients.
+    pub fn order_state(&self, perm_id: i64) -> Option<TrackedOrder> {
+        self.orders.as_ref()?.get(perm_id)
+    }
+
+    /// Number of events discarded because the bounded event channel was
+    /// full (see [`connect_bounded`](Self::connect_bounded)). Always 0 for
+    /// the unbounded channel.
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events.load(Ordering::Relaxed)
     }
 
     /// Get the next request ID (atomic increment).
     ///
     /// Request IDs are used to correlate requests with their responses.
+    /// The sequence starts at 1. The `*_async` helpers and `subscribe_*`
+    /// handles do not draw from it: they allocate IDs from 2^30 up, so IDs
+    /// taken here (or picked by hand below that) never collide with theirs.
     pub fn next_req_id(&self) -> i32 {
         self.next_req_id.fetch_add(1, Ordering::Relaxed)
     }
 
+    /// Request ID for a collector or subscription registered in `pending`.
+    fn next_collector_id(&self) -> i32 {
+        self.collector_ids.fetch_add(1, Ordering::Relaxed)
+    }
+
     /// Set the base for the next request ID.
     ///
     /// Typically called after receiving `NextValidId` to synchronize with
@@ -150,27 +559,165 @@ impl IBClient {
         self.next_req_id.store(id, Ordering::Relaxed);
     }
 
+    /// Get the next order ID (atomic increment).
+    ///
+    /// TWS keeps one order ID sequence per client ID on the server side:
+    /// each new order must use an ID above every ID used before on that
+    /// connection, or TWS rejects it (error 103, duplicate order id). The
+    /// sequence here is raised to every `NextValidId` the server sends
+    /// (on connect, after [`req_ids`](Self::req_ids) and after a
+    /// reconnect), so the IDs it hands out are monotonic and valid.
+    ///
+    /// It is independent of [`next_req_id`](Self::next_req_id); request IDs
+    /// allocated by the `*_async` helpers start at 2^30, so they never equal
+    /// an order ID. Before
+    /// the first `NextValidId` has been read it counts from 1, so call this
+    /// after [`connect_ready`](Self::connect_ready) or once `NextValidId`
+    /// has arrived.
+    pub fn next_order_id(&self) -> i64 {
+        self.order_ids.next()
+    }
+
+    /// Deadline applied to correlated requests (`*_async` methods).
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout
+    }
+
+    /// Change the deadline applied to correlated requests.
+    pub fn set_request_timeout(&mut self, timeout: Duration) {
+        self.request_timeout = timeout;
+    }
+
+    /// How long [`executions`](Self::executions) waits for late commission
+    /// reports.
+    pub fn commission_timeout(&self) -> Duration {
+        self.commission_timeout
+    }
+
+    /// Change how long [`executions`](Self::executions) waits for late
+    /// commission reports.
+    pub fn set_commission_timeout(&mut self, timeout: Duration) {
+        self.commission_timeout = timeout;
+    }
+
+    /// Whether [`place_order`](Self::place_order) checks orders with
+    /// [`Order::validate`] before sending them.
+    pub fn validate_orders(&self) -> bool {
+        self.validate_orders
+    }
+
+    /// Make [`place_order`](Self::place_order) check each order with
+    /// [`Order::validate`] and fail with `IBApiError::Encoding` listing the
+    /// problems instead of sending it. Off by default.
+    pub fn set_validate_orders(&mut self, validate: bool) {
+        self.validate_orders = validate;
+    }
+
+    /// Map of in-flight correlated requests shared with the reader task.
+    pub fn pending_requests(&self) -> &PendingRequests {
+        &self.pending
+    }
+
+    /// Another handle to this connection, e.g. to move into a spawned task.
+    ///
+    /// Handles share the socket, the request and order ID sequences, the
+    /// pending request map and the cached scanner parameters; messages sent
+    /// from different handles are never interleaved on the wire. The
+    /// timeouts and the order validation setting are copied and can then be
+    /// changed per handle. The connection stays open until the last handle
+    /// is dropped or [`disconnect`](Self::disconnect) is called on any of
+    /// them.
+    pub fn handle(&self) -> Self {
+        Self {
+            writer: self.writer.clone(),
+            server_version: self.server_version.clone(),
+            tws_time: self.tws_time.clone(),
+            optional_capabilities: self.optional_capabilities.clone(),
+            client_id: self.client_id,
+            next_req_id: self.next_req_id.clone(),
+            collector_ids: self.collector_ids.clone(),
+            order_ids: self.order_ids.clone(),
+            state: self.state.clone(),
+            closing: self.closing.clone(),
+            tasks: self.tasks.clone(),
+            heartbeat: self.heartbeat.clone(),
+            accounts: self.accounts.clone(),
+            events: self.events.clone(),
+            dropped_events: self.dropped_events.clone(),
+            pending: self.pending.clone(),
+            replay: self.replay.clone(),
+            depth_flags: self.depth_flags.clone(),
+            orders: self.orders.clone(),
+            request_timeout: self.request_timeout,
+            commission_timeout: self.commission_timeout,
+            validate_orders: self.validate_orders,
+            #[cfg(feature = "xml")]
+            scanner_params: self.scanner_params.clone(),
+        }
+    }
+
     // ========================================================================
     // Connection Management
     // ========================================================================
 
+    /// Start a keepalive watchdog that detects half-open connections.
+    ///
+    /// Every `interval` the watchdog sends REQ_CURRENT_TIME. If no
+    /// `IBEvent::CurrentTime` arrives within `2 * interval`, the client
+    /// moves to `ConnectionState::Failed`, `IBEvent::ConnectionLost` is emitted, and the
+    /// watchdog stops. The probe answers are still delivered to the event
+    /// channel. Calling this again replaces the running watchdog.
+    ///
+    /// With [`connect_with_reconnect`](Self::connect_with_reconnect), the
+    /// watchdog pauses while the supervisor is reconnecting after a socket
+    /// close and resumes once the connection is re-established. It does not
+    /// trigger a reconnect itself: on `ConnectionLost`, call `disconnect()`
+    /// and connect again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn start_keepalive(&self, interval: Duration) {
+        assert!(!interval.is_zero(), "keepalive interval must be non-zero");
+        let watchdog = Watchdog {
+            interval,
+            heartbeat: self.heartbeat.clone(),
+            writer: self.writer.clone(),
+            server_version: self.server_version.clone(),
+            state: self.state.clone(),
+            closing: self.closing.clone(),
+            events: self.events.clone(),
+        };
+        self.tasks.replace_keepalive(Some(watchdog.spawn()));
+    }
+
     /// Disconnect from the server.
     ///
     /// Shuts down the write half of the TCP connection, which causes the
-    /// reader task to receive an EOF and exit cleanly.
-    pub async fn disconnect(&mut self) {
-        if !self.connected.swap(false, Ordering::Relaxed) {
+    /// reader task to receive an EOF and exit cleanly. Also stops any
+    /// automatic reconnection and the keepalive watchdog. This closes the
+    /// connection for every handle of the client.
+    pub async fn disconnect(&self) {
+        if self.closing.swap(true, Ordering::Relaxed) {
             return; // Already disconnected
         }
+        let was_connected =
+            self.state.set(ConnectionState::Disconnected) == ConnectionState::Connected;
 
         tracing::info!("IBClient disconnecting");
 
+        self.tasks.replace_keepalive(None);
+
         // Shut down writer — this triggers EOF on the server side,
         // and the reader task will exit when the server closes its end.
-        self.writer.shutdown().await;
+        self.writer.lock().await.shutdown().await;
 
-        // Wait for reader task to finish
-        if let Some(handle) = self.reader_handle.take() {
+        // Wait for reader task to finish. A connection the keepalive has
+        // declared lost may never deliver EOF, so don't wait on it.
+        if let Some(handle) = self.tasks.take_reader() {
+            if !was_connected {
+                handle.abort();
+            }
             let _ = handle.await;
         }
     }
@@ -183,46 +730,103 @@ impl IBClient {
     ///
     /// The data should already include the 4-byte length header
     /// (as produced by `MessageEncoder::finalize()`).
-    pub async fn send_raw(&mut self, data: &[u8]) -> Result<()> {
+    pub async fn send_raw(&self, data: &[u8]) -> Result<()> {
         if !self.is_connected() {
             return Err(IBApiError::Connection(
                 "not connected".into(),
             ));
         }
-        self.writer.send_message(data).await
+        self.writer.lock().await.send_message(data).await
     }
 
     /// Create a new `MessageEncoder` configured with the server version.
     pub fn encoder(&self) -> MessageEncoder {
-        MessageEncoder::new(self.server_version)
+        MessageEncoder::new(self.server_version())
     }
 
     // ========================================================================
     // Private Helpers
     // ========================================================================
 
-    fn check_server_version(&self, min_version: i32, feature: &str) -> Result<()> {
-        if self.server_version < min_version {
+    fn check_server_version(&self, feature: Feature, what: &str) -> Result<()> {
+        if !self.supports(feature) {
             return Err(IBApiError::Encoding(format!(
-                "{feature} requires server version >= {min_version} (current: {})",
-                self.server_version
+                "{what} requires server version >= {} (current: {})",
+                feature.min_server_version(),
+                self.server_version()
             )));
         }
         Ok(())
     }
 
-    async fn send_encoded(&mut self, enc: MessageEncoder) -> Result<()> {
+    async fn send_encoded(&self, enc: MessageEncoder) -> Result<()> {
         let bytes = enc.finalize()?;
         self.send_raw(&bytes).await
     }
 
+    /// Send a streaming request and remember it so a reconnect can replay it.
+    async fn send_recorded(&self, key: ReplayKey, enc: MessageEncoder) -> Result<()> {
+        let bytes = enc.finalize()?;
+        self.send_raw(&bytes).await?;
+        self.replay.record(key, &bytes);
+        Ok(())
+    }
+
+    /// Feed events routed to `req_id` into `on_event` until it yields a
+    /// result, the connection closes, or the request deadline passes.
+    ///
+    /// The registration is removed from the pending map on every exit path.
+    async fn await_response<T, F>(
+        &self,
+        req_id: i32,
+        mut rx: mpsc::UnboundedReceiver<IBEvent>,
+        what: &str,
+        on_event: F,
+    ) -> Result<T>
+    where
+        F: FnMut(IBEvent) -> Option<Result<T>>,
+    {
+        let what = format!("{what} (req_id {req_id})");
+        let result = self.await_events(&mut rx, &what, on_event).await;
+        self.pending.remove(req_id);
+        result
+    }
+
+    /// Feed events from `rx` into `on_event` until it yields a result, the
+    /// connection closes, or the request deadline passes. `what` names the
+    /// awaited response in errors.
+    async fn await_events<T, F>(
+        &self,
+        rx: &mut mpsc::UnboundedReceiver<IBEvent>,
+        what: &str,
+        mut on_event: F,
+    ) -> Result<T>
+    where
+        F: FnMut(IBEvent) -> Option<Result<T>>,
+    {
+        let collect = async {
+            while let Some(event) = rx.recv().await {
+                if let Some(result) = on_event(event) {
+                    return result;
+                }
+            }
+            Err(IBApiError::Disconnected(format!("connection closed while waiting for {what}")))
+        };
+        tokio::time::timeout(self.request_timeout, collect).await.unwrap_or_else(|_| {
+            Err(IBApiError::Timeout(format!(
+                "{what} did not complete within {:?}",
+                self.request_timeout
+            )))
+        })
+    }
+
     // ========================================================================
     // Utility Requests
     // ========================================================================
 
     /// Request the current server time.
     /// Response: `IBEvent::CurrentTime`.
-    pub async fn req_current_time(&mut self) -> Result<()> {
+    pub async fn req_current_time(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_CURRENT_TIME);
         enc.encode_field_i32(1); // version
@@ -231,8 +835,8 @@ impl IBClient {
 
     /// Request the current server time in milliseconds.
     /// Response: `IBEvent::CurrentTimeInMillis`.
-    pub async fn req_current_time_in_millis(&mut self) -> Result<()> {
-        self.check_server_version(server_version::CURRENT_TIME_IN_MILLIS, "req_current_time_in_millis")?;
+    pub async fn req_current_time_in_millis(&self) -> Result<()> {
+        self.check_server_version(Feature::CurrentTimeInMillis, "req_current_time_in_millis")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_CURRENT_TIME_IN_MILLIS);
         self.send_encoded(enc).await
@@ -240,7 +844,7 @@ impl IBClient {
 
     /// Request the next valid order ID.
     /// Response: `IBEvent::NextValidId`.
-    pub async fn req_ids(&mut self) -> Result<()> {
+    pub async fn req_ids(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_IDS);
         enc.encode_field_i32(1); // version
@@ -249,7 +853,7 @@ impl IBClient {
     }
 
     /// Set the server log level.
-    pub async fn set_server_log_level(&mut self, log_level: i32) -> Result<()> {
+    pub async fn set_server_log_level(&self, log_level: i32) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::SET_SERVER_LOGLEVEL);
         enc.encode_field_i32(1); // version
@@ -259,7 +863,7 @@ impl IBClient {
 
     /// Request managed accounts list.
     /// Response: `IBEvent::ManagedAccounts`.
-    pub async fn req_managed_accts(&mut self) -> Result<()> {
+    pub async fn req_managed_accts(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MANAGED_ACCTS);
         enc.encode_field_i32(1); // version
@@ -272,8 +876,18 @@ impl IBClient {
 
     /// Request real-time market data.
     /// Response: `IBEvent::TickPrice`, `TickSize`, `TickString`, etc.
+    ///
+    /// `generic_ticks` is a comma-separated list of codes; build it from
+    /// [`GenericTick`](crate::GenericTick) values with
+    /// [`to_generic_string`](crate::GenericTickList::to_generic_string).
+    ///
+    /// `snapshot` asks for one set of ticks closed by `TickSnapshotEnd`.
+    /// `regulatory_snapshot` (server version `REQ_SMART_COMPONENTS`, ignored
+    /// before) asks for a fee-based NBBO snapshot for US stocks without a
+    /// market data subscription; each one is billed. See
+    /// [`regulatory_snapshot`](Self::regulatory_snapshot).
     pub async fn req_mkt_data(
-        &mut self,
+        &self,
         ticker_id: i32,
         contract: &Contract,
         generic_ticks: &str,
@@ -281,29 +895,21 @@ impl IBClient {
         regulatory_snapshot: bool,
         mkt_data_options: &[TagValue],
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MKT_DATA);
         enc.encode_field_i32(11); // version
         enc.encode_field_i32(ticker_id);
 
-        // Contract fields
-        if sv >= server_version::REQ_MKT_DATA_CONID {
-            enc.encode_field_i64(contract.con_id);
-        }
-        enc.encode_field_str(&contract.symbol);
-        enc.encode_field_opt_display(contract.sec_type.as_ref());
-        enc.encode_field_str(&contract.last_trade_date_or_contract_month);
-        enc.encode_field_max_f64(contract.strike);
-        enc.encode_field_opt_display(contract.right.as_ref());
-        enc.encode_field_str(&contract.multiplier);
-        enc.encode_field_str(&contract.exchange);
-        enc.encode_field_str(&contract.primary_exchange);
-        enc.encode_field_str(&contract.currency);
-        enc.encode_field_str(&contract.local_symbol);
-        if sv >= server_version::TRADING_CLASS {
-            enc.encode_field_str(&contract.trading_class);
-        }
+        enc.encode_contract_for_request(
+            contract,
+            ContractFields {
+                con_id: sv >= server_version::REQ_MKT_DATA_CONID,
+                trading_class: sv >= server_version::TRADING_CLASS,
+                include_expired: false,
+                ..ContractFields::STANDARD
+            },
+        );
 
         // Combo legs for BAG
         if contract.sec_type.as_ref().map(|s| s.to_string()).as_deref() == Some("BAG") {
@@ -311,7 +917,7 @@ impl IBClient {
                 enc.encode_field_i32(legs.len() as i32);
                 for leg in legs {
                     enc.encode_field_i64(leg.con_id);
-                    enc.encode_field_i64(leg.ratio);
+                    enc.encode_field_decimal(&leg.wire_ratio(sv)?);
                     enc.encode_field_opt_display(leg.action.as_ref());
                     enc.encode_field_str(&leg.exchange);
                 }
@@ -342,28 +948,41 @@ impl IBClient {
         if sv >= server_version::LINKING {
             enc.encode_tag_value_list(mkt_data_options);
         }
-        self.send_encoded(enc).await
+        if snapshot || regulatory_snapshot {
+            self.send_encoded(enc).await
+        } else {
+            self.send_recorded(ReplayKey::MktData(ticker_id), enc).await
+        }
     }
 
     /// Cancel market data subscription.
-    pub async fn cancel_mkt_data(&mut self, ticker_id: i32) -> Result<()> {
+    pub async fn cancel_mkt_data(&self, ticker_id: i32) -> Result<()> {
+        self.replay.remove(ReplayKey::MktData(ticker_id));
+        let enc = self.encode_cancel_mkt_data(ticker_id);
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_mkt_data(&self, ticker_id: i32) -> MessageEncoder {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_MKT_DATA);
         enc.encode_field_i32(2); // version
         enc.encode_field_i32(ticker_id);
-        self.send_encoded(enc).await
+        enc
     }
 
     /// Request market depth (Level II data).
+    ///
+    /// The client remembers `is_smart_depth` for `ticker_id`, so that
+    /// [`cancel_mkt_depth`](Self::cancel_mkt_depth) sends the matching flag.
     pub async fn req_mkt_depth(
-        &mut self,
+        &self,
         ticker_id: i32,
         contract: &Contract,
         num_rows: i32,
         is_smart_depth: bool,
         mkt_depth_options: &[TagValue],
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MKT_DEPTH);
         enc.encode_field_i32(5); // version
@@ -394,24 +1013,40 @@ impl IBClient {
         if sv >= server_version::LINKING {
             enc.encode_tag_value_list(mkt_depth_options);
         }
-        self.send_encoded(enc).await
+        self.send_encoded(enc).await?;
+        self.depth_flags.insert(ticker_id, is_smart_depth);
+        Ok(())
     }
 
     /// Cancel market depth subscription.
-    pub async fn cancel_mkt_depth(&mut self, ticker_id: i32, is_smart_depth: bool) -> Result<()> {
+    ///
+    /// Unlike C++ `cancelMktDepth`, takes no `isSmartDepth`: TWS rejects a
+    /// cancel whose flag differs from the request's, so the flag recorded by
+    /// [`req_mkt_depth`](Self::req_mkt_depth) is sent. A ticker ID this
+    /// client did not request is cancelled as non-smart depth.
+    pub async fn cancel_mkt_depth(&self, ticker_id: i32) -> Result<()> {
+        let is_smart_depth = self.depth_flags.remove(ticker_id).unwrap_or_else(|| {
+            tracing::warn!(ticker_id, "cancel_mkt_depth for unknown ticker, sending non-smart");
+            false
+        });
+        let enc = self.encode_cancel_mkt_depth(ticker_id, is_smart_depth);
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_mkt_depth(&self, ticker_id: i32, is_smart_depth: bool) -> MessageEncoder {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_MKT_DEPTH);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(ticker_id);
-        if self.server_version >= server_version::SMART_DEPTH {
+        if self.server_version() >= server_version::SMART_DEPTH {
             enc.encode_field_bool(is_smart_depth);
         }
-        self.send_encoded(enc).await
+        enc
     }
 
     /// Set market data type (real-time, frozen, delayed, delayed-frozen).
-    pub async fn req_market_data_type(&mut self, market_data_type: i32) -> Result<()> {
-        self.check_server_version(server_version::REQ_MARKET_DATA_TYPE, "req_market_data_type")?;
+    pub async fn req_market_data_type(&self, market_data_type: i32) -> Result<()> {
+        self.check_server_version(Feature::MarketDataType, "req_market_data_type")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MARKET_DATA_TYPE);
         enc.encode_field_i32(1); // version
@@ -425,18 +1060,30 @@ impl IBClient {
 
     /// Place an order.
     /// Response: `IBEvent::OpenOrder`, `IBEvent::OrderStatus`.
+    ///
+    /// A negative `id` takes the next ID from
+    /// [`next_order_id`](Self::next_order_id). Returns the order ID used.
+    /// With [`set_validate_orders`](Self::set_validate_orders) on, the order
+    /// is checked with [`Order::validate`] first.
     #[allow(clippy::too_many_lines)]
     pub async fn place_order(
-        &mut self,
+        &self,
         id: i64,
         contract: &Contract,
         order: &Order,
-    ) -> Result<()> {
-        let sv = self.server_version;
+    ) -> Result<i64> {
+        if self.validate_orders {
+            if let Err(errors) = order.validate(contract) {
+                let problems: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                return Err(IBApiError::Encoding(format!("invalid order: {}", problems.join("; "))));
+            }
+        }
+        let id = if id < 0 { self.next_order_id() } else { id };
+        let sv = self.server_version();
 
         // Protobuf path for sv >= 203
         if sv >= server_version::PROTOBUF_PLACE_ORDER {
-            return self.place_order_protobuf(id, contract, order).await;
+            return self.place_order_protobuf(id, contract, order).await.map(|()| id);
         }
 
         let mut enc = self.encoder();
@@ -477,22 +1124,25 @@ impl IBClient {
         if sv >= server_version::FRACTIONAL_POSITIONS {
             enc.encode_field_max_decimal(order.total_quantity.as_ref());
         } else {
-            let qty = order.total_quantity
-                .map(|d| d.trunc().to_string().parse::<i64>().unwrap_or(0))
-                .unwrap_or(0);
-            enc.encode_field_i64(qty);
+            let qty = order.total_quantity.unwrap_or_default();
+            if !qty.fract().is_zero() {
+                return Err(IBApiError::Encoding(format!(
+                    "fractional quantity {qty} not supported by server version {sv}"
+                )));
+            }
+            enc.encode_field_i64(qty.trunc().to_string().parse::<i64>().unwrap_or(0));
         }
         enc.encode_field_opt_display(order.order_type.as_ref());
 
         if sv >= server_version::ORDER_COMBO_LEGS_PRICE {
-            enc.encode_field_max_f64(order.lmt_price);
+            enc.encode_field_max_price(order.lmt_price);
         } else {
-            enc.encode_field_f64(order.lmt_price.unwrap_or(0.0));
+            enc.encode_field_price(order.lmt_price.unwrap_or(0.0));
         }
         if sv >= server_version::TRAILING_PERCENT {
-            enc.encode_field_max_f64(order.aux_price);
+            enc.encode_field_max_price(order.aux_price);
         } else {
-            enc.encode_field_f64(order.aux_price.unwrap_or(0.0));
+            enc.encode_field_price(order.aux_price.unwrap_or(0.0));
         }
 
         // TIF, OCA, Account, etc.
@@ -518,7 +1168,7 @@ impl IBClient {
                 enc.encode_field_i32(legs.len() as i32);
                 for leg in legs {
                     enc.encode_field_i64(leg.con_id);
-                    enc.encode_field_i64(leg.ratio);
+                    enc.encode_field_decimal(&leg.wire_ratio(sv)?);
                     enc.encode_field_opt_display(leg.action.as_ref());
                     enc.encode_field_str(&leg.exchange);
                     enc.encode_field_i32(leg.open_close as i32);
@@ -537,7 +1187,7 @@ impl IBClient {
                 if let Some(ref ocl) = order.order_combo_legs {
                     enc.encode_field_i32(ocl.len() as i32);
                     for leg in ocl {
-                        enc.encode_field_max_f64(leg.price);
+                        enc.encode_field_max_price(leg.price);
                     }
                 } else {
                     enc.encode_field_i32(0);
@@ -594,8 +1244,8 @@ impl IBClient {
         enc.encode_field_max_f64(None); // nbboPriceCap
 
         enc.encode_field_i32(order.auction_strategy as i32);
-        enc.encode_field_max_f64(order.starting_price);
-        enc.encode_field_max_f64(order.stock_ref_price);
+        enc.encode_field_max_price(order.starting_price);
+        enc.encode_field_max_price(order.stock_ref_price);
         enc.encode_field_max_f64(order.delta);
         enc.encode_field_max_f64(order.stock_range_lower);
         enc.encode_field_max_f64(order.stock_range_upper);
@@ -606,7 +1256,7 @@ impl IBClient {
         enc.encode_field_max_f64(order.volatility);
         enc.encode_field_max_i32(order.volatility_type);
         enc.encode_field_str(&order.delta_neutral_order_type);
-        enc.encode_field_max_f64(order.delta_neutral_aux_price);
+        enc.encode_field_max_price(order.delta_neutral_aux_price);
 
         if sv >= server_version::DELTA_NEUTRAL_CONID
             && !order.delta_neutral_order_type.is_empty()
@@ -628,7 +1278,7 @@ impl IBClient {
         enc.encode_field_bool(order.continuous_update);
         enc.encode_field_max_i32(order.reference_price_type);
 
-        enc.encode_field_max_f64(order.trail_stop_price);
+        enc.encode_field_max_price(order.trail_stop_price);
         if sv >= server_version::TRAILING_PERCENT {
             enc.encode_field_max_f64(order.trailing_percent);
         }
@@ -747,6 +1397,13 @@ impl IBClient {
             enc.encode_field_i32(order.conditions.len() as i32);
             if !order.conditions.is_empty() {
                 for cond in &order.conditions {
+                    if let OrderCondition::Unknown { raw_type, fields, .. } = cond {
+                        if fields.is_empty() {
+                            return Err(IBApiError::Encoding(format!(
+                                "order condition type {raw_type} has no fields to send"
+                            )));
+                        }
+                    }
                     encode_condition(&mut enc, cond);
                 }
                 enc.encode_field_bool(order.conditions_ignore_rth);
@@ -755,10 +1412,10 @@ impl IBClient {
 
             // Adjusted order fields
             enc.encode_field_str(&order.adjusted_order_type);
-            enc.encode_field_max_f64(order.trigger_price);
+            enc.encode_field_max_price(order.trigger_price);
             enc.encode_field_max_f64(order.lmt_price_offset);
-            enc.encode_field_max_f64(order.adjusted_stop_price);
-            enc.encode_field_max_f64(order.adjusted_stop_limit_price);
+            enc.encode_field_max_price(order.adjusted_stop_price);
+            enc.encode_field_max_price(order.adjusted_stop_limit_price);
             enc.encode_field_max_f64(order.adjusted_trailing_amount);
             enc.encode_field_max_i32(order.adjustable_trailing_unit);
         }
@@ -862,12 +1519,13 @@ impl IBClient {
             enc.encode_field_bool(order.imbalance_only);
         }
 
-        self.send_encoded(enc).await
+        self.send_encoded(enc).await?;
+        Ok(id)
     }
 
     /// Cancel an order.
-    pub async fn cancel_order(&mut self, id: i64, order_cancel: &OrderCancel) -> Result<()> {
-        let sv = self.server_version;
+    pub async fn cancel_order(&self, id: i64, order_cancel: &OrderCancel) -> Result<()> {
+        let sv = self.server_version();
 
         if sv >= server_version::PROTOBUF_PLACE_ORDER {
             return self.cancel_order_protobuf(id, order_cancel).await;
@@ -899,7 +1557,7 @@ impl IBClient {
     }
 
     /// Request all open orders.
-    pub async fn req_open_orders(&mut self) -> Result<()> {
+    pub async fn req_open_orders(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_OPEN_ORDERS);
         enc.encode_field_i32(1); // version
@@ -907,7 +1565,7 @@ impl IBClient {
     }
 
     /// Request auto open orders.
-    pub async fn req_auto_open_orders(&mut self, auto_bind: bool) -> Result<()> {
+    pub async fn req_auto_open_orders(&self, auto_bind: bool) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_AUTO_OPEN_ORDERS);
         enc.encode_field_i32(1); // version
@@ -916,7 +1574,7 @@ impl IBClient {
     }
 
     /// Request all open orders from all clients.
-    pub async fn req_all_open_orders(&mut self) -> Result<()> {
+    pub async fn req_all_open_orders(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_ALL_OPEN_ORDERS);
         enc.encode_field_i32(1); // version
@@ -924,10 +1582,12 @@ impl IBClient {
     }
 
     /// Cancel all orders globally.
-    pub async fn req_global_cancel(&mut self, order_cancel: &OrderCancel) -> Result<()> {
-        self.check_server_version(server_version::REQ_GLOBAL_CANCEL, "req_global_cancel")?;
-        let sv = self.server_version;
+    pub async fn req_global_cancel(&self, order_cancel: &OrderCancel) -> Result<()> {
+        self.check_server_version(Feature::GlobalCancel, "req_global_cancel")?;
+        let sv = self.server_version();
 
+        // Same gate as C++ `EClient::reqGlobalCancel`: global cancel moved to
+        // protobuf together with place/cancel order, not with `PROTOBUF`.
         if sv >= server_version::PROTOBUF_PLACE_ORDER {
             return self.req_global_cancel_protobuf(order_cancel).await;
         }
@@ -943,8 +1603,8 @@ impl IBClient {
     }
 
     /// Request completed orders.
-    pub async fn req_completed_orders(&mut self, api_only: bool) -> Result<()> {
-        self.check_server_version(server_version::COMPLETED_ORDERS, "req_completed_orders")?;
+    pub async fn req_completed_orders(&self, api_only: bool) -> Result<()> {
+        self.check_server_version(Feature::CompletedOrders, "req_completed_orders")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_COMPLETED_ORDERS);
         enc.encode_field_bool(api_only);
@@ -952,8 +1612,8 @@ impl IBClient {
     }
 
     /// Request execution reports.
-    pub async fn req_executions(&mut self, req_id: i32, filter: &ExecutionFilter) -> Result<()> {
-        let sv = self.server_version;
+    pub async fn req_executions(&self, req_id: i32, filter: &ExecutionFilter) -> Result<()> {
+        let sv = self.server_version();
 
         if sv >= server_version::PROTOBUF {
             return self.req_executions_protobuf(req_id, filter).await;
@@ -989,23 +1649,28 @@ impl IBClient {
     // ========================================================================
 
     /// Subscribe/unsubscribe to account updates.
-    pub async fn req_account_updates(&mut self, subscribe: bool, acct_code: &str) -> Result<()> {
+    pub async fn req_account_updates(&self, subscribe: bool, acct_code: &str) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_ACCT_DATA);
         enc.encode_field_i32(2); // version
         enc.encode_field_bool(subscribe);
         enc.encode_field_str(acct_code);
-        self.send_encoded(enc).await
+        if subscribe {
+            self.send_recorded(ReplayKey::AccountUpdates, enc).await
+        } else {
+            self.replay.remove(ReplayKey::AccountUpdates);
+            self.send_encoded(enc).await
+        }
     }
 
     /// Request account summary.
     pub async fn req_account_summary(
-        &mut self,
+        &self,
         req_id: i32,
         group_name: &str,
         tags: &str,
     ) -> Result<()> {
-        self.check_server_version(server_version::ACCOUNT_SUMMARY, "req_account_summary")?;
+        self.check_server_version(Feature::AccountSummary, "req_account_summary")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_ACCOUNT_SUMMARY);
         enc.encode_field_i32(1); // version
@@ -1016,8 +1681,8 @@ impl IBClient {
     }
 
     /// Cancel account summary.
-    pub async fn cancel_account_summary(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::ACCOUNT_SUMMARY, "cancel_account_summary")?;
+    pub async fn cancel_account_summary(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::AccountSummary, "cancel_account_summary")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_ACCOUNT_SUMMARY);
         enc.encode_field_i32(1); // version
@@ -1027,13 +1692,13 @@ impl IBClient {
 
     /// Request account updates for multiple accounts/models.
     pub async fn req_account_updates_multi(
-        &mut self,
+        &self,
         req_id: i32,
         account: &str,
         model_code: &str,
         ledger_and_nlv: bool,
     ) -> Result<()> {
-        self.check_server_version(server_version::MODELS_SUPPORT, "req_account_updates_multi")?;
+        self.check_server_version(Feature::Models, "req_account_updates_multi")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_ACCOUNT_UPDATES_MULTI);
         enc.encode_field_i32(1); // version
@@ -1045,8 +1710,8 @@ impl IBClient {
     }
 
     /// Cancel account updates multi.
-    pub async fn cancel_account_updates_multi(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::MODELS_SUPPORT, "cancel_account_updates_multi")?;
+    pub async fn cancel_account_updates_multi(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::Models, "cancel_account_updates_multi")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_ACCOUNT_UPDATES_MULTI);
         enc.encode_field_i32(1); // version
@@ -1055,17 +1720,18 @@ impl IBClient {
     }
 
     /// Request positions for all accounts.
-    pub async fn req_positions(&mut self) -> Result<()> {
-        self.check_server_version(server_version::POSITIONS, "req_positions")?;
+    pub async fn req_positions(&self) -> Result<()> {
+        self.check_server_version(Feature::Positions, "req_positions")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_POSITIONS);
         enc.encode_field_i32(1); // version
-        self.send_encoded(enc).await
+        self.send_recorded(ReplayKey::Positions, enc).await
     }
 
     /// Cancel positions subscription.
-    pub async fn cancel_positions(&mut self) -> Result<()> {
-        self.check_server_version(server_version::POSITIONS, "cancel_positions")?;
+    pub async fn cancel_positions(&self) -> Result<()> {
+        self.check_server_version(Feature::Positions, "cancel_positions")?;
+        self.replay.remove(ReplayKey::Positions);
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_POSITIONS);
         enc.encode_field_i32(1); // version
@@ -1074,12 +1740,12 @@ impl IBClient {
 
     /// Request positions for specific account/model.
     pub async fn req_positions_multi(
-        &mut self,
+        &self,
         req_id: i32,
         account: &str,
         model_code: &str,
     ) -> Result<()> {
-        self.check_server_version(server_version::MODELS_SUPPORT, "req_positions_multi")?;
+        self.check_server_version(Feature::Models, "req_positions_multi")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_POSITIONS_MULTI);
         enc.encode_field_i32(1); // version
@@ -1090,13 +1756,18 @@ impl IBClient {
     }
 
     /// Cancel positions multi.
-    pub async fn cancel_positions_multi(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::MODELS_SUPPORT, "cancel_positions_multi")?;
+    pub async fn cancel_positions_multi(&self, req_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_positions_multi(req_id)?;
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_positions_multi(&self, req_id: i32) -> Result<MessageEncoder> {
+        self.check_server_version(Feature::Models, "cancel_positions_multi")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_POSITIONS_MULTI);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(req_id);
-        self.send_encoded(enc).await
+        Ok(enc)
     }
 
     // ========================================================================
@@ -1105,11 +1776,11 @@ impl IBClient {
 
     /// Request contract details.
     pub async fn req_contract_details(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_CONTRACT_DATA);
         enc.encode_field_i32(8); // version
@@ -1148,8 +1819,8 @@ impl IBClient {
     }
 
     /// Search for matching symbols.
-    pub async fn req_matching_symbols(&mut self, req_id: i32, pattern: &str) -> Result<()> {
-        self.check_server_version(server_version::REQ_MATCHING_SYMBOLS, "req_matching_symbols")?;
+    pub async fn req_matching_symbols(&self, req_id: i32, pattern: &str) -> Result<()> {
+        self.check_server_version(Feature::MatchingSymbols, "req_matching_symbols")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MATCHING_SYMBOLS);
         enc.encode_field_i32(req_id);
@@ -1158,8 +1829,8 @@ impl IBClient {
     }
 
     /// Request smart components for an exchange.
-    pub async fn req_smart_components(&mut self, req_id: i32, bbo_exchange: &str) -> Result<()> {
-        self.check_server_version(server_version::REQ_SMART_COMPONENTS, "req_smart_components")?;
+    pub async fn req_smart_components(&self, req_id: i32, bbo_exchange: &str) -> Result<()> {
+        self.check_server_version(Feature::SmartComponents, "req_smart_components")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_SMART_COMPONENTS);
         enc.encode_field_i32(req_id);
@@ -1169,14 +1840,14 @@ impl IBClient {
 
     /// Request security definition option parameters.
     pub async fn req_sec_def_opt_params(
-        &mut self,
+        &self,
         req_id: i32,
         underlying_symbol: &str,
         fut_fop_exchange: &str,
         underlying_sec_type: &str,
         underlying_con_id: i64,
     ) -> Result<()> {
-        self.check_server_version(server_version::SEC_DEF_OPT_PARAMS_REQ, "req_sec_def_opt_params")?;
+        self.check_server_version(Feature::SecDefOptParams, "req_sec_def_opt_params")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_SEC_DEF_OPT_PARAMS);
         enc.encode_field_i32(req_id);
@@ -1192,21 +1863,30 @@ impl IBClient {
     // ========================================================================
 
     /// Request historical data bars.
+    ///
+    /// `duration_str` takes a [`Duration`](crate::models::bar::Duration) or
+    /// a string such as `"1 D"`. `bar_size_setting` and `what_to_show` take
+    /// the typed [`BarSize`] and [`WhatToShow`] values, or any `&str` for
+    /// values without a variant.
+    ///
+    /// `format_date` selects the form of intraday `Bar::time` values: `1`
+    /// for `"20240225 09:30:00"` local date/times, `2` for epoch seconds.
+    /// `Bar::parsed_time` (`chrono` feature) reads either.
     #[allow(clippy::too_many_arguments)]
     pub async fn req_historical_data(
-        &mut self,
+        &self,
         ticker_id: i32,
         contract: &Contract,
         end_date_time: &str,
-        duration_str: &str,
-        bar_size_setting: &str,
-        what_to_show: &str,
+        duration_str: impl Into<String>,
+        bar_size_setting: impl Into<BarSize>,
+        what_to_show: impl Into<WhatToShow>,
         use_rth: bool,
         format_date: i32,
         keep_up_to_date: bool,
         chart_options: &[TagValue],
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_HISTORICAL_DATA);
         if sv < server_version::SYNT_REALTIME_BARS {
@@ -1214,28 +1894,20 @@ impl IBClient {
         }
         enc.encode_field_i32(ticker_id);
 
-        if sv >= server_version::TRADING_CLASS {
-            enc.encode_field_i64(contract.con_id);
-        }
-        enc.encode_field_str(&contract.symbol);
-        enc.encode_field_opt_display(contract.sec_type.as_ref());
-        enc.encode_field_str(&contract.last_trade_date_or_contract_month);
-        enc.encode_field_max_f64(contract.strike);
-        enc.encode_field_opt_display(contract.right.as_ref());
-        enc.encode_field_str(&contract.multiplier);
-        enc.encode_field_str(&contract.exchange);
-        enc.encode_field_str(&contract.primary_exchange);
-        enc.encode_field_str(&contract.currency);
-        enc.encode_field_str(&contract.local_symbol);
-        if sv >= server_version::TRADING_CLASS {
-            enc.encode_field_str(&contract.trading_class);
-        }
-        enc.encode_field_bool(contract.include_expired);
+        let has_trading_class = sv >= server_version::TRADING_CLASS;
+        enc.encode_contract_for_request(
+            contract,
+            ContractFields {
+                con_id: has_trading_class,
+                trading_class: has_trading_class,
+                ..ContractFields::STANDARD
+            },
+        );
         enc.encode_field_str(end_date_time);
-        enc.encode_field_str(bar_size_setting);
-        enc.encode_field_str(duration_str);
+        enc.encode_field_display(&bar_size_setting.into());
+        enc.encode_field_str(&duration_str.into());
         enc.encode_field_bool(use_rth);
-        enc.encode_field_str(what_to_show);
+        enc.encode_field_display(&what_to_show.into());
         enc.encode_field_i32(format_date);
 
         // Combo legs for BAG
@@ -1244,7 +1916,7 @@ impl IBClient {
                 enc.encode_field_i32(legs.len() as i32);
                 for leg in legs {
                     enc.encode_field_i64(leg.con_id);
-                    enc.encode_field_i64(leg.ratio);
+                    enc.encode_field_decimal(&leg.wire_ratio(sv)?);
                     enc.encode_field_opt_display(leg.action.as_ref());
                     enc.encode_field_str(&leg.exchange);
                 }
@@ -1263,24 +1935,29 @@ impl IBClient {
     }
 
     /// Cancel historical data.
-    pub async fn cancel_historical_data(&mut self, ticker_id: i32) -> Result<()> {
+    pub async fn cancel_historical_data(&self, ticker_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_historical_data(ticker_id);
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_historical_data(&self, ticker_id: i32) -> MessageEncoder {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_HISTORICAL_DATA);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(ticker_id);
-        self.send_encoded(enc).await
+        enc
     }
 
     /// Request earliest available data point.
     pub async fn req_head_timestamp(
-        &mut self,
+        &self,
         ticker_id: i32,
         contract: &Contract,
         what_to_show: &str,
         use_rth: bool,
         format_date: i32,
     ) -> Result<()> {
-        self.check_server_version(server_version::REQ_HEAD_TIMESTAMP, "req_head_timestamp")?;
+        self.check_server_version(Feature::HeadTimestamp, "req_head_timestamp")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_HEAD_TIMESTAMP);
         enc.encode_field_i32(ticker_id);
@@ -1292,8 +1969,8 @@ impl IBClient {
     }
 
     /// Cancel head timestamp request.
-    pub async fn cancel_head_timestamp(&mut self, ticker_id: i32) -> Result<()> {
-        self.check_server_version(server_version::CANCEL_HEADTIMESTAMP, "cancel_head_timestamp")?;
+    pub async fn cancel_head_timestamp(&self, ticker_id: i32) -> Result<()> {
+        self.check_server_version(Feature::CancelHeadTimestamp, "cancel_head_timestamp")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_HEAD_TIMESTAMP);
         enc.encode_field_i32(ticker_id);
@@ -1303,7 +1980,7 @@ impl IBClient {
     /// Request historical ticks.
     #[allow(clippy::too_many_arguments)]
     pub async fn req_historical_ticks(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
         start_date_time: &str,
@@ -1314,7 +1991,7 @@ impl IBClient {
         ignore_size: bool,
         misc_options: &[TagValue],
     ) -> Result<()> {
-        self.check_server_version(server_version::HISTORICAL_TICKS, "req_historical_ticks")?;
+        self.check_server_version(Feature::HistoricalTicks, "req_historical_ticks")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_HISTORICAL_TICKS);
         enc.encode_field_i32(req_id);
@@ -1331,13 +2008,13 @@ impl IBClient {
 
     /// Request histogram data.
     pub async fn req_histogram_data(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
         use_rth: bool,
         time_period: &str,
     ) -> Result<()> {
-        self.check_server_version(server_version::REQ_HISTOGRAM, "req_histogram_data")?;
+        self.check_server_version(Feature::Histogram, "req_histogram_data")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_HISTOGRAM_DATA);
         enc.encode_field_i32(req_id);
@@ -1348,8 +2025,8 @@ impl IBClient {
     }
 
     /// Cancel histogram data.
-    pub async fn cancel_histogram_data(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::REQ_HISTOGRAM, "cancel_histogram_data")?;
+    pub async fn cancel_histogram_data(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::Histogram, "cancel_histogram_data")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_HISTOGRAM_DATA);
         enc.encode_field_i32(req_id);
@@ -1359,7 +2036,7 @@ impl IBClient {
     /// Request historical news headlines.
     #[allow(clippy::too_many_arguments)]
     pub async fn req_historical_news(
-        &mut self,
+        &self,
         req_id: i32,
         con_id: i64,
         provider_codes: &str,
@@ -1368,7 +2045,7 @@ impl IBClient {
         total_results: i32,
         historical_news_options: &[TagValue],
     ) -> Result<()> {
-        self.check_server_version(server_version::REQ_HISTORICAL_NEWS, "req_historical_news")?;
+        self.check_server_version(Feature::HistoricalNews, "req_historical_news")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_HISTORICAL_NEWS);
         enc.encode_field_i32(req_id);
@@ -1387,7 +2064,7 @@ impl IBClient {
 
     /// Request real-time 5-second bars.
     pub async fn req_real_time_bars(
-        &mut self,
+        &self,
         ticker_id: i32,
         contract: &Contract,
         bar_size: i32,
@@ -1395,28 +2072,22 @@ impl IBClient {
         use_rth: bool,
         real_time_bars_options: &[TagValue],
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_REAL_TIME_BARS);
         enc.encode_field_i32(3); // version
         enc.encode_field_i32(ticker_id);
 
-        if sv >= server_version::TRADING_CLASS {
-            enc.encode_field_i64(contract.con_id);
-        }
-        enc.encode_field_str(&contract.symbol);
-        enc.encode_field_opt_display(contract.sec_type.as_ref());
-        enc.encode_field_str(&contract.last_trade_date_or_contract_month);
-        enc.encode_field_max_f64(contract.strike);
-        enc.encode_field_opt_display(contract.right.as_ref());
-        enc.encode_field_str(&contract.multiplier);
-        enc.encode_field_str(&contract.exchange);
-        enc.encode_field_str(&contract.primary_exchange);
-        enc.encode_field_str(&contract.currency);
-        enc.encode_field_str(&contract.local_symbol);
-        if sv >= server_version::TRADING_CLASS {
-            enc.encode_field_str(&contract.trading_class);
-        }
+        let has_trading_class = sv >= server_version::TRADING_CLASS;
+        enc.encode_contract_for_request(
+            contract,
+            ContractFields {
+                con_id: has_trading_class,
+                trading_class: has_trading_class,
+                include_expired: false,
+                ..ContractFields::STANDARD
+            },
+        );
         enc.encode_field_i32(bar_size);
         enc.encode_field_str(what_to_show);
         enc.encode_field_bool(use_rth);
@@ -1427,24 +2098,29 @@ impl IBClient {
     }
 
     /// Cancel real-time bars.
-    pub async fn cancel_real_time_bars(&mut self, ticker_id: i32) -> Result<()> {
+    pub async fn cancel_real_time_bars(&self, ticker_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_real_time_bars(ticker_id);
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_real_time_bars(&self, ticker_id: i32) -> MessageEncoder {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_REAL_TIME_BARS);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(ticker_id);
-        self.send_encoded(enc).await
+        enc
     }
 
     /// Request tick-by-tick data.
     pub async fn req_tick_by_tick_data(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
         tick_type: &str,
         number_of_ticks: i32,
         ignore_size: bool,
     ) -> Result<()> {
-        self.check_server_version(server_version::TICK_BY_TICK, "req_tick_by_tick_data")?;
+        self.check_server_version(Feature::TickByTick, "req_tick_by_tick_data")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_TICK_BY_TICK_DATA);
         enc.encode_field_i32(req_id);
@@ -1462,7 +2138,7 @@ impl IBClient {
         enc.encode_field_str(&contract.trading_class);
         enc.encode_field_str(tick_type);
 
-        if self.server_version >= server_version::TICK_BY_TICK_IGNORE_SIZE {
+        if self.server_version() >= server_version::TICK_BY_TICK_IGNORE_SIZE {
             enc.encode_field_i32(number_of_ticks);
             enc.encode_field_bool(ignore_size);
         }
@@ -1470,12 +2146,17 @@ impl IBClient {
     }
 
     /// Cancel tick-by-tick data.
-    pub async fn cancel_tick_by_tick_data(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::TICK_BY_TICK, "cancel_tick_by_tick_data")?;
+    pub async fn cancel_tick_by_tick_data(&self, req_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_tick_by_tick_data(req_id)?;
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_tick_by_tick_data(&self, req_id: i32) -> Result<MessageEncoder> {
+        self.check_server_version(Feature::TickByTick, "cancel_tick_by_tick_data")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_TICK_BY_TICK_DATA);
         enc.encode_field_i32(req_id);
-        self.send_encoded(enc).await
+        Ok(enc)
     }
 
     // ========================================================================
@@ -1483,7 +2164,7 @@ impl IBClient {
     // ========================================================================
 
     /// Request scanner parameters XML.
-    pub async fn req_scanner_parameters(&mut self) -> Result<()> {
+    pub async fn req_scanner_parameters(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_SCANNER_PARAMETERS);
         enc.encode_field_i32(1); // version
@@ -1492,13 +2173,13 @@ impl IBClient {
 
     /// Request scanner subscription.
     pub async fn req_scanner_subscription(
-        &mut self,
+        &self,
         ticker_id: i32,
         subscription: &ScannerSubscription,
         scanner_subscription_options: &[TagValue],
         scanner_subscription_filter_options: &[TagValue],
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_SCANNER_SUBSCRIPTION);
         if sv < server_version::SCANNER_GENERIC_OPTS {
@@ -1537,12 +2218,17 @@ impl IBClient {
     }
 
     /// Cancel scanner subscription.
-    pub async fn cancel_scanner_subscription(&mut self, ticker_id: i32) -> Result<()> {
+    pub async fn cancel_scanner_subscription(&self, ticker_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_scanner_subscription(ticker_id);
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_scanner_subscription(&self, ticker_id: i32) -> MessageEncoder {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_SCANNER_SUBSCRIPTION);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(ticker_id);
-        self.send_encoded(enc).await
+        enc
     }
 
     // ========================================================================
@@ -1551,15 +2237,15 @@ impl IBClient {
 
     /// Calculate implied volatility.
     pub async fn calculate_implied_volatility(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
         option_price: f64,
         under_price: f64,
         misc_options: &[TagValue],
     ) -> Result<()> {
-        self.check_server_version(server_version::REQ_CALC_IMPLIED_VOLAT, "calculate_implied_volatility")?;
-        let sv = self.server_version;
+        self.check_server_version(Feature::CalcImpliedVolatility, "calculate_implied_volatility")?;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_CALC_IMPLIED_VOLAT);
         enc.encode_field_i32(2); // version
@@ -1587,26 +2273,34 @@ impl IBClient {
     }
 
     /// Cancel calculate implied volatility.
-    pub async fn cancel_calculate_implied_volatility(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::CANCEL_CALC_IMPLIED_VOLAT, "cancel_calculate_implied_volatility")?;
+    pub async fn cancel_calculate_implied_volatility(&self, req_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_calculate_implied_volatility(req_id)?;
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_calculate_implied_volatility(&self, req_id: i32) -> Result<MessageEncoder> {
+        self.check_server_version(
+            Feature::CancelCalcImpliedVolatility,
+            "cancel_calculate_implied_volatility",
+        )?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_CALC_IMPLIED_VOLAT);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(req_id);
-        self.send_encoded(enc).await
+        Ok(enc)
     }
 
     /// Calculate option price.
     pub async fn calculate_option_price(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
         volatility: f64,
         under_price: f64,
         misc_options: &[TagValue],
     ) -> Result<()> {
-        self.check_server_version(server_version::REQ_CALC_OPTION_PRICE, "calculate_option_price")?;
-        let sv = self.server_version;
+        self.check_server_version(Feature::CalcOptionPrice, "calculate_option_price")?;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_CALC_OPTION_PRICE);
         enc.encode_field_i32(2); // version
@@ -1634,19 +2328,24 @@ impl IBClient {
     }
 
     /// Cancel calculate option price.
-    pub async fn cancel_calculate_option_price(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::CANCEL_CALC_OPTION_PRICE, "cancel_calculate_option_price")?;
+    pub async fn cancel_calculate_option_price(&self, req_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_calculate_option_price(req_id)?;
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_calculate_option_price(&self, req_id: i32) -> Result<MessageEncoder> {
+        self.check_server_version(Feature::CancelCalcOptionPrice, "cancel_calculate_option_price")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_CALC_OPTION_PRICE);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(req_id);
-        self.send_encoded(enc).await
+        Ok(enc)
     }
 
     /// Exercise options.
     #[allow(clippy::too_many_arguments)]
     pub async fn exercise_options(
-        &mut self,
+        &self,
         ticker_id: i32,
         contract: &Contract,
         exercise_action: i32,
@@ -1657,7 +2356,7 @@ impl IBClient {
         customer_account: &str,
         professional_customer: bool,
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::EXERCISE_OPTIONS);
         enc.encode_field_i32(2); // version
@@ -1699,14 +2398,14 @@ impl IBClient {
 
     /// Request fundamental data.
     pub async fn req_fundamental_data(
-        &mut self,
+        &self,
         req_id: i32,
         contract: &Contract,
         report_type: &str,
         fundamental_data_options: &[TagValue],
     ) -> Result<()> {
-        self.check_server_version(server_version::FUNDAMENTAL_DATA, "req_fundamental_data")?;
-        let sv = self.server_version;
+        self.check_server_version(Feature::FundamentalData, "req_fundamental_data")?;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_FUNDAMENTAL_DATA);
         enc.encode_field_i32(2); // version
@@ -1728,8 +2427,8 @@ impl IBClient {
     }
 
     /// Cancel fundamental data.
-    pub async fn cancel_fundamental_data(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::FUNDAMENTAL_DATA, "cancel_fundamental_data")?;
+    pub async fn cancel_fundamental_data(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::FundamentalData, "cancel_fundamental_data")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_FUNDAMENTAL_DATA);
         enc.encode_field_i32(1); // version
@@ -1742,7 +2441,7 @@ impl IBClient {
     // ========================================================================
 
     /// Request news bulletins.
-    pub async fn req_news_bulletins(&mut self, all_msgs: bool) -> Result<()> {
+    pub async fn req_news_bulletins(&self, all_msgs: bool) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_NEWS_BULLETINS);
         enc.encode_field_i32(1); // version
@@ -1751,7 +2450,7 @@ impl IBClient {
     }
 
     /// Cancel news bulletins.
-    pub async fn cancel_news_bulletins(&mut self) -> Result<()> {
+    pub async fn cancel_news_bulletins(&self) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_NEWS_BULLETINS);
         enc.encode_field_i32(1); // version
@@ -1759,8 +2458,8 @@ impl IBClient {
     }
 
     /// Request news providers.
-    pub async fn req_news_providers(&mut self) -> Result<()> {
-        self.check_server_version(server_version::REQ_NEWS_PROVIDERS, "req_news_providers")?;
+    pub async fn req_news_providers(&self) -> Result<()> {
+        self.check_server_version(Feature::NewsProviders, "req_news_providers")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_NEWS_PROVIDERS);
         self.send_encoded(enc).await
@@ -1768,13 +2467,13 @@ impl IBClient {
 
     /// Request a news article.
     pub async fn req_news_article(
-        &mut self,
+        &self,
         req_id: i32,
         provider_code: &str,
         article_id: &str,
         news_article_options: &[TagValue],
     ) -> Result<()> {
-        self.check_server_version(server_version::REQ_NEWS_ARTICLE, "req_news_article")?;
+        self.check_server_version(Feature::NewsArticle, "req_news_article")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_NEWS_ARTICLE);
         enc.encode_field_i32(req_id);
@@ -1789,8 +2488,8 @@ impl IBClient {
     // ========================================================================
 
     /// Subscribe to P&L updates.
-    pub async fn req_pnl(&mut self, req_id: i32, account: &str, model_code: &str) -> Result<()> {
-        self.check_server_version(server_version::PNL, "req_pnl")?;
+    pub async fn req_pnl(&self, req_id: i32, account: &str, model_code: &str) -> Result<()> {
+        self.check_server_version(Feature::Pnl, "req_pnl")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_PNL);
         enc.encode_field_i32(req_id);
@@ -1800,23 +2499,28 @@ impl IBClient {
     }
 
     /// Cancel P&L subscription.
-    pub async fn cancel_pnl(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::PNL, "cancel_pnl")?;
-        let mut enc = self.encoder();
+    pub async fn cancel_pnl(&self, req_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_pnl(req_id)?;
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_pnl(&self, req_id: i32) -> Result<MessageEncoder> {
+        self.check_server_version(Feature::Pnl, "cancel_pnl")?;
+        let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_PNL);
         enc.encode_field_i32(req_id);
-        self.send_encoded(enc).await
+        Ok(enc)
     }
 
     /// Subscribe to single-position P&L.
     pub async fn req_pnl_single(
-        &mut self,
+        &self,
         req_id: i32,
         account: &str,
         model_code: &str,
         con_id: i64,
     ) -> Result<()> {
-        self.check_server_version(server_version::PNL, "req_pnl_single")?;
+        self.check_server_version(Feature::Pnl, "req_pnl_single")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_PNL_SINGLE);
         enc.encode_field_i32(req_id);
@@ -1827,12 +2531,17 @@ impl IBClient {
     }
 
     /// Cancel single P&L subscription.
-    pub async fn cancel_pnl_single(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::PNL, "cancel_pnl_single")?;
+    pub async fn cancel_pnl_single(&self, req_id: i32) -> Result<()> {
+        let enc = self.encode_cancel_pnl_single(req_id)?;
+        self.send_encoded(enc).await
+    }
+
+    fn encode_cancel_pnl_single(&self, req_id: i32) -> Result<MessageEncoder> {
+        self.check_server_version(Feature::Pnl, "cancel_pnl_single")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_PNL_SINGLE);
         enc.encode_field_i32(req_id);
-        self.send_encoded(enc).await
+        Ok(enc)
     }
 
     // ========================================================================
@@ -1840,8 +2549,8 @@ impl IBClient {
     // ========================================================================
 
     /// Request market rule details.
-    pub async fn req_market_rule(&mut self, market_rule_id: i32) -> Result<()> {
-        self.check_server_version(server_version::MARKET_RULES, "req_market_rule")?;
+    pub async fn req_market_rule(&self, market_rule_id: i32) -> Result<()> {
+        self.check_server_version(Feature::MarketRules, "req_market_rule")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MARKET_RULE);
         enc.encode_field_i32(market_rule_id);
@@ -1849,16 +2558,16 @@ impl IBClient {
     }
 
     /// Request market depth exchanges.
-    pub async fn req_mkt_depth_exchanges(&mut self) -> Result<()> {
-        self.check_server_version(server_version::REQ_MKT_DEPTH_EXCHANGES, "req_mkt_depth_exchanges")?;
+    pub async fn req_mkt_depth_exchanges(&self) -> Result<()> {
+        self.check_server_version(Feature::MktDepthExchanges, "req_mkt_depth_exchanges")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_MKT_DEPTH_EXCHANGES);
         self.send_encoded(enc).await
     }
 
     /// Request soft dollar tiers.
-    pub async fn req_soft_dollar_tiers(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::SOFT_DOLLAR_TIER, "req_soft_dollar_tiers")?;
+    pub async fn req_soft_dollar_tiers(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::SoftDollarTiers, "req_soft_dollar_tiers")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_SOFT_DOLLAR_TIERS);
         enc.encode_field_i32(req_id);
@@ -1866,8 +2575,8 @@ impl IBClient {
     }
 
     /// Request family codes.
-    pub async fn req_family_codes(&mut self) -> Result<()> {
-        self.check_server_version(server_version::REQ_FAMILY_CODES, "req_family_codes")?;
+    pub async fn req_family_codes(&self) -> Result<()> {
+        self.check_server_version(Feature::FamilyCodes, "req_family_codes")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_FAMILY_CODES);
         self.send_encoded(enc).await
@@ -1878,7 +2587,7 @@ impl IBClient {
     // ========================================================================
 
     /// Request FA configuration data.
-    pub async fn request_fa(&mut self, fa_data_type: i32) -> Result<()> {
+    pub async fn request_fa(&self, fa_data_type: i32) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_FA);
         enc.encode_field_i32(1); // version
@@ -1887,13 +2596,13 @@ impl IBClient {
     }
 
     /// Replace FA configuration data.
-    pub async fn replace_fa(&mut self, req_id: i32, fa_data_type: i32, cxml: &str) -> Result<()> {
+    pub async fn replace_fa(&self, req_id: i32, fa_data_type: i32, cxml: &str) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REPLACE_FA);
         enc.encode_field_i32(1); // version
         enc.encode_field_i32(fa_data_type);
         enc.encode_field_str(cxml);
-        if self.server_version >= server_version::REPLACE_FA_END {
+        if self.server_version() >= server_version::REPLACE_FA_END {
             enc.encode_field_i32(req_id);
         }
         self.send_encoded(enc).await
@@ -1904,8 +2613,8 @@ impl IBClient {
     // ========================================================================
 
     /// Query display groups.
-    pub async fn query_display_groups(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::LINKING, "query_display_groups")?;
+    pub async fn query_display_groups(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::Linking, "query_display_groups")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::QUERY_DISPLAY_GROUPS);
         enc.encode_field_i32(1); // version
@@ -1914,8 +2623,8 @@ impl IBClient {
     }
 
     /// Subscribe to group events.
-    pub async fn subscribe_to_group_events(&mut self, req_id: i32, group_id: i32) -> Result<()> {
-        self.check_server_version(server_version::LINKING, "subscribe_to_group_events")?;
+    pub async fn subscribe_to_group_events(&self, req_id: i32, group_id: i32) -> Result<()> {
+        self.check_server_version(Feature::Linking, "subscribe_to_group_events")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::SUBSCRIBE_TO_GROUP_EVENTS);
         enc.encode_field_i32(1); // version
@@ -1925,8 +2634,8 @@ impl IBClient {
     }
 
     /// Update display group.
-    pub async fn update_display_group(&mut self, req_id: i32, contract_info: &str) -> Result<()> {
-        self.check_server_version(server_version::LINKING, "update_display_group")?;
+    pub async fn update_display_group(&self, req_id: i32, contract_info: &str) -> Result<()> {
+        self.check_server_version(Feature::Linking, "update_display_group")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::UPDATE_DISPLAY_GROUP);
         enc.encode_field_i32(1); // version
@@ -1936,8 +2645,8 @@ impl IBClient {
     }
 
     /// Unsubscribe from group events.
-    pub async fn unsubscribe_from_group_events(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::LINKING, "unsubscribe_from_group_events")?;
+    pub async fn unsubscribe_from_group_events(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::Linking, "unsubscribe_from_group_events")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::UNSUBSCRIBE_FROM_GROUP_EVENTS);
         enc.encode_field_i32(1); // version
@@ -1950,8 +2659,8 @@ impl IBClient {
     // ========================================================================
 
     /// Verify API request.
-    pub async fn verify_request(&mut self, api_name: &str, api_version: &str) -> Result<()> {
-        self.check_server_version(server_version::LINKING, "verify_request")?;
+    pub async fn verify_request(&self, api_name: &str, api_version: &str) -> Result<()> {
+        self.check_server_version(Feature::Linking, "verify_request")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::VERIFY_REQUEST);
         enc.encode_field_i32(1); // version
@@ -1961,8 +2670,8 @@ impl IBClient {
     }
 
     /// Verify message.
-    pub async fn verify_message(&mut self, api_data: &str) -> Result<()> {
-        self.check_server_version(server_version::LINKING, "verify_message")?;
+    pub async fn verify_message(&self, api_data: &str) -> Result<()> {
+        self.check_server_version(Feature::Linking, "verify_message")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::VERIFY_MESSAGE);
         enc.encode_field_i32(1); // version
@@ -1972,12 +2681,12 @@ impl IBClient {
 
     /// Verify and auth request.
     pub async fn verify_and_auth_request(
-        &mut self,
+        &self,
         api_name: &str,
         api_version: &str,
         opaque_isv_key: &str,
     ) -> Result<()> {
-        self.check_server_version(server_version::LINKING_AUTH, "verify_and_auth_request")?;
+        self.check_server_version(Feature::LinkingAuth, "verify_and_auth_request")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::VERIFY_AND_AUTH_REQUEST);
         enc.encode_field_i32(1); // version
@@ -1989,11 +2698,11 @@ impl IBClient {
 
     /// Verify and auth message.
     pub async fn verify_and_auth_message(
-        &mut self,
+        &self,
         api_data: &str,
         xyz_response: &str,
     ) -> Result<()> {
-        self.check_server_version(server_version::LINKING_AUTH, "verify_and_auth_message")?;
+        self.check_server_version(Feature::LinkingAuth, "verify_and_auth_message")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::VERIFY_AND_AUTH_MESSAGE);
         enc.encode_field_i32(1); // version
@@ -2007,7 +2716,7 @@ impl IBClient {
     // ========================================================================
 
     /// Request WSH meta data.
-    pub async fn req_wsh_meta_data(&mut self, req_id: i32) -> Result<()> {
+    pub async fn req_wsh_meta_data(&self, req_id: i32) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_WSH_META_DATA);
         enc.encode_field_i32(req_id);
@@ -2015,7 +2724,7 @@ impl IBClient {
     }
 
     /// Cancel WSH meta data.
-    pub async fn cancel_wsh_meta_data(&mut self, req_id: i32) -> Result<()> {
+    pub async fn cancel_wsh_meta_data(&self, req_id: i32) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_WSH_META_DATA);
         enc.encode_field_i32(req_id);
@@ -2023,9 +2732,13 @@ impl IBClient {
     }
 
     /// Request WSH event data.
+    ///
+    /// Request the meta data first (see [`wsh_meta_data`](Self::wsh_meta_data))
+    /// and wait for it: TWS rejects event data requests made before that
+    /// with an error for which [`IbErrorCode::is_wsh_not_subscribed`] holds.
     #[allow(clippy::too_many_arguments)]
     pub async fn req_wsh_event_data(
-        &mut self,
+        &self,
         req_id: i32,
         con_id: i32,
         filter: &str,
@@ -2036,7 +2749,7 @@ impl IBClient {
         end_date: &str,
         total_limit: Option<i32>,
     ) -> Result<()> {
-        let sv = self.server_version;
+        let sv = self.server_version();
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_WSH_EVENT_DATA);
         enc.encode_field_i32(req_id);
@@ -2056,7 +2769,7 @@ impl IBClient {
     }
 
     /// Cancel WSH event data.
-    pub async fn cancel_wsh_event_data(&mut self, req_id: i32) -> Result<()> {
+    pub async fn cancel_wsh_event_data(&self, req_id: i32) -> Result<()> {
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::CANCEL_WSH_EVENT_DATA);
         enc.encode_field_i32(req_id);
@@ -2068,8 +2781,8 @@ impl IBClient {
     // ========================================================================
 
     /// Request user info.
-    pub async fn req_user_info(&mut self, req_id: i32) -> Result<()> {
-        self.check_server_version(server_version::USER_INFO, "req_user_info")?;
+    pub async fn req_user_info(&self, req_id: i32) -> Result<()> {
+        self.check_server_version(Feature::UserInfo, "req_user_info")?;
         let mut enc = self.encoder();
         enc.encode_msg_id(outgoing::REQ_USER_INFO);
         enc.encode_field_i32(req_id);
@@ -2077,332 +2790,3951 @@ impl IBClient {
     }
 
     // ========================================================================
-    // Private: Protobuf Encoding
+    // Correlated Requests
     // ========================================================================
 
-    async fn place_order_protobuf(
-        &mut self,
-        id: i64,
-        contract: &Contract,
-        order: &Order,
-    ) -> Result<()> {
-        use prost::Message;
-        let request = crate::proto_encode::build_place_order_request(id, contract, order);
-        let mut enc = self.encoder();
-        enc.encode_raw_int(outgoing::PLACE_ORDER + outgoing::PROTOBUF_MSG_ID);
-        enc.write_raw(&request.encode_to_vec());
-        self.send_encoded(enc).await
+    /// Fetch the server time in milliseconds since the epoch.
+    ///
+    /// Sends `REQ_CURRENT_TIME_IN_MILLIS` and waits for the
+    /// `CurrentTimeInMillis` answer. Servers before
+    /// `CURRENT_TIME_IN_MILLIS` get `REQ_CURRENT_TIME` instead, and the
+    /// answer (whole seconds) is multiplied by 1000; on those servers a
+    /// keepalive probe's answer may resolve the call. Fails with
+    /// `IBApiError::InProgress` while another call is waiting.
+    pub async fn current_time_millis(&self) -> Result<i64> {
+        let millis = self.supports(Feature::CurrentTimeInMillis);
+        let stream = if millis { Stream::CurrentTimeInMillis } else { Stream::CurrentTime };
+        let mut rx = self.pending.register_stream(stream)?;
+        let sent = if millis {
+            self.req_current_time_in_millis().await
+        } else {
+            self.req_current_time().await
+        };
+        if let Err(e) = sent {
+            self.pending.remove_stream(stream);
+            return Err(e);
+        }
+        let result = self
+            .await_events(&mut rx, "current time", |event| match event {
+                IBEvent::CurrentTimeInMillis { time_in_millis } => Some(Ok(time_in_millis)),
+                IBEvent::CurrentTime { time } if !millis => Some(Ok(time * 1000)),
+                _ => None,
+            })
+            .await;
+        self.pending.remove_stream(stream);
+        result
     }
 
-    async fn cancel_order_protobuf(
-        &mut self,
-        id: i64,
-        order_cancel: &OrderCancel,
-    ) -> Result<()> {
-        use prost::Message;
-        let request = crate::proto_encode::build_cancel_order_request(id, order_cancel);
-        let mut enc = self.encoder();
-        enc.encode_raw_int(outgoing::CANCEL_ORDER + outgoing::PROTOBUF_MSG_ID);
-        enc.write_raw(&request.encode_to_vec());
-        self.send_encoded(enc).await
-    }
+    /// Request contract details and wait for the complete result set.
+    ///
+    /// Allocates a request ID, sends `REQ_CONTRACT_DATA`, and collects every
+    /// `ContractDetails` / `BondContractDetails` for that ID until
+    /// `ContractDetailsEnd` arrives. These responses are not delivered to
+    /// the event channel. Fails with `IBApiError::Timeout` if the end
+    /// message does not arrive within [`request_timeout`](Self::request_timeout).
+    pub async fn req_contract_details_async(
+        &self,
+        contract: &Contract,
+    ) -> Result<Vec<ContractDetails>> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        if let Err(e) = self.req_contract_details(req_id, contract).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
 
-    async fn req_executions_protobuf(
-        &mut self,
-        req_id: i32,
-        filter: &ExecutionFilter,
-    ) -> Result<()> {
-        use prost::Message;
-        let request = crate::proto_encode::build_execution_request(req_id, filter);
-        let mut enc = self.encoder();
-        enc.encode_raw_int(outgoing::REQ_EXECUTIONS + outgoing::PROTOBUF_MSG_ID);
-        enc.write_raw(&request.encode_to_vec());
-        self.send_encoded(enc).await
+        let mut details = Vec::new();
+        self.await_response(req_id, rx, "contract details", |event| match event {
+            IBEvent::ContractDetails { details: d, .. }
+            | IBEvent::BondContractDetails { details: d, .. } => {
+                details.push(*d);
+                None
+            }
+            IBEvent::ContractDetailsEnd { .. } => Some(Ok(std::mem::take(&mut details))),
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
     }
 
-    async fn req_global_cancel_protobuf(&mut self, order_cancel: &OrderCancel) -> Result<()> {
-        use prost::Message;
-        let request = crate::proto_encode::build_global_cancel_request(order_cancel);
-        let mut enc = self.encoder();
-        enc.encode_raw_int(outgoing::REQ_GLOBAL_CANCEL + outgoing::PROTOBUF_MSG_ID);
-        enc.write_raw(&request.encode_to_vec());
-        self.send_encoded(enc).await
-    }
-}
+    /// Request executions matching `filter` and join each with its
+    /// commission report.
+    ///
+    /// Collects `ExecDetails` until `ExecDetailsEnd` (subject to
+    /// [`request_timeout`](Self::request_timeout)), along with any
+    /// `CommissionReport` whose `exec_id` matches one of them. TWS may send
+    /// reports after the end marker, so the call then waits up to
+    /// [`commission_timeout`](Self::commission_timeout) for the missing
+    /// ones; executions still without a report get `commission: None`.
+    /// None of these events are delivered to the event channel.
+    pub async fn executions(
+        &self,
+        filter: &ExecutionFilter,
+    ) -> Result<Vec<ExecutionWithCommission>> {
+        let req_id = self.next_collector_id();
+        let mut rx = self.pending.register(req_id);
+        if let Err(e) = self.req_executions(req_id, filter).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
 
-/// Encode an order condition to the wire format.
-fn encode_condition(enc: &mut MessageEncoder, cond: &OrderCondition) {
-    match cond {
-        OrderCondition::Price {
-            is_conjunction_connection,
-            is_more,
-            con_id,
-            exchange,
-            price,
-            trigger_method,
-        } => {
-            enc.encode_field_i32(1); // Price type
-            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
-            enc.encode_field_bool(*is_more);
-            enc.encode_field_i32(*con_id);
-            enc.encode_field_str(exchange);
-            enc.encode_field_f64(*price);
-            enc.encode_field_i32(*trigger_method as i32);
+        let mut executions = Vec::new();
+        let mut reports = HashMap::new();
+        let what = format!("executions (req_id {req_id})");
+        let collected = self
+            .await_events(&mut rx, &what, |event| {
+                match event {
+                    IBEvent::ExecDetails { contract, execution, .. } => {
+                        executions.push(ExecutionWithCommission {
+                            contract: *contract,
+                            execution: *execution,
+                            commission: None,
+                        });
+                    }
+                    IBEvent::CommissionReport { report } => {
+                        reports.insert(report.exec_id.clone(), report);
+                    }
+                    IBEvent::ExecDetailsEnd { .. } => return Some(Ok(())),
+                    event @ IBEvent::Error { .. } => return event.api_error().map(Err),
+                    _ => {}
+                }
+                None
+            })
+            .await;
+        if let Err(e) = collected {
+            self.pending.remove(req_id);
+            return Err(e);
         }
-        OrderCondition::Time {
-            is_conjunction_connection,
-            is_more,
-            time,
-        } => {
-            enc.encode_field_i32(3); // Time type
-            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
-            enc.encode_field_bool(*is_more);
-            enc.encode_field_str(time);
+
+        // Commission reports may trail the end marker.
+        let missing = |reports: &HashMap<String, _>| {
+            executions.iter().any(|e| !reports.contains_key(&e.execution.exec_id))
+        };
+        let late = async {
+            while missing(&reports) {
+                match rx.recv().await {
+                    Some(IBEvent::CommissionReport { report }) => {
+                        reports.insert(report.exec_id.clone(), report);
+                    }
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+        };
+        let _ = tokio::time::timeout(self.commission_timeout, late).await;
+        self.pending.remove(req_id);
+
+        for e in &mut executions {
+            e.commission = reports.remove(&e.execution.exec_id);
         }
-        OrderCondition::Margin {
-            is_conjunction_connection,
-            is_more,
-            percent,
-        } => {
-            enc.encode_field_i32(4); // Margin type
-            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
-            enc.encode_field_bool(*is_more);
-            enc.encode_field_i32(*percent);
+        Ok(executions)
+    }
+
+    /// Request all positions of the accounts this login can see and wait
+    /// for the initial snapshot.
+    ///
+    /// Sends `REQ_POSITIONS` and collects every `Position` up to
+    /// `PositionEnd`; these are not delivered to the event channel. TWS
+    /// keeps streaming position changes after the snapshot. With
+    /// `keep_subscription` those later updates arrive on the event channel
+    /// as for [`req_positions`](Self::req_positions); otherwise the call
+    /// sends `CANCEL_POSITIONS`, which also ends a positions subscription
+    /// started earlier on this connection (TWS keeps one per client).
+    /// Fails with `IBApiError::InProgress` while another call is waiting.
+    pub async fn positions(&self, keep_subscription: bool) -> Result<Vec<PositionRecord>> {
+        let mut rx = self.pending.register_stream(Stream::Positions)?;
+        if let Err(e) = self.req_positions().await {
+            self.pending.remove_stream(Stream::Positions);
+            return Err(e);
         }
-        OrderCondition::Execution {
-            is_conjunction_connection,
-            exchange,
-            sec_type,
-            symbol,
-        } => {
-            enc.encode_field_i32(5); // Execution type
-            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
-            enc.encode_field_str(sec_type);
-            enc.encode_field_str(exchange);
-            enc.encode_field_str(symbol);
+
+        let mut positions = Vec::new();
+        let result = self
+            .await_events(&mut rx, "positions", |event| match event {
+                IBEvent::Position { account, contract, position, avg_cost } => {
+                    positions.push(PositionRecord {
+                        account,
+                        contract: *contract,
+                        position,
+                        avg_cost,
+                    });
+                    None
+                }
+                IBEvent::PositionEnd => Some(Ok(std::mem::take(&mut positions))),
+                _ => None,
+            })
+            .await;
+        self.pending.remove_stream(Stream::Positions);
+        if !keep_subscription {
+            let cancelled = self.cancel_positions().await;
+            let positions = result?;
+            cancelled?;
+            return Ok(positions);
         }
-        OrderCondition::Volume {
-            is_conjunction_connection,
-            is_more,
-            con_id,
-            exchange,
-            volume,
-        } => {
-            enc.encode_field_i32(6); // Volume type
-            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
-            enc.encode_field_bool(*is_more);
-            enc.encode_field_i32(*con_id);
-            enc.encode_field_str(exchange);
-            enc.encode_field_i32(*volume);
+        result
+    }
+
+    /// Subscribe to the account updates of `acct` and wait for the initial
+    /// download.
+    ///
+    /// Sends `REQ_ACCT_DATA` and collects every `UpdateAccountValue`,
+    /// `UpdatePortfolio` and `UpdateAccountTime` up to `AccountDownloadEnd`;
+    /// these are not delivered to the event channel. An empty `acct` asks
+    /// for the login's default account, which the snapshot's `account`
+    /// then names. With `keep_subscription` later updates arrive on the
+    /// event channel as for [`req_account_updates`](Self::req_account_updates);
+    /// otherwise the call unsubscribes again. TWS keeps one account
+    /// subscription per client, so this replaces any started earlier.
+    /// Fails with `IBApiError::InProgress` while another call is waiting.
+    pub async fn account_snapshot(
+        &self,
+        acct: &str,
+        keep_subscription: bool,
+    ) -> Result<AccountSnapshot> {
+        let mut rx = self.pending.register_stream(Stream::AccountUpdates)?;
+        if let Err(e) = self.req_account_updates(true, acct).await {
+            self.pending.remove_stream(Stream::AccountUpdates);
+            return Err(e);
         }
-        OrderCondition::PercentChange {
-            is_conjunction_connection,
-            is_more,
-            con_id,
-            exchange,
-            change_percent,
-        } => {
-            enc.encode_field_i32(7); // PercentChange type
-            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
-            enc.encode_field_bool(*is_more);
-            enc.encode_field_i32(*con_id);
-            enc.encode_field_str(exchange);
-            enc.encode_field_max_f64(*change_percent);
+
+        let mut snapshot = AccountSnapshot::default();
+        let result = self
+            .await_events(&mut rx, "account updates", |event| {
+                if let Some(value) = event.account_value() {
+                    snapshot.values.push(value);
+                    return None;
+                }
+                match event {
+                    IBEvent::UpdatePortfolio {
+                        contract,
+                        position,
+                        market_price,
+                        market_value,
+                        average_cost,
+                        unrealized_pnl,
+                        realized_pnl,
+                        account_name,
+                    } => {
+                        snapshot.portfolio.push(PortfolioItem {
+                            contract: *contract,
+                            position,
+                            market_price,
+                            market_value,
+                            average_cost,
+                            unrealized_pnl,
+                            realized_pnl,
+                            account_name,
+                        });
+                        None
+                    }
+                    IBEvent::UpdateAccountTime { timestamp } => {
+                        snapshot.update_time = timestamp;
+                        None
+                    }
+                    IBEvent::AccountDownloadEnd { account } => {
+                        snapshot.account = account;
+                        Some(Ok(std::mem::take(&mut snapshot)))
+                    }
+                    _ => None,
+                }
+            })
+            .await;
+        self.pending.remove_stream(Stream::AccountUpdates);
+        if !keep_subscription {
+            let unsubscribed = self.req_account_updates(false, acct).await;
+            let snapshot = result?;
+            unsubscribed?;
+            return Ok(snapshot);
         }
+        result
     }
-}
 
-impl Drop for IBClient {
-    fn drop(&mut self) {
-        if self.connected.load(Ordering::Relaxed) {
-            // Can't do async in Drop, but we can abort the reader task
-            if let Some(handle) = self.reader_handle.take() {
-                handle.abort();
-            }
-        }
+    /// Fetch the scanner parameters catalog, or return the copy fetched
+    /// earlier on this connection.
+    ///
+    /// Sends `REQ_SCANNER_PARAMETERS` on the first call and parses the XML
+    /// answer with [`ScannerParameters::parse`]. The `ScannerParameters`
+    /// event is consumed rather than delivered to the event channel.
+    /// Requires the `xml` feature.
+    #[cfg(feature = "xml")]
+    pub async fn scanner_parameters(&self) -> Result<&ScannerParameters> {
+        self.scanner_params
+            .get_or_try_init(|| async {
+                let mut rx = self.pending.register_stream(Stream::ScannerParameters)?;
+                if let Err(e) = self.req_scanner_parameters().await {
+                    self.pending.remove_stream(Stream::ScannerParameters);
+                    return Err(e);
+                }
+                let xml = self
+                    .await_events(&mut rx, "scanner parameters", |event| match event {
+                        IBEvent::ScannerParameters { xml } => Some(Ok(xml)),
+                        _ => None,
+                    })
+                    .await;
+                self.pending.remove_stream(Stream::ScannerParameters);
+                ScannerParameters::parse(&xml?)
+            })
+            .await
     }
-}
 
-// ============================================================================
-// Tests
-// ============================================================================
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-    use tokio::net::TcpListener;
+    /// Check `subscription` against the scanner parameters catalog before
+    /// sending it, as IB recommends.
+    ///
+    /// Fetches the catalog on first use (see
+    /// [`scanner_parameters`](Self::scanner_parameters)), then fails with
+    /// `IBApiError::Encoding` listing the valid values if the instrument,
+    /// location code or scan code is unknown -- instead of the terse
+    /// server rejection `req_scanner_subscription` would get. Requires the
+    /// `xml` feature.
+    #[cfg(feature = "xml")]
+    pub async fn validate_scanner_subscription(
+        &self,
+        subscription: &ScannerSubscription,
+    ) -> Result<()> {
+        self.scanner_parameters().await?.validate(subscription)
+    }
 
-    /// Build a framed message from null-terminated fields.
-    fn build_framed_msg(fields: &[&str]) -> Vec<u8> {
-        let mut body = Vec::new();
-        for f in fields {
-            body.extend_from_slice(f.as_bytes());
-            body.push(0);
+    /// List the exchanges that offer market depth and wait for the answer.
+    ///
+    /// Sends `REQ_MKT_DEPTH_EXCHANGES` and returns the descriptions from the
+    /// `MktDepthExchanges` event, which is consumed rather than delivered to
+    /// the event channel. `service_data_type` is `"Deep2"` for exchanges
+    /// with L2 (market maker) depth and `"Deep"` for L1 depth only. Servers
+    /// before `SERVICE_DATA_TYPE` send no `listing_exch` or `agg_group`.
+    /// Fails with `IBApiError::InProgress` while another call is waiting.
+    pub async fn mkt_depth_exchanges(&self) -> Result<Vec<DepthMktDataDescription>> {
+        let mut rx = self.pending.register_stream(Stream::MktDepthExchanges)?;
+        if let Err(e) = self.req_mkt_depth_exchanges().await {
+            self.pending.remove_stream(Stream::MktDepthExchanges);
+            return Err(e);
         }
-        let mut frame = Vec::new();
-        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
-        frame.extend(body);
-        frame
+        let result = self
+            .await_events(&mut rx, "market depth exchanges", |event| match event {
+                IBEvent::MktDepthExchanges { descriptions } => Some(Ok(descriptions)),
+                _ => None,
+            })
+            .await;
+        self.pending.remove_stream(Stream::MktDepthExchanges);
+        result
     }
 
-    /// Create a mock TWS server that performs handshake, reads start_api,
-    /// and sends the given messages.
-    async fn mock_tws(sv: i32, messages: Vec<Vec<u8>>) -> u16 {
-        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let port = listener.local_addr().unwrap().port();
-
-        tokio::spawn(async move {
-            let (mut stream, _) = listener.accept().await.unwrap();
-
-            // Read connect request
-            let mut buf = vec![0u8; 512];
-            let _ = stream.read(&mut buf).await.unwrap();
+    /// Request a one-off market data snapshot and wait for it to complete.
+    ///
+    /// Sends `REQ_MKT_DATA` with `snapshot = true` and folds every tick for
+    /// the allocated ticker ID into a [`MarketSnapshot`] until
+    /// `TickSnapshotEnd` arrives. Fails with `IBApiError::Server` if TWS
+    /// rejects the request, e.g. code 354 (no market data subscription) or
+    /// 10197 (no market data during a competing live session, see
+    /// [`IbErrorCode`](crate::errors::IbErrorCode)). Informational codes
+    /// 2100-2199 and 10167 (delayed data shown) are ignored.
+    pub async fn req_mkt_data_snapshot(&self, contract: &Contract) -> Result<MarketSnapshot> {
+        self.collect_snapshot(contract, false).await
+    }
 
-            // Send handshake
-            let handshake = build_framed_msg(&[&sv.to_string(), "20260101 12:00:00"]);
-            stream.write_all(&handshake).await.unwrap();
+    /// Request a regulatory snapshot and wait for it to complete.
+    ///
+    /// Sends `REQ_MKT_DATA` with `snapshot` and `regulatory_snapshot` set
+    /// (servers before `REQ_SMART_COMPONENTS` do not support the flag and
+    /// fail with `IBApiError::Encoding`) and folds the ticks like
+    /// [`req_mkt_data_snapshot`](Self::req_mkt_data_snapshot). A regulatory
+    /// snapshot gives NBBO data without a market data subscription, but IB
+    /// charges a fee for each one (USD 0.01 at the time of writing), so avoid
+    /// calling it in a loop. Errors are reported as for
+    /// `req_mkt_data_snapshot`.
+    pub async fn regulatory_snapshot(&self, contract: &Contract) -> Result<MarketSnapshot> {
+        self.check_server_version(Feature::SmartComponents, "regulatory_snapshot")?;
+        self.collect_snapshot(contract, true).await
+    }
 
-            // Read start_api
-            let _ = stream.read(&mut buf).await.unwrap();
+    /// Shared body of the snapshot collectors.
+    async fn collect_snapshot(
+        &self,
+        contract: &Contract,
+        regulatory: bool,
+    ) -> Result<MarketSnapshot> {
+        let ticker_id = self.next_collector_id();
+        let rx = self.pending.register(ticker_id);
+        let sent = self.req_mkt_data(ticker_id, contract, "", true, regulatory, &[]).await;
+        if let Err(e) = sent {
+            self.pending.remove(ticker_id);
+            return Err(e);
+        }
 
-            // Send test messages
-            for msg in messages {
-                stream.write_all(&msg).await.unwrap();
+        let mut snapshot = MarketSnapshot::default();
+        self.await_response(ticker_id, rx, "market data snapshot", |event| {
+            match event {
+                IBEvent::TickPrice { tick_type, price, size, .. } => {
+                    snapshot.apply_price(tick_type, price, size)
+                }
+                IBEvent::TickSize { tick_type, size, .. } => snapshot.apply_size(tick_type, size),
+                IBEvent::TickString { tick_type, value, .. } => {
+                    snapshot.apply_string(tick_type, &value)
+                }
+                IBEvent::TickGeneric { tick_type, value, .. } => {
+                    snapshot.apply_generic(tick_type, value)
+                }
+                IBEvent::TickSnapshotEnd { .. } => return Some(Ok(std::mem::take(&mut snapshot))),
+                event @ IBEvent::Error { .. } => return event.api_error().map(Err),
+                _ => {}
             }
+            None
+        })
+        .await
+    }
 
-            // Keep connection open briefly, then close
-            tokio::task::yield_now().await;
-            drop(stream);
-        });
+    /// Search for contracts matching `pattern` and wait for the results.
+    ///
+    /// Sends `REQ_MATCHING_SYMBOLS` and resolves on the `SymbolSamples`
+    /// response for the allocated request ID. TWS sends exactly one such
+    /// message (there is no end marker); an empty search yields an empty Vec.
+    pub async fn search_symbols(&self, pattern: &str) -> Result<Vec<ContractDescription>> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        if let Err(e) = self.req_matching_symbols(req_id, pattern).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
 
-        tokio::task::yield_now().await;
-        port
+        self.await_response(req_id, rx, "matching symbols", |event| match event {
+            IBEvent::SymbolSamples { descriptions, .. } => Some(Ok(descriptions)),
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
     }
 
-    #[tokio::test]
-    async fn client_connect_and_receive_events() {
-        let messages = vec![
-            build_framed_msg(&["9", "1", "100"]),   // NEXT_VALID_ID
-            build_framed_msg(&["15", "1", "DU123"]), // MANAGED_ACCTS
-        ];
+    /// Fetch a news article and wait for its body.
+    ///
+    /// Sends `REQ_NEWS_ARTICLE` and resolves on the `NewsArticle` response.
+    /// Binary articles (`article_type` 1, typically PDFs) arrive base64-encoded
+    /// and are decoded; malformed base64 fails with `IBApiError::Decoding`.
+    pub async fn get_news_article(
+        &self,
+        provider_code: &str,
+        article_id: &str,
+    ) -> Result<NewsArticleContent> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        if let Err(e) = self.req_news_article(req_id, provider_code, article_id, &[]).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
 
-        let port = mock_tws(176, messages).await;
+        self.await_response(req_id, rx, "news article", |event| match event {
+            IBEvent::NewsArticle { article_type, article_text, .. } => {
+                Some(NewsArticleContent::from_article(article_type, article_text))
+            }
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
+    }
 
-        let (mut client, mut rx) =
-            IBClient::connect("127.0.0.1", port, 0, None)
-                .await
-                .unwrap();
+    /// Fetch the Wall Street Horizon meta data (available event types and
+    /// filters) as JSON.
+    ///
+    /// Sends `REQ_WSH_META_DATA` and waits for the `WshMetaData` answering
+    /// it. Call this once per connection and let it complete before
+    /// [`req_wsh_event_data`](Self::req_wsh_event_data); TWS rejects event
+    /// data requests made earlier with an error for which
+    /// [`IbErrorCode::is_wsh_not_subscribed`] holds. Fails with
+    /// `IBApiError::Encoding` if the server predates `WSHE_CALENDAR`.
+    pub async fn wsh_meta_data(&self) -> Result<String> {
+        self.check_server_version(Feature::WshCalendar, "wsh_meta_data")?;
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        if let Err(e) = self.req_wsh_meta_data(req_id).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
 
-        assert!(client.is_connected());
-        assert_eq!(client.server_version(), 176);
-        assert_eq!(client.client_id(), 0);
+        self.await_response(req_id, rx, "WSH meta data", |event| match event {
+            IBEvent::WshMetaData { data_json, .. } => Some(Ok(data_json)),
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
+    }
 
-        // Receive events
-        let event1 = rx.recv().await.unwrap();
-        match event1 {
-            IBEvent::NextValidId { order_id } => assert_eq!(order_id, 100),
-            other => panic!("expected NextValidId, got {other:?}"),
+    /// Fetch historical bars and wait for the complete series.
+    ///
+    /// Sends `REQ_HISTORICAL_DATA` with `keep_up_to_date = false` and dates
+    /// formatted as `yyyyMMdd HH:mm:ss`, then collects `HistoricalData` for
+    /// the allocated request ID until `HistoricalDataEnd`. Servers before
+    /// `HISTORICAL_DATA_END` send the whole series in one `HistoricalData`
+    /// message, which resolves the request directly. Fails with
+    /// `IBApiError::PacingViolation` if TWS rejects the request for pacing.
+    ///
+    /// For a series that keeps updating, use
+    /// [`subscribe_historical_data`](Self::subscribe_historical_data)
+    /// instead. `SCHEDULE` is answered with a schedule rather than bars and
+    /// fails with `IBApiError::Encoding`; use
+    /// [`historical_schedule`](Self::historical_schedule).
+    #[allow(clippy::too_many_arguments)]
+    pub async fn historical_bars(
+        &self,
+        contract: &Contract,
+        end_date_time: &str,
+        duration: impl Into<String>,
+        bar_size: impl Into<BarSize>,
+        what_to_show: impl Into<WhatToShow>,
+        use_rth: bool,
+    ) -> Result<Vec<Bar>> {
+        let what_to_show = what_to_show.into();
+        if what_to_show == WhatToShow::Schedule {
+            return Err(IBApiError::Encoding(
+                "SCHEDULE returns no bars; use historical_schedule".into(),
+            ));
         }
-
-        let event2 = rx.recv().await.unwrap();
-        match event2 {
-            IBEvent::ManagedAccounts { accounts } => assert_eq!(accounts, "DU123"),
-            other => panic!("expected ManagedAccounts, got {other:?}"),
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .req_historical_data(
+                req_id,
+                contract,
+                end_date_time,
+                duration,
+                bar_size,
+                what_to_show,
+                use_rth,
+                1,
+                false,
+                &[],
+            )
+            .await;
+        if let Err(e) = sent {
+            self.pending.remove(req_id);
+            return Err(e);
         }
 
-        client.disconnect().await;
-        assert!(!client.is_connected());
+        let separate_end = self.server_version() >= server_version::HISTORICAL_DATA_END;
+        let mut series = Vec::new();
+        let result = self
+            .await_response(req_id, rx, "historical data", |event| match event {
+                IBEvent::HistoricalData { bars, .. } if !separate_end => Some(Ok(bars)),
+                IBEvent::HistoricalData { bars, .. } => {
+                    series.extend(bars);
+                    None
+                }
+                IBEvent::HistoricalDataEnd { .. } => Some(Ok(std::mem::take(&mut series))),
+                event @ IBEvent::Error { .. } => event.api_error().map(Err),
+                _ => None,
+            })
+            .await;
+        if matches!(result, Err(IBApiError::Timeout(_))) {
+            let _ = self.cancel_historical_data(req_id).await;
+        }
+        result
     }
 
-    #[tokio::test]
-    async fn client_next_req_id() {
-        let port = mock_tws(176, vec![]).await;
+    /// Fetch the trading schedule of `contract` for the `duration` ending at
+    /// `end_date_time` (empty for now).
+    ///
+    /// Sends `REQ_HISTORICAL_DATA` with `whatToShow = SCHEDULE`, a bar size
+    /// of one day and regular trading hours only, and resolves on the
+    /// `HistoricalSchedule` answer for the allocated request ID. Requires
+    /// server version `HISTORICAL_SCHEDULE`.
+    pub async fn historical_schedule(
+        &self,
+        contract: &Contract,
+        end_date_time: &str,
+        duration: impl Into<String>,
+    ) -> Result<HistoricalSchedule> {
+        self.check_server_version(Feature::HistoricalSchedule, "historical_schedule")?;
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .req_historical_data(
+                req_id,
+                contract,
+                end_date_time,
+                duration,
+                BarSize::OneDay,
+                WhatToShow::Schedule,
+                true,
+                1,
+                false,
+                &[],
+            )
+            .await;
+        if let Err(e) = sent {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
 
-        let (client, _rx) =
-            IBClient::connect("127.0.0.1", port, 0, None)
-                .await
-                .unwrap();
+        self.await_response(req_id, rx, "historical schedule", |event| match event {
+            IBEvent::HistoricalSchedule {
+                start_date_time, end_date_time, time_zone, sessions, ..
+            } => Some(Ok(HistoricalSchedule {
+                start_date_time,
+                end_date_time,
+                time_zone,
+                sessions,
+            })),
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
+    }
 
-        assert_eq!(client.next_req_id(), 1);
-        assert_eq!(client.next_req_id(), 2);
-        assert_eq!(client.next_req_id(), 3);
+    /// Fetch every historical tick of `kind` between unix times `start` and
+    /// `end` (inclusive), paging through `REQ_HISTORICAL_TICKS`.
+    ///
+    /// Each page asks for [`HISTORICAL_TICKS_PAGE_SIZE`] ticks from the
+    /// current start time. Ticks have one-second resolution and a full page
+    /// may end partway through a second, so the next page starts at the
+    /// newest tick's second and skips the ticks of that second already
+    /// taken. A short page, one reaching `end`, or one adding no new ticks
+    /// is the last. Ticks after `end` are dropped. Fails on the first page
+    /// that fails.
+    pub async fn historical_ticks_all(
+        &self,
+        contract: &Contract,
+        start: i64,
+        end: i64,
+        kind: TickKind,
+        use_rth: bool,
+    ) -> Result<HistoricalTickSeries> {
+        let mut series = HistoricalTickSeries::new(kind);
+        let mut cursor = start;
+        // Ticks at `cursor` taken from earlier pages.
+        let mut seen_at_cursor = 0;
+        while cursor <= end {
+            let mut page = self.historical_ticks_page(contract, cursor, kind, use_rth).await?;
+            let full = page.len() >= HISTORICAL_TICKS_PAGE_SIZE as usize;
+            page.skip_leading_at(cursor, seen_at_cursor);
+            let last = page.last_time();
+            series.extend_until(page, end);
+            match last {
+                Some(last) if full => {
+                    seen_at_cursor = series.trailing_at(last);
+                    cursor = last;
+                }
+                _ => break,
+            }
+        }
+        Ok(series)
+    }
+
+    /// One page of [`historical_ticks_all`](Self::historical_ticks_all):
+    /// the ticks of every response message up to the one marked `done`.
+    async fn historical_ticks_page(
+        &self,
+        contract: &Contract,
+        start: i64,
+        kind: TickKind,
+        use_rth: bool,
+    ) -> Result<HistoricalTickSeries> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .req_historical_ticks(
+                req_id,
+                contract,
+                &format_utc_date_time(start),
+                "",
+                HISTORICAL_TICKS_PAGE_SIZE,
+                kind.what_to_show(),
+                use_rth,
+                false,
+                &[],
+            )
+            .await;
+        if let Err(e) = sent {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
+
+        let mut page = HistoricalTickSeries::new(kind);
+        self.await_response(req_id, rx, "historical ticks", |event| {
+            let (ticks, done) = match event {
+                IBEvent::HistoricalTicks { ticks, done, .. } => {
+                    (HistoricalTickSeries::Midpoint(ticks), done)
+                }
+                IBEvent::HistoricalTicksBidAsk { ticks, done, .. } => {
+                    (HistoricalTickSeries::BidAsk(ticks), done)
+                }
+                IBEvent::HistoricalTicksLast { ticks, done, .. } => {
+                    (HistoricalTickSeries::Trades(ticks), done)
+                }
+                event @ IBEvent::Error { .. } => return event.api_error().map(Err),
+                _ => return None,
+            };
+            page.extend_until(ticks, i64::MAX);
+            done.then(|| Ok(std::mem::replace(&mut page, HistoricalTickSeries::new(kind))))
+        })
+        .await
+    }
+
+    /// Request the SMART routing components for `bbo_exchange` and wait for
+    /// the result.
+    ///
+    /// `bbo_exchange` is the value reported by `TickReqParams` for a market
+    /// data request. Wrap the result in [`SmartComponents`] to resolve
+    /// exchange bitmasks or tick exchange letters.
+    ///
+    /// [`SmartComponents`]: crate::models::common::SmartComponents
+    pub async fn smart_components(&self, bbo_exchange: &str) -> Result<Vec<SmartComponent>> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        if let Err(e) = self.req_smart_components(req_id, bbo_exchange).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
+
+        self.await_response(req_id, rx, "smart components", |event| match event {
+            IBEvent::SmartComponents { components, .. } => Some(Ok(components)),
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Request an account summary and wait for the complete set of rows.
+    ///
+    /// Sends `REQ_ACCOUNT_SUMMARY` for `group` (usually `"All"`) and `tags`
+    /// (comma-separated, see [`models::account::tags`](crate::models::account::tags)),
+    /// collects every `AccountSummary` row keyed by account until
+    /// `AccountSummaryEnd`, then sends `CANCEL_ACCOUNT_SUMMARY` -- TWS would
+    /// otherwise keep streaming updates and count the request against its
+    /// limit of two active account summaries.
+    pub async fn account_summary(&self, group: &str, tags: &str) -> Result<AccountSummary> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        if let Err(e) = self.req_account_summary(req_id, group, tags).await {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
+
+        let mut summary = AccountSummary::default();
+        let result = self
+            .await_response(req_id, rx, "account summary", |event| match event {
+                IBEvent::AccountSummary { account, tag, value, currency, .. } => {
+                    summary.insert(account, tag, value, currency);
+                    None
+                }
+                IBEvent::AccountSummaryEnd { .. } => Some(Ok(std::mem::take(&mut summary))),
+                event @ IBEvent::Error { .. } => event.api_error().map(Err),
+                _ => None,
+            })
+            .await;
+        let cancelled = self.cancel_account_summary(req_id).await;
+        let summary = result?;
+        cancelled?;
+        Ok(summary)
+    }
+
+    /// Run a market scan once and return its results, ordered by rank.
+    ///
+    /// Sends `REQ_SCANNER_SUBSCRIPTION` and waits for the first
+    /// `ScannerData` message -- TWS delivers every rank of one scan in a
+    /// single message -- then sends `CANCEL_SCANNER_SUBSCRIPTION`, since the
+    /// subscription would otherwise keep pushing refreshed results. Build
+    /// `subscription` with [`ScannerSubscriptionBuilder`](crate::ScannerSubscriptionBuilder).
+    pub async fn scan(
+        &self,
+        subscription: &ScannerSubscription,
+    ) -> Result<Vec<ScannerDataItem>> {
+        let ticker_id = self.next_collector_id();
+        let rx = self.pending.register(ticker_id);
+        if let Err(e) = self.req_scanner_subscription(ticker_id, subscription, &[], &[]).await {
+            self.pending.remove(ticker_id);
+            return Err(e);
+        }
+
+        let result = self
+            .await_response(ticker_id, rx, "scanner data", |event| match event {
+                IBEvent::ScannerData { mut items, .. } => {
+                    items.sort_by_key(|item| item.rank);
+                    Some(Ok(items))
+                }
+                event @ IBEvent::Error { .. } => event.api_error().map(Err),
+                _ => None,
+            })
+            .await;
+        let cancelled = self.cancel_scanner_subscription(ticker_id).await;
+        let items = result?;
+        cancelled?;
+        Ok(items)
+    }
+
+    /// Compute the implied volatility of an option and return the first result.
+    ///
+    /// Sends `REQ_CALC_IMPLIED_VOLAT` for `option_price` with the underlying
+    /// at `under_price` and resolves on the first `TickOptionComputation`
+    /// for the allocated request ID. TWS keeps recomputing until cancelled,
+    /// so `CANCEL_CALC_IMPLIED_VOLAT` is sent afterwards.
+    pub async fn implied_volatility(
+        &self,
+        contract: &Contract,
+        option_price: f64,
+        under_price: f64,
+    ) -> Result<OptionComputation> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .calculate_implied_volatility(req_id, contract, option_price, under_price, &[])
+            .await;
+        if let Err(e) = sent {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
+
+        let result = self.await_option_computation(req_id, rx, "implied volatility").await;
+        let cancelled = self.cancel_calculate_implied_volatility(req_id).await;
+        let computation = result?;
+        cancelled?;
+        Ok(computation)
+    }
+
+    /// Compute the theoretical price and greeks of an option and return the
+    /// first result.
+    ///
+    /// Same as [`implied_volatility`](Self::implied_volatility), starting
+    /// from `volatility` instead of a price: sends `REQ_CALC_OPTION_PRICE`,
+    /// then `CANCEL_CALC_OPTION_PRICE`.
+    pub async fn option_price(
+        &self,
+        contract: &Contract,
+        volatility: f64,
+        under_price: f64,
+    ) -> Result<OptionComputation> {
+        let req_id = self.next_collector_id();
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .calculate_option_price(req_id, contract, volatility, under_price, &[])
+            .await;
+        if let Err(e) = sent {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
+
+        let result = self.await_option_computation(req_id, rx, "option price").await;
+        let cancelled = self.cancel_calculate_option_price(req_id).await;
+        let computation = result?;
+        cancelled?;
+        Ok(computation)
+    }
+
+    /// Wait for the first `TickOptionComputation` of a calculation request.
+    async fn await_option_computation(
+        &self,
+        req_id: i32,
+        rx: mpsc::UnboundedReceiver<IBEvent>,
+        what: &str,
+    ) -> Result<OptionComputation> {
+        self.await_response(req_id, rx, what, |event| match event {
+            IBEvent::TickOptionComputation {
+                tick_type,
+                tick_attrib,
+                implied_vol,
+                delta,
+                opt_price,
+                pv_dividend,
+                gamma,
+                vega,
+                theta,
+                und_price,
+                ..
+            } => Some(Ok(OptionComputation {
+                tick_type,
+                price_based: tick_attrib == 1,
+                implied_vol,
+                delta,
+                opt_price,
+                pv_dividend,
+                gamma,
+                vega,
+                theta,
+                und_price,
+            })),
+            event @ IBEvent::Error { .. } => event.api_error().map(Err),
+            _ => None,
+        })
+        .await
+    }
+
+    /// Place an order and wait until TWS acknowledges it.
+    ///
+    /// Sends the order like [`place_order`](Self::place_order), then waits
+    /// for the first `OpenOrder` or `OrderStatus` for `id`. A what-if order
+    /// is answered by an `OpenOrder` with its margin impact and no status,
+    /// so that also resolves the call. Fails with `IBApiError::Server` if
+    /// TWS rejects the order, or `IBApiError::Timeout` if nothing arrives
+    /// within [`request_timeout`](Self::request_timeout).
+    ///
+    /// The returned [`OrderHandle`] yields the later events for the order.
+    /// As with `place_order`, a negative `id` takes the next order ID.
+    pub async fn place_order_ack(
+        &self,
+        id: i64,
+        contract: &Contract,
+        order: &Order,
+    ) -> Result<OrderHandle> {
+        let id = if id < 0 { self.next_order_id() } else { id };
+        let mut rx = self.pending.register_order(id);
+        if let Err(e) = self.place_order(id, contract, order).await {
+            self.pending.remove_order(id);
+            return Err(e);
+        }
+
+        let what = format!("acknowledgement of order {id}");
+        let ack = self
+            .await_events(&mut rx, &what, |event| match event {
+                IBEvent::OpenOrder { .. } | IBEvent::OrderStatus { .. } => Some(Ok(event)),
+                event @ IBEvent::Error { .. } => event.api_error().map(Err),
+                _ => None,
+            })
+            .await;
+        match ack {
+            Ok(ack) => Ok(OrderHandle::new(id, ack, rx, self.pending.clone())),
+            Err(e) => {
+                self.pending.remove_order(id);
+                Err(e)
+            }
+        }
+    }
+
+    /// Start a market data stream and return a handle that yields only its ticks.
+    ///
+    /// Allocates a ticker ID and sends `REQ_MKT_DATA`. Tick events for that
+    /// ID are routed to the returned [`MarketDataSubscription`] instead of the
+    /// event channel; dropping the handle sends `CANCEL_MKT_DATA`.
+    pub async fn subscribe_mkt_data(
+        &self,
+        contract: &Contract,
+        generic_ticks: &str,
+        snapshot: bool,
+    ) -> Result<MarketDataSubscription> {
+        let ticker_id = self.next_collector_id();
+        let cancel_msg = self.encode_cancel_mkt_data(ticker_id).finalize()?.to_vec();
+        let replay = self.replay.clone();
+        let on_cancel: CancelFn = Box::new(move || {
+            replay.remove(ReplayKey::MktData(ticker_id));
+            Some(cancel_msg)
+        });
+        let rx = self.pending.register(ticker_id);
+        let sent = self
+            .req_mkt_data(ticker_id, contract, generic_ticks, snapshot, false, &[])
+            .await;
+        self.open_subscription(ticker_id, rx, sent, on_cancel)
+    }
+
+    /// Stream news headlines for `contract` and return a handle that yields
+    /// only its `TickNews` events.
+    ///
+    /// Sends `REQ_MKT_DATA` with the generic tick list `mdoff,292`, or
+    /// `mdoff,292:BRFG+DJNL` for `provider_codes` `["BRFG", "DJNL"]` (the form
+    /// broad tape contracts such as `BRF:BRF_ALL` on exchange `BRF` need).
+    /// `mdoff` turns off the regular ticks. Dropping the handle sends
+    /// `CANCEL_MKT_DATA`.
+    pub async fn subscribe_tick_news(
+        &self,
+        contract: &Contract,
+        provider_codes: &[&str],
+    ) -> Result<NewsSubscription> {
+        let generic_ticks = match provider_codes {
+            [] => "mdoff,292".to_string(),
+            codes => format!("mdoff,292:{}", codes.join("+")),
+        };
+        let sub = self.subscribe_mkt_data(contract, &generic_ticks, false).await?;
+        Ok(NewsSubscription::new(sub))
+    }
+
+    /// Start a market depth stream; dropping the handle sends `CANCEL_MKT_DEPTH`.
+    pub async fn subscribe_mkt_depth(
+        &self,
+        contract: &Contract,
+        num_rows: i32,
+        is_smart_depth: bool,
+        mkt_depth_options: &[TagValue],
+    ) -> Result<Subscription> {
+        let ticker_id = self.next_collector_id();
+        let cancel_msg =
+            self.encode_cancel_mkt_depth(ticker_id, is_smart_depth).finalize()?.to_vec();
+        let depth_flags = self.depth_flags.clone();
+        let on_cancel: CancelFn = Box::new(move || {
+            depth_flags.remove(ticker_id);
+            Some(cancel_msg)
+        });
+        let rx = self.pending.register(ticker_id);
+        let sent = self
+            .req_mkt_depth(ticker_id, contract, num_rows, is_smart_depth, mkt_depth_options)
+            .await;
+        self.open_subscription(ticker_id, rx, sent, on_cancel)
+    }
+
+    /// Start a real-time bars stream; dropping the handle sends
+    /// `CANCEL_REAL_TIME_BARS`.
+    pub async fn subscribe_real_time_bars(
+        &self,
+        contract: &Contract,
+        bar_size: i32,
+        what_to_show: &str,
+        use_rth: bool,
+        real_time_bars_options: &[TagValue],
+    ) -> Result<Subscription> {
+        let ticker_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_real_time_bars(ticker_id))?;
+        let rx = self.pending.register(ticker_id);
+        let sent = self
+            .req_real_time_bars(
+                ticker_id,
+                contract,
+                bar_size,
+                what_to_show,
+                use_rth,
+                real_time_bars_options,
+            )
+            .await;
+        self.open_subscription(ticker_id, rx, sent, on_cancel)
+    }
+
+    /// Start a historical data stream that keeps up to date.
+    ///
+    /// Sends `REQ_HISTORICAL_DATA` with `keep_up_to_date = true`, an empty
+    /// end date (required by TWS for streaming requests) and dates formatted
+    /// as `yyyyMMdd HH:mm:ss`. The returned [`HistoricalSubscription`] yields
+    /// the initial series once it is complete, then each update bar; dropping
+    /// it sends `CANCEL_HISTORICAL_DATA`.
+    pub async fn subscribe_historical_data(
+        &self,
+        contract: &Contract,
+        duration: impl Into<String>,
+        bar_size: impl Into<BarSize>,
+        what_to_show: impl Into<WhatToShow>,
+        use_rth: bool,
+        chart_options: &[TagValue],
+    ) -> Result<HistoricalSubscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_historical_data(req_id))?;
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .req_historical_data(
+                req_id,
+                contract,
+                "",
+                duration,
+                bar_size,
+                what_to_show,
+                use_rth,
+                1,
+                true,
+                chart_options,
+            )
+            .await;
+        let sub = self.open_subscription(req_id, rx, sent, on_cancel)?;
+        let separate_end = self.server_version() >= server_version::HISTORICAL_DATA_END;
+        Ok(HistoricalSubscription::new(sub, separate_end))
+    }
+
+    /// Start a tick-by-tick stream; dropping the handle sends
+    /// `CANCEL_TICK_BY_TICK_DATA`.
+    pub async fn subscribe_tick_by_tick(
+        &self,
+        contract: &Contract,
+        tick_type: &str,
+        number_of_ticks: i32,
+        ignore_size: bool,
+    ) -> Result<Subscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_tick_by_tick_data(req_id)?)?;
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .req_tick_by_tick_data(req_id, contract, tick_type, number_of_ticks, ignore_size)
+            .await;
+        self.open_subscription(req_id, rx, sent, on_cancel)
+    }
+
+    /// Start a scanner subscription; dropping the handle sends
+    /// `CANCEL_SCANNER_SUBSCRIPTION`.
+    pub async fn subscribe_scanner(
+        &self,
+        subscription: &ScannerSubscription,
+        scanner_subscription_options: &[TagValue],
+        scanner_subscription_filter_options: &[TagValue],
+    ) -> Result<Subscription> {
+        let ticker_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_scanner_subscription(ticker_id))?;
+        let rx = self.pending.register(ticker_id);
+        let sent = self
+            .req_scanner_subscription(
+                ticker_id,
+                subscription,
+                scanner_subscription_options,
+                scanner_subscription_filter_options,
+            )
+            .await;
+        self.open_subscription(ticker_id, rx, sent, on_cancel)
+    }
+
+    /// Subscribe to account P&L; dropping the handle sends `CANCEL_PNL`.
+    pub async fn subscribe_pnl(
+        &self,
+        account: &str,
+        model_code: &str,
+    ) -> Result<PnlSubscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_pnl(req_id)?)?;
+        let rx = self.pending.register(req_id);
+        let sent = self.req_pnl(req_id, account, model_code).await;
+        self.open_subscription(req_id, rx, sent, on_cancel).map(PnlSubscription::new)
+    }
+
+    /// Subscribe to single-position P&L; dropping the handle sends
+    /// `CANCEL_PNL_SINGLE`.
+    pub async fn subscribe_pnl_single(
+        &self,
+        account: &str,
+        model_code: &str,
+        con_id: i64,
+    ) -> Result<PnlSubscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_pnl_single(req_id)?)?;
+        let rx = self.pending.register(req_id);
+        let sent = self.req_pnl_single(req_id, account, model_code, con_id).await;
+        self.open_subscription(req_id, rx, sent, on_cancel).map(PnlSubscription::new)
+    }
+
+    /// Subscribe to positions for an account/model; dropping the handle sends
+    /// `CANCEL_POSITIONS_MULTI`.
+    pub async fn subscribe_positions_multi(
+        &self,
+        account: &str,
+        model_code: &str,
+    ) -> Result<Subscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_positions_multi(req_id)?)?;
+        let rx = self.pending.register(req_id);
+        let sent = self.req_positions_multi(req_id, account, model_code).await;
+        self.open_subscription(req_id, rx, sent, on_cancel)
+    }
+
+    /// Stream implied volatility computations for `option_price` with the
+    /// underlying at `under_price`.
+    ///
+    /// TWS recomputes until cancelled, sending a `TickOptionComputation` for
+    /// each result; dropping the handle sends `CANCEL_CALC_IMPLIED_VOLAT`.
+    /// See [`implied_volatility`](Self::implied_volatility) for a one-shot
+    /// result.
+    pub async fn subscribe_implied_volatility(
+        &self,
+        contract: &Contract,
+        option_price: f64,
+        under_price: f64,
+        misc_options: &[TagValue],
+    ) -> Result<CalcSubscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_calculate_implied_volatility(req_id)?)?;
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .calculate_implied_volatility(req_id, contract, option_price, under_price, misc_options)
+            .await;
+        self.open_subscription(req_id, rx, sent, on_cancel)
+    }
+
+    /// Stream option price and greeks computations for `volatility` with the
+    /// underlying at `under_price`; dropping the handle sends
+    /// `CANCEL_CALC_OPTION_PRICE`. See [`option_price`](Self::option_price)
+    /// for a one-shot result.
+    pub async fn subscribe_option_price(
+        &self,
+        contract: &Contract,
+        volatility: f64,
+        under_price: f64,
+        misc_options: &[TagValue],
+    ) -> Result<CalcSubscription> {
+        let req_id = self.next_collector_id();
+        let on_cancel = cancel_with(self.encode_cancel_calculate_option_price(req_id)?)?;
+        let rx = self.pending.register(req_id);
+        let sent = self
+            .calculate_option_price(req_id, contract, volatility, under_price, misc_options)
+            .await;
+        self.open_subscription(req_id, rx, sent, on_cancel)
+    }
+
+    /// Wrap a registered request in a [`Subscription`] once its request has
+    /// been sent, or unregister it if sending failed.
+    fn open_subscription(
+        &self,
+        req_id: i32,
+        rx: mpsc::UnboundedReceiver<IBEvent>,
+        sent: Result<()>,
+        on_cancel: CancelFn,
+    ) -> Result<Subscription> {
+        if let Err(e) = sent {
+            self.pending.remove(req_id);
+            return Err(e);
+        }
+        Ok(Subscription::new(
+            req_id,
+            rx,
+            self.pending.clone(),
+            self.writer.clone(),
+            on_cancel,
+        ))
+    }
+
+    // ========================================================================
+    // Private: Protobuf Encoding
+    // ========================================================================
+
+    async fn place_order_protobuf(
+        &self,
+        id: i64,
+        contract: &Contract,
+        order: &Order,
+    ) -> Result<()> {
+        use prost::Message;
+        for leg in contract.combo_legs.iter().flatten() {
+            leg.wire_ratio(self.server_version())?;
+        }
+        let request = crate::proto_encode::build_place_order_request(id, contract, order);
+        let mut enc = self.encoder();
+        enc.encode_raw_int(outgoing::PLACE_ORDER + outgoing::PROTOBUF_MSG_ID);
+        enc.write_raw(&request.encode_to_vec());
+        self.send_encoded(enc).await
+    }
+
+    async fn cancel_order_protobuf(
+        &self,
+        id: i64,
+        order_cancel: &OrderCancel,
+    ) -> Result<()> {
+        use prost::Message;
+        let request = crate::proto_encode::build_cancel_order_request(id, order_cancel);
+        let mut enc = self.encoder();
+        enc.encode_raw_int(outgoing::CANCEL_ORDER + outgoing::PROTOBUF_MSG_ID);
+        enc.write_raw(&request.encode_to_vec());
+        self.send_encoded(enc).await
+    }
+
+    async fn req_executions_protobuf(
+        &self,
+        req_id: i32,
+        filter: &ExecutionFilter,
+    ) -> Result<()> {
+        use prost::Message;
+        let request = crate::proto_encode::build_execution_request(req_id, filter);
+        let mut enc = self.encoder();
+        enc.encode_raw_int(outgoing::REQ_EXECUTIONS + outgoing::PROTOBUF_MSG_ID);
+        enc.write_raw(&request.encode_to_vec());
+        self.send_encoded(enc).await
+    }
+
+    async fn req_global_cancel_protobuf(&self, order_cancel: &OrderCancel) -> Result<()> {
+        use prost::Message;
+        let request = crate::proto_encode::build_global_cancel_request(order_cancel);
+        let mut enc = self.encoder();
+        enc.encode_raw_int(outgoing::REQ_GLOBAL_CANCEL + outgoing::PROTOBUF_MSG_ID);
+        enc.write_raw(&request.encode_to_vec());
+        self.send_encoded(enc).await
+    }
+}
+
+/// Cancel closure that just sends the pre-encoded `cancel` message.
+fn cancel_with(cancel: MessageEncoder) -> Result<CancelFn> {
+    let msg = cancel.finalize()?.to_vec();
+    Ok(Box::new(move || Some(msg)))
+}
+
+/// Encode an order condition to the wire format.
+fn encode_condition(enc: &mut MessageEncoder, cond: &OrderCondition) {
+    match cond {
+        OrderCondition::Price {
+            is_conjunction_connection,
+            is_more,
+            con_id,
+            exchange,
+            price,
+            trigger_method,
+        } => {
+            enc.encode_field_i32(1); // Price type
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            enc.encode_field_bool(*is_more);
+            enc.encode_field_i32(*con_id);
+            enc.encode_field_str(exchange);
+            enc.encode_field_f64(*price);
+            enc.encode_field_i32(*trigger_method as i32);
+        }
+        OrderCondition::Time {
+            is_conjunction_connection,
+            is_more,
+            time,
+        } => {
+            enc.encode_field_i32(3); // Time type
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            enc.encode_field_bool(*is_more);
+            enc.encode_field_str(time);
+        }
+        OrderCondition::Margin {
+            is_conjunction_connection,
+            is_more,
+            percent,
+        } => {
+            enc.encode_field_i32(4); // Margin type
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            enc.encode_field_bool(*is_more);
+            enc.encode_field_i32(*percent);
+        }
+        OrderCondition::Execution {
+            is_conjunction_connection,
+            exchange,
+            sec_type,
+            symbol,
+        } => {
+            enc.encode_field_i32(5); // Execution type
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            enc.encode_field_str(sec_type);
+            enc.encode_field_str(exchange);
+            enc.encode_field_str(symbol);
+        }
+        OrderCondition::Volume {
+            is_conjunction_connection,
+            is_more,
+            con_id,
+            exchange,
+            volume,
+        } => {
+            enc.encode_field_i32(6); // Volume type
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            enc.encode_field_bool(*is_more);
+            enc.encode_field_i32(*con_id);
+            enc.encode_field_str(exchange);
+            enc.encode_field_i32(*volume);
+        }
+        OrderCondition::PercentChange {
+            is_conjunction_connection,
+            is_more,
+            con_id,
+            exchange,
+            change_percent,
+        } => {
+            enc.encode_field_i32(7); // PercentChange type
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            enc.encode_field_bool(*is_more);
+            enc.encode_field_i32(*con_id);
+            enc.encode_field_str(exchange);
+            enc.encode_field_max_f64(*change_percent);
+        }
+        OrderCondition::Unknown { is_conjunction_connection, raw_type, fields } => {
+            enc.encode_field_i32(*raw_type);
+            enc.encode_field_str(if *is_conjunction_connection { "a" } else { "o" });
+            for field in fields {
+                enc.encode_field_str(field);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::IbErrorCode;
+    use crate::models::contract::ComboLeg;
+    use crate::models::enums::{Action, OrderType, SecType};
+    use crate::subscription::HistoricalUpdate;
+    use crate::testutil::{frame, MockTws};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    /// Create a mock TWS server that performs handshake, reads start_api,
+    /// sends the given messages and closes the connection.
+    async fn mock_tws(sv: i32, messages: Vec<Vec<u8>>) -> u16 {
+        let builder = MockTws::builder().server_version(sv).close_after_messages();
+        messages.into_iter().fold(builder, |b, msg| b.message(msg)).start().await.port()
+    }
+
+    /// Read one length-prefixed frame from the client.
+    async fn read_frame(stream: &mut TcpStream) -> Option<Vec<u8>> {
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).await.ok()?;
+        let mut body = vec![0u8; u32::from_be_bytes(len) as usize];
+        stream.read_exact(&mut body).await.ok()?;
+        Some(body)
+    }
+
+    /// Split a frame body into its null-terminated text fields.
+    fn frame_fields(body: &[u8]) -> Vec<String> {
+        body.split(|b| *b == 0)
+            .filter(|f| !f.is_empty())
+            .map(|f| String::from_utf8_lossy(f).into_owned())
+            .collect()
+    }
+
+    /// Create a mock TWS server that performs the handshake and then answers
+    /// every request frame with the messages returned by `respond`.
+    async fn mock_tws_responding<F>(sv: i32, respond: F) -> u16
+    where
+        F: Fn(&[String]) -> Vec<Vec<u8>> + Send + 'static,
+    {
+        mock_tws_responding_raw(sv, move |body| respond(&frame_fields(body))).await
+    }
+
+    /// Like `mock_tws_responding`, but hands `respond` the raw frame body,
+    /// for requests with a binary (raw int or protobuf) encoding.
+    async fn mock_tws_responding_raw<F>(sv: i32, respond: F) -> u16
+    where
+        F: Fn(&[u8]) -> Vec<Vec<u8>> + Send + 'static,
+    {
+        MockTws::builder().server_version(sv).respond_raw(respond).start().await.port()
+    }
+
+    /// CONTRACT_DATA (10) for a plain stock at server version 176.
+    fn contract_data_msg(req_id: &str, symbol: &str, con_id: &str) -> Vec<u8> {
+        frame(&[
+            "10", req_id, symbol, "STK", "", "", "", "SMART", "USD", symbol,
+            "NMS", "NMS", con_id, "0.01", "", "ACTIVETIM,LMT,MKT", "SMART,NASDAQ",
+            "1", "0", "APPLE INC", "NASDAQ", "", "Technology", "Computers", "Computers",
+            "US/Eastern", "", "", "", "", "0", "1", "", "", "26,26", "", "COMMON",
+            "0.0001", "0.0001", "100",
+        ])
+    }
+
+    #[cfg(feature = "xml")]
+    #[tokio::test]
+    async fn client_validate_scanner_subscription_fetches_parameters_once() {
+        use std::sync::atomic::AtomicUsize;
+
+        const XML: &str = "<ScanParameterResponse>\
+            <InstrumentList><Instrument><name>US Stocks</name><type>STK</type></Instrument>\
+            </InstrumentList>\
+            <LocationTree><Location><locationCode>STK.US.MAJOR</locationCode>\
+            <instruments>STK</instruments></Location></LocationTree>\
+            <ScanTypeList><ScanType><scanCode>TOP_PERC_GAIN</scanCode>\
+            <instruments>STK</instruments></ScanType></ScanTypeList>\
+            </ScanParameterResponse>";
+        let requests = Arc::new(AtomicUsize::new(0));
+        let seen = requests.clone();
+        let port = mock_tws_responding(176, move |fields| {
+            // REQ_SCANNER_PARAMETERS: msg_id=24, version=1
+            assert_eq!(fields[0], "24");
+            seen.fetch_add(1, Ordering::Relaxed);
+            vec![frame(&["19", "1", XML])] // SCANNER_PARAMETERS
+        })
+        .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+
+        let valid = ScannerSubscription {
+            instrument: "STK".into(),
+            location_code: "STK.US.MAJOR".into(),
+            scan_code: "TOP_PERC_GAIN".into(),
+            ..ScannerSubscription::default()
+        };
+        client.validate_scanner_subscription(&valid).await.unwrap();
+        let invalid = ScannerSubscription { scan_code: "TOP_PERC_GIAN".into(), ..valid };
+        match client.validate_scanner_subscription(&invalid).await {
+            Err(IBApiError::Encoding(msg)) => {
+                assert!(msg.contains("TOP_PERC_GIAN") && msg.contains("TOP_PERC_GAIN"), "{msg}")
+            }
+            other => panic!("expected Encoding error, got {other:?}"),
+        }
+        assert_eq!(client.scanner_parameters().await.unwrap().scan_types.len(), 1);
+        assert_eq!(requests.load(Ordering::Relaxed), 1, "parameters are cached");
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_connect_and_receive_events() {
+        let messages = vec![
+            frame(&["9", "1", "100"]),   // NEXT_VALID_ID
+            frame(&["15", "1", "DU123"]), // MANAGED_ACCTS
+        ];
+
+        let port = mock_tws(176, messages).await;
+
+        let (client, mut rx) =
+            IBClient::connect("127.0.0.1", port, 0, None)
+                .await
+                .unwrap();
+
+        assert!(client.is_connected());
+        assert_eq!(client.server_version(), 176);
+        assert_eq!(client.client_id(), 0);
+
+        // Receive events
+        let event1 = rx.recv().await.unwrap();
+        match event1 {
+            IBEvent::NextValidId { order_id } => assert_eq!(order_id, 100),
+            other => panic!("expected NextValidId, got {other:?}"),
+        }
+
+        let event2 = rx.recv().await.unwrap();
+        match event2 {
+            IBEvent::ManagedAccounts { accounts } => assert_eq!(accounts, "DU123"),
+            other => panic!("expected ManagedAccounts, got {other:?}"),
+        }
+
+        client.disconnect().await;
+        assert!(!client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn client_next_order_id_follows_next_valid_id() {
+        let mut tws = MockTws::start(176).await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        tws.push_event(frame(&["9", "1", "500"])); // NEXT_VALID_ID
+        assert!(matches!(rx.recv().await, Some(IBEvent::NextValidId { order_id: 500 })));
+        assert_eq!(client.next_order_id(), 500);
+        assert_eq!(client.next_order_id(), 501);
+        assert_eq!(client.next_req_id(), 1, "request ids are a separate sequence");
+
+        // A stale NextValidId never moves the sequence backwards.
+        tws.push_event(frame(&["9", "1", "400"]));
+        assert!(matches!(rx.recv().await, Some(IBEvent::NextValidId { order_id: 400 })));
+        assert_eq!(client.next_order_id(), 502);
+        tws.push_event(frame(&["9", "1", "1000"]));
+        assert!(matches!(rx.recv().await, Some(IBEvent::NextValidId { order_id: 1000 })));
+
+        // A negative id is auto-assigned.
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::ONE),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
+        let id = client.place_order(-1, &Contract::stock("AAPL"), &order).await.unwrap();
+        assert_eq!(id, 1000);
+        assert_eq!(tws.expect_message().await[..2], ["3", "1000"]); // PLACE_ORDER, id
+        assert_eq!(client.place_order(7, &Contract::stock("AAPL"), &order).await.unwrap(), 7);
+        assert_eq!(client.next_order_id(), 1001);
+    }
+
+    #[tokio::test]
+    async fn client_place_order_validates_when_enabled() {
+        use crate::models::order::OrderBuilder;
+
+        let mut tws = MockTws::start(176).await;
+        let (mut client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        let contract = Contract::stock("AAPL");
+        let order = Order { lmt_price: None, ..OrderBuilder::limit(Action::Buy, 1, 1.0).build() };
+
+        assert!(!client.validate_orders());
+        client.set_validate_orders(true);
+        match client.place_order(7, &contract, &order).await {
+            Err(IBApiError::Encoding(msg)) => {
+                assert_eq!(msg, "invalid order: LMT orders need lmt_price")
+            }
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+
+        // Not sent: the next message TWS sees is the valid order.
+        let order = OrderBuilder::limit(Action::Buy, 1, 1.0).build();
+        assert_eq!(client.place_order(8, &contract, &order).await.unwrap(), 8);
+        assert_eq!(tws.expect_message().await[..2], ["3", "8"]);
+    }
+
+    #[tokio::test]
+    async fn client_connect_tolerates_data_farm_messages_before_next_valid_id() {
+        let messages = || {
+            vec![
+                frame(&["4", "2", "-1", "2104", "Market data farm connection is OK:usfarm", ""]),
+                frame(&["4", "2", "-1", "2106", "HMDS data farm connection is OK:ushmds", ""]),
+                frame(&["9", "1", "42"]), // NEXT_VALID_ID
+                frame(&["15", "1", "DU123"]), // MANAGED_ACCTS
+            ]
+        };
+
+        // connect: the info messages arrive as non-fatal Error events first.
+        let port = mock_tws(176, messages()).await;
+        let (_client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+        for expected in [2104, 2106] {
+            match rx.recv().await.unwrap() {
+                event @ IBEvent::Error { code, .. } if code == expected => {
+                    assert!(event.api_error().is_none(), "{code} is informational")
+                }
+                other => panic!("expected Error {expected}, got {other:?}"),
+            }
+        }
+        assert!(matches!(rx.recv().await, Some(IBEvent::NextValidId { order_id: 42 })));
+        assert!(matches!(rx.recv().await, Some(IBEvent::ManagedAccounts { .. })));
+
+        // connect_ready: skips them and still becomes ready.
+        let port = mock_tws(176, messages()).await;
+        let (client, _rx) =
+            IBClient::connect_ready("127.0.0.1", port, 0, None, Duration::from_secs(5))
+                .await
+                .unwrap();
+        assert_eq!(client.next_order_id(), 42);
+        assert_eq!(client.managed_accounts(), ["DU123"]);
+    }
+
+    #[tokio::test]
+    async fn client_optional_capabilities_round_trip_through_handshake() {
+        use crate::testutil::MockTws;
+
+        // The server acknowledges a subset of what was sent.
+        let tws = MockTws::builder().optional_capabilities("+PACEAPI").start().await;
+        let (client, _rx) =
+            IBClient::connect("127.0.0.1", tws.port(), 0, Some("+PACEAPI +FOO")).await.unwrap();
+        assert_eq!(client.optional_capabilities(), "+PACEAPI");
+        assert_eq!(client.handle().optional_capabilities(), "+PACEAPI");
+
+        // No echo: what was sent is in effect.
+        let tws = MockTws::start(176).await;
+        let (client, _rx) =
+            IBClient::connect("127.0.0.1", tws.port(), 0, Some("+PACEAPI")).await.unwrap();
+        assert_eq!(client.optional_capabilities(), "+PACEAPI");
+        assert_eq!(client.tws_time(), "20260101 12:00:00");
+
+        let tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        assert_eq!(client.optional_capabilities(), "");
+    }
+
+    #[tokio::test]
+    async fn client_connect_ready_starts_order_ids_at_next_valid_id() {
+        let messages = vec![
+            frame(&["15", "1", "DU123,DU456"]), // MANAGED_ACCTS
+            frame(&["4", "2", "-1", "2104", "Market data farm connection is OK:usfarm", ""]),
+            frame(&["9", "1", "500"]), // NEXT_VALID_ID
+            frame(&["49", "1", "1767225600"]), // CURRENT_TIME, after the two
+        ];
+        let port = mock_tws(176, messages).await;
+
+        let timeout = Duration::from_secs(5);
+        let (client, mut rx) =
+            IBClient::connect_ready("127.0.0.1", port, 0, None, timeout).await.unwrap();
+        assert_eq!(client.next_order_id(), 500);
+        assert_eq!(client.next_req_id(), 1);
+        assert_eq!(client.managed_accounts(), ["DU123", "DU456"]);
+        assert!(matches!(rx.recv().await, Some(IBEvent::CurrentTime { time: 1767225600 })));
+
+        // No NextValidId on an open connection: times out.
+        let tws = MockTws::builder().message(frame(&["15", "1", "DU123"])).start().await;
+        let timeout = Duration::from_millis(100);
+        let result = IBClient::connect_ready("127.0.0.1", tws.port(), 0, None, timeout).await;
+        assert!(matches!(result, Err(IBApiError::Timeout(_))));
+
+        // Closed before NextValidId.
+        let port = mock_tws(176, vec![frame(&["15", "1", "DU123"])]).await;
+        let result =
+            IBClient::connect_ready("127.0.0.1", port, 0, None, Duration::from_secs(5)).await;
+        assert!(matches!(result, Err(IBApiError::Disconnected(_))));
+    }
+
+    #[tokio::test]
+    async fn client_next_req_id() {
+        let port = mock_tws(176, vec![]).await;
+
+        let (client, _rx) =
+            IBClient::connect("127.0.0.1", port, 0, None)
+                .await
+                .unwrap();
+
+        assert_eq!(client.next_req_id(), 1);
+        assert_eq!(client.next_req_id(), 2);
+        assert_eq!(client.next_req_id(), 3);
+
+        client.set_next_req_id(100);
+        assert_eq!(client.next_req_id(), 100);
+        assert_eq!(client.next_req_id(), 101);
+    }
+
+    #[tokio::test]
+    async fn client_req_current_time() {
+        let mut tws = MockTws::builder()
+            .respond(|fields| match fields[0].as_str() {
+                "49" => vec![frame(&["49", "1", "1708876800"])], // CURRENT_TIME
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+
+        let (client, mut rx) =
+            IBClient::connect("127.0.0.1", tws.port(), 0, None)
+                .await
+                .unwrap();
+
+        client.req_current_time().await.unwrap();
+        assert_eq!(tws.expect_message().await, ["49", "1"]);
+
+        // Receive the response
+        let event = rx.recv().await.unwrap();
+        match event {
+            IBEvent::CurrentTime { time } => assert_eq!(time, 1708876800),
+            other => panic!("expected CurrentTime, got {other:?}"),
+        }
+        assert_eq!(tws.sent_messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn client_current_time_millis_falls_back_to_seconds() {
+        let respond = |fields: &[String]| match fields[0].as_str() {
+            "105" => vec![frame(&["109", "1708876800123"])], // CURRENT_TIME_IN_MILLIS
+            "49" => vec![frame(&["49", "1", "1708876800"])],  // CURRENT_TIME
+            _ => Vec::new(),
+        };
+
+        let mut tws = MockTws::builder().server_version(200).respond(respond).start().await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        assert_eq!(client.current_time_millis().await.unwrap(), 1708876800123);
+        assert_eq!(tws.expect_message().await, ["105"]);
+
+        let mut tws = MockTws::builder().server_version(176).respond(respond).start().await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        assert_eq!(client.current_time_millis().await.unwrap(), 1708876800000);
+        assert_eq!(tws.expect_message().await, ["49", "1"]);
+
+        // Only the answer is routed to the collector; later ones reach the channel.
+        client.req_current_time().await.unwrap();
+        assert!(matches!(rx.recv().await, Some(IBEvent::CurrentTime { time: 1708876800 })));
+    }
+
+    #[tokio::test]
+    async fn client_current_time_millis_ignores_seconds_answers() {
+        // A keepalive probe's CURRENT_TIME answer arrives ahead of ours.
+        let tws = MockTws::builder()
+            .server_version(200)
+            .respond(|fields| match fields[0].as_str() {
+                "105" => vec![frame(&["49", "1", "1708876800"]), frame(&["109", "1708876800123"])],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        assert_eq!(client.current_time_millis().await.unwrap(), 1708876800123);
+        assert!(matches!(rx.recv().await, Some(IBEvent::CurrentTime { time: 1708876800 })));
+    }
+
+    #[tokio::test]
+    async fn client_disconnect() {
+        let port = mock_tws(176, vec![]).await;
+
+        let (client, mut rx) =
+            IBClient::connect("127.0.0.1", port, 0, None)
+                .await
+                .unwrap();
+
+        assert!(client.is_connected());
+        client.disconnect().await;
+        assert!(!client.is_connected());
+
+        // Double disconnect is safe
+        client.disconnect().await;
+
+        // Channel should be closed (reader task exited)
+        // Drain any remaining events
+        while rx.recv().await.is_some() {}
+    }
+
+    #[tokio::test]
+    async fn client_connection_state_follows_server_close_and_disconnect() {
+        let tws = MockTws::builder().server_version(176).start().await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        let mut state = client.watch_connection_state();
+        assert_eq!(*state.borrow(), ConnectionState::Connected);
+
+        client.disconnect().await;
+        assert!(state.has_changed().unwrap());
+        assert_eq!(*state.borrow_and_update(), ConnectionState::Disconnected);
+
+        // Without reconnect, a server close also ends in Disconnected.
+        let port = mock_tws(176, vec![]).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+        let mut state = client.watch_connection_state();
+        tokio::time::timeout(
+            Duration::from_secs(2),
+            state.wait_for(|s| *s == ConnectionState::Disconnected),
+        )
+        .await
+        .expect("state never became Disconnected")
+        .unwrap();
+        assert!(!client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn client_keepalive_detects_silent_server() {
+        // Server completes the handshake, then never answers anything.
+        let port = mock_tws_responding(176, |_| vec![]).await;
+
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+        client.start_keepalive(Duration::from_millis(50));
+
+        let event = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("watchdog did not fire")
+            .unwrap();
+        assert!(matches!(event, IBEvent::ConnectionLost), "got {event:?}");
+        assert!(!client.is_connected());
+        assert_eq!(client.connection_state(), ConnectionState::Failed);
+
+        // The socket is still open; disconnect must not wait for EOF.
+        tokio::time::timeout(Duration::from_secs(2), client.disconnect())
+            .await
+            .expect("disconnect hung");
+    }
+
+    #[tokio::test]
+    async fn client_keepalive_stays_quiet_while_answered() {
+        let port = mock_tws_responding(176, |fields| {
+            assert_eq!(fields[0], "49"); // REQ_CURRENT_TIME
+            vec![frame(&["49", "1", "1708876800"])]
+        })
+        .await;
+
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+        client.start_keepalive(Duration::from_millis(50));
+
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(300);
+        while let Ok(Some(event)) = tokio::time::timeout_at(deadline, rx.recv()).await {
+            assert!(matches!(event, IBEvent::CurrentTime { .. }), "got {event:?}");
+        }
+        assert!(client.is_connected());
+        client.disconnect().await;
+    }
+
+    #[tokio::test]
+    async fn client_req_contract_details_async() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_CONTRACT_DATA: msg_id=9, version=8, req_id, ...
+            assert_eq!(fields[0], "9");
+            let req_id = fields[2].as_str();
+            vec![
+                contract_data_msg(req_id, "AAPL", "265598"),
+                frame(&["52", "1", req_id]), // CONTRACT_DATA_END
+            ]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let contract = Contract {
+            symbol: "AAPL".into(),
+            sec_type: Some(crate::models::enums::SecType::Stock),
+            exchange: "SMART".into(),
+            currency: "USD".into(),
+            ..Contract::default()
+        };
+        let details = client.req_contract_details_async(&contract).await.unwrap();
+
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].contract.symbol, "AAPL");
+        assert_eq!(details[0].contract.con_id, 265598);
+        assert_eq!(details[0].long_name, "APPLE INC");
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_async_requests_work_without_event_receiver() {
+        let port = mock_tws_responding(176, |fields| {
+            let req_id = fields[2].as_str();
+            vec![
+                // Unrouted event ahead of the response.
+                frame(&["4", "2", "-1", "2104", "Market data farm connection is OK", ""]),
+                contract_data_msg(req_id, "AAPL", "265598"),
+                frame(&["52", "1", req_id]), // CONTRACT_DATA_END
+            ]
+        })
+        .await;
+
+        let (client, rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+        drop(rx);
+
+        for _ in 0..2 {
+            let details = client.req_contract_details_async(&Contract::stock("AAPL")).await;
+            assert_eq!(details.unwrap()[0].contract.con_id, 265598);
+        }
+        assert!(client.is_connected());
+    }
+
+    #[tokio::test]
+    async fn client_contract_details_resolves_continuous_future_to_front_month() {
+        use crate::models::contract::ContractBuilder;
+        use crate::models::enums::SecType;
+
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_CONTRACT_DATA: msg_id=9, version=8, req_id, con_id, symbol, sec_type, ...
+            assert_eq!(fields[0], "9");
+            assert_eq!(fields[4..6], ["ES", "CONTFUT"]);
+            let req_id = fields[2].as_str();
+            vec![
+                frame(&[
+                    "10", req_id, "ES", "FUT", "20261218", "", "", "CME", "USD", "ESZ6",
+                    "ES", "ES", "649180695", "0.25", "50", "LMT,MKT,STP", "CME", "1", "0",
+                    "E-mini S&P 500", "", "202612", "", "", "", "US/Central", "", "", "",
+                    "", "0", "1", "", "", "67", "20261218", "", "1", "1", "1",
+                ]),
+                frame(&["52", "1", req_id]), // CONTRACT_DATA_END
+            ]
+        })
+        .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+
+        let contfut = ContractBuilder::new().continuous_future("ES", "CME").build().unwrap();
+        assert_eq!(contfut.sec_type, Some(SecType::ContinuousFuture));
+        let details = client.req_contract_details_async(&contfut).await.unwrap();
+
+        assert_eq!(details.len(), 1);
+        let front = &details[0].contract;
+        assert_eq!(front.sec_type, Some(SecType::Future));
+        assert_eq!(front.last_trade_date_or_contract_month, "20261218");
+        assert_eq!((front.con_id, front.local_symbol.as_str()), (649180695, "ESZ6"));
+        assert_eq!(details[0].contract_month, "202612");
+    }
+
+    #[tokio::test]
+    async fn client_req_contract_details_async_collects_ambiguous_results() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_CONTRACT_DATA: msg_id=9, version=8, req_id, ...
+            assert_eq!(fields[0], "9");
+            let req_id = fields[2].as_str();
+            vec![
+                contract_data_msg(req_id, "ABC", "1001"),
+                contract_data_msg(req_id, "ABC", "1002"),
+                contract_data_msg("999", "XYZ", "9999"), // someone else's request
+                contract_data_msg(req_id, "ABC", "1003"),
+                frame(&["52", "1", req_id]), // CONTRACT_DATA_END
+            ]
+        })
+        .await;
+
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let details = client.req_contract_details_async(&Contract::stock("ABC")).await.unwrap();
+        let con_ids: Vec<i64> = details.iter().map(|d| d.contract.con_id).collect();
+        assert_eq!(con_ids, [1001, 1002, 1003]);
+        assert!(details.iter().all(|d| d.contract.symbol == "ABC"));
+        assert!(client.pending_requests().is_empty());
+        match rx.recv().await.unwrap() {
+            IBEvent::ContractDetails { req_id: 999, details } => {
+                assert_eq!(details.contract.con_id, 9999)
+            }
+            other => panic!("expected the unrelated ContractDetails, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_req_contract_details_async_times_out() {
+        // Server never answers the request.
+        let port = mock_tws_responding(176, |_| Vec::new()).await;
+
+        let (mut client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+        client.set_request_timeout(Duration::from_millis(50));
+
+        let result = client.req_contract_details_async(&Contract::default()).await;
+        assert!(matches!(result, Err(IBApiError::Timeout(_))), "got {result:?}");
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_req_contract_details_async_rejects_on_error() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_CONTRACT_DATA: msg_id=9, version=8, req_id, ...
+            assert_eq!(fields[0], "9");
+            let req_id = fields[2].as_str();
+            // ERROR: version, id, code, message, advanced order reject JSON
+            vec![
+                frame(&["4", "2", "-1", "2104", "Market data farm is OK", ""]),
+                frame(&["4", "2", req_id, "2176", "Warning: fractional shares", ""]),
+                frame(&["4", "2", req_id, "200", "No security definition", ""]),
+            ]
+        })
+        .await;
+
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let result = client.req_contract_details_async(&Contract::stock("NOPE")).await;
+        match result {
+            Err(IBApiError::Server { id, code: 200, message, .. }) => {
+                assert!(id > 0);
+                assert_eq!(message, "No security definition");
+            }
+            other => panic!("expected error 200, got {other:?}"),
+        }
+        assert!(client.pending_requests().is_empty());
+
+        // The connection-level notice still reaches the event channel.
+        match rx.recv().await.unwrap() {
+            IBEvent::Error { req_id: -1, code: 2104, .. } => {}
+            other => panic!("expected connection-level Error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_subscribe_tick_news_yields_only_news() {
+        let mut tws = MockTws::builder()
+            .respond(|fields| {
+                // REQ_MKT_DATA: msg_id=1, version=11, ticker_id, ...
+                if fields[0] != "1" {
+                    return Vec::new();
+                }
+                let ticker_id = fields[2].as_str();
+                let news = |headline| {
+                    // TICK_NEWS (84): ticker_id, time, provider, article, headline, extra
+                    frame(&["84", ticker_id, "1767225600", "BRFG", "BRFG$1", headline, ""])
+                };
+                vec![
+                    frame(&["1", "6", ticker_id, "1", "101.5", "100", "0"]),
+                    news("First"),
+                    frame(&["4", "2", ticker_id, "10167", "Displaying delayed data", ""]),
+                    news("Second"),
+                    frame(&["4", "2", ticker_id, "354", "Not subscribed", ""]),
+                ]
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let mut news =
+            client.subscribe_tick_news(&Contract::stock("AAPL"), &["BRFG", "DJNL"]).await.unwrap();
+        let request = tws.expect_message().await;
+        assert!(request.iter().any(|f| f == "mdoff,292:BRFG+DJNL"), "{request:?}");
+
+        match news.recv().await.unwrap() {
+            IBEvent::TickNews { req_id, headline, .. } => {
+                assert_eq!(req_id, news.req_id());
+                assert_eq!(headline, "First");
+            }
+            other => panic!("expected TickNews, got {other:?}"),
+        }
+        let second = news.try_next().await.unwrap().unwrap();
+        assert!(matches!(second, IBEvent::TickNews { ref headline, .. } if headline == "Second"));
+        let err = news.try_next().await.unwrap_err();
+        assert!(matches!(err, IBApiError::Server { code: 354, .. }), "got {err:?}");
+
+        let ticker_id = news.req_id().to_string();
+        news.cancel().await.unwrap();
+        assert_eq!(tws.expect_message().await, ["2", "2", ticker_id.as_str()]);
+    }
+
+    #[tokio::test]
+    async fn client_subscription_try_next_yields_request_error() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_MKT_DATA: msg_id=1, version=11, ticker_id, ...
+            if fields[0] != "1" {
+                return Vec::new();
+            }
+            let ticker_id = fields[2].as_str();
+            vec![
+                frame(&["4", "2", ticker_id, "10167", "Displaying delayed data", ""]),
+                frame(&["4", "2", ticker_id, "354", "Not subscribed", ""]),
+            ]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let mut sub =
+            client.subscribe_mkt_data(&Contract::stock("AAPL"), "", false).await.unwrap();
+        let warning = sub.try_next().await.unwrap().unwrap();
+        assert!(matches!(warning, IBEvent::Error { code: 10167, .. }));
+        let err = sub.try_next().await.unwrap_err();
+        assert!(matches!(err, IBApiError::Server { code: 354, .. }), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn client_req_mkt_data_encodes_built_contract_like_literal() {
+        use crate::models::contract::ContractBuilder;
+        use crate::models::enums::{Right, SecType};
+
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<String>>();
+        let port = mock_tws_responding(176, move |fields| {
+            seen_tx.send(fields.to_vec()).unwrap();
+            Vec::new()
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let pairs = [
+            (
+                Contract::stock("AAPL"),
+                Contract {
+                    symbol: "AAPL".into(),
+                    sec_type: Some(SecType::Stock),
+                    exchange: "SMART".into(),
+                    currency: "USD".into(),
+                    ..Contract::default()
+                },
+            ),
+            (
+                ContractBuilder::new()
+                    .option("AAPL", "20260116", 200.0, Right::Put)
+                    .build()
+                    .unwrap(),
+                Contract {
+                    symbol: "AAPL".into(),
+                    sec_type: Some(SecType::Option),
+                    last_trade_date_or_contract_month: "20260116".into(),
+                    strike: Some(200.0),
+                    right: Some(Right::Put),
+                    multiplier: "100".into(),
+                    exchange: "SMART".into(),
+                    currency: "USD".into(),
+                    ..Contract::default()
+                },
+            ),
+        ];
+        for (built, literal) in pairs {
+            client.req_mkt_data(7, &built, "", true, false, &[]).await.unwrap();
+            client.req_mkt_data(7, &literal, "", true, false, &[]).await.unwrap();
+            let from_builder = seen_rx.recv().await.unwrap();
+            let from_literal = seen_rx.recv().await.unwrap();
+            assert_eq!(from_builder[0], "1"); // REQ_MKT_DATA
+            assert_eq!(from_builder, from_literal);
+        }
+    }
+
+    #[tokio::test]
+    async fn client_req_mkt_data_snapshot_folds_ticks() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_MKT_DATA: msg_id=1, version=11, ticker_id, ... snapshot flag set
+            assert_eq!(fields[0], "1");
+            let id = fields[2].as_str();
+            vec![
+                frame(&["1", "6", id, "1", "150.25", "300", "0"]), // BID
+                frame(&["1", "6", id, "2", "150.50", "200", "0"]), // ASK
+                frame(&["1", "6", id, "4", "150.40", "100", "0"]), // LAST
+                frame(&["2", "2", id, "8", "123456"]),             // VOLUME
+                frame(&["46", "2", id, "45", "1708876800"]),        // LAST_TIMESTAMP
+                frame(&["57", "1", id]),                            // TICK_SNAPSHOT_END
+            ]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+        let snapshot = client.req_mkt_data_snapshot(&Contract::stock("AAPL")).await.unwrap();
+
+        assert_eq!(snapshot.bid, Some(150.25));
+        assert_eq!(snapshot.ask, Some(150.50));
+        assert_eq!(snapshot.last, Some(150.40));
+        assert_eq!(snapshot.bid_size, Some(rust_decimal::Decimal::from(300)));
+        assert_eq!(snapshot.volume, Some(rust_decimal::Decimal::from(123456)));
+        assert_eq!(snapshot.last_timestamp, Some(1708876800));
+        assert_eq!(snapshot.high, None);
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_req_mkt_data_snapshot_not_subscribed() {
+        let port = mock_tws_responding(176, |fields| {
+            let id = fields[2].as_str();
+            vec![
+                frame(&["4", "2", id, "10167", "Displaying delayed market data", ""]),
+                frame(&["4", "2", id, "354", "Requested market data is not subscribed", ""]),
+            ]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+        let result = client.req_mkt_data_snapshot(&Contract::stock("AAPL")).await;
+        assert!(
+            matches!(result, Err(IBApiError::Server { code: 354, .. })),
+            "got {result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn client_mkt_data_subscriptions_are_independent() {
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<String>>();
+        let port = mock_tws_responding(176, move |fields| {
+            seen_tx.send(fields.to_vec()).unwrap();
+            if fields[0] != "1" {
+                return Vec::new();
+            }
+            // REQ_MKT_DATA: msg_id=1, version=11, ticker_id, ...
+            // Reply with TICK_PRICE: version=6, req_id, tick_type=1(BID), price, size, attrib
+            let ticker_id = fields[2].as_str();
+            let n = ticker_id.parse::<i32>().unwrap() - COLLECTOR_ID_BASE;
+            let price = format!("{}.5", 101 + n);
+            vec![frame(&["1", "6", ticker_id, "1", &price, "100", "0"])]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let mut sub1 = client.subscribe_mkt_data(&Contract::default(), "", false).await.unwrap();
+        let mut sub2 = client.subscribe_mkt_data(&Contract::default(), "", false).await.unwrap();
+        assert_ne!(sub1.req_id(), sub2.req_id());
+
+        for (sub, expected) in [(&mut sub2, 102.5), (&mut sub1, 101.5)] {
+            match sub.recv().await.unwrap() {
+                IBEvent::TickPrice { req_id, price, .. } => {
+                    assert_eq!(req_id, sub.req_id());
+                    assert!((price - expected).abs() < 1e-10);
+                }
+                other => panic!("expected TickPrice, got {other:?}"),
+            }
+        }
+
+        // Dropping a subscription sends CANCEL_MKT_DATA for its ticker id only.
+        let cancelled = sub1.req_id().to_string();
+        drop(sub1);
+        let cancel = loop {
+            let fields = seen_rx.recv().await.unwrap();
+            if fields[0] == "2" {
+                break fields;
+            }
+        };
+        assert_eq!(cancel, vec!["2".to_string(), "2".to_string(), cancelled]);
+        assert!(client.pending_requests().contains(sub2.req_id()));
+    }
+
+    #[tokio::test]
+    async fn client_scanner_subscription_cancels_on_drop() {
+        let mut tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let sub = client
+            .subscribe_scanner(&ScannerSubscription::default(), &[], &[])
+            .await
+            .unwrap();
+        let ticker_id = sub.req_id().to_string();
+        // REQ_SCANNER_SUBSCRIPTION (22) at sv 176 has no version field.
+        let request = tws.expect_message().await;
+        assert_eq!(request[..2], ["22".to_string(), ticker_id.clone()]);
+        assert!(client.pending_requests().contains(sub.req_id()));
+
+        drop(sub);
+        let cancel = tws.expect_message().await;
+        assert_eq!(cancel, vec!["23".to_string(), "1".to_string(), ticker_id]);
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_pnl_subscription_cancel_sends_cancel_pnl() {
+        let mut tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let sub = client.subscribe_pnl("DU123", "").await.unwrap();
+        let req_id = sub.req_id().to_string();
+        assert_eq!(tws.expect_message().await[..2], ["92".to_string(), req_id.clone()]);
+
+        sub.cancel().await.unwrap();
+        assert_eq!(tws.expect_message().await, vec!["93".to_string(), req_id]);
+    }
+
+    #[tokio::test]
+    async fn client_pnl_subscription_maps_unset_values_on_old_server() {
+        // Server version 128 predates UNREALIZED_PNL and REALIZED_PNL.
+        let mut tws = MockTws::start(128).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let mut sub = client.subscribe_pnl("DU123", "").await.unwrap();
+        let req_id = sub.req_id().to_string();
+        assert_eq!(tws.expect_message().await[0], "92");
+
+        tws.push_event(frame(&["4", "2", &req_id, "2104", "Market data farm connection is OK"]));
+        tws.push_event(frame(&["94", &req_id, "-12.5"]));
+        match sub.try_next().await.unwrap() {
+            Some(IBEvent::Pnl { daily_pnl, unrealized_pnl, realized_pnl, .. }) => {
+                assert_eq!(daily_pnl, Some(-12.5));
+                assert_eq!(unrealized_pnl, None);
+                assert_eq!(realized_pnl, None);
+            }
+            other => panic!("expected Pnl, got {other:?}"),
+        }
+
+        drop(sub);
+        assert_eq!(tws.expect_message().await, vec!["93".to_string(), req_id]);
+    }
+
+    #[tokio::test]
+    async fn client_supports_features_by_server_version() {
+        let expected = [
+            (Feature::TickByTick, true, true),
+            (Feature::CompletedOrders, true, true),
+            (Feature::SmartDepth, true, true),
+            (Feature::HistoricalSchedule, true, false),
+            (Feature::UserInfo, true, false),
+            (Feature::CurrentTimeInMillis, false, false),
+            (Feature::ProtobufPlaceOrder, false, false),
+        ];
+        for sv in [176, 150] {
+            let tws = MockTws::start(sv).await;
+            let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+                .await
+                .unwrap();
+            for (feature, at_176, at_150) in expected {
+                let supported = if sv == 176 { at_176 } else { at_150 };
+                assert_eq!(client.supports(feature), supported, "{feature:?} at {sv}");
+            }
+
+            let result = client.req_current_time_in_millis().await;
+            match result {
+                Err(IBApiError::Encoding(msg)) => assert!(msg.contains(">= 197"), "{msg}"),
+                other => panic!("expected Encoding error, got {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn client_place_order_rejects_fractional_quantity_on_old_server() {
+        // 100 is the only accepted server version below FRACTIONAL_POSITIONS (101).
+        let port = mock_tws(100, vec![]).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::new(5, 1)),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
+        match client.place_order(1, &Contract::stock("AAPL"), &order).await {
+            Err(IBApiError::Encoding(msg)) => {
+                assert!(msg.contains("0.5") && msg.contains("100"), "got {msg}")
+            }
+            other => panic!("expected Encoding error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn client_place_order_rejects_unknown_condition_without_fields() {
+        let port = mock_tws(176, vec![]).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let unknown = |fields: Vec<String>| OrderCondition::Unknown {
+            is_conjunction_connection: true,
+            raw_type: 2,
+            fields,
+        };
+        let mut order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::from(100)),
+            order_type: Some(OrderType::Market),
+            conditions: vec![unknown(Vec::new())],
+            ..Order::default()
+        };
+        match client.place_order(1, &Contract::stock("AAPL"), &order).await {
+            Err(IBApiError::Encoding(msg)) => assert!(msg.contains("type 2"), "got {msg}"),
+            other => panic!("expected Encoding error, got {other:?}"),
+        }
+        order.conditions = vec![unknown(vec!["1".into()])];
+        client.place_order(1, &Contract::stock("AAPL"), &order).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn client_place_order_encodes_combo_leg_ratios() {
+        use rust_decimal::Decimal;
+
+        let combo = |ratio: Decimal| Contract {
+            symbol: "SPY".into(),
+            sec_type: Some(SecType::Combo),
+            exchange: "SMART".into(),
+            currency: "USD".into(),
+            combo_legs: Some(vec![
+                ComboLeg {
+                    con_id: 1001,
+                    ratio: Decimal::ONE,
+                    action: Some(Action::Buy),
+                    exchange: "SMART".into(),
+                    ..ComboLeg::default()
+                },
+                ComboLeg {
+                    con_id: 1002,
+                    ratio,
+                    action: Some(Action::Sell),
+                    exchange: "SMART".into(),
+                    ..ComboLeg::default()
+                },
+            ]),
+            ..Contract::default()
+        };
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(Decimal::ONE),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
+
+        // Whole ratios go out as integers, however they are written.
+        let mut tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        client.place_order(1, &combo(Decimal::new(20, 1)), &order).await.unwrap();
+        let fields = tws.expect_message().await;
+        let leg = ["1002", "2", "SELL", "SMART"].map(String::from);
+        assert!(fields.windows(4).any(|w| w == leg), "{fields:?}");
+
+        // A fractional ratio fails locally on every server version, including
+        // the protobuf encoding of the newest.
+        for sv in [176, server_version::PROTOBUF_PLACE_ORDER] {
+            let port = mock_tws(sv, vec![]).await;
+            let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+            match client.place_order(2, &combo(Decimal::new(15, 1)), &order).await {
+                Err(IBApiError::Encoding(msg)) => {
+                    assert!(msg.contains("1.5") && msg.contains("1002"), "got {msg}")
+                }
+                other => panic!("expected Encoding error at sv {sv}, got {other:?}"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn client_search_symbols_collects_samples() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_MATCHING_SYMBOLS: msg_id=81, req_id, pattern
+            if fields[0] != "81" {
+                return Vec::new();
+            }
+            let req_id = fields[1].as_str();
+            if fields[2] == "ZZZZ" {
+                return vec![frame(&["79", req_id, "0"])];
+            }
+            // SYMBOL_SAMPLES: req_id, count, then per contract: con_id, symbol,
+            // sec_type, primary_exchange, currency, derivative types, description, issuer
+            vec![frame(&[
+                "79", req_id, "2",
+                "265598", "AAPL", "STK", "NASDAQ", "USD", "2", "OPT", "WAR", "APPLE INC", "",
+                "38708077", "AAPL", "STK", "MEXI", "MXN", "0", "APPLE INC", "",
+            ])]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let found = client.search_symbols("AAPL").await.unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].contract.con_id, 265598);
+        assert_eq!(found[0].contract.primary_exchange, "NASDAQ");
+        assert_eq!(found[0].derivative_sec_types, vec!["OPT", "WAR"]);
+        assert_eq!(found[1].contract.currency, "MXN");
+        assert!(found[1].derivative_sec_types.is_empty());
+
+        assert!(client.search_symbols("ZZZZ").await.unwrap().is_empty());
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_bounded_channel_counts_dropped_events() {
+        let messages = vec![
+            frame(&["9", "1", "100"]),
+            frame(&["9", "1", "101"]),
+            frame(&["9", "1", "102"]),
+        ];
+        let port = mock_tws(176, messages).await;
+        let (client, mut rx) =
+            IBClient::connect_bounded("127.0.0.1", port, 0, None, 1, OverflowPolicy::DropNewest)
+                .await
+                .unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.dropped_events() < 2 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("reader never overflowed the channel");
+
+        match rx.recv().await.unwrap() {
+            IBEvent::NextValidId { order_id } => assert_eq!(order_id, 100),
+            other => panic!("expected NextValidId, got {other:?}"),
+        }
+        assert_eq!(rx.dropped_events(), client.dropped_events());
+    }
 
-        client.set_next_req_id(100);
-        assert_eq!(client.next_req_id(), 100);
-        assert_eq!(client.next_req_id(), 101);
+    #[tokio::test]
+    async fn client_get_news_article_decodes_text_and_binary() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_NEWS_ARTICLE: msg_id=84, req_id, provider, article_id, options
+            if fields[0] != "84" {
+                return Vec::new();
+            }
+            let req_id = fields[1].as_str();
+            // NEWS_ARTICLE: req_id, article_type, article_text
+            let reply = match fields[3].as_str() {
+                "TEXT" => frame(&["83", req_id, "0", "<p>Headline</p>"]),
+                "PDF" => frame(&["83", req_id, "1", "JVBERi0xLjQ="]),
+                _ => frame(&["83", req_id, "1", "not base64!"]),
+            };
+            vec![reply]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            client.get_news_article("BRFG", "TEXT").await.unwrap(),
+            NewsArticleContent::Text("<p>Headline</p>".into())
+        );
+        assert_eq!(
+            client.get_news_article("BRFG", "PDF").await.unwrap(),
+            NewsArticleContent::Binary(b"%PDF-1.4".to_vec())
+        );
+        let result = client.get_news_article("BRFG", "BAD").await;
+        assert!(matches!(result, Err(IBApiError::Decoding(_))), "got {result:?}");
     }
 
     #[tokio::test]
-    async fn client_req_current_time() {
+    async fn client_wsh_meta_data_correlates_and_surfaces_not_subscribed() {
+        let port = mock_tws_responding(176, |fields| match fields[0].as_str() {
+            // REQ_WSH_META_DATA: msg_id=100, req_id
+            "100" => vec![
+                frame(&["104", "999", r#"{"other":true}"#]), // someone else's request
+                frame(&["104", fields[1].as_str(), r#"{"validated_filters":[]}"#]),
+            ],
+            // REQ_WSH_EVENT_DATA before the meta data was requested.
+            "102" => vec![frame(&[
+                "4", "2", fields[1].as_str(), "10282", "WSH meta data not requested", "",
+            ])],
+            _ => Vec::new(),
+        })
+        .await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+
+        client.req_wsh_event_data(7, 265598, "", false, false, false, "", "", None).await.unwrap();
+        match rx.recv().await.unwrap() {
+            IBEvent::Error { req_id: 7, code, .. } => {
+                assert!(IbErrorCode(code).is_wsh_not_subscribed())
+            }
+            other => panic!("expected the WSH error, got {other:?}"),
+        }
+
+        let json = client.wsh_meta_data().await.unwrap();
+        assert_eq!(json, r#"{"validated_filters":[]}"#);
+        assert!(client.pending_requests().is_empty());
+        assert!(matches!(rx.recv().await, Some(IBEvent::WshMetaData { req_id: 999, .. })));
+
+        let old = MockTws::builder().server_version(160).start().await;
+        let (old_client, _rx) = IBClient::connect("127.0.0.1", old.port(), 0, None).await.unwrap();
+        let result = old_client.wsh_meta_data().await;
+        assert!(matches!(result, Err(IBApiError::Encoding(_))), "got {result:?}");
+    }
+
+    #[tokio::test]
+    async fn client_reconnects_and_replays_subscriptions() {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let port = listener.local_addr().unwrap().port();
 
         let server = tokio::spawn(async move {
-            let (mut stream, _) = listener.accept().await.unwrap();
-            let mut buf = vec![0u8; 512];
-
-            // Read connect request
-            let _ = stream.read(&mut buf).await.unwrap();
+            let mut replayed = Vec::new();
+            let mut last_stream = None;
+            for session in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut prefix = [0u8; 4];
+                stream.read_exact(&mut prefix).await.unwrap();
+                read_frame(&mut stream).await.unwrap();
+                let handshake = frame(&["176", "20260101 12:00:00"]);
+                stream.write_all(&handshake).await.unwrap();
+                read_frame(&mut stream).await.unwrap(); // start_api
+
+                if session == 0 {
+                    // Wait for the subscription, then drop the connection.
+                    read_frame(&mut stream).await.unwrap();
+                } else {
+                    stream.write_all(&frame(&["9", "1", "500"])).await.unwrap();
+                    replayed = frame_fields(&read_frame(&mut stream).await.unwrap());
+                    last_stream = Some(stream);
+                }
+            }
+            (replayed, last_stream)
+        });
+        tokio::task::yield_now().await;
 
-            // Send handshake
-            let handshake = build_framed_msg(&["176", "20260101 12:00:00"]);
-            stream.write_all(&handshake).await.unwrap();
+        let policy = ReconnectPolicy {
+            max_retries: 3,
+            base_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            jitter: 0.0,
+        };
+        let (client, mut rx) = IBClient::connect_with_reconnect("127.0.0.1", port, 0, policy)
+            .await
+            .unwrap();
+        client.depth_flags.insert(9, true);
+        client.req_mkt_data(42, &Contract::default(), "", false, false, &[]).await.unwrap();
+
+        match rx.recv().await.unwrap() {
+            IBEvent::ConnectionReestablished { server_version } => assert_eq!(server_version, 176),
+            other => panic!("expected ConnectionReestablished, got {other:?}"),
+        }
+        assert_eq!(client.depth_flags.remove(9), None, "depth flags belong to the old session");
+        match rx.recv().await.unwrap() {
+            IBEvent::NextValidId { order_id } => assert_eq!(order_id, 500),
+            other => panic!("expected NextValidId, got {other:?}"),
+        }
 
-            // Read start_api
-            let _ = stream.read(&mut buf).await.unwrap();
+        // REQ_MKT_DATA for ticker 42 was re-sent on the new connection.
+        let (replayed, stream) = server.await.unwrap();
+        assert_eq!(&replayed[..3], &["1", "11", "42"]);
+        assert!(client.is_connected());
 
-            // Read req_current_time
-            let n = stream.read(&mut buf).await.unwrap();
-            let received = buf[..n].to_vec();
+        drop(stream);
+        client.disconnect().await;
+        assert!(!client.is_connected());
+    }
 
-            // Send response: CURRENT_TIME
-            let response = build_framed_msg(&["49", "1", "1708876800"]);
-            stream.write_all(&response).await.unwrap();
+    #[tokio::test]
+    async fn client_connection_state_tracks_reconnect_cycle() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
 
-            tokio::task::yield_now().await;
-            received
+        let server = tokio::spawn(async move {
+            let mut released = Some(released);
+            let mut last_stream = None;
+            for session in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut prefix = [0u8; 4];
+                stream.read_exact(&mut prefix).await.unwrap();
+                read_frame(&mut stream).await.unwrap();
+                if session == 1 {
+                    // Hold the reconnect in Handshaking until the test looks.
+                    released.take().unwrap().await.unwrap();
+                }
+                let handshake = frame(&["176", "20260101 12:00:00"]);
+                stream.write_all(&handshake).await.unwrap();
+                read_frame(&mut stream).await.unwrap(); // start_api
+                if session == 1 {
+                    last_stream = Some(stream);
+                } // session 0 is dropped here
+            }
+            last_stream
         });
-
         tokio::task::yield_now().await;
 
-        let (mut client, mut rx) =
-            IBClient::connect("127.0.0.1", port, 0, None)
+        let policy = ReconnectPolicy {
+            max_retries: 3,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(100),
+            jitter: 0.0,
+        };
+        let (client, _rx) = IBClient::connect_with_reconnect("127.0.0.1", port, 0, policy)
+            .await
+            .unwrap();
+        let mut state = client.watch_connection_state();
+        assert_eq!(*state.borrow(), ConnectionState::Connected);
+
+        async fn wait(state: &mut watch::Receiver<ConnectionState>, want: ConnectionState) {
+            tokio::time::timeout(Duration::from_secs(2), state.wait_for(|s| *s == want))
                 .await
+                .unwrap_or_else(|_| panic!("state never became {want:?}"))
                 .unwrap();
+        }
+        wait(&mut state, ConnectionState::Reconnecting).await;
+        wait(&mut state, ConnectionState::Handshaking).await;
+        assert!(!client.is_connected());
+        release.send(()).unwrap();
+        wait(&mut state, ConnectionState::Connected).await;
+
+        drop(server.await.unwrap());
+        client.disconnect().await;
+        assert_eq!(client.connection_state(), ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn client_connection_state_fails_when_reconnect_gives_up() {
+        let tws = MockTws::start(176).await;
+        let policy = ReconnectPolicy {
+            max_retries: 1,
+            base_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            jitter: 0.0,
+        };
+        let (client, mut rx) = IBClient::connect_with_reconnect("127.0.0.1", tws.port(), 0, policy)
+            .await
+            .unwrap();
+        tws.close();
+
+        loop {
+            match rx.recv().await {
+                Some(IBEvent::ConnectionClosed) | None => break,
+                Some(_) => {}
+            }
+        }
+        assert_eq!(client.connection_state(), ConnectionState::Failed);
+        client.disconnect().await;
+        assert_eq!(client.connection_state(), ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn client_reconnect_drops_replayed_order_statuses() {
+        use rust_decimal::Decimal;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // ORDER_STATUS at sv 176: order_id, status, filled, remaining, avg_fill_price,
+        // perm_id, parent_id, last_fill_price, client_id, why_held, mkt_cap_price
+        let status = |filled: &str, remaining: &str| {
+            frame(&["3", "7", "Submitted", filled, remaining, "0", "9001", "0", "0", "0", "", ""])
+        };
+        let server = tokio::spawn(async move {
+            let mut last_stream = None;
+            for session in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut prefix = [0u8; 4];
+                stream.read_exact(&mut prefix).await.unwrap();
+                read_frame(&mut stream).await.unwrap();
+                let handshake = frame(&["176", "20260101 12:00:00"]);
+                stream.write_all(&handshake).await.unwrap();
+                read_frame(&mut stream).await.unwrap(); // start_api
+
+                if session == 0 {
+                    stream.write_all(&status("0", "100")).await.unwrap();
+                    stream.write_all(&status("40", "60")).await.unwrap();
+                    // Drop the connection once the client has seen both.
+                    read_frame(&mut stream).await.unwrap();
+                } else {
+                    // TWS replays the open order's unchanged status, then it fills.
+                    stream.write_all(&status("40", "60")).await.unwrap();
+                    stream.write_all(&status("100", "0")).await.unwrap();
+                    last_stream = Some(stream);
+                }
+            }
+            last_stream
+        });
+        tokio::task::yield_now().await;
 
+        let policy = ReconnectPolicy {
+            max_retries: 3,
+            base_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            jitter: 0.0,
+        };
+        let (client, mut rx) = IBClient::connect_with_reconnect("127.0.0.1", port, 0, policy)
+            .await
+            .unwrap();
+
+        for expected in [0, 40] {
+            match rx.recv().await.unwrap() {
+                IBEvent::OrderStatus { perm_id: 9001, filled, .. } => {
+                    assert_eq!(filled, Decimal::from(expected));
+                }
+                other => panic!("expected OrderStatus, got {other:?}"),
+            }
+        }
+        assert_eq!(client.order_state(9001).unwrap().remaining, Decimal::from(60));
         client.req_current_time().await.unwrap();
 
-        // Receive the response
-        let event = rx.recv().await.unwrap();
-        match event {
-            IBEvent::CurrentTime { time } => assert_eq!(time, 1708876800),
-            other => panic!("expected CurrentTime, got {other:?}"),
+        assert!(matches!(rx.recv().await, Some(IBEvent::ConnectionReestablished { .. })));
+        // The replayed 40/60 status is dropped; the fill comes through.
+        match rx.recv().await.unwrap() {
+            IBEvent::OrderStatus { perm_id: 9001, filled, remaining, .. } => {
+                assert_eq!((filled, remaining), (Decimal::from(100), Decimal::ZERO));
+            }
+            other => panic!("expected the fill, got {other:?}"),
+        }
+        let order = client.order_state(9001).unwrap();
+        assert_eq!((order.order_id, order.status.as_str()), (7, "Submitted"));
+        assert_eq!(order.filled, Decimal::from(100));
+        assert_eq!(client.order_state(1), None);
+
+        let stream = server.await.unwrap();
+        drop(stream);
+        client.disconnect().await;
+    }
+
+    fn fast_reconnect_policy() -> ReconnectPolicy {
+        ReconnectPolicy {
+            max_retries: 3,
+            base_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+            jitter: 0.0,
         }
+    }
+
+    #[tokio::test]
+    async fn client_order_state_tracks_statuses_routed_to_order_handles() {
+        let tws = MockTws::builder()
+            .respond(|fields| match fields[0].as_str() {
+                // PLACE_ORDER: msg_id=3, order_id, ...
+                "3" => vec![order_status_msg(&fields[1], "Submitted")],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let policy = fast_reconnect_policy();
+        let (client, _rx) = IBClient::connect_with_reconnect("127.0.0.1", tws.port(), 0, policy)
+            .await
+            .unwrap();
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::from(100)),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
 
-        // Verify the server received a valid message
-        let received = server.await.unwrap();
-        assert!(!received.is_empty());
+        let handle = client.place_order_ack(7, &Contract::default(), &order).await.unwrap();
+        assert!(matches!(handle.ack(), IBEvent::OrderStatus { order_id: 7, .. }));
+        let tracked = client.order_state(9001).unwrap();
+        assert_eq!((tracked.order_id, tracked.status.as_str()), (7, "Submitted"));
     }
 
     #[tokio::test]
-    async fn client_disconnect() {
-        let port = mock_tws(176, vec![]).await;
+    async fn client_reconnect_delivers_repeated_statuses_outside_replay() {
+        let tws = MockTws::builder()
+            .respond(|fields| match fields[0].as_str() {
+                // REQ_OPEN_ORDERS: msg_id=5
+                "5" => vec![order_status_msg("7", "Submitted"), frame(&["53", "1"])],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let policy = fast_reconnect_policy();
+        let (client, mut rx) = IBClient::connect_with_reconnect("127.0.0.1", tws.port(), 0, policy)
+            .await
+            .unwrap();
+
+        tws.push_event(order_status_msg("7", "Submitted"));
+        assert!(matches!(rx.recv().await, Some(IBEvent::OrderStatus { order_id: 7, .. })));
+
+        // Without a reconnect, the same status is an answer, not a replay.
+        client.req_open_orders().await.unwrap();
+        assert!(matches!(rx.recv().await, Some(IBEvent::OrderStatus { order_id: 7, .. })));
+        assert!(matches!(rx.recv().await, Some(IBEvent::OpenOrderEnd)));
+    }
 
-        let (mut client, mut rx) =
-            IBClient::connect("127.0.0.1", port, 0, None)
+    #[tokio::test]
+    async fn client_account_summary_collects_accounts_and_cancels() {
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<String>>();
+        let port = mock_tws_responding(176, move |fields| {
+            seen_tx.send(fields.to_vec()).unwrap();
+            // REQ_ACCOUNT_SUMMARY: msg_id=62, version, req_id, group, tags
+            if fields[0] != "62" {
+                return Vec::new();
+            }
+            let req_id = fields[2].as_str();
+            vec![
+                frame(&["63", "1", req_id, "DU111", "NetLiquidation", "100250.5", "USD"]),
+                frame(&["63", "1", req_id, "DU111", "AccountType", "INDIVIDUAL", ""]),
+                frame(&["63", "1", req_id, "DU222", "NetLiquidation", "5000", "EUR"]),
+                frame(&["64", "1", req_id]),
+            ]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let summary = client.account_summary("All", "NetLiquidation,AccountType").await.unwrap();
+        assert_eq!(summary.accounts.len(), 2);
+        let du111 = summary.account("DU111").unwrap();
+        assert_eq!(du111.net_liquidation(), Some(rust_decimal::Decimal::new(1002505, 1)));
+        assert_eq!(du111.get("NetLiquidation").unwrap().currency, "USD");
+        assert_eq!(du111.get("AccountType").unwrap().value, "INDIVIDUAL");
+        assert_eq!(du111.decimal("AccountType"), None);
+        assert_eq!(summary.account("DU222").unwrap().net_liquidation(), Some(5000.into()));
+        // Ambiguous with two accounts.
+        assert_eq!(summary.net_liquidation(), None);
+        assert!(client.pending_requests().is_empty());
+
+        let request = seen_rx.recv().await.unwrap();
+        assert_eq!(request[3..], ["All".to_string(), "NetLiquidation,AccountType".to_string()]);
+        let req_id = request[2].clone();
+        assert_eq!(seen_rx.recv().await.unwrap(), vec!["63".to_string(), "1".to_string(), req_id]);
+    }
+
+    #[tokio::test]
+    async fn client_caches_managed_accounts() {
+        let port = mock_tws(176, vec![frame(&["15", "1", "DU123,DU456,"])]).await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        // The event still reaches the channel, after the cache is updated.
+        match rx.recv().await.unwrap() {
+            IBEvent::ManagedAccounts { accounts } => assert_eq!(accounts, "DU123,DU456,"),
+            other => panic!("expected ManagedAccounts, got {other:?}"),
+        }
+        assert_eq!(client.managed_accounts(), vec!["DU123", "DU456"]);
+    }
+
+    #[tokio::test]
+    async fn client_smart_components_resolves_components() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_SMART_COMPONENTS: msg_id=83, req_id, bbo_exchange
+            if fields[0] != "83" || fields[2] != "a6" {
+                return Vec::new();
+            }
+            // SMART_COMPONENTS: req_id, count, then bit_number, exchange, letter
+            vec![frame(&[
+                "82", fields[1].as_str(), "2", "0", "NYSE", "N", "4", "NASDAQ", "Q",
+            ])]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let components = client.smart_components("a6").await.unwrap();
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[1].exchange, "NASDAQ");
+        assert_eq!(components[1].exchange_letter, 'Q');
+        let components = crate::models::common::SmartComponents::from(components);
+        assert_eq!(components.exchanges_for_mask(1 << 4)[0].exchange, "NASDAQ");
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_scan_collects_ranks_and_cancels() {
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<String>>();
+        let port = mock_tws_responding(176, move |fields| {
+            seen_tx.send(fields.to_vec()).unwrap();
+            // REQ_SCANNER_SUBSCRIPTION: msg_id=22, ticker_id, rows, instrument, location, code
+            if fields[0] != "22" {
+                return Vec::new();
+            }
+            // SCANNER_DATA: version, ticker_id, count, then per rank: rank, con_id,
+            // symbol, sec_type, expiry, strike, right, exchange, currency,
+            // local_symbol, market_name, trading_class, distance, benchmark,
+            // projection, legs
+            let row = |rank, con_id, symbol| {
+                [rank, con_id, symbol, "STK", "", "0", "", "SMART", "USD", symbol, "NMS",
+                 symbol, "", "", "", ""]
+            };
+            let mut msg = vec!["20", "3", fields[1].as_str(), "2"];
+            msg.extend(row("1", "4391", "AMD"));
+            msg.extend(row("0", "265598", "AAPL"));
+            vec![frame(&msg)]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let sub = crate::ScannerSubscriptionBuilder::new().number_of_rows(2).build();
+        let items = client.scan(&sub).await.unwrap();
+        let symbols: Vec<_> =
+            items.iter().map(|i| i.contract_details.contract.symbol.as_str()).collect();
+        assert_eq!(symbols, ["AAPL", "AMD"]);
+        assert_eq!(items[1].contract_details.contract.con_id, 4391);
+
+        let request = seen_rx.recv().await.unwrap();
+        let ticker_id = request[1].clone();
+        assert_eq!(request[2..6], ["2", "STK", "STK.US.MAJOR", "TOP_PERC_GAIN"]);
+        let cancel = seen_rx.recv().await.unwrap();
+        assert_eq!(cancel, vec!["23".to_string(), "1".to_string(), ticker_id]);
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_implied_volatility_resolves_first_computation_and_cancels() {
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<String>>();
+        let port = mock_tws_responding(176, move |fields| {
+            seen_tx.send(fields.to_vec()).unwrap();
+            // REQ_CALC_IMPLIED_VOLAT: msg_id=54, version=2, req_id, contract...
+            if fields[0] != "54" {
+                return Vec::new();
+            }
+            // TICK_OPTION_COMPUTATION at sv >= PRICE_BASED_VOLATILITY: req_id,
+            // tick_type (53 = CUST_OPTION_COMPUTATION), tick_attrib, implied_vol,
+            // delta, opt_price, pv_dividend, gamma, vega, theta, und_price.
+            // -2 gamma and -1 pv_dividend mean "not computed".
+            let req_id = fields[2].as_str();
+            let tick = |vol| {
+                frame(&[
+                    "21", req_id, "53", "1", vol, "0.52", "7.25", "-1", "-2", "0.31", "-0.04",
+                    "185.5",
+                ])
+            };
+            vec![tick("0.27"), tick("0.28")]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let contract = crate::ContractBuilder::new()
+            .option("AAPL", "20260116", 190.0, crate::Right::Call)
+            .build()
+            .unwrap();
+        let result = client.implied_volatility(&contract, 7.25, 185.5).await.unwrap();
+        assert_eq!(result.tick_type, crate::TickType::CustOptionComputation);
+        assert!(result.price_based);
+        assert_eq!(result.implied_vol, Some(0.27));
+        assert_eq!(result.delta, Some(0.52));
+        assert_eq!(result.opt_price, Some(7.25));
+        assert_eq!(result.pv_dividend, None);
+        assert_eq!(result.gamma, None);
+        assert_eq!(result.vega, Some(0.31));
+        assert_eq!(result.und_price, Some(185.5));
+
+        let request = seen_rx.recv().await.unwrap();
+        let req_id = request[2].clone();
+        let cancel = seen_rx.recv().await.unwrap();
+        assert_eq!(cancel, vec!["56".to_string(), "1".to_string(), req_id]);
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_calc_subscription_streams_until_dropped() {
+        let mut tws = MockTws::builder()
+            .respond(|fields| {
+                // REQ_CALC_OPTION_PRICE: msg_id=55, version=2, req_id, contract...
+                if fields[0] != "55" {
+                    return Vec::new();
+                }
+                let req_id = fields[2].as_str();
+                let tick = |price| {
+                    frame(&[
+                        "21", req_id, "53", "0", "0.25", "0.52", price, "-1", "0.02", "0.31",
+                        "-0.04", "185.5",
+                    ])
+                };
+                vec![tick("7.25"), tick("7.30")]
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let contract = crate::ContractBuilder::new()
+            .option("AAPL", "20260116", 190.0, crate::Right::Call)
+            .build()
+            .unwrap();
+        let mut sub = client.subscribe_option_price(&contract, 0.25, 185.5, &[]).await.unwrap();
+        let req_id = sub.req_id().to_string();
+        assert_eq!(tws.expect_message().await[..3], ["55", "2", req_id.as_str()]);
+
+        for expected in [7.25, 7.30] {
+            match sub.recv().await.unwrap() {
+                IBEvent::TickOptionComputation { opt_price, .. } => {
+                    assert_eq!(opt_price, Some(expected))
+                }
+                other => panic!("expected TickOptionComputation, got {other:?}"),
+            }
+        }
+
+        drop(sub);
+        assert_eq!(tws.expect_message().await, ["57", "1", req_id.as_str()]);
+        assert!(client.pending_requests().is_empty());
+
+        let sub = client.subscribe_implied_volatility(&contract, 7.25, 185.5, &[]).await.unwrap();
+        let req_id = sub.req_id().to_string();
+        assert_eq!(tws.expect_message().await[..3], ["54", "2", req_id.as_str()]);
+        drop(sub);
+        assert_eq!(tws.expect_message().await, ["56", "1", req_id.as_str()]);
+    }
+
+    #[tokio::test]
+    async fn client_req_global_cancel_uses_protobuf_from_sv_203() {
+        use prost::Message;
+
+        let order_cancel = OrderCancel {
+            manual_order_cancel_time: "20260105 09:30:00".into(),
+            ext_operator: "desk7".into(),
+            manual_order_indicator: Some(1),
+        };
+        let gate = server_version::PROTOBUF_PLACE_ORDER;
+        for sv in [gate - 1, gate] {
+            let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+            let port = mock_tws_responding_raw(sv, move |body| {
+                seen_tx.send(body.to_vec()).unwrap();
+                Vec::new()
+            })
+            .await;
+            let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
                 .await
                 .unwrap();
 
-        assert!(client.is_connected());
-        client.disconnect().await;
-        assert!(!client.is_connected());
+            client.req_global_cancel(&order_cancel).await.unwrap();
+            let body = seen_rx.recv().await.unwrap();
+            let (msg_id, rest) = body.split_at(4);
+            let msg_id = i32::from_be_bytes(msg_id.try_into().unwrap());
+            if sv < gate {
+                // Text fields: version, ext_operator, manual_order_indicator.
+                assert_eq!(msg_id, outgoing::REQ_GLOBAL_CANCEL);
+                assert_eq!(rest, [&b"1\0desk7\0"[..], b"1\0"].concat());
+            } else {
+                assert_eq!(msg_id, outgoing::REQ_GLOBAL_CANCEL + outgoing::PROTOBUF_MSG_ID);
+                let expected = crate::proto_encode::build_global_cancel_request(&order_cancel);
+                assert_eq!(rest, expected.encode_to_vec());
+            }
+        }
+    }
 
-        // Double disconnect is safe
+    /// ORDER_STATUS (3) at server version 176 (no version field).
+    fn order_status_msg(order_id: &str, status: &str) -> Vec<u8> {
+        frame(&["3", order_id, status, "0", "100", "0", "9001", "0", "0", "0", "", ""])
+    }
+
+    #[tokio::test]
+    async fn client_place_order_ack_resolves_on_status_or_rejection() {
+        let tws = MockTws::builder()
+            .respond(|fields| match (fields[0].as_str(), fields[1].as_str()) {
+                // PLACE_ORDER: msg_id=3, order_id, ...
+                ("3", "7") => vec![
+                    order_status_msg("7", "PreSubmitted"),
+                    order_status_msg("7", "Submitted"),
+                ],
+                ("3", id) => vec![frame(&["4", "2", id, "201", "Order rejected", ""])],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::from(100)),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
+
+        let mut handle = client.place_order_ack(7, &Contract::default(), &order).await.unwrap();
+        assert_eq!(handle.order_id(), 7);
+        let ack = handle.ack();
+        assert!(matches!(ack, IBEvent::OrderStatus { status, .. } if status == "PreSubmitted"));
+        match handle.recv().await.unwrap() {
+            IBEvent::OrderStatus { order_id: 7, status, .. } => assert_eq!(status, "Submitted"),
+            other => panic!("expected OrderStatus, got {other:?}"),
+        }
+        drop(handle);
+        assert!(!client.pending_requests().contains_order(7));
+
+        let result = client.place_order_ack(8, &Contract::default(), &order).await;
+        assert!(matches!(result, Err(IBApiError::Server { code: 201, .. })), "got {result:?}");
+        assert!(!client.pending_requests().contains_order(8));
+    }
+
+    #[tokio::test]
+    async fn client_order_error_does_not_reach_request_with_same_start_id() {
+        // Both sequences used to start at NextValidId, so the first order
+        // and the first `*_async` request shared an ID and the order's
+        // rejection completed the contract details request instead.
+        let tws = MockTws::builder()
+            .respond(|fields| match fields[0].as_str() {
+                // PLACE_ORDER: reject the order, then finish the request.
+                "3" => vec![
+                    frame(&["4", "2", &fields[1], "201", "Order rejected", ""]),
+                    frame(&["52", "1", &COLLECTOR_ID_BASE.to_string()]), // CONTRACT_DATA_END
+                ],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        tws.push_event(frame(&["9", "1", "1"])); // NEXT_VALID_ID
+        assert!(matches!(rx.recv().await, Some(IBEvent::NextValidId { order_id: 1 })));
+
+        let handle = client.handle();
+        let details = tokio::spawn(async move {
+            handle.req_contract_details_async(&Contract::stock("AAPL")).await
+        });
+        while !client.pending_requests().contains(COLLECTOR_ID_BASE) {
+            tokio::task::yield_now().await;
+        }
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::from(100)),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
+        let result = client.place_order_ack(-1, &Contract::default(), &order).await;
+        assert!(matches!(result, Err(IBApiError::Server { code: 201, .. })), "got {result:?}");
+        assert!(details.await.unwrap().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_place_order_ack_resolves_what_if_on_open_order() {
+        use crate::proto_decode::pb;
+
+        let tws = MockTws::builder()
+            .server_version(server_version::PROTOBUF_PLACE_ORDER)
+            .respond_raw(|body| {
+                let msg_id = i32::from_be_bytes(body[..4].try_into().unwrap());
+                if msg_id != outgoing::PLACE_ORDER + outgoing::PROTOBUF_MSG_ID {
+                    return Vec::new();
+                }
+                // OPEN_ORDER (5) as protobuf, with the what-if margin impact.
+                let open_order = pb::OpenOrder {
+                    order_id: Some(12),
+                    order: Some(pb::Order { what_if: Some(true), ..Default::default() }),
+                    order_state: Some(pb::OrderState {
+                        init_margin_after: Some(2500.0),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                };
+                vec![open_order_pb_frame(&open_order)]
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        let order = Order {
+            action: Some(Action::Sell),
+            total_quantity: Some(rust_decimal::Decimal::from(10)),
+            order_type: Some(OrderType::Market),
+            what_if: true,
+            ..Order::default()
+        };
+
+        let handle = client.place_order_ack(12, &Contract::default(), &order).await.unwrap();
+        match handle.ack() {
+            IBEvent::OpenOrder { order_id: 12, order, order_state, .. } => {
+                assert!(order.what_if);
+                assert_eq!(order_state.init_margin_after, "2500");
+            }
+            other => panic!("expected OpenOrder, got {other:?}"),
+        }
+    }
+
+    /// OPEN_ORDER (5) as a protobuf frame.
+    fn open_order_pb_frame(open_order: &crate::proto_decode::pb::OpenOrder) -> Vec<u8> {
+        use prost::Message;
+
+        let msg_id = crate::protocol::incoming::OPEN_ORDER + outgoing::PROTOBUF_MSG_ID;
+        let mut msg = msg_id.to_be_bytes().to_vec();
+        msg.extend(open_order.encode_to_vec());
+        let mut framed = (msg.len() as u32).to_be_bytes().to_vec();
+        framed.extend(msg);
+        framed
+    }
+
+    #[tokio::test]
+    async fn client_place_order_ack_ignores_order_notices() {
+        use crate::proto_decode::pb;
+
+        let tws = MockTws::builder()
+            .server_version(server_version::PROTOBUF_PLACE_ORDER)
+            .respond_raw(|body| {
+                let msg_id = i32::from_be_bytes(body[..4].try_into().unwrap());
+                if msg_id != outgoing::PLACE_ORDER + outgoing::PROTOBUF_MSG_ID {
+                    return Vec::new();
+                }
+                // ERR_MSG: version, id, code, message, advanced reject json, error time
+                let notice = "Order message: BUY 100 AAPL STK \
+                    Warning: your order will not be placed at the exchange until 09:30 US/Eastern";
+                let open_order = pb::OpenOrder { order_id: Some(13), ..Default::default() };
+                vec![
+                    frame(&["4", "2", "13", "399", notice, "", "0"]),
+                    open_order_pb_frame(&open_order),
+                ]
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        let order = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(rust_decimal::Decimal::from(100)),
+            order_type: Some(OrderType::Market),
+            ..Order::default()
+        };
+
+        let handle = client.place_order_ack(13, &Contract::default(), &order).await.unwrap();
+        assert!(matches!(handle.ack(), IBEvent::OpenOrder { order_id: 13, .. }));
+    }
+
+    #[tokio::test]
+    async fn client_executions_joins_commission_reports() {
+        let exec = |req_id: &str, exec_id: &str| {
+            // EXECUTION_DATA (11) at sv 176: no version field.
+            frame(&[
+                "11", req_id, "42", "265598", "AAPL", "STK", "", "", "", "", "SMART", "USD",
+                "AAPL", "NMS", exec_id, "20260105 10:30:00", "DU123", "NASDAQ", "BOT", "10",
+                "150.5", "9001", "0", "0", "10", "150.5", "", "", "", "", "1",
+            ])
+        };
+        let commission = |exec_id: &str| frame(&["59", "1", exec_id, "1.25", "USD", "", "", ""]);
+        let tws = MockTws::builder()
+            .respond(move |fields| {
+                // REQ_EXECUTIONS: msg_id=7, version=3, req_id, filter...
+                if fields[0] != "7" {
+                    return Vec::new();
+                }
+                let id = fields[2].as_str();
+                vec![
+                    exec(id, "A"),
+                    commission("A"),
+                    exec(id, "B"),
+                    exec(id, "C"),
+                    frame(&["55", "1", id]),
+                    commission("B"),
+                    // Not one of ours: stays on the event channel.
+                    commission("X"),
+                ]
+            })
+            .start()
+            .await;
+        let (mut client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        client.set_commission_timeout(Duration::from_millis(100));
+
+        let executions = client.executions(&ExecutionFilter::default()).await.unwrap();
+        let joined: Vec<_> = executions
+            .iter()
+            .map(|e| {
+                let report = e.commission.as_ref().map(|c| c.exec_id.as_str());
+                (e.execution.exec_id.as_str(), report)
+            })
+            .collect();
+        assert_eq!(joined, [("A", Some("A")), ("B", Some("B")), ("C", None)]);
+        assert_eq!(executions[0].contract.symbol, "AAPL");
+        assert_eq!(executions[0].commission.as_ref().unwrap().commission_and_fees, 1.25);
+        assert!(client.pending_requests().is_empty());
+
+        loop {
+            match rx.recv().await.unwrap() {
+                IBEvent::CommissionReport { report } => break assert_eq!(report.exec_id, "X"),
+                IBEvent::ExecDetails { .. } => panic!("execution leaked to the event channel"),
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn client_executions_collects_protobuf_execution_details() {
+        use crate::proto_decode::pb;
+        use crate::protocol::incoming;
+        use prost::Message;
+
+        let pb_frame = |msg_id: i32, body: Vec<u8>| {
+            let mut msg = (msg_id + outgoing::PROTOBUF_MSG_ID).to_be_bytes().to_vec();
+            msg.extend(body);
+            let mut framed = (msg.len() as u32).to_be_bytes().to_vec();
+            framed.extend(msg);
+            framed
+        };
+        let tws = MockTws::builder()
+            .server_version(server_version::PROTOBUF)
+            .respond_raw(move |body| {
+                let msg_id = i32::from_be_bytes(body[..4].try_into().unwrap());
+                if msg_id != outgoing::REQ_EXECUTIONS + outgoing::PROTOBUF_MSG_ID {
+                    return Vec::new();
+                }
+                let request = pb::ExecutionRequest::decode(&body[4..]).unwrap();
+                let exec = |exec_id: &str| pb::ExecutionDetails {
+                    req_id: request.req_id,
+                    contract: Some(pb::Contract {
+                        symbol: Some("AAPL".into()),
+                        ..Default::default()
+                    }),
+                    execution: Some(pb::Execution {
+                        exec_id: Some(exec_id.into()),
+                        shares: Some("10".into()),
+                        ..Default::default()
+                    }),
+                };
+                let end = pb::ExecutionDetailsEnd { req_id: request.req_id };
+                vec![
+                    pb_frame(incoming::EXECUTION_DATA, exec("A").encode_to_vec()),
+                    pb_frame(incoming::EXECUTION_DATA, exec("B").encode_to_vec()),
+                    pb_frame(incoming::EXECUTION_DATA_END, end.encode_to_vec()),
+                ]
+            })
+            .start()
+            .await;
+        let (mut client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+        client.set_commission_timeout(Duration::from_millis(10));
+
+        let executions = client.executions(&ExecutionFilter::default()).await.unwrap();
+        let ids: Vec<_> = executions.iter().map(|e| e.execution.exec_id.as_str()).collect();
+        assert_eq!(ids, ["A", "B"]);
+        assert_eq!(executions[0].contract.symbol, "AAPL");
+        assert_eq!(executions[0].execution.shares, Some(rust_decimal::Decimal::from(10)));
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_positions_collects_snapshot_then_cancels_or_keeps_streaming() {
+        let position = |symbol: &str, qty: &str| {
+            // POSITION_DATA (61), version 3.
+            frame(&[
+                "61", "3", "DU123", "265598", symbol, "STK", "", "", "", "", "", "USD", symbol,
+                "NMS", qty, "150.25",
+            ])
+        };
+        let mut tws = MockTws::builder()
+            .respond(move |fields| match fields[0].as_str() {
+                // REQ_POSITIONS: snapshot, end marker, then a live update.
+                "61" => vec![
+                    position("AAPL", "100"),
+                    position("MSFT", "-5"),
+                    frame(&["62", "1"]),
+                    position("AAPL", "110"),
+                ],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let positions = client.positions(false).await.unwrap();
+        let held: Vec<_> = positions
+            .iter()
+            .map(|p| (p.contract.symbol.as_str(), p.position.to_string()))
+            .collect();
+        assert_eq!(held, [("AAPL", "100".to_string()), ("MSFT", "-5".to_string())]);
+        assert_eq!(positions[0].account, "DU123");
+        assert_eq!(positions[0].avg_cost, rust_decimal::Decimal::new(15025, 2));
+        assert_eq!(tws.expect_message().await, ["61", "1"]);
+        assert_eq!(tws.expect_message().await, ["64", "1"]); // CANCEL_POSITIONS
+
+        // Kept subscription: the update after PositionEnd reaches the channel.
+        assert_eq!(client.positions(true).await.unwrap().len(), 2);
+        assert_eq!(tws.expect_message().await, ["61", "1"]);
+        let mut updates = Vec::new();
+        while updates.len() < 2 {
+            if let IBEvent::Position { position, .. } = rx.recv().await.unwrap() {
+                updates.push(position.to_string());
+            }
+        }
+        // One update after each snapshot; the snapshots themselves never leak.
+        assert_eq!(updates, ["110", "110"]);
+    }
+
+    #[tokio::test]
+    async fn client_positions_rejects_a_concurrent_call() {
+        let mut tws = MockTws::builder()
+            .respond(|fields| match fields[0].as_str() {
+                // REQ_POSITIONS: POSITION_DATA (61) v3, then POSITION_END.
+                "61" => vec![
+                    frame(&[
+                        "61", "3", "DU123", "265598", "AAPL", "STK", "", "", "", "", "", "USD",
+                        "AAPL", "NMS", "100", "150.25",
+                    ]),
+                    frame(&["62", "1"]),
+                ],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let (first, second) = tokio::join!(client.positions(true), client.positions(true));
+        assert_eq!(first.unwrap().len(), 1);
+        assert!(matches!(second, Err(IBApiError::InProgress(_))), "got {second:?}");
+        // The rejected call sent nothing.
+        assert_eq!(tws.expect_message().await, ["61", "1"]);
+        assert_eq!(tws.sent_messages().len(), 1);
+        assert_eq!(client.positions(true).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn client_account_snapshot_collects_until_download_end() {
+        let mut tws = MockTws::builder()
+            .respond(|fields| match fields {
+                // REQ_ACCT_DATA: msg_id=6, version=2, subscribe, acct_code
+                [id, _, subscribe, ..] if id == "6" && subscribe == "1" => vec![
+                    frame(&["6", "2", "NetLiquidation", "100000.55", "USD", "DU123"]),
+                    frame(&["6", "2", "AccountType", "INDIVIDUAL", "", "DU123"]),
+                    frame(&[
+                        "7", "8", "265598", "AAPL", "STK", "", "0", "", "", "NASDAQ", "USD",
+                        "AAPL", "NMS", "100", "190.5", "19050.0", "150.25", "4025.0", "0",
+                        "DU123",
+                    ]),
+                    frame(&["8", "1", "14:32"]),
+                    frame(&["54", "1", "DU123"]),
+                    frame(&["6", "2", "NetLiquidation", "100010.00", "USD", "DU123"]),
+                ],
+                _ => Vec::new(),
+            })
+            .start()
+            .await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        // Default account: resolved from AccountDownloadEnd, then unsubscribed.
+        let snapshot = client.account_snapshot("", false).await.unwrap();
+        assert_eq!(snapshot.account, "DU123");
+        assert_eq!(snapshot.values.len(), 2);
+        let net_liq = snapshot.value("NetLiquidation", "USD").unwrap();
+        assert_eq!(net_liq.as_decimal(), Some(rust_decimal::Decimal::new(10000055, 2)));
+        assert_eq!(snapshot.value("AccountType", "").unwrap().value, "INDIVIDUAL");
+        assert_eq!(snapshot.portfolio.len(), 1);
+        assert_eq!(snapshot.portfolio[0].contract.symbol, "AAPL");
+        assert_eq!(snapshot.portfolio[0].average_cost, rust_decimal::Decimal::new(15025, 2));
+        assert_eq!(snapshot.update_time, "14:32");
+        assert_eq!(tws.expect_message().await[..3], ["6", "2", "1"]);
+        assert_eq!(tws.expect_message().await[..3], ["6", "2", "0"]);
+
+        // Kept subscription: the value after AccountDownloadEnd reaches the channel.
+        let snapshot = client.account_snapshot("DU123", true).await.unwrap();
+        assert_eq!(snapshot.portfolio.len(), 1);
+        assert_eq!(tws.expect_message().await, ["6", "2", "1", "DU123"]);
+        let mut values = Vec::new();
+        while values.len() < 2 {
+            if let Some(value) = rx.recv().await.unwrap().account_value() {
+                values.push(value.value);
+            }
+        }
+        // One update after each snapshot; the snapshots themselves never leak.
+        assert_eq!(values, ["100010.00", "100010.00"]);
+    }
+
+    #[tokio::test]
+    async fn client_historical_ticks_all_pages_until_short_page() {
+        const START: i64 = 1_767_225_600; // 2026-01-01 00:00:00 UTC
+        // HISTORICAL_TICKS (96): req_id, count, (time, unused, price, size)*, done
+        let ticks_msg = |req_id: &str, times: &[i64], done: bool| {
+            let mut fields = vec!["96".to_string(), req_id.to_string(), times.len().to_string()];
+            for time in times {
+                fields.extend([time.to_string(), "0".into(), "101.5".into(), "0".into()]);
+            }
+            fields.push(if done { "1" } else { "0" }.into());
+            frame(&fields.iter().map(String::as_str).collect::<Vec<_>>())
+        };
+        let mut tws = MockTws::builder()
+            .respond(move |fields| {
+                // REQ_HISTORICAL_TICKS: msg_id=96, req_id, contract..., start, end, ...
+                if fields[0] != "96" {
+                    return Vec::new();
+                }
+                let req_id = fields[1].as_str();
+                if fields.iter().any(|f| f == "20260101-00:00:00") {
+                    // A full page of 1000 ticks, ten per second, in two messages.
+                    let times: Vec<i64> = (0..1000).map(|i| START + i / 10).collect();
+                    vec![
+                        ticks_msg(req_id, &times[..600], false),
+                        ticks_msg(req_id, &times[600..], true),
+                    ]
+                } else {
+                    // Short page from the second the first one was cut in:
+                    // its ten ticks again, two more, and one past `end`.
+                    let mut times = vec![START + 99; 12];
+                    times.extend([START + 100, START + 101, START + 500]);
+                    vec![ticks_msg(req_id, &times, true)]
+                }
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let contract = Contract::stock("AAPL");
+        let series = client
+            .historical_ticks_all(&contract, START, START + 200, TickKind::Midpoint, true)
+            .await
+            .unwrap();
+        assert_eq!(series.kind(), TickKind::Midpoint);
+        assert_eq!(series.len(), 1004);
+        assert_eq!(series.last_time(), Some(START + 101));
+        // No tick of the second the page limit cut through is lost or repeated.
+        let HistoricalTickSeries::Midpoint(ticks) = &series else { panic!("{series:?}") };
+        assert_eq!(ticks.iter().filter(|t| t.time == START + 99).count(), 12);
+
+        let starts: Vec<String> = [tws.expect_message().await, tws.expect_message().await]
+            .iter()
+            .map(|fields| {
+                assert_eq!(fields[0], "96");
+                assert!(fields.iter().any(|f| f == "MIDPOINT"), "{fields:?}");
+                fields.iter().find(|f| f.len() == 17 && f.as_bytes()[8] == b'-').unwrap().clone()
+            })
+            .collect();
+        // The second page starts at the second of the newest tick of the first.
+        assert_eq!(starts, ["20260101-00:00:00", "20260101-00:01:39"]);
+        assert_eq!(tws.sent_messages().len(), 2);
+    }
+
+    /// HISTORICAL_DATA (17) bar fields: time, open, high, low, close, volume, wap, count.
+    const HIST_BARS: [&str; 16] = [
+        "20260105", "100", "105", "99", "104", "1000", "102.5", "50",
+        "20260106", "104", "106", "103", "105", "800", "104.5", "40",
+    ];
+
+    #[tokio::test]
+    async fn client_historical_bars_resolves_on_single_message_for_old_servers() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_HISTORICAL_DATA: msg_id=20, req_id, contract...
+            if fields[0] != "20" {
+                return Vec::new();
+            }
+            let id = fields[1].as_str();
+            if fields[3] == "BUSY" {
+                return vec![frame(&[
+                    "4", "2", id, "162",
+                    "Historical Market Data Service error message:\
+                     Historical data request pacing violation",
+                    "",
+                ])];
+            }
+            let mut msg = vec!["17", id, "20260105", "20260107", "2"];
+            msg.extend(HIST_BARS);
+            vec![frame(&msg)]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let bars = client
+            .historical_bars(
+                &Contract::stock("AAPL"),
+                "",
+                "2 D",
+                BarSize::OneDay,
+                WhatToShow::Trades,
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[0].time, "20260105");
+        assert_eq!(bars[1].close, 105.0);
+
+        match client
+            .historical_bars(&Contract::stock("BUSY"), "", "2 D", "1 day", "TRADES", true)
+            .await
+        {
+            Err(IBApiError::PacingViolation { code, .. }) => assert_eq!(code, 162),
+            other => panic!("expected PacingViolation, got {other:?}"),
+        }
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_historical_bars_waits_for_end_message() {
+        let port = mock_tws_responding(200, |fields| {
+            if fields[0] != "20" {
+                return Vec::new();
+            }
+            let id = fields[1].as_str();
+            let mut first = vec!["17", id, "1"];
+            first.extend(&HIST_BARS[..8]);
+            let mut second = vec!["17", id, "1"];
+            second.extend(&HIST_BARS[8..]);
+            vec![
+                frame(&first),
+                frame(&second),
+                frame(&["108", id, "20260105", "20260107"]),
+            ]
+        })
+        .await;
+
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let bars = client
+            .historical_bars(
+                &Contract::stock("AAPL"),
+                "",
+                "2 D",
+                BarSize::OneDay,
+                WhatToShow::Trades,
+                true,
+            )
+            .await
+            .unwrap();
+        let times: Vec<_> = bars.iter().map(|b| b.time.as_str()).collect();
+        assert_eq!(times, ["20260105", "20260106"]);
+    }
+
+    #[tokio::test]
+    async fn client_subscribe_historical_data_yields_initial_then_updates() {
+        let mut tws = MockTws::builder()
+            .server_version(200)
+            .respond(|fields| {
+                if fields[0] != "20" {
+                    return Vec::new();
+                }
+                let id = fields[1].as_str();
+                let mut first = vec!["17", id, "1"];
+                first.extend(&HIST_BARS[..8]);
+                let mut second = vec!["17", id, "1"];
+                second.extend(&HIST_BARS[8..]);
+                let update = |close| {
+                    frame(&["90", id, "20260106", "104", "106", "103", close, "900", "104.6", "45"])
+                };
+                vec![
+                    frame(&first),
+                    frame(&second),
+                    frame(&["108", id, "20260105", "20260107"]),
+                    update("105.5"),
+                    update("105.75"),
+                ]
+            })
+            .start()
+            .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let mut sub = client
+            .subscribe_historical_data(
+                &Contract::stock("AAPL"),
+                "2 D",
+                BarSize::OneDay,
+                WhatToShow::Trades,
+                true,
+                &[],
+            )
+            .await
+            .unwrap();
+        let request = tws.expect_message().await;
+        assert_eq!(request[1], sub.req_id().to_string());
+
+        match sub.recv().await.unwrap() {
+            HistoricalUpdate::Initial(bars) => {
+                let times: Vec<_> = bars.iter().map(|b| b.time.as_str()).collect();
+                assert_eq!(times, ["20260105", "20260106"]);
+            }
+            other => panic!("expected Initial, got {other:?}"),
+        }
+        for close in [105.5, 105.75] {
+            match sub.try_next().await.unwrap().unwrap() {
+                HistoricalUpdate::Update(bar) => {
+                    assert_eq!(bar.time, "20260106");
+                    assert_eq!(bar.close, close);
+                }
+                other => panic!("expected Update, got {other:?}"),
+            }
+        }
+
+        let req_id = sub.req_id().to_string();
+        drop(sub);
+        assert_eq!(tws.expect_message().await, ["25", "1", req_id.as_str()]);
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_cancel_mkt_depth_sends_the_requested_smart_flag() {
+        let mut tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        let contract = Contract::stock("AAPL");
+
+        client.req_mkt_depth(7, &contract, 5, true, &[]).await.unwrap();
+        client.req_mkt_depth(8, &contract, 5, false, &[]).await.unwrap();
+        assert_eq!(tws.expect_message().await[..3], ["10", "5", "7"]);
+        assert_eq!(tws.expect_message().await[..3], ["10", "5", "8"]);
+
+        client.handle().cancel_mkt_depth(7).await.unwrap();
+        assert_eq!(tws.expect_message().await, ["11", "1", "7", "1"]);
+        client.cancel_mkt_depth(8).await.unwrap();
+        assert_eq!(tws.expect_message().await, ["11", "1", "8", "0"]);
+
+        let sub = client.subscribe_mkt_depth(&contract, 5, true, &[]).await.unwrap();
+        let sub_id = sub.req_id();
+        let id = sub_id.to_string();
+        assert_eq!(tws.expect_message().await[0], "10");
+        drop(sub);
+        assert_eq!(tws.expect_message().await, ["11", "1", id.as_str(), "1"]);
+        assert_eq!(client.depth_flags.remove(sub_id), None);
+
+        // The flags are dropped with the connection.
+        client.req_mkt_depth(9, &contract, 5, true, &[]).await.unwrap();
+        assert_eq!(tws.expect_message().await[..3], ["10", "5", "9"]);
         client.disconnect().await;
+        assert_eq!(client.depth_flags.remove(9), None);
+    }
 
-        // Channel should be closed (reader task exited)
-        // Drain any remaining events
-        while rx.recv().await.is_some() {}
+    #[tokio::test]
+    async fn client_mkt_depth_exchanges_returns_descriptions() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_MKT_DEPTH_EXCHANGES: msg_id=82, no fields
+            if fields[0] != "82" {
+                return Vec::new();
+            }
+            vec![frame(&[
+                "80", "2",
+                "ISLAND", "STK", "NASDAQ", "Deep2", "7",
+                "IDEALPRO", "CASH", "", "Deep", "",
+            ])]
+        })
+        .await;
+        let (client, mut rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let exchanges = client.mkt_depth_exchanges().await.unwrap();
+        assert_eq!(exchanges.len(), 2);
+        assert_eq!(exchanges[0].exchange, "ISLAND");
+        assert_eq!(exchanges[0].listing_exch, "NASDAQ");
+        assert_eq!(exchanges[0].service_data_type, "Deep2");
+        assert_eq!(exchanges[0].agg_group, Some(7));
+        assert_eq!(exchanges[1].sec_type, "CASH");
+        assert_eq!(exchanges[1].agg_group, None);
+        assert!(rx.try_recv().is_err(), "the answer is not delivered to the event channel");
+    }
+
+    #[tokio::test]
+    async fn client_regulatory_snapshot_sets_flag() {
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<Vec<String>>();
+        let port = mock_tws_responding(176, move |fields| {
+            seen_tx.send(fields.to_vec()).unwrap();
+            if fields[0] != "1" {
+                return Vec::new();
+            }
+            let id = fields[2].as_str();
+            vec![
+                frame(&["1", "6", id, "4", "150.40", "100", "0"]), // LAST
+                frame(&["57", "1", id]),                            // TICK_SNAPSHOT_END
+            ]
+        })
+        .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        let snapshot = client.regulatory_snapshot(&Contract::stock("AAPL")).await.unwrap();
+        assert_eq!(snapshot.last, Some(150.40));
+
+        // ..., delta neutral flag, snapshot, regulatory snapshot (the
+        // responder does not see the empty generic ticks and options fields)
+        let request = seen_rx.recv().await.unwrap();
+        let n = request.len();
+        assert_eq!(request[0], "1");
+        assert_eq!(request[n - 3..], ["0", "1", "1"], "{request:?}");
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_snapshot_fails_during_competing_live_session() {
+        let port = mock_tws_responding(176, |fields| {
+            if fields[0] != "1" {
+                return Vec::new();
+            }
+            let id = fields[2].as_str();
+            let message = "No market data during competing live session";
+            vec![frame(&["4", "2", id, "10197", message, ""])]
+        })
+        .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None)
+            .await
+            .unwrap();
+
+        for regulatory in [false, true] {
+            let result = client.collect_snapshot(&Contract::stock("AAPL"), regulatory).await;
+            match result {
+                Err(IBApiError::Server { code, .. }) => {
+                    assert!(IbErrorCode(code).is_competing_live_session());
+                }
+                other => panic!("expected error 10197, got {other:?}"),
+            }
+        }
+        assert!(client.pending_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn client_handles_send_concurrently_from_two_tasks() {
+        const PER_TASK: usize = 20;
+        let mut tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None)
+            .await
+            .unwrap();
+
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let handle = client.handle();
+                tokio::spawn(async move {
+                    let mut ids = Vec::new();
+                    for _ in 0..PER_TASK {
+                        let req_id = handle.next_req_id();
+                        handle.req_contract_details(req_id, &Contract::stock("AAPL")).await?;
+                        ids.push(req_id);
+                    }
+                    Ok::<_, IBApiError>(ids)
+                })
+            })
+            .collect();
+        let mut sent = Vec::new();
+        for task in tasks {
+            sent.extend(task.await.unwrap().unwrap());
+        }
+        assert!(client.is_connected(), "dropping a handle keeps the connection open");
+
+        // Every frame arrives intact, and the shared ID sequence hands out
+        // each request ID once.
+        let mut received = Vec::new();
+        for _ in 0..2 * PER_TASK {
+            let fields = tws.expect_message().await;
+            assert_eq!(fields[0], "9", "REQ_CONTRACT_DATA, got {fields:?}");
+            assert!(fields.iter().any(|f| f == "AAPL"), "{fields:?}");
+            received.push(fields[2].parse::<i32>().unwrap());
+        }
+        sent.sort_unstable();
+        received.sort_unstable();
+        assert_eq!(received, sent);
+        sent.dedup();
+        assert_eq!(sent.len(), 2 * PER_TASK);
+    }
+
+    #[tokio::test]
+    async fn client_historical_schedule_correlates_sessions() {
+        let port = mock_tws_responding(176, |fields| {
+            // REQ_HISTORICAL_DATA: msg_id=20, req_id, contract...
+            if fields[0] != "20" {
+                return Vec::new();
+            }
+            assert!(fields.iter().any(|f| f == "SCHEDULE"), "{fields:?}");
+            let id = fields[1].as_str();
+            vec![
+                // Another request's schedule must not resolve this one.
+                frame(&["106", "999", "", "", "UTC", "0"]),
+                frame(&[
+                    "106", id, "20260105-09:30:00", "20260106-16:00:00", "US/Eastern", "2",
+                    "20260105-09:30:00", "20260105-16:00:00", "20260105",
+                    "20260106-09:30:00", "20260106-16:00:00", "20260106",
+                ]),
+            ]
+        })
+        .await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", port, 0, None).await.unwrap();
+
+        let schedule =
+            client.historical_schedule(&Contract::stock("AAPL"), "", "2 D").await.unwrap();
+        assert_eq!(schedule.time_zone, "US/Eastern");
+        assert_eq!(schedule.start_date_time, "20260105-09:30:00");
+        assert_eq!(schedule.sessions.len(), 2);
+        assert_eq!(schedule.sessions[1].ref_date, "20260106");
+        assert_eq!(schedule.sessions[1].end_date_time, "20260106-16:00:00");
+        assert!(client.pending_requests().is_empty());
+
+        let bars = client
+            .historical_bars(&Contract::stock("AAPL"), "", "2 D", "1 day", "SCHEDULE", true)
+            .await;
+        assert!(matches!(bars, Err(IBApiError::Encoding(_))), "got {bars:?}");
     }
 }
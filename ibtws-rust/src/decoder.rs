@@ -27,6 +27,7 @@ use crate::models::enums::*;
 use crate::models::execution::{CommissionAndFeesReport, Execution};
 use crate::models::market_data::{DepthMktDataDescription, TickAttrib, TickAttribBidAsk, TickAttribLast};
 use crate::models::order::{Order, OrderAllocation, OrderComboLeg, OrderCondition, OrderState};
+use crate::models::unset::{Unset, UNSET_DOUBLE, UNSET_INTEGER};
 use crate::protocol::{incoming, server_version, TickType, RAW_INT_LEN};
 use crate::wrapper::{IBEvent, ScannerDataItem};
 
@@ -175,6 +176,9 @@ impl<'a> MessageDecoder<'a> {
 
     /// Decode a Decimal field.
     ///
+    /// Also accepts the scientific notation (`1.5E7`) TWS uses for some
+    /// doubles, such as large average costs.
+    ///
     /// Mirrors C++ `DecodeField(Decimal&, ...)`.
     pub fn decode_decimal(&mut self) -> Result<Decimal> {
         let s = self.read_field_str()?;
@@ -182,6 +186,7 @@ impl<'a> MessageDecoder<'a> {
             return Ok(Decimal::ZERO);
         }
         Decimal::from_str(s)
+            .or_else(|_| Decimal::from_scientific(s))
             .map_err(|e| IBApiError::Decoding(format!("invalid Decimal '{s}': {e}")))
     }
 
@@ -343,20 +348,86 @@ impl<'a> MessageDecoder<'a> {
 /// C++ `EDecoder::parseAndProcessMsg`. It reads the message ID, then
 /// dispatches to the appropriate per-message decoder.
 ///
-/// Messages that are not yet implemented return `IBEvent::Unknown`.
+/// Messages that are not yet implemented return `IBEvent::Unknown`, as do
+/// malformed messages -- including ones that make a decoder panic, so a
+/// single bad message cannot take down the reader task.
 pub fn decode_server_msg(data: &[u8], server_version: i32) -> IBEvent {
-    match decode_server_msg_inner(data, server_version) {
-        Ok(event) => event,
-        Err(e) => {
+    let decoded = std::panic::catch_unwind(|| decode_server_msg_inner(data, server_version));
+    match decoded {
+        Ok(Ok(event)) => event,
+        Ok(Err(e)) => {
             tracing::error!("failed to decode server message: {e}");
             IBEvent::Unknown {
                 msg_id: -1,
                 data: data.to_vec(),
             }
         }
+        Err(_) => {
+            tracing::error!("decoder panicked on server message, skipping it");
+            IBEvent::Unknown {
+                msg_id: -1,
+                data: data.to_vec(),
+            }
+        }
     }
 }
 
+/// Decode a complete server message like [`decode_server_msg`], optionally
+/// splitting the size out of a tick price the way the C++ decoder does.
+///
+/// C++ `processTickPriceMsg` calls `tickPrice` and then `tickSize` with the
+/// matching size tick type (`BidSize` for `Bid`, see
+/// [`TickType::size_tick`]). With `synthetic_tick_size` set, a `TickPrice`
+/// of such a type is followed by that `TickSize` event, for code ported
+/// from a C++ `EWrapper`. The default elsewhere in this crate is `false`:
+/// a single `TickPrice` carrying its `size`.
+pub fn decode_server_msgs(
+    data: &[u8],
+    server_version: i32,
+    synthetic_tick_size: bool,
+) -> Vec<IBEvent> {
+    let event = decode_server_msg(data, server_version);
+    let size_event = match &event {
+        IBEvent::TickPrice { req_id, tick_type, size, .. } if synthetic_tick_size => {
+            tick_type.size_tick().map(|tick_type| IBEvent::TickSize {
+                req_id: *req_id,
+                tick_type,
+                size: *size,
+            })
+        }
+        _ => None,
+    };
+    std::iter::once(event).chain(size_event).collect()
+}
+
+/// Decode a recorded byte stream of length-prefixed server messages.
+///
+/// `bytes` is what the socket delivered after the handshake, e.g. a
+/// capture saved to a `.bin` file: a concatenation of V100+ frames
+/// (`[4-byte BE length][body]`). Each frame is decoded with
+/// [`decode_server_msg`]. Trailing bytes that do not form a complete
+/// frame, or a frame header with an impossible length, end the replay
+/// with one `IBEvent::Unknown { msg_id: -1, .. }` holding the rest.
+///
+/// There is no C++ equivalent; this is the offline counterpart of the
+/// reader task, meant for regression tests.
+pub fn replay_stream(bytes: &[u8], server_version: i32) -> Vec<IBEvent> {
+    let mut buf = bytes::BytesMut::from(bytes);
+    let mut events = Vec::new();
+    loop {
+        match crate::transport::take_frame(&mut buf) {
+            Ok(Some(msg)) => events.push(decode_server_msg(&msg, server_version)),
+            Ok(None) if buf.is_empty() => break,
+            Ok(None) | Err(_) => {
+                tracing::warn!(remaining = buf.len(), "replay ended on an incomplete frame");
+                events.push(IBEvent::Unknown { msg_id: -1, data: buf.to_vec() });
+                break;
+            }
+        }
+    }
+    events
+}
+
 /// Inner implementation that returns Result for cleaner error handling.
 fn decode_server_msg_inner(data: &[u8], server_version: i32) -> Result<IBEvent> {
     let mut dec = MessageDecoder::new(data, server_version);
@@ -522,13 +593,7 @@ fn decode_err_msg(dec: &mut MessageDecoder) -> Result<IBEvent> {
         0
     };
 
-    Ok(IBEvent::Error {
-        req_id: id,
-        error_time,
-        code: error_code,
-        message: error_msg,
-        advanced_order_reject_json,
-    })
+    Ok(IBEvent::from_error(id, error_time, error_code, error_msg, advanced_order_reject_json))
 }
 
 /// Decode NEXT_VALID_ID (9).
@@ -657,7 +722,12 @@ fn decode_order_condition(dec: &mut MessageDecoder, condition_type: i32) -> Resu
                 is_conjunction_connection: is_conjunction, is_more, con_id, exchange, change_percent,
             })
         }
-        _ => Err(IBApiError::Decoding(format!("unknown condition type {condition_type}"))),
+        // The text protocol does not delimit condition payloads, so the
+        // fields after an unknown type cannot be skipped: decoding on would
+        // read the rest of the order from the wrong position.
+        Err(raw_type) => Err(IBApiError::Decoding(format!(
+            "unknown order condition type {raw_type}"
+        ))),
     }
 }
 
@@ -702,7 +772,7 @@ fn decode_tick_option_computation(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let version = if sv < server_version::PRICE_BASED_VOLATILITY {
         dec.decode_i32()?
     } else {
-        i32::MAX
+        UNSET_INTEGER
     };
     let req_id = dec.decode_i32()?;
     let tick_type = decode_tick_type(dec)?;
@@ -713,42 +783,26 @@ fn decode_tick_option_computation(dec: &mut MessageDecoder) -> Result<IBEvent> {
         0
     };
 
-    let mut implied_vol = dec.decode_f64()?;
-    if implied_vol == -1.0 { implied_vol = f64::MAX; }
-    let mut delta = dec.decode_f64()?;
-    if delta == -2.0 { delta = f64::MAX; }
+    // TWS sends -1 (prices, volatility) or -2 (greeks) for "not computed";
+    // C++ replaces them with UNSET_DOUBLE, which becomes `None` here.
+    let mut decode = |not_computed: f64| -> Result<Option<f64>> {
+        let value = dec.decode_f64()?;
+        let value = if value == not_computed { UNSET_DOUBLE } else { value };
+        Ok(value.unset_to_none())
+    };
+    let implied_vol = decode(-1.0)?;
+    let delta = decode(-2.0)?;
 
     let is_model = matches!(tick_type, TickType::ModelOption | TickType::DelayedModelOptionComputation);
     let (opt_price, pv_dividend, gamma, vega, theta, und_price) = if version >= 6 || is_model {
-        let mut op = dec.decode_f64()?;
-        if op == -1.0 { op = f64::MAX; }
-        let mut pvd = dec.decode_f64()?;
-        if pvd == -1.0 { pvd = f64::MAX; }
-        let mut g = dec.decode_f64()?;
-        if g == -2.0 { g = f64::MAX; }
-        let mut v = dec.decode_f64()?;
-        if v == -2.0 { v = f64::MAX; }
-        let mut t = dec.decode_f64()?;
-        if t == -2.0 { t = f64::MAX; }
-        let mut u = dec.decode_f64()?;
-        if u == -1.0 { u = f64::MAX; }
-        (Some(op), Some(pvd), Some(g), Some(v), Some(t), Some(u))
+        (decode(-1.0)?, decode(-1.0)?, decode(-2.0)?, decode(-2.0)?, decode(-2.0)?, decode(-1.0)?)
     } else {
         (None, None, None, None, None, None)
     };
 
-    let to_opt = |v: f64| if v == f64::MAX { None } else { Some(v) };
-
     Ok(IBEvent::TickOptionComputation {
         req_id, tick_type, tick_attrib,
-        implied_vol: to_opt(implied_vol),
-        delta: to_opt(delta),
-        opt_price: opt_price.and_then(to_opt),
-        pv_dividend: pv_dividend.and_then(to_opt),
-        gamma: gamma.and_then(to_opt),
-        vega: vega.and_then(to_opt),
-        theta: theta.and_then(to_opt),
-        und_price: und_price.and_then(to_opt),
+        implied_vol, delta, opt_price, pv_dividend, gamma, vega, theta, und_price,
     })
 }
 
@@ -864,7 +918,10 @@ fn decode_tick_by_tick(dec: &mut MessageDecoder) -> Result<IBEvent> {
             let mid_point = dec.decode_f64()?;
             Ok(IBEvent::TickByTickMidPoint { req_id, time, mid_point })
         }
-        _ => Ok(IBEvent::Unknown { msg_id: incoming::TICK_BY_TICK, data: Vec::new() }),
+        _ => {
+            tracing::warn!(req_id, tick_type, "unknown tick-by-tick type");
+            Ok(IBEvent::TickByTickUnknown { req_id, tick_type, time })
+        }
     }
 }
 
@@ -889,9 +946,9 @@ fn decode_order_status(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let client_id = dec.decode_i32()?;
     let why_held = dec.decode_string()?;
     let mkt_cap_price = if sv >= server_version::MARKET_CAP_PRICE {
-        dec.decode_f64()?
+        dec.decode_f64_max()?
     } else {
-        0.0
+        None
     };
     Ok(IBEvent::OrderStatus {
         order_id, status, filled, remaining, avg_fill_price,
@@ -1029,7 +1086,7 @@ fn decode_open_order(dec: &mut MessageDecoder) -> Result<IBEvent> {
             for _ in 0..count {
                 let mut leg = ComboLeg::default();
                 leg.con_id = dec.decode_i32()? as i64;
-                leg.ratio = dec.decode_i32()? as i64;
+                leg.ratio = dec.decode_decimal()?;
                 leg.action = dec.decode_enum_opt()?;
                 leg.exchange = dec.decode_string()?;
                 leg.open_close = LegOpenClose::try_from(dec.decode_i32()?).unwrap_or(LegOpenClose::Same);
@@ -1252,15 +1309,10 @@ fn decode_open_order(dec: &mut MessageDecoder) -> Result<IBEvent> {
     }
     if sv >= server_version::DURATION { order.duration = dec.decode_i32_max()?; }
     if sv >= server_version::POST_TO_ATS { order.post_to_ats = dec.decode_i32_max()?; }
-    if sv >= server_version::AUTO_CANCEL_PARENT {
-        order.auto_cancel_date = dec.decode_string()?;
-        order.filled_quantity = dec.decode_decimal_max()?;
-        order.ref_futures_con_id = dec.decode_i32_max()?;
+    // C++ `decodeAutoCancelParent`: OPEN_ORDER carries only autoCancelParent here.
+    // The autoCancelDate..parentPermId block belongs to COMPLETED_ORDER.
+    if version >= server_version::AUTO_CANCEL_PARENT {
         order.auto_cancel_parent = dec.decode_bool()?;
-        order.shareholder = dec.decode_string()?;
-        order.imbalance_only = dec.decode_bool()?;
-        order.route_marketable_to_bbo = dec.decode_bool()?;
-        order.parent_perm_id = dec.decode_i64_max()?;
     }
     if sv >= server_version::PEGBEST_PEGMID_OFFSETS {
         order.min_trade_qty = dec.decode_i32_max()?;
@@ -1293,7 +1345,7 @@ fn decode_open_order(dec: &mut MessageDecoder) -> Result<IBEvent> {
 /// Decode COMPLETED_ORDER (101). C++ `processCompletedOrderMsg`.
 fn decode_completed_order(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let sv = dec.server_version();
-    let version = i32::MAX; // COMPLETED_ORDER always uses UNSET_INTEGER
+    let version = UNSET_INTEGER; // COMPLETED_ORDER always uses UNSET_INTEGER
 
     let mut contract = decode_order_contract(dec)?;
     let mut order = Order::default();
@@ -1380,7 +1432,7 @@ fn decode_completed_order(dec: &mut MessageDecoder) -> Result<IBEvent> {
         for _ in 0..count {
             let mut leg = ComboLeg::default();
             leg.con_id = dec.decode_i32()? as i64;
-            leg.ratio = dec.decode_i32()? as i64;
+            leg.ratio = dec.decode_decimal()?;
             leg.action = dec.decode_enum_opt()?;
             leg.exchange = dec.decode_string()?;
             leg.open_close = LegOpenClose::try_from(dec.decode_i32()?).unwrap_or(LegOpenClose::Same);
@@ -1514,6 +1566,8 @@ fn decode_completed_order(dec: &mut MessageDecoder) -> Result<IBEvent> {
     order_state.completed_time = dec.decode_string()?;
     order_state.completed_status = dec.decode_string()?;
 
+    // All five are always sent, whatever the order type and exchange; only
+    // PLACE_ORDER makes them conditional. C++ `decodePegBestPegMidOrderAttributes`.
     if sv >= server_version::PEGBEST_PEGMID_OFFSETS {
         order.min_trade_qty = dec.decode_i32_max()?;
         order.min_compete_size = dec.decode_i32_max()?;
@@ -1648,9 +1702,9 @@ fn decode_portfolio_value(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let market_price = dec.decode_f64()?;
     let market_value = dec.decode_f64()?;
     let (average_cost, unrealized_pnl, realized_pnl) = if version >= 3 {
-        (dec.decode_f64()?, dec.decode_f64()?, dec.decode_f64()?)
+        (dec.decode_decimal()?, dec.decode_f64()?, dec.decode_f64()?)
     } else {
-        (0.0, 0.0, 0.0)
+        (Decimal::ZERO, 0.0, 0.0)
     };
     let account_name = if version >= 4 { dec.decode_string()? } else { String::new() };
 
@@ -1710,7 +1764,7 @@ fn decode_position_data(dec: &mut MessageDecoder) -> Result<IBEvent> {
     contract.local_symbol = dec.decode_string()?;
     if version >= 2 { contract.trading_class = dec.decode_string()?; }
     let position = dec.decode_decimal()?;
-    let avg_cost = if version >= 3 { dec.decode_f64()? } else { 0.0 };
+    let avg_cost = if version >= 3 { dec.decode_decimal()? } else { Decimal::ZERO };
     Ok(IBEvent::Position { account, contract: Box::new(contract), position, avg_cost })
 }
 
@@ -1721,7 +1775,7 @@ fn decode_position_multi(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let account = dec.decode_string()?;
     let contract = decode_order_contract(dec)?;
     let pos = dec.decode_decimal()?;
-    let avg_cost = dec.decode_f64()?;
+    let avg_cost = dec.decode_decimal()?;
     let model_code = dec.decode_string()?;
     Ok(IBEvent::PositionMulti { req_id, account, model_code, contract: Box::new(contract), pos, avg_cost })
 }
@@ -1845,24 +1899,8 @@ fn decode_contract_data(dec: &mut MessageDecoder) -> Result<IBEvent> {
         d.fund_subsequent_minimum_purchase = dec.decode_string()?;
         d.fund_blue_sky_states = dec.decode_string()?;
         d.fund_blue_sky_territories = dec.decode_string()?;
-        let dp = dec.decode_string()?;
-        d.fund_distribution_policy_indicator = match dp.as_str() {
-            "Y" | "1" => FundDistributionPolicyIndicator::AccumulationFund,
-            "D" | "2" => FundDistributionPolicyIndicator::IncomeFund,
-            _ => FundDistributionPolicyIndicator::None,
-        };
-        let at = dec.decode_string()?;
-        d.fund_asset_type = match at.as_str() {
-            "001" => FundAssetType::Others,
-            "002" => FundAssetType::MoneyMarket,
-            "003" => FundAssetType::FixedIncome,
-            "004" => FundAssetType::MultiAsset,
-            "005" => FundAssetType::Equity,
-            "006" => FundAssetType::Sector,
-            "007" => FundAssetType::Guaranteed,
-            "008" => FundAssetType::Alternative,
-            _ => FundAssetType::None,
-        };
+        d.fund_distribution_policy_indicator = dec.decode_enum()?;
+        d.fund_asset_type = dec.decode_enum()?;
     }
     if sv >= server_version::INELIGIBILITY_REASONS {
         let count = dec.decode_i32()?;
@@ -2000,9 +2038,19 @@ fn decode_sec_def_opt_params(dec: &mut MessageDecoder) -> Result<IBEvent> {
     for _ in 0..exp_count { expirations.push(dec.decode_string()?); }
     let strike_count = dec.decode_i32()?;
     let mut strikes = Vec::with_capacity(strike_count as usize);
-    for _ in 0..strike_count { strikes.push(dec.decode_f64()?); }
+    let mut strike_strings = Vec::with_capacity(strike_count as usize);
+    for _ in 0..strike_count {
+        // Keep the wire text too: "2.5" and "2.50" parse to the same f64.
+        let raw = dec.decode_string()?;
+        let strike = raw
+            .parse::<f64>()
+            .map_err(|e| IBApiError::Decoding(format!("invalid strike '{raw}': {e}")))?;
+        strikes.push(strike);
+        strike_strings.push(raw);
+    }
     Ok(IBEvent::SecurityDefinitionOptionalParameter {
         req_id, exchange, underlying_con_id, trading_class, multiplier, expirations, strikes,
+        strike_strings,
     })
 }
 
@@ -2047,6 +2095,10 @@ fn decode_market_depth_l2(dec: &mut MessageDecoder) -> Result<IBEvent> {
 }
 
 /// Decode MKT_DEPTH_EXCHANGES (80).
+///
+/// Before `SERVICE_DATA_TYPE` each entry is `exchange, secType, isL2`, with
+/// no listing exchange or aggregation group; like C++, the flag is mapped to
+/// a service data type of `"Deep2"` or `"Deep"`.
 fn decode_mkt_depth_exchanges(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let count = dec.decode_i32()?;
     let mut descriptions = Vec::with_capacity(count as usize);
@@ -2054,10 +2106,13 @@ fn decode_mkt_depth_exchanges(dec: &mut MessageDecoder) -> Result<IBEvent> {
         let mut d = DepthMktDataDescription::default();
         d.exchange = dec.decode_string()?;
         d.sec_type = dec.decode_string()?;
-        d.listing_exch = dec.decode_string()?;
-        d.service_data_type = dec.decode_string()?;
         if dec.server_version() >= server_version::SERVICE_DATA_TYPE {
+            d.listing_exch = dec.decode_string()?;
+            d.service_data_type = dec.decode_string()?;
             d.agg_group = dec.decode_i32_max()?;
+        } else {
+            let is_l2 = dec.decode_bool()?;
+            d.service_data_type = if is_l2 { "Deep2" } else { "Deep" }.to_string();
         }
         descriptions.push(d);
     }
@@ -2266,13 +2321,8 @@ fn decode_scanner_parameters(dec: &mut MessageDecoder) -> Result<IBEvent> {
 /// Decode PNL (94). C++ `processPnLMsg`.
 fn decode_pnl(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let req_id = dec.decode_i32()?;
-    let daily_pnl = dec.decode_f64()?;
-    let unrealized_pnl = if dec.server_version() >= server_version::UNREALIZED_PNL {
-        dec.decode_f64()?
-    } else { f64::MAX };
-    let realized_pnl = if dec.server_version() >= server_version::REALIZED_PNL {
-        dec.decode_f64()?
-    } else { f64::MAX };
+    let daily_pnl = dec.decode_f64()?.unset_to_none();
+    let (unrealized_pnl, realized_pnl) = decode_unrealized_realized_pnl(dec)?;
     Ok(IBEvent::Pnl { req_id, daily_pnl, unrealized_pnl, realized_pnl })
 }
 
@@ -2280,15 +2330,29 @@ fn decode_pnl(dec: &mut MessageDecoder) -> Result<IBEvent> {
 fn decode_pnl_single(dec: &mut MessageDecoder) -> Result<IBEvent> {
     let req_id = dec.decode_i32()?;
     let pos = dec.decode_decimal()?;
-    let daily_pnl = dec.decode_f64()?;
+    let daily_pnl = dec.decode_f64()?.unset_to_none();
+    let (unrealized_pnl, realized_pnl) = decode_unrealized_realized_pnl(dec)?;
+    let value = dec.decode_f64()?.unset_to_none();
+    Ok(IBEvent::PnlSingle { req_id, pos, daily_pnl, unrealized_pnl, realized_pnl, value })
+}
+
+/// Unrealized and realized P&L of PNL / PNL_SINGLE. Older servers omit
+/// them and C++ leaves them at `UNSET_DOUBLE`; TWS also sends `DBL_MAX`
+/// itself when a value is unavailable. Both become `None`.
+fn decode_unrealized_realized_pnl(
+    dec: &mut MessageDecoder,
+) -> Result<(Option<f64>, Option<f64>)> {
     let unrealized_pnl = if dec.server_version() >= server_version::UNREALIZED_PNL {
         dec.decode_f64()?
-    } else { f64::MAX };
+    } else {
+        UNSET_DOUBLE
+    };
     let realized_pnl = if dec.server_version() >= server_version::REALIZED_PNL {
         dec.decode_f64()?
-    } else { f64::MAX };
-    let value = dec.decode_f64()?;
-    Ok(IBEvent::PnlSingle { req_id, pos, daily_pnl, unrealized_pnl, realized_pnl, value })
+    } else {
+        UNSET_DOUBLE
+    };
+    Ok((unrealized_pnl.unset_to_none(), realized_pnl.unset_to_none()))
 }
 
 /// Decode NEWS_BULLETINS (14). C++ `processNewsBulletinsMsg`.
@@ -2634,6 +2698,14 @@ mod tests {
         assert_eq!(dec.decode_decimal().unwrap(), Decimal::ZERO);
     }
 
+    #[test]
+    fn decode_decimal_accepts_scientific() {
+        let data = make_fields(&["1.25E7", "2.5E-4"]);
+        let mut dec = MessageDecoder::new(&data, 150);
+        assert_eq!(dec.decode_decimal().unwrap(), Decimal::from(12_500_000));
+        assert_eq!(dec.decode_decimal().unwrap(), Decimal::new(25, 5));
+    }
+
     #[test]
     fn decode_i32_max_empty_is_none() {
         let data = make_fields(&[""]);
@@ -2848,6 +2920,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_server_msg_err_msg_connectivity() {
+        let msg = "Connectivity restored - data maintained";
+        let data = make_fields(&["4", "2", "-1", "1102", msg]);
+        match super::decode_server_msg(&data, 150) {
+            IBEvent::ConnectivityStatus { connected, data_lost, code, message } => {
+                assert!(connected);
+                assert!(!data_lost);
+                assert_eq!(code, 1102);
+                assert_eq!(message, msg);
+            }
+            other => panic!("expected ConnectivityStatus, got {other:?}"),
+        }
+
+        let data = make_fields(&["4", "2", "-1", "1101", "Connectivity restored - data lost"]);
+        assert!(matches!(
+            super::decode_server_msg(&data, 150),
+            IBEvent::ConnectivityStatus { connected: true, data_lost: true, .. }
+        ));
+        let data = make_fields(&["4", "2", "-1", "1100", "Connectivity lost"]);
+        assert!(matches!(
+            super::decode_server_msg(&data, 150),
+            IBEvent::ConnectivityStatus { connected: false, .. }
+        ));
+    }
+
     #[test]
     fn decode_server_msg_err_msg_v1() {
         // Old format: version=1, just a message
@@ -2916,6 +3014,90 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_order_condition_known_and_unknown_types() {
+        let data = make_fields(&["a", "1", "20260101 09:30:00"]);
+        let mut dec = MessageDecoder::new(&data, 176);
+        match super::decode_order_condition(&mut dec, 3).unwrap() {
+            OrderCondition::Time { is_conjunction_connection, is_more, time } => {
+                assert!(is_conjunction_connection);
+                assert!(is_more);
+                assert_eq!(time, "20260101 09:30:00");
+            }
+            other => panic!("expected Time, got {other:?}"),
+        }
+
+        // Type 2 is not assigned by IB. Its payload length is unknown, so
+        // the order fails to decode rather than decoding misaligned.
+        let data = make_fields(&["o", "next"]);
+        let mut dec = MessageDecoder::new(&data, 176);
+        assert!(matches!(
+            super::decode_order_condition(&mut dec, 2),
+            Err(IBApiError::Decoding(msg)) if msg.contains("type 2")
+        ));
+    }
+
+    #[test]
+    fn decode_tick_by_tick_midpoint() {
+        let data = make_fields(&["99", "7", "4", "1708876800", "150.125"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::TickByTickMidPoint { req_id, time, mid_point } => {
+                assert_eq!(req_id, 7);
+                assert_eq!(time, 1708876800);
+                assert_eq!(mid_point, 150.125);
+            }
+            other => panic!("expected TickByTickMidPoint, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_tick_by_tick_keeps_last_and_all_last_apart() {
+        for tick_type in [1, 2] {
+            let data = make_fields(&[
+                "99", "7", &tick_type.to_string(), "1708876800", "150.25", "100", "2", "ARCA", "I",
+            ]);
+            let event = super::decode_server_msg(&data, 176);
+            let kind = if tick_type == 1 { "TickByTickLast" } else { "TickByTickAllLast" };
+            assert!(event.to_string().starts_with(kind), "{event}");
+            match event {
+                IBEvent::TickByTickAllLast {
+                    req_id,
+                    tick_type: decoded,
+                    time,
+                    price,
+                    size,
+                    attrib,
+                    exchange,
+                    special_conditions,
+                } => {
+                    assert_eq!(req_id, 7);
+                    assert_eq!(decoded, tick_type);
+                    assert_eq!(time, 1708876800);
+                    assert_eq!(price, 150.25);
+                    assert_eq!(size, rust_decimal::Decimal::from(100));
+                    assert!(!attrib.past_limit);
+                    assert!(attrib.unreported);
+                    assert_eq!(exchange, "ARCA");
+                    assert_eq!(special_conditions, "I");
+                }
+                other => panic!("expected TickByTickAllLast, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn decode_tick_by_tick_unknown_type_keeps_req_id() {
+        let data = make_fields(&["99", "7", "5", "1708876800", "opaque", "payload"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::TickByTickUnknown { req_id, tick_type, time } => {
+                assert_eq!(req_id, 7);
+                assert_eq!(tick_type, 5);
+                assert_eq!(time, 1708876800);
+            }
+            other => panic!("expected TickByTickUnknown, got {other:?}"),
+        }
+    }
+
     #[test]
     fn decode_server_msg_unknown() {
         // Unknown message ID (below PROTOBUF_MSG_ID=200 to avoid protobuf dispatch)
@@ -2962,6 +3144,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_tick_price_synthetic_tick_size() {
+        use crate::protocol::TickType;
+
+        // TICK_PRICE: req_id=3, tick_type=68(DELAYED_LAST), price=9.5, size=40
+        let data = make_fields(&["1", "6", "3", "68", "9.5", "40", "0"]);
+        assert_eq!(super::decode_server_msgs(&data, 176, false).len(), 1);
+        let events = super::decode_server_msgs(&data, 176, true);
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], IBEvent::TickPrice { tick_type: TickType::DelayedLast, .. }));
+        match &events[1] {
+            IBEvent::TickSize { req_id, tick_type, size } => {
+                assert_eq!(*req_id, 3);
+                assert_eq!(*tick_type, TickType::DelayedLastSize);
+                assert_eq!(*size, rust_decimal::Decimal::from(40));
+            }
+            other => panic!("expected TickSize, got {other:?}"),
+        }
+
+        // HIGH has no size tick, so nothing is synthesized.
+        let data = make_fields(&["1", "6", "3", "6", "10.0", "0", "0"]);
+        assert_eq!(super::decode_server_msgs(&data, 176, true).len(), 1);
+    }
+
+    #[test]
+    fn decode_tick_option_computation_maps_sentinels_to_none() {
+        // TICK_OPTION_COMPUTATION: req_id=4, tick_type=10(BID_OPTION), attrib=1,
+        // iv, delta, opt_price, pv_dividend, gamma, vega, theta, und_price
+        let data = make_fields(&[
+            "21", "4", "10", "1", "-1", "-1", "-1", "0.5", "-2", "1.7976931348623157E308",
+            "-2", "-1",
+        ]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::TickOptionComputation {
+                tick_attrib, implied_vol, delta, opt_price, pv_dividend, gamma, vega, theta,
+                und_price, ..
+            } => {
+                assert_eq!(tick_attrib, 1);
+                // -1 is "not computed" for volatility and prices only.
+                assert_eq!(implied_vol, None);
+                assert_eq!(delta, Some(-1.0));
+                assert_eq!(opt_price, None);
+                assert_eq!(pv_dividend, Some(0.5));
+                // -2 is "not computed" for greeks; UNSET_DOUBLE on the wire too.
+                assert_eq!(gamma, None);
+                assert_eq!(vega, None);
+                assert_eq!(theta, None);
+                assert_eq!(und_price, None);
+            }
+            other => panic!("expected TickOptionComputation, got {other:?}"),
+        }
+    }
+
     #[test]
     fn decode_tick_size_msg() {
         // TICK_SIZE: msg_id=2, version=2, req_id=1, tick_type=0(BID_SIZE), size=500
@@ -2992,6 +3227,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decode_sec_def_opt_params_keeps_strike_order_and_text() {
+        // SECURITY_DEFINITION_OPTION_PARAMETER: req_id, exchange, underlying con_id,
+        // trading class, multiplier, expirations, strikes
+        let data = make_fields(&[
+            "75", "9", "SMART", "265598", "AAPL", "100",
+            "2", "20260116", "20260220",
+            "3", "2.5", "200.0", "2.50",
+        ]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::SecurityDefinitionOptionalParameter {
+                req_id, expirations, strikes, strike_strings, ..
+            } => {
+                assert_eq!(req_id, 9);
+                assert_eq!(expirations, vec!["20260116", "20260220"]);
+                assert_eq!(strikes, vec![2.5, 200.0, 2.5]);
+                assert_eq!(strike_strings, vec!["2.5", "200.0", "2.50"]);
+            }
+            other => panic!("expected SecurityDefinitionOptionalParameter, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_panicking_message_degrades_to_unknown() {
+        // SYMBOL_SAMPLES with count=-1 would size a Vec at usize::MAX.
+        let data = make_fields(&["79", "1", "-1"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::Unknown { msg_id, data: raw } => {
+                assert_eq!(msg_id, -1);
+                assert_eq!(raw, data);
+            }
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_delayed_tick_types() {
+        use crate::protocol::TickType;
+
+        // TICK_PRICE with tick_type=66 (DELAYED_BID), sent after reqMarketDataType(3)
+        let data = make_fields(&["1", "6", "1", "66", "150.25", "100", "0"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::TickPrice { tick_type, price, .. } => {
+                assert_eq!(tick_type, TickType::DelayedBid);
+                assert!((price - 150.25).abs() < 1e-10);
+            }
+            other => panic!("expected TickPrice, got {other:?}"),
+        }
+
+        // TICK_SIZE with tick_type=69 (DELAYED_BID_SIZE)
+        let data = make_fields(&["2", "2", "1", "69", "500"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::TickSize { tick_type, .. } => assert_eq!(tick_type, TickType::DelayedBidSize),
+            other => panic!("expected TickSize, got {other:?}"),
+        }
+
+        // TICK_GENERIC with tick_type=90 (DELAYED_HALTED)
+        let data = make_fields(&["45", "2", "1", "90", "0.0"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::TickGeneric { tick_type, .. } => assert_eq!(tick_type, TickType::DelayedHalted),
+            other => panic!("expected TickGeneric, got {other:?}"),
+        }
+    }
+
     #[test]
     fn decode_tick_string_msg() {
         // TICK_STRING: msg_id=46, version=2, req_id=1, tick_type=45(LAST_TIMESTAMP), value="1708876800"
@@ -3047,16 +3346,27 @@ mod tests {
             "150.50", "200", "0", "150.50", "1", "", "0.0"]);
         let event = super::decode_server_msg(&data, 176);
         match event {
-            IBEvent::OrderStatus { order_id, status, filled, remaining, avg_fill_price, perm_id, .. } => {
+            IBEvent::OrderStatus {
+                order_id, status, filled, remaining, avg_fill_price, perm_id, mkt_cap_price, ..
+            } => {
                 assert_eq!(order_id, 100);
                 assert_eq!(status, "Filled");
                 assert_eq!(filled, rust_decimal::Decimal::from(10));
                 assert_eq!(remaining, rust_decimal::Decimal::from(0));
                 assert!((avg_fill_price - 150.50).abs() < 1e-10);
                 assert_eq!(perm_id, 200);
+                assert_eq!(mkt_cap_price, Some(0.0));
             }
             other => panic!("expected OrderStatus, got {other:?}"),
         }
+
+        // An empty mktCapPrice is unset rather than zero.
+        let data = make_fields(&["3", "100", "Submitted", "0", "10",
+            "0", "200", "0", "0", "1", "", ""]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::OrderStatus { mkt_cap_price, .. } => assert_eq!(mkt_cap_price, None),
+            other => panic!("expected OrderStatus, got {other:?}"),
+        }
     }
 
     #[test]
@@ -3151,6 +3461,26 @@ mod tests {
     // Phase 4: Position decoder tests
     // ========================================================================
 
+    #[test]
+    fn decode_portfolio_value_keeps_average_cost_exact() {
+        // PORTFOLIO_VALUE: msg_id=7, version=8, conId, symbol, secType,
+        // lastTradeDate, strike, right, multiplier, primaryExchange, currency,
+        // localSymbol, tradingClass, position, marketPrice, marketValue,
+        // averageCost, unrealizedPNL, realizedPNL, accountName
+        let data = make_fields(&["7", "8",
+            "495512552", "ES", "FUT", "20260320", "0", "", "50", "", "USD", "ESH6", "ES",
+            "2", "5012.25", "501225.0", "250612.4500000001", "-12.5", "0", "DU123"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::UpdatePortfolio { contract, position, average_cost, account_name, .. } => {
+                assert_eq!(contract.symbol, "ES");
+                assert_eq!(position, Decimal::from(2));
+                assert_eq!(average_cost.to_string(), "250612.4500000001");
+                assert_eq!(account_name, "DU123");
+            }
+            other => panic!("expected UpdatePortfolio, got {other:?}"),
+        }
+    }
+
     #[test]
     fn decode_position_data_msg() {
         // POSITION_DATA: msg_id=61, version=3,
@@ -3167,7 +3497,7 @@ mod tests {
                 assert_eq!(account, "DU123");
                 assert_eq!(contract.symbol, "AAPL");
                 assert_eq!(position, rust_decimal::Decimal::from(100));
-                assert!((avg_cost - 150.0).abs() < 1e-10);
+                assert_eq!(avg_cost, Decimal::new(15000, 2));
             }
             other => panic!("expected Position, got {other:?}"),
         }
@@ -3279,9 +3609,44 @@ mod tests {
         match event {
             IBEvent::Pnl { req_id, daily_pnl, unrealized_pnl, realized_pnl } => {
                 assert_eq!(req_id, 1);
-                assert!((daily_pnl - 250.50).abs() < 1e-10);
-                assert!((unrealized_pnl - 500.0).abs() < 1e-10);
-                assert!((realized_pnl - 100.0).abs() < 1e-10);
+                assert_eq!(daily_pnl, Some(250.50));
+                assert_eq!(unrealized_pnl, Some(500.0));
+                assert_eq!(realized_pnl, Some(100.0));
+            }
+            other => panic!("expected Pnl, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_pnl_maps_unset_values_to_none() {
+        // Before UNREALIZED_PNL (129) only the daily P&L is sent.
+        let data = make_fields(&["94", "1", "250.50"]);
+        match super::decode_server_msg(&data, 128) {
+            IBEvent::Pnl { daily_pnl, unrealized_pnl, realized_pnl, .. } => {
+                assert_eq!(daily_pnl, Some(250.50));
+                assert_eq!(unrealized_pnl, None);
+                assert_eq!(realized_pnl, None);
+            }
+            other => panic!("expected Pnl, got {other:?}"),
+        }
+
+        // Before REALIZED_PNL (135) the realized P&L is missing.
+        let data = make_fields(&["95", "1", "100", "25.50", "50.0", "15025.0"]);
+        match super::decode_server_msg(&data, 134) {
+            IBEvent::PnlSingle { unrealized_pnl, realized_pnl, value, .. } => {
+                assert_eq!(unrealized_pnl, Some(50.0));
+                assert_eq!(realized_pnl, None);
+                assert_eq!(value, Some(15025.0));
+            }
+            other => panic!("expected PnlSingle, got {other:?}"),
+        }
+
+        // A current server sends DBL_MAX for values it cannot compute.
+        let data = make_fields(&["94", "1", "1.7976931348623157E308", "500.0", "100.0"]);
+        match super::decode_server_msg(&data, 176) {
+            IBEvent::Pnl { daily_pnl, unrealized_pnl, .. } => {
+                assert_eq!(daily_pnl, None);
+                assert_eq!(unrealized_pnl, Some(500.0));
             }
             other => panic!("expected Pnl, got {other:?}"),
         }
@@ -3297,10 +3662,10 @@ mod tests {
             IBEvent::PnlSingle { req_id, pos, daily_pnl, unrealized_pnl, realized_pnl, value } => {
                 assert_eq!(req_id, 1);
                 assert_eq!(pos, rust_decimal::Decimal::from(100));
-                assert!((daily_pnl - 25.50).abs() < 1e-10);
-                assert!((unrealized_pnl - 50.0).abs() < 1e-10);
-                assert!((realized_pnl - 10.0).abs() < 1e-10);
-                assert!((value - 15025.0).abs() < 1e-10);
+                assert_eq!(daily_pnl, Some(25.50));
+                assert_eq!(unrealized_pnl, Some(50.0));
+                assert_eq!(realized_pnl, Some(10.0));
+                assert_eq!(value, Some(15025.0));
             }
             other => panic!("expected PnlSingle, got {other:?}"),
         }
@@ -3639,4 +4004,176 @@ mod tests {
         assert_eq!(UsePriceMgmtAlgo::try_from(1).unwrap(), UsePriceMgmtAlgo::Use);
         assert!(UsePriceMgmtAlgo::try_from(99).is_err());
     }
+
+    #[test]
+    fn decode_bond_contract_data_as_bond() {
+        // BOND_CONTRACT_DATA at sv 176: no version field, no lastTradeDate
+        // or trading hours fields.
+        let data = make_fields(&[
+            "18", "7", "IBCID123", "BOND", "912828ZQ6", "0.5", "20300131", "20200131",
+            "AA+", "GOVT", "FIXED", "0", "1", "0", "T 0 1/2 01/31/30", "SMART", "USD",
+            "GOVT", "", "4711", "0.0001", "LMT", "SMART,BONDDESK", "20250131", "CALL",
+            "0", "", "United States Treasury", "", "0", "0", "", "", "1", "1", "1",
+        ]);
+        let IBEvent::BondContractDetails { req_id, details } = super::decode_server_msg(&data, 176)
+        else {
+            panic!("expected BondContractDetails");
+        };
+        assert_eq!(req_id, 7);
+        let bond = details.as_bond().unwrap();
+        assert_eq!(bond.con_id, 4711);
+        assert_eq!(bond.cusip, "912828ZQ6");
+        assert_eq!(bond.coupon, 0.5);
+        assert_eq!(bond.maturity, "20300131");
+        assert_eq!(bond.issue_date, "20200131");
+        assert_eq!(bond.ratings, "AA+");
+        assert_eq!((bond.convertible, bond.callable, bond.putable), (false, true, false));
+        assert_eq!(bond.desc_append, "T 0 1/2 01/31/30");
+        assert_eq!(bond.next_option_type, "CALL");
+
+        let mut stock = *details;
+        stock.contract.sec_type = Some(SecType::Stock);
+        assert!(stock.as_bond().is_none());
+    }
+
+    #[test]
+    fn decode_open_order_auto_cancel_parent_layout() {
+        // Full OPEN_ORDER body at sv 176: no version field, faProfile still
+        // sent, and only autoCancelParent in the AUTO_CANCEL_PARENT slot.
+        let data = make_fields(&[
+            "5", "42",
+            // contract
+            "265598", "AAPL", "STK", "", "0", "", "", "SMART", "USD", "AAPL", "NMS",
+            // action .. goodAfterTime, sharesAllocation
+            "BUY", "100", "LMT", "150.5", "", "DAY", "", "DU123", "", "0", "ref1", "0", "777",
+            "0", "0", "0", "", "",
+            // FA params, faProfile, modelCode
+            "", "", "", "", "",
+            // goodTillDate .. settlingFirm, short sale params, auctionStrategy
+            "", "", "", "", "0", "", "-1", "0",
+            // box, peg to stk, displaySize .. ocaType, eTradeOnly/firmQuoteOnly/nbboPriceCap
+            "", "", "", "", "", "0", "0", "0", "0", "", "3", "0", "0", "",
+            // parentId, triggerMethod, vol params, trail params, basis points
+            "0", "0", "", "", "", "", "0", "", "", "", "", "",
+            // combo legs, order combo legs, smart combo routing params
+            "", "0", "0", "0",
+            // scale, hedge, optOutSmartRouting, clearing, notHeld, delta neutral
+            "", "", "", "", "0", "", "", "0", "0",
+            // algoStrategy, solicited, whatIf, status
+            "", "0", "0", "Submitted",
+            // what-if margins, commissions, warningText, randomize
+            "", "", "", "", "", "", "", "", "", "", "", "", "", "", "0", "0",
+            // conditions, adjusted order params, soft dollar tier
+            "0", "", "", "", "", "", "", "", "0", "", "", "",
+            // cashQty .. postToAts
+            "", "0", "0", "0", "0", "", "",
+            // autoCancelParent
+            "1",
+            // PEGBEST_PEGMID_OFFSETS
+            "5", "", "", "", "0.25",
+        ]);
+        let IBEvent::OpenOrder { order_id, contract, order, order_state } =
+            super::decode_server_msg(&data, 176)
+        else {
+            panic!("expected OpenOrder");
+        };
+        assert_eq!(order_id, 42);
+        assert_eq!(contract.symbol, "AAPL");
+        assert_eq!(order.lmt_price, Some(150.5));
+        assert_eq!(order.perm_id, 777);
+        assert_eq!(order_state.status, "Submitted");
+        assert!(order.auto_cancel_parent);
+        assert!(!order.imbalance_only, "imbalanceOnly is only sent from IMBALANCE_ONLY");
+        assert_eq!(order.parent_perm_id, None);
+        assert_eq!(order.min_trade_qty, Some(5));
+        assert_eq!(order.mid_offset_at_half, Some(0.25));
+    }
+
+    #[test]
+    fn decode_completed_order_reads_peg_offsets_for_every_order_type() {
+        // Full COMPLETED_ORDER body at sv 176. Unlike PLACE_ORDER, which only
+        // sends minTradeQty for IBKRATS and the offsets for PEG BEST / PEG MID,
+        // TWS always sends all five PEGBEST_PEGMID_OFFSETS fields (C++
+        // `decodePegBestPegMidOrderAttributes`).
+        let completed_order = |order_type: &str, exchange: &str, peg: [&str; 5]| {
+            let mut fields = vec![
+                "101",
+                // contract
+                "265598", "AAPL", "STK", "", "0", "", "", exchange, "USD", "AAPL", "NMS",
+                // action .. goodAfterTime
+                "BUY", "100", order_type, "", "", "DAY", "", "DU123", "", "0", "ref1", "777",
+                "0", "0", "0", "",
+                // FA params, faProfile, modelCode
+                "", "", "", "", "",
+                // goodTillDate .. settlingFirm, short sale params
+                "", "", "", "", "0", "", "-1",
+                // box, stock range, displaySize .. triggerMethod
+                "", "", "", "", "", "0", "0", "0", "", "3", "0",
+                // vol params, continuousUpdate, referencePriceType, trail params
+                "", "", "", "", "0", "", "", "",
+                // combo legs, order combo legs, smart combo routing params
+                "", "0", "0", "0",
+                // scale, hedge, clearing, notHeld, delta neutral, algo, solicited
+                "", "", "", "", "", "", "0", "0", "", "0",
+                // status, randomize, conditions
+                "Filled", "0", "0", "0",
+                // trailStopPrice, lmtPriceOffset, cashQty, autoPriceForHedge, omsContainer
+                "", "", "", "0", "0",
+                // autoCancelDate .. parentPermId
+                "", "100", "", "0", "", "0", "0", "",
+                // completedTime, completedStatus
+                "20260105 10:30:00", "Filled Size: 100",
+            ];
+            fields.extend(peg);
+            make_fields(&fields)
+        };
+
+        let data = completed_order("PEG BEST", "IBKRATS", ["5", "10", "0.01", "0.5", "0.25"]);
+        let IBEvent::CompletedOrder { contract, order, order_state } =
+            super::decode_server_msg(&data, 176)
+        else {
+            panic!("expected CompletedOrder");
+        };
+        assert_eq!(contract.exchange, "IBKRATS");
+        assert_eq!(order.order_type, Some(OrderType::Other("PEG BEST".into())));
+        assert_eq!(order.perm_id, 777);
+        assert_eq!(order.filled_quantity, Some(Decimal::from(100)));
+        assert_eq!(order_state.status, "Filled");
+        assert_eq!(order_state.completed_status, "Filled Size: 100");
+        assert_eq!(order.min_trade_qty, Some(5));
+        assert_eq!(order.min_compete_size, Some(10));
+        assert_eq!(order.compete_against_best_offset, Some(0.01));
+        assert_eq!(order.mid_offset_at_whole, Some(0.5));
+        assert_eq!(order.mid_offset_at_half, Some(0.25));
+
+        // A market order on SMART still gets the five (empty) fields.
+        let data = completed_order("MKT", "SMART", ["", "", "", "", ""]);
+        let IBEvent::CompletedOrder { order, order_state, .. } =
+            super::decode_server_msg(&data, 176)
+        else {
+            panic!("expected CompletedOrder");
+        };
+        assert_eq!(order.order_type, Some(OrderType::Market));
+        assert_eq!(order_state.completed_time, "20260105 10:30:00");
+        assert_eq!(order_state.completed_status, "Filled Size: 100");
+        assert_eq!(order.min_trade_qty, None);
+        assert_eq!(order.min_compete_size, None);
+        assert_eq!(order.mid_offset_at_half, None);
+    }
+
+    #[test]
+    fn decode_mkt_depth_exchanges_before_service_data_type() {
+        // sv 115: exchange, secType, isL2 per entry.
+        let data = make_fields(&["80", "2", "ISLAND", "STK", "1", "LSE", "STK", "0"]);
+        let IBEvent::MktDepthExchanges { descriptions } = super::decode_server_msg(&data, 115)
+        else {
+            panic!("expected MktDepthExchanges");
+        };
+        assert_eq!(descriptions.len(), 2);
+        assert_eq!(descriptions[0].exchange, "ISLAND");
+        assert_eq!(descriptions[0].service_data_type, "Deep2");
+        assert_eq!(descriptions[0].listing_exch, "");
+        assert_eq!(descriptions[0].agg_group, None);
+        assert_eq!(descriptions[1].service_data_type, "Deep");
+    }
 }
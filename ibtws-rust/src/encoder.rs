@@ -28,6 +28,46 @@ fn is_ascii_printable(s: &str) -> bool {
         .all(|b| (32..127).contains(&b) || b == 9 || b == 10 || b == 13)
 }
 
+// ============================================================================
+// ContractFields
+// ============================================================================
+
+/// Which optional contract fields [`MessageEncoder::encode_contract_for_request`]
+/// writes.
+///
+/// The request messages share one contract field sequence but differ in which
+/// optional fields they carry, usually depending on the server version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractFields {
+    /// Leading `conId`.
+    pub con_id: bool,
+    /// `primaryExchange`, after `exchange`.
+    pub primary_exchange: bool,
+    /// `tradingClass`, after `localSymbol`.
+    pub trading_class: bool,
+    /// `includeExpired`, after `tradingClass`.
+    pub include_expired: bool,
+    /// `secIdType` and `secId`, after `includeExpired`.
+    pub sec_id: bool,
+}
+
+impl ContractFields {
+    /// The sequence written by [`MessageEncoder::encode_contract`].
+    pub const STANDARD: Self = Self {
+        con_id: true,
+        primary_exchange: true,
+        trading_class: true,
+        include_expired: true,
+        sec_id: false,
+    };
+}
+
+impl Default for ContractFields {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
 // ============================================================================
 // MessageEncoder
 // ============================================================================
@@ -66,12 +106,19 @@ impl MessageEncoder {
     /// Finalize the message: compute length, write big-endian length header,
     /// return the complete framed message as bytes.
     ///
+    /// Fails with `IBApiError::Encoding` for an empty body or one longer than
+    /// `MAX_MSG_LEN`; TWS drops the connection on either instead of
+    /// answering with an error.
+    ///
     /// Mirrors C++ `EClientSocket::encodeMsgLen` + `closeAndSend`.
     pub fn finalize(mut self) -> Result<BytesMut> {
         let msg_len = self.buf.len() - HEADER_LEN;
+        if msg_len == 0 {
+            return Err(IBApiError::Encoding("message is empty".into()));
+        }
         if msg_len > MAX_MSG_LEN {
             return Err(IBApiError::Encoding(format!(
-                "message too long: {msg_len} bytes (max {MAX_MSG_LEN})"
+                "message exceeds max length: {msg_len} bytes (max {MAX_MSG_LEN})"
             )));
         }
         // Write big-endian length at offset 0.
@@ -130,6 +177,18 @@ impl MessageEncoder {
         self
     }
 
+    /// Encode a price field with the precision of C++ `EncodeField<double>`.
+    ///
+    /// The value is rounded to 14 significant digits (`%.14g`) before it is
+    /// written, so arithmetic noise such as `150.000000000001` or
+    /// `0.30000000000000004` goes out as `150` and `0.3`, which TWS may
+    /// otherwise reject as off the tick grid. Unlike
+    /// [`encode_field_f64`](Self::encode_field_f64) this does not round-trip
+    /// every bit, so use it for prices only.
+    pub fn encode_field_price(&mut self, value: f64) -> &mut Self {
+        self.encode_field_f64(round_significant(value))
+    }
+
     /// Encode a bool field: "1\0" for true, "0\0" for false.
     ///
     /// Mirrors C++ `EncodeField<bool>` which converts to int first.
@@ -189,6 +248,12 @@ impl MessageEncoder {
         }
     }
 
+    /// Encode Option<f64> price: None → "\0", Some(v) → value rounded like
+    /// [`encode_field_price`](Self::encode_field_price).
+    pub fn encode_field_max_price(&mut self, value: Option<f64>) -> &mut Self {
+        self.encode_field_max_f64(value.map(round_significant))
+    }
+
     /// Encode Option<Decimal>: None → "\0", Some(v) → value.
     pub fn encode_field_max_decimal(&mut self, value: Option<&Decimal>) -> &mut Self {
         match value {
@@ -265,19 +330,45 @@ impl MessageEncoder {
     /// Mirrors C++ `EClient::EncodeContract`. Encodes the standard set of
     /// contract fields used in most request messages.
     pub fn encode_contract(&mut self, contract: &Contract) -> &mut Self {
-        self.encode_field_i64(contract.con_id)
-            .encode_field_str(&contract.symbol)
+        self.encode_contract_for_request(contract, ContractFields::STANDARD)
+    }
+
+    /// Encode the contract field sequence, including only the optional
+    /// fields selected by `fields`.
+    ///
+    /// Mirrors the inline contract encoding in C++ `EClient::reqMktData`,
+    /// `reqHistoricalData`, `reqRealTimeBars` and friends.
+    pub fn encode_contract_for_request(
+        &mut self,
+        contract: &Contract,
+        fields: ContractFields,
+    ) -> &mut Self {
+        if fields.con_id {
+            self.encode_field_i64(contract.con_id);
+        }
+        self.encode_field_str(&contract.symbol)
             .encode_field_opt_display(contract.sec_type.as_ref())
             .encode_field_str(&contract.last_trade_date_or_contract_month)
             .encode_field_max_f64(contract.strike)
             .encode_field_opt_display(contract.right.as_ref())
             .encode_field_str(&contract.multiplier)
-            .encode_field_str(&contract.exchange)
-            .encode_field_str(&contract.primary_exchange)
-            .encode_field_str(&contract.currency)
-            .encode_field_str(&contract.local_symbol)
-            .encode_field_str(&contract.trading_class)
-            .encode_field_bool(contract.include_expired)
+            .encode_field_str(&contract.exchange);
+        if fields.primary_exchange {
+            self.encode_field_str(&contract.primary_exchange);
+        }
+        self.encode_field_str(&contract.currency)
+            .encode_field_str(&contract.local_symbol);
+        if fields.trading_class {
+            self.encode_field_str(&contract.trading_class);
+        }
+        if fields.include_expired {
+            self.encode_field_bool(contract.include_expired);
+        }
+        if fields.sec_id {
+            self.encode_field_opt_display(contract.sec_id_type.as_ref())
+                .encode_field_str(&contract.sec_id);
+        }
+        self
     }
 
     /// Encode a TagValue list as `"key1=val1;key2=val2;\0"`.
@@ -318,6 +409,14 @@ impl MessageEncoder {
     }
 }
 
+/// Round `value` to the 14 significant digits of C++ `%.14g`.
+fn round_significant(value: f64) -> f64 {
+    if !value.is_finite() {
+        return value;
+    }
+    format!("{value:.13e}").parse().unwrap_or(value)
+}
+
 // ============================================================================
 // Connect request builder (special case — not null-terminated fields)
 // ============================================================================
@@ -442,6 +541,25 @@ mod tests {
         assert_eq!(parsed, 100.0);
     }
 
+    #[test]
+    fn encode_field_price_drops_spurious_digits() {
+        let encode = |value: f64| {
+            encode_body(|enc| {
+                enc.encode_field_price(value);
+            })
+        };
+        assert_eq!(encode(150.1), b"150.1\0");
+        assert_eq!(encode(150.0 + 1e-12), b"150\0");
+        assert_eq!(encode(0.1 + 0.2), b"0.3\0");
+        assert_eq!(encode(1234.5678901234), b"1234.5678901234\0");
+        assert_eq!(encode(f64::INFINITY), b"Infinity\0");
+
+        let body = encode_body(|enc| {
+            enc.encode_field_max_price(Some(99.99 * 3.0)).encode_field_max_price(None);
+        });
+        assert_eq!(body, b"299.97\0\0");
+    }
+
     #[test]
     fn encode_field_str_basic() {
         let body = encode_body(|enc| {
@@ -600,6 +718,121 @@ mod tests {
         assert_eq!(fields[12], b"0");        // includeExpired = false
     }
 
+    fn option_contract() -> Contract {
+        use crate::models::enums::{Right, SecIdType, SecType};
+
+        Contract {
+            con_id: 12087792,
+            symbol: "SPY".to_string(),
+            sec_type: Some(SecType::Option),
+            last_trade_date_or_contract_month: "20261218".to_string(),
+            strike: Some(450.5),
+            right: Some(Right::Call),
+            multiplier: "100".to_string(),
+            exchange: "SMART".to_string(),
+            primary_exchange: "ARCA".to_string(),
+            currency: "USD".to_string(),
+            local_symbol: "SPY   261218C00450500".to_string(),
+            trading_class: "SPY".to_string(),
+            include_expired: true,
+            sec_id_type: Some(SecIdType::Isin),
+            sec_id: "US78462F1030".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The contract fields as `req_mkt_data`, `req_historical_data` and
+    /// `req_real_time_bars` wrote them inline before
+    /// `encode_contract_for_request` existed.
+    fn inline_contract(
+        enc: &mut MessageEncoder,
+        contract: &Contract,
+        con_id: bool,
+        trading_class: bool,
+        include_expired: bool,
+    ) {
+        if con_id {
+            enc.encode_field_i64(contract.con_id);
+        }
+        enc.encode_field_str(&contract.symbol);
+        enc.encode_field_opt_display(contract.sec_type.as_ref());
+        enc.encode_field_str(&contract.last_trade_date_or_contract_month);
+        enc.encode_field_max_f64(contract.strike);
+        enc.encode_field_opt_display(contract.right.as_ref());
+        enc.encode_field_str(&contract.multiplier);
+        enc.encode_field_str(&contract.exchange);
+        enc.encode_field_str(&contract.primary_exchange);
+        enc.encode_field_str(&contract.currency);
+        enc.encode_field_str(&contract.local_symbol);
+        if trading_class {
+            enc.encode_field_str(&contract.trading_class);
+        }
+        if include_expired {
+            enc.encode_field_bool(contract.include_expired);
+        }
+    }
+
+    #[test]
+    fn encode_contract_for_request_matches_inline_sequences() {
+        let contract = option_contract();
+        for con_id in [false, true] {
+            for trading_class in [false, true] {
+                for include_expired in [false, true] {
+                    let fields = ContractFields {
+                        con_id,
+                        trading_class,
+                        include_expired,
+                        ..ContractFields::STANDARD
+                    };
+                    assert_eq!(
+                        encode_body(|enc| {
+                            enc.encode_contract_for_request(&contract, fields);
+                        }),
+                        encode_body(|enc| {
+                            inline_contract(enc, &contract, con_id, trading_class, include_expired)
+                        }),
+                        "{fields:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn encode_contract_is_standard_fields() {
+        let contract = option_contract();
+        let standard = encode_body(|enc| {
+            enc.encode_contract_for_request(&contract, ContractFields::STANDARD);
+        });
+        assert_eq!(standard, encode_body(|enc| {
+            enc.encode_contract(&contract);
+        }));
+        let expected = [
+            "12087792", "SPY", "OPT", "20261218", "450.5", "C", "100", "SMART", "ARCA",
+            "USD", "SPY   261218C00450500", "SPY", "1",
+        ]
+        .map(|f| format!("{f}\0"))
+        .concat();
+        assert_eq!(standard, expected.as_bytes());
+    }
+
+    #[test]
+    fn encode_contract_for_request_optional_fields() {
+        let contract = option_contract();
+        let body = encode_body(|enc| {
+            enc.encode_contract_for_request(
+                &contract,
+                ContractFields { primary_exchange: false, sec_id: true, ..ContractFields::STANDARD },
+            );
+        });
+        let fields: Vec<&[u8]> = body.split(|&b| b == 0).collect();
+        assert_eq!(fields[7], b"SMART");
+        assert_eq!(fields[8], b"USD"); // no primaryExchange
+        assert_eq!(fields[12], b"ISIN");
+        assert_eq!(fields[13], b"US78462F1030");
+        assert_eq!(fields.len(), 15);
+    }
+
     #[test]
     fn finalize_message_length() {
         let mut enc = MessageEncoder::new(150);
@@ -614,6 +847,26 @@ mod tests {
         assert_eq!(buf.len(), HEADER_LEN + 5);
     }
 
+    #[test]
+    fn finalize_rejects_empty_and_oversized_messages() {
+        let err = MessageEncoder::new(150).finalize().unwrap_err();
+        assert!(matches!(err, IBApiError::Encoding(ref msg) if msg.contains("empty")), "{err:?}");
+
+        // 16 MiB of text plus its terminator is past MAX_MSG_LEN (16 MiB - 1).
+        let mut enc = MessageEncoder::new(150);
+        enc.encode_field_str(&"x".repeat(16 * 1024 * 1024));
+        let err = enc.finalize().unwrap_err();
+        assert!(
+            matches!(err, IBApiError::Encoding(ref msg) if msg.contains("exceeds max length")),
+            "{err:?}"
+        );
+
+        // The largest allowed body still frames.
+        let mut enc = MessageEncoder::new(150);
+        enc.encode_field_str(&"x".repeat(MAX_MSG_LEN - 1));
+        assert_eq!(enc.finalize().unwrap().len(), HEADER_LEN + MAX_MSG_LEN);
+    }
+
     #[test]
     fn build_connect_request_basic() {
         let buf = build_connect_request(None).unwrap();
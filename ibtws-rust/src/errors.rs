@@ -30,6 +30,16 @@ pub enum IBApiError {
         advanced_order_reject_json: String,
     },
 
+    /// The server rejected a request for exceeding its historical data
+    /// pacing limits (error 162 or 420 with a "pacing violation" message).
+    /// Retry after waiting.
+    #[error("Pacing violation (id={id}, code={code}): {message}")]
+    PacingViolation {
+        id: i32,
+        code: i32,
+        message: String,
+    },
+
     /// Operation timed out waiting for a response.
     #[error("Timeout: {0}")]
     Timeout(String),
@@ -38,10 +48,290 @@ pub enum IBApiError {
     #[error("Disconnected: {0}")]
     Disconnected(String),
 
+    /// A request whose answer carries no request ID is already waiting for
+    /// that answer (e.g. a second `IBClient::positions` call before the
+    /// first has returned). Retry once the first call is done.
+    #[error("Request in progress: {0}")]
+    InProgress(String),
+
     /// I/O error from the underlying transport.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+impl IBApiError {
+    /// Error for an `IBEvent::Error` answering a request.
+    ///
+    /// Pacing violations become [`PacingViolation`](Self::PacingViolation);
+    /// everything else is [`Server`](Self::Server).
+    pub fn server(id: i32, code: i32, message: String, advanced_order_reject_json: String) -> Self {
+        let pacing = IbErrorCode(code).is_pacing_violation()
+            && message.to_ascii_lowercase().contains("pacing violation");
+        if pacing {
+            Self::PacingViolation { id, code, message }
+        } else {
+            Self::Server { id, code, message, advanced_order_reject_json }
+        }
+    }
+}
+
+// ============================================================================
+// IbErrorCode
+// ============================================================================
+
+/// Error code of an `IBEvent::Error`, with classification helpers.
+///
+/// TWS reports failures, warnings and status notices through the same
+/// `EWrapper::error` callback; these predicates sort the common codes into
+/// categories so callers need not hardcode integers. Codes not listed here
+/// fall into no category.
+///
+/// There is no C++ equivalent -- `EClientErrors.h` only defines the
+/// client-side codes (5xx).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct IbErrorCode(pub i32);
+
+impl IbErrorCode {
+    pub fn code(self) -> i32 {
+        self.0
+    }
+
+    /// Informational rather than a failure: the 2100-2199 range (data farm
+    /// status, order warnings), the notices TWS sends for orders it still
+    /// accepts -- 399 ("Order message: ... will not be placed at the
+    /// exchange until ...") and 404 (order held while shares are located
+    /// for a short sale) -- and 10167 ("Displaying delayed market data").
+    /// These sometimes carry a request or order ID but do not end it.
+    pub fn is_warning(self) -> bool {
+        (2100..2200).contains(&self.0) || matches!(self.0, 399 | 404 | 10167)
+    }
+
+    /// Historical data pacing (162) or real-time query (420) rejections.
+    /// Both codes are also used for other failures; see
+    /// [`IBApiError::server`] for the message check.
+    pub fn is_pacing_violation(self) -> bool {
+        matches!(self.0, 162 | 420)
+    }
+
+    /// Connectivity between TWS and IB's servers was lost (1100, 2110) or
+    /// restored (1101, 1102).
+    pub fn is_connectivity(self) -> bool {
+        matches!(self.0, 1100 | 1101 | 1102 | 2110)
+    }
+
+    /// No market data during competing live session (10197): another
+    /// session of the same username is receiving live data, so this one
+    /// gets none. Snapshot requests fail with it.
+    pub fn is_competing_live_session(self) -> bool {
+        self.0 == 10197
+    }
+
+    /// Wall Street Horizon event data was requested before the meta data
+    /// (10282). Call `IBClient::wsh_meta_data` first.
+    pub fn is_wsh_not_subscribed(self) -> bool {
+        self.0 == 10282
+    }
+
+    /// Market data, HMDS and sec-def farm connection status notices
+    /// (e.g. 2104 "Market data farm connection is OK").
+    pub fn is_market_data_farm(self) -> bool {
+        matches!(self.0, 2103..=2108 | 2119 | 2157 | 2158)
+    }
+
+    /// Short description of well-known codes, or a generic fallback.
+    /// The server's message is usually more specific.
+    pub fn description(self) -> &'static str {
+        match self.0 {
+            162 => "Historical market data service error",
+            200 => "No security definition has been found for the request",
+            201 => "Order rejected",
+            202 => "Order cancelled",
+            320 => "Server error when reading an API client request",
+            321 => "Server error when validating an API client request",
+            322 => "Server error when processing an API client request",
+            354 => "Requested market data is not subscribed",
+            366 => "No historical data query found for ticker id",
+            399 => "Order message",
+            404 => "Order held while locating shares for a short sale",
+            420 => "Invalid real-time query",
+            502 => "Couldn't connect to TWS",
+            504 => "Not connected",
+            1100 => "Connectivity between IB and TWS has been lost",
+            1101 => "Connectivity restored, data lost",
+            1102 => "Connectivity restored, data maintained",
+            2103 => "Market data farm connection is broken",
+            2104 => "Market data farm connection is OK",
+            2105 => "HMDS data farm connection is broken",
+            2106 => "HMDS data farm connection is OK",
+            2107 => "HMDS data farm connection is inactive",
+            2108 => "Market data farm connection is inactive",
+            2110 => "Connectivity between TWS and server is broken",
+            2119 => "Market data farm is connecting",
+            2157 => "Sec-def data farm connection is broken",
+            2158 => "Sec-def data farm connection is OK",
+            10167 => "Displaying delayed market data",
+            10197 => "No market data during competing live session",
+            10282 => "WSH meta data not requested",
+            _ if self.is_warning() => "Warning",
+            _ => "Unknown error code",
+        }
+    }
+}
+
+impl From<i32> for IbErrorCode {
+    fn from(code: i32) -> Self {
+        Self(code)
+    }
+}
+
+impl From<IbErrorCode> for i32 {
+    fn from(code: IbErrorCode) -> Self {
+        code.0
+    }
+}
+
+impl std::fmt::Display for IbErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// ============================================================================
+// AdvancedOrderReject
+// ============================================================================
+
+/// Parsed `advanced_order_reject_json` of an error for a rejected order.
+///
+/// There is no C++ equivalent -- `EWrapper::error` passes the JSON string
+/// through unchanged. Requires the `serde` feature.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdvancedOrderReject {
+    /// `rejectReason`, empty when absent.
+    pub reject_reason: String,
+    /// `orderRef` of the rejected order, empty when absent.
+    pub order_ref: String,
+    /// The JSON payload as received.
+    pub raw: String,
+}
+
+#[cfg(feature = "serde")]
+impl AdvancedOrderReject {
+    /// Whether the error carried a reject payload at all.
+    pub fn is_reject(&self) -> bool {
+        !self.raw.is_empty()
+    }
+}
+
+/// Parse the `advanced_order_reject_json` of an `IBEvent::Error` or
+/// [`IBApiError::Server`].
+///
+/// An empty string (the error is not an advanced order reject) parses to
+/// the default value, for which [`AdvancedOrderReject::is_reject`] is false.
+#[cfg(feature = "serde")]
+pub fn parse_advanced_order_reject(json: &str) -> Result<AdvancedOrderReject> {
+    #[derive(Default, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    struct Payload {
+        reject_reason: String,
+        order_ref: String,
+    }
+
+    if json.trim().is_empty() {
+        return Ok(AdvancedOrderReject::default());
+    }
+    let payload: Payload = serde_json::from_str(json)
+        .map_err(|e| IBApiError::Decoding(format!("invalid advanced order reject JSON: {e}")))?;
+    Ok(AdvancedOrderReject {
+        reject_reason: payload.reject_reason,
+        order_ref: payload.order_ref,
+        raw: json.to_string(),
+    })
+}
+
 /// Convenience Result type for this library.
 pub type Result<T> = std::result::Result<T, IBApiError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_error_detects_pacing_violation() {
+        let err = IBApiError::server(
+            7,
+            162,
+            "Historical Market Data Service error message:Historical data request pacing violation"
+                .into(),
+            String::new(),
+        );
+        assert!(matches!(err, IBApiError::PacingViolation { id: 7, code: 162, .. }));
+
+        let err = IBApiError::server(
+            7,
+            162,
+            "Historical Market Data Service error message:HMDS query returned no data".into(),
+            String::new(),
+        );
+        assert!(matches!(err, IBApiError::Server { code: 162, .. }));
+        assert!(matches!(
+            IBApiError::server(7, 200, "No security definition".into(), String::new()),
+            IBApiError::Server { code: 200, .. }
+        ));
+    }
+
+    #[test]
+    fn error_code_categories() {
+        let code = IbErrorCode::from;
+        assert!(code(2104).is_warning());
+        assert!(code(2176).is_warning());
+        assert!(code(10167).is_warning());
+        assert!(code(399).is_warning());
+        assert!(code(404).is_warning());
+        assert!(!code(200).is_warning());
+        assert!(!code(1100).is_warning());
+
+        assert!(code(162).is_pacing_violation());
+        assert!(code(420).is_pacing_violation());
+        assert!(!code(200).is_pacing_violation());
+
+        for c in [1100, 1101, 1102, 2110] {
+            assert!(code(c).is_connectivity(), "{c}");
+        }
+        assert!(!code(2104).is_connectivity());
+
+        for c in [2104, 2106, 2108, 2158] {
+            assert!(code(c).is_market_data_farm(), "{c}");
+        }
+        assert!(!code(2110).is_market_data_farm());
+        assert!(!code(354).is_market_data_farm());
+
+        assert!(code(10282).is_wsh_not_subscribed());
+        assert!(!code(10197).is_wsh_not_subscribed());
+        assert!(code(10197).is_competing_live_session());
+        assert!(!code(10167).is_competing_live_session());
+
+        assert!(code(200).description().starts_with("No security definition"));
+        assert_eq!(code(2199).description(), "Warning");
+        assert_eq!(code(99999).description(), "Unknown error code");
+        assert_eq!(i32::from(code(354)), 354);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn parse_advanced_order_reject_payload() {
+        let json =
+            r#"{"rejectReason":"Order size exceeds the limit","orderRef":"rebal-7","code":8229}"#;
+        let reject = parse_advanced_order_reject(json).unwrap();
+        assert!(reject.is_reject());
+        assert_eq!(reject.reject_reason, "Order size exceeds the limit");
+        assert_eq!(reject.order_ref, "rebal-7");
+        assert_eq!(reject.raw, json);
+
+        let none = parse_advanced_order_reject("").unwrap();
+        assert!(!none.is_reject());
+        assert_eq!(none, AdvancedOrderReject::default());
+
+        assert!(matches!(parse_advanced_order_reject("{"), Err(IBApiError::Decoding(_))));
+    }
+}
@@ -0,0 +1,264 @@
+//! Typed parsing of the Financial Advisor (FA) configuration XML.
+//!
+//! `IBEvent::ReceiveFa` (the answer to `request_fa`) carries the account
+//! groups or account aliases of an FA login as an XML document.
+//! [`parse_fa_groups`] and [`parse_fa_aliases`] turn it into [`FaGroup`]s
+//! and [`FaAlias`]es; [`parse_fa`] picks the parser from the event's
+//! `fa_data_type`. The event keeps the raw XML, e.g. for `replace_fa`.
+//!
+//! There is no C++ equivalent -- `EWrapper::receiveFA` passes the XML
+//! string through unchanged. Requires the `xml` feature.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::errors::{IBApiError, Result};
+use crate::models::enums::FaDataType;
+
+// ============================================================================
+// Entries
+// ============================================================================
+
+/// An `<Account>` of a group's `ListOfAccts`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaGroupAccount {
+    pub account: String,
+    /// Allocation amount, when the group's method uses one.
+    pub amount: Option<f64>,
+}
+
+/// A `<Group>` of the `ListOfGroups` (FA data type 1).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FaGroup {
+    pub name: String,
+    /// Allocation method, e.g. `"ContractsOrShares"` or `"NetLiq"`.
+    pub default_method: String,
+    pub accounts: Vec<FaGroupAccount>,
+}
+
+impl FaGroup {
+    /// Account codes in the group, in document order.
+    pub fn account_codes(&self) -> impl Iterator<Item = &str> {
+        self.accounts.iter().map(|a| a.account.as_str())
+    }
+}
+
+/// An `<AccountAlias>` of the `ListOfAccountAliases` (FA data type 3).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaAlias {
+    pub account: String,
+    pub alias: String,
+}
+
+/// Parsed FA configuration, by data type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FaData {
+    Groups(Vec<FaGroup>),
+    Aliases(Vec<FaAlias>),
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse the `xml` of an `IBEvent::ReceiveFa`, choosing the parser from its
+/// `fa_data_type` (1 = groups, 3 = aliases).
+///
+/// Fails with `IBApiError::Decoding` for any other data type (profiles,
+/// type 2, are no longer sent by TWS).
+pub fn parse_fa(fa_data_type: i32, xml: &str) -> Result<FaData> {
+    match FaDataType::try_from(fa_data_type) {
+        Ok(FaDataType::Groups) => parse_fa_groups(xml).map(FaData::Groups),
+        Ok(FaDataType::Aliases) => parse_fa_aliases(xml).map(FaData::Aliases),
+        Err(other) => Err(IBApiError::Decoding(format!("unsupported FA data type {other}"))),
+    }
+}
+
+/// Parse a `ListOfGroups` document (FA data type 1).
+///
+/// Accepts both the current `<Account><acct>..</acct></Account>` entries
+/// and the older `<String>..</String>` ones.
+pub fn parse_fa_groups(xml: &str) -> Result<Vec<FaGroup>> {
+    let mut groups: Vec<FaGroup> = Vec::new();
+    parse_elements(xml, |node| match node {
+        Node::Start(b"Group") => groups.push(FaGroup::default()),
+        Node::Start(b"Account") => {
+            if let Some(g) = groups.last_mut() {
+                g.accounts.push(FaGroupAccount::default());
+            }
+        }
+        Node::Start(_) => {}
+        Node::Text(parent, field, value) => {
+            let Some(g) = groups.last_mut() else {
+                return;
+            };
+            match (parent, field) {
+                (b"Group", b"name") => g.name = value,
+                (b"Group", b"defaultMethod") => g.default_method = value,
+                (b"ListOfAccts", b"String") => {
+                    g.accounts.push(FaGroupAccount { account: value, amount: None });
+                }
+                (b"Account", b"acct") => {
+                    if let Some(a) = g.accounts.last_mut() {
+                        a.account = value;
+                    }
+                }
+                (b"Account", b"amount") => {
+                    if let Some(a) = g.accounts.last_mut() {
+                        a.amount = value.parse().ok();
+                    }
+                }
+                _ => {}
+            }
+        }
+    })?;
+    Ok(groups)
+}
+
+/// Parse a `ListOfAccountAliases` document (FA data type 3).
+pub fn parse_fa_aliases(xml: &str) -> Result<Vec<FaAlias>> {
+    let mut aliases: Vec<FaAlias> = Vec::new();
+    parse_elements(xml, |node| match node {
+        Node::Start(b"AccountAlias") => aliases.push(FaAlias::default()),
+        Node::Start(_) => {}
+        Node::Text(parent, field, value) => {
+            let Some(a) = aliases.last_mut() else {
+                return;
+            };
+            match (parent, field) {
+                (b"AccountAlias", b"account") => a.account = value,
+                (b"AccountAlias", b"alias") => a.alias = value,
+                _ => {}
+            }
+        }
+    })?;
+    Ok(aliases)
+}
+
+/// What [`parse_elements`] reports while walking a document.
+enum Node<'a> {
+    /// An element was opened.
+    Start(&'a [u8]),
+    /// Trimmed text of `<parent><field>`.
+    Text(&'a [u8], &'a [u8], String),
+}
+
+/// Walk `xml`, reporting each opened element and each text node.
+fn parse_elements(xml: &str, mut visit: impl FnMut(Node<'_>)) -> Result<()> {
+    let mut reader = Reader::from_str(xml);
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            IBApiError::Decoding(format!(
+                "invalid FA XML at byte {}: {e}",
+                reader.error_position()
+            ))
+        })?;
+        match event {
+            Event::Start(element) => {
+                let name = element.name().as_ref().to_vec();
+                visit(Node::Start(&name));
+                path.push(name);
+            }
+            Event::End(_) => {
+                path.pop();
+            }
+            Event::Text(value) => {
+                let [.., parent, field] = path.as_slice() else {
+                    continue;
+                };
+                let value = value
+                    .unescape()
+                    .map_err(|e| IBApiError::Decoding(format!("invalid FA XML: {e}")))?;
+                visit(Node::Text(parent, field, value.trim().to_string()));
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GROUPS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListOfGroups>
+  <Group>
+    <name>Growth</name>
+    <ListOfAccts varName="list">
+      <Account>
+        <acct>DU111</acct>
+        <amount>60.0</amount>
+      </Account>
+      <Account>
+        <acct>DU222</acct>
+        <amount>40.0</amount>
+      </Account>
+    </ListOfAccts>
+    <defaultMethod>Percent</defaultMethod>
+  </Group>
+  <Group>
+    <name>Legacy &amp; Co</name>
+    <ListOfAccts varName="list">
+      <String>DU333</String>
+    </ListOfAccts>
+    <defaultMethod>NetLiq</defaultMethod>
+  </Group>
+</ListOfGroups>"#;
+
+    const ALIASES: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ListOfAccountAliases>
+  <AccountAlias>
+    <account>DU111</account>
+    <alias>Main</alias>
+  </AccountAlias>
+  <AccountAlias>
+    <account>DU222</account>
+    <alias>IRA</alias>
+  </AccountAlias>
+</ListOfAccountAliases>"#;
+
+    #[test]
+    fn parse_fa_groups_sample() {
+        let groups = parse_fa_groups(GROUPS).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "Growth");
+        assert_eq!(groups[0].default_method, "Percent");
+        assert_eq!(groups[0].account_codes().collect::<Vec<_>>(), ["DU111", "DU222"]);
+        assert_eq!(groups[0].accounts[1].amount, Some(40.0));
+        assert_eq!(groups[1].name, "Legacy & Co");
+        assert_eq!(
+            groups[1].accounts,
+            [FaGroupAccount { account: "DU333".into(), amount: None }]
+        );
+    }
+
+    #[test]
+    fn parse_fa_aliases_sample() {
+        let aliases = parse_fa_aliases(ALIASES).unwrap();
+        assert_eq!(
+            aliases,
+            [
+                FaAlias { account: "DU111".into(), alias: "Main".into() },
+                FaAlias { account: "DU222".into(), alias: "IRA".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_fa_selects_parser_by_data_type() {
+        assert!(matches!(parse_fa(1, GROUPS).unwrap(), FaData::Groups(g) if g.len() == 2));
+        assert!(matches!(parse_fa(3, ALIASES).unwrap(), FaData::Aliases(a) if a.len() == 2));
+        assert!(matches!(parse_fa(2, "<ListOfProfiles/>"), Err(IBApiError::Decoding(_))));
+        assert!(matches!(
+            parse_fa_groups("<ListOfGroups><Group></ListOfGroups>"),
+            Err(IBApiError::Decoding(_))
+        ));
+    }
+}
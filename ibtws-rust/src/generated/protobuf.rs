@@ -211,6 +211,52 @@ pub struct GlobalCancelRequest {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoricalDataBar {
+    #[prost(string, optional, tag = "1")]
+    pub date: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(double, optional, tag = "2")]
+    pub open: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "3")]
+    pub high: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "4")]
+    pub low: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "5")]
+    pub close: ::core::option::Option<f64>,
+    #[prost(string, optional, tag = "6")]
+    pub volume: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "7")]
+    pub wap: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(int32, optional, tag = "8")]
+    pub bar_count: ::core::option::Option<i32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoricalData {
+    #[prost(int32, optional, tag = "1")]
+    pub req_id: ::core::option::Option<i32>,
+    #[prost(message, repeated, tag = "2")]
+    pub historical_data_bars: ::prost::alloc::vec::Vec<HistoricalDataBar>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoricalDataEnd {
+    #[prost(int32, optional, tag = "1")]
+    pub req_id: ::core::option::Option<i32>,
+    #[prost(string, optional, tag = "2")]
+    pub start_date_str: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "3")]
+    pub end_date_str: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HistoricalDataUpdate {
+    #[prost(int32, optional, tag = "1")]
+    pub req_id: ::core::option::Option<i32>,
+    #[prost(message, optional, tag = "2")]
+    pub historical_data_bar: ::core::option::Option<HistoricalDataBar>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OrderCondition {
     #[prost(int32, optional, tag = "1")]
     pub r#type: ::core::option::Option<i32>,
@@ -0,0 +1,147 @@
+//! Liveness watchdog for `IBClient`.
+//!
+//! A half-open connection -- TWS stops sending but the socket never closes --
+//! is invisible to the reader task, which simply waits forever. The watchdog
+//! started by `IBClient::start_keepalive` probes the server with
+//! REQ_CURRENT_TIME at a fixed interval; the reader records when each
+//! `CurrentTime` answer arrives. If no answer arrives within two intervals,
+//! the client state becomes `ConnectionState::Failed` and
+//! `IBEvent::ConnectionLost` is emitted.
+//!
+//! There is no C++ equivalent -- `EClientSocket` relies on the socket to
+//! report errors.
+
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::task::JoinHandle;
+
+use crate::channel::WeakEventSink;
+use crate::encoder::MessageEncoder;
+use crate::errors::Result;
+use crate::protocol::outgoing;
+use crate::state::{ConnectionState, ConnectionStatus};
+use crate::subscription::SharedWriter;
+use crate::wrapper::IBEvent;
+
+// ============================================================================
+// Heartbeat
+// ============================================================================
+
+/// Local time (Unix milliseconds) at which the last `CurrentTime` arrived.
+///
+/// Shared between the reader task, which updates it, and the watchdog.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Heartbeat {
+    last_seen: Arc<AtomicI64>,
+}
+
+impl Heartbeat {
+    /// Record `event` if it answers a keepalive probe.
+    pub(crate) fn observe(&self, event: &IBEvent) {
+        if matches!(event, IBEvent::CurrentTime { .. }) {
+            self.touch();
+        }
+    }
+
+    /// Mark the connection as alive now.
+    pub(crate) fn touch(&self) {
+        self.last_seen.store(now_millis(), Ordering::Relaxed);
+    }
+
+    /// Time since the connection was last known to be alive.
+    fn elapsed(&self) -> Duration {
+        let elapsed = now_millis() - self.last_seen.load(Ordering::Relaxed);
+        Duration::from_millis(elapsed.max(0) as u64)
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+// ============================================================================
+// Watchdog
+// ============================================================================
+
+/// Periodic REQ_CURRENT_TIME probe plus the timeout check.
+pub(crate) struct Watchdog {
+    pub(crate) interval: Duration,
+    pub(crate) heartbeat: Heartbeat,
+    pub(crate) writer: SharedWriter,
+    pub(crate) server_version: Arc<AtomicI32>,
+    pub(crate) state: ConnectionStatus,
+    pub(crate) closing: Arc<AtomicBool>,
+    pub(crate) events: WeakEventSink,
+}
+
+impl Watchdog {
+    pub(crate) fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(self) {
+        let window = self.interval * 2;
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        self.heartbeat.touch();
+
+        loop {
+            ticker.tick().await;
+            if self.closing.load(Ordering::Relaxed) {
+                return;
+            }
+            if !self.state.is_connected() {
+                // Between connections (reconnect in progress): nothing to
+                // probe, and the outage must not count against the new socket.
+                self.heartbeat.touch();
+                continue;
+            }
+            if self.heartbeat.elapsed() > window {
+                tracing::warn!(
+                    interval = ?self.interval,
+                    "no response to keepalive probe, connection lost"
+                );
+                self.state.set(ConnectionState::Failed);
+                if let Some(tx) = self.events.upgrade() {
+                    tx.send(IBEvent::ConnectionLost).await;
+                }
+                return;
+            }
+            if let Err(e) = self.probe().await {
+                tracing::debug!("keepalive probe failed: {e}");
+            }
+        }
+    }
+
+    async fn probe(&self) -> Result<()> {
+        let mut enc = MessageEncoder::new(self.server_version.load(Ordering::Relaxed));
+        enc.encode_msg_id(outgoing::REQ_CURRENT_TIME);
+        enc.encode_field_i32(1); // version
+        let bytes = enc.finalize()?;
+        self.writer.lock().await.send_message(&bytes).await
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heartbeat_observes_current_time_only() {
+        let heartbeat = Heartbeat::default();
+        heartbeat.observe(&IBEvent::ConnectionClosed);
+        assert!(heartbeat.elapsed() > Duration::from_secs(3600));
+
+        heartbeat.observe(&IBEvent::CurrentTime { time: 1708876800 });
+        assert!(heartbeat.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -11,70 +11,141 @@
 //! - [`errors`] -- Error types for the library
 //! - [`encoder`] -- Wire-format message encoding
 //! - [`decoder`] -- Wire-format message decoding + server message dispatch
-//! - [`transport`] -- Async TCP transport with V100+ framing
+//! - [`transport`] -- Async TCP transport with V100+ framing (TLS with the `tls` feature)
+//! - [`wiretap`] -- Capture of the raw framed messages
 //! - [`wrapper`] -- IBEvent enum (all server callback events)
 //! - [`reader`] -- Async message reader (spawned tokio task)
+//! - [`channel`] -- Bounded event channel with overflow policies
+//! - [`book`] -- Order book maintenance for market depth events
+//! - [`pending`] -- Request/response correlation keyed by request ID
+//! - [`reconnect`] -- Reconnect policy and supervisor for `IBClient`
+//! - [`state`] -- Observable connection state of `IBClient`
+//! - [`subscription`] -- Typed handles for streaming requests (cancel on drop)
+//! - [`shared`] -- Market data streams shared between several subscribers
 //! - [`client`] -- IBClient (main API entry point)
+//! - [`pool`] -- Several client connections behind one merged event stream
+//! - `wsh` -- Typed Wall Street Horizon event parsing (`serde` feature)
+//! - `scanner_params` -- Scanner parameters XML catalog (`xml` feature)
+//! - `fa` -- Typed FA groups and aliases XML parsing (`xml` feature)
+//! - `stream` -- `futures::Stream` adapter for the event channel (`stream` feature)
+//! - `testutil` -- Scriptable mock TWS server for tests (`testing` feature)
 
+pub mod book;
+pub mod channel;
 pub mod client;
 pub mod decoder;
 pub mod encoder;
 pub mod errors;
+#[cfg(feature = "xml")]
+pub mod fa;
 mod generated;
+mod keepalive;
 pub mod models;
+pub mod pending;
+pub mod pool;
 pub mod proto_decode;
 pub mod proto_encode;
 pub mod protocol;
 pub mod reader;
+pub mod reconnect;
+#[cfg(feature = "xml")]
+pub mod scanner_params;
+pub mod shared;
+pub mod state;
+#[cfg(feature = "stream")]
+pub mod stream;
+pub mod subscription;
+#[cfg(any(test, feature = "testing"))]
+pub mod testutil;
 pub mod transport;
+pub mod wiretap;
 pub mod wrapper;
+#[cfg(feature = "serde")]
+pub mod wsh;
 
 // ============================================================================
 // Re-exports for convenience
 // ============================================================================
 
-pub use errors::IBApiError;
+pub use errors::{IBApiError, IbErrorCode};
+#[cfg(feature = "serde")]
+pub use errors::{parse_advanced_order_reject, AdvancedOrderReject};
 
 // Contract types
 pub use models::contract::{
-    ComboLeg, Contract, ContractDescription, ContractDetails, DeltaNeutralContract,
+    BondDetails, ComboLeg, Contract, ContractBuilder, ContractDescription, ContractDetails,
+    DeltaNeutralContract, TradingSession,
 };
 
 // Order types
 pub use models::order::{
-    Order, OrderAllocation, OrderCancel, OrderComboLeg, OrderCondition, OrderState,
+    Order, OrderAllocation, OrderBuilder, OrderCancel, OrderComboLeg, OrderCondition, OrderState,
+    OrderValidationError,
 };
 
 // Execution types
-pub use models::execution::{CommissionAndFeesReport, Execution, ExecutionFilter};
+pub use models::execution::{
+    CommissionAndFeesReport, Execution, ExecutionFilter, ExecutionFilterBuilder,
+    ExecutionWithCommission,
+};
 
 // Bar / historical data types
-pub use models::bar::{Bar, HistoricalSession, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast};
+pub use models::bar::{
+    Bar, HistoricalSchedule, HistoricalSession, HistoricalTick, HistoricalTickBidAsk,
+    HistoricalTickLast, HistoricalTickSeries, TickKind,
+};
+
+// Account types
+pub use models::account::{
+    AccountSnapshot, AccountSummary, AccountSummaryValue, AccountSummaryValues, AccountTag,
+    AccountValue, PortfolioItem, PositionRecord,
+};
 
 // Market data types
-pub use models::market_data::{DepthMktDataDescription, TickAttrib, TickAttribBidAsk, TickAttribLast};
+pub use models::market_data::{
+    DepthMktDataDescription, MarketSnapshot, OptionComputation, SnapshotPermissions, TickAttrib,
+    TickAttribBidAsk, TickAttribLast,
+};
 
 // Scanner
-pub use models::scanner::ScannerSubscription;
+pub use models::scanner::{
+    parse_combo_legs_str, ScannerLeg, ScannerSubscription, ScannerSubscriptionBuilder,
+};
 
 // Common types
 pub use models::common::{
-    FamilyCode, HistogramEntry, NewsProvider, PriceIncrement, SmartComponent, SoftDollarTier,
-    TagValue,
+    FamilyCode, HistogramEntry, NewsArticleContent, NewsProvider, PriceIncrement, SmartComponent,
+    SmartComponents, SoftDollarTier, TagValue,
 };
 
 // Enums
 pub use models::enums::*;
 
 // Protocol
-pub use protocol::TickType;
+pub use protocol::{Feature, TickType};
 
 // Encoder / Decoder / Transport
-pub use decoder::MessageDecoder;
-pub use encoder::MessageEncoder;
-pub use transport::Transport;
+pub use decoder::{replay_stream, MessageDecoder};
+pub use encoder::{ContractFields, MessageEncoder};
+pub use transport::{Transport, TransportConfig};
+pub use wiretap::{WireDirection, WireTap};
+#[cfg(feature = "tls")]
+pub use transport::TlsConfig;
 
 // Client / Reader / Events
+pub use book::{BookLevel, OrderBook};
+pub use channel::{BoundedReceiver, OverflowPolicy};
 pub use client::IBClient;
+pub use pending::PendingRequests;
+pub use pool::{ClientPool, PoolEvent};
 pub use reader::MessageReader;
+pub use reconnect::{ReconnectPolicy, TrackedOrder};
+pub use shared::{SharedMarketData, SharedMarketDataSubscription};
+pub use state::ConnectionState;
+#[cfg(feature = "stream")]
+pub use stream::IBEventStream;
+pub use subscription::{
+    CalcSubscription, HistoricalSubscription, HistoricalUpdate, MarketDataSubscription,
+    NewsSubscription, OrderHandle, PnlSubscription, Subscription,
+};
 pub use wrapper::{IBEvent, ScannerDataItem};
@@ -0,0 +1,435 @@
+//! Account summary and account update values.
+//!
+//! There is no C++ equivalent -- `EWrapper::accountSummary` delivers one
+//! `(account, tag, value, currency)` row per callback.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::contract::Contract;
+
+/// Well-known account summary tags, as passed to `req_account_summary`.
+///
+/// C++ source: `AccountSummaryTags.h`.
+pub mod tags {
+    pub const ACCOUNT_TYPE: &str = "AccountType";
+    pub const NET_LIQUIDATION: &str = "NetLiquidation";
+    pub const TOTAL_CASH_VALUE: &str = "TotalCashValue";
+    pub const SETTLED_CASH: &str = "SettledCash";
+    pub const ACCRUED_CASH: &str = "AccruedCash";
+    pub const BUYING_POWER: &str = "BuyingPower";
+    pub const EQUITY_WITH_LOAN_VALUE: &str = "EquityWithLoanValue";
+    pub const PREVIOUS_EQUITY_WITH_LOAN_VALUE: &str = "PreviousEquityWithLoanValue";
+    pub const GROSS_POSITION_VALUE: &str = "GrossPositionValue";
+    pub const REQ_T_EQUITY: &str = "ReqTEquity";
+    pub const REQ_T_MARGIN: &str = "ReqTMargin";
+    pub const SMA: &str = "SMA";
+    pub const INIT_MARGIN_REQ: &str = "InitMarginReq";
+    pub const MAINT_MARGIN_REQ: &str = "MaintMarginReq";
+    pub const AVAILABLE_FUNDS: &str = "AvailableFunds";
+    pub const EXCESS_LIQUIDITY: &str = "ExcessLiquidity";
+    pub const CUSHION: &str = "Cushion";
+    pub const FULL_INIT_MARGIN_REQ: &str = "FullInitMarginReq";
+    pub const FULL_MAINT_MARGIN_REQ: &str = "FullMaintMarginReq";
+    pub const FULL_AVAILABLE_FUNDS: &str = "FullAvailableFunds";
+    pub const FULL_EXCESS_LIQUIDITY: &str = "FullExcessLiquidity";
+    pub const LOOK_AHEAD_NEXT_CHANGE: &str = "LookAheadNextChange";
+    pub const LOOK_AHEAD_INIT_MARGIN_REQ: &str = "LookAheadInitMarginReq";
+    pub const LOOK_AHEAD_MAINT_MARGIN_REQ: &str = "LookAheadMaintMarginReq";
+    pub const LOOK_AHEAD_AVAILABLE_FUNDS: &str = "LookAheadAvailableFunds";
+    pub const LOOK_AHEAD_EXCESS_LIQUIDITY: &str = "LookAheadExcessLiquidity";
+    pub const HIGHEST_SEVERITY: &str = "HighestSeverity";
+    pub const DAY_TRADES_REMAINING: &str = "DayTradesRemaining";
+    pub const LEVERAGE: &str = "Leverage";
+
+    /// Every tag above, comma-separated.
+    pub const ALL: &str = "AccountType,NetLiquidation,TotalCashValue,SettledCash,AccruedCash,\
+        BuyingPower,EquityWithLoanValue,PreviousEquityWithLoanValue,GrossPositionValue,\
+        ReqTEquity,ReqTMargin,SMA,InitMarginReq,MaintMarginReq,AvailableFunds,ExcessLiquidity,\
+        Cushion,FullInitMarginReq,FullMaintMarginReq,FullAvailableFunds,FullExcessLiquidity,\
+        LookAheadNextChange,LookAheadInitMarginReq,LookAheadMaintMarginReq,\
+        LookAheadAvailableFunds,LookAheadExcessLiquidity,HighestSeverity,DayTradesRemaining,\
+        Leverage";
+}
+
+// ============================================================================
+// AccountTag
+// ============================================================================
+
+/// Well-known keys of account value updates (`UpdateAccountValue`) and
+/// account summary tags.
+///
+/// Keys this enum does not name -- including segment-suffixed keys such as
+/// `"NetLiquidation-S"` -- parse to `Other`. There is no C++ equivalent;
+/// `EWrapper::updateAccountValue` passes the key as a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AccountTag {
+    AccountType,
+    NetLiquidation,
+    TotalCashValue,
+    SettledCash,
+    AccruedCash,
+    BuyingPower,
+    EquityWithLoanValue,
+    PreviousEquityWithLoanValue,
+    GrossPositionValue,
+    InitMarginReq,
+    MaintMarginReq,
+    AvailableFunds,
+    ExcessLiquidity,
+    Cushion,
+    DayTradesRemaining,
+    Leverage,
+    CashBalance,
+    UnrealizedPnL,
+    RealizedPnL,
+    NetDividend,
+    StockMarketValue,
+    Other(String),
+}
+
+impl AccountTag {
+    /// Key as sent by TWS.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::AccountType => tags::ACCOUNT_TYPE,
+            Self::NetLiquidation => tags::NET_LIQUIDATION,
+            Self::TotalCashValue => tags::TOTAL_CASH_VALUE,
+            Self::SettledCash => tags::SETTLED_CASH,
+            Self::AccruedCash => tags::ACCRUED_CASH,
+            Self::BuyingPower => tags::BUYING_POWER,
+            Self::EquityWithLoanValue => tags::EQUITY_WITH_LOAN_VALUE,
+            Self::PreviousEquityWithLoanValue => tags::PREVIOUS_EQUITY_WITH_LOAN_VALUE,
+            Self::GrossPositionValue => tags::GROSS_POSITION_VALUE,
+            Self::InitMarginReq => tags::INIT_MARGIN_REQ,
+            Self::MaintMarginReq => tags::MAINT_MARGIN_REQ,
+            Self::AvailableFunds => tags::AVAILABLE_FUNDS,
+            Self::ExcessLiquidity => tags::EXCESS_LIQUIDITY,
+            Self::Cushion => tags::CUSHION,
+            Self::DayTradesRemaining => tags::DAY_TRADES_REMAINING,
+            Self::Leverage => tags::LEVERAGE,
+            Self::CashBalance => "CashBalance",
+            Self::UnrealizedPnL => "UnrealizedPnL",
+            Self::RealizedPnL => "RealizedPnL",
+            Self::NetDividend => "NetDividend",
+            Self::StockMarketValue => "StockMarketValue",
+            Self::Other(key) => key,
+        }
+    }
+}
+
+impl fmt::Display for AccountTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AccountTag {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            tags::ACCOUNT_TYPE => Self::AccountType,
+            tags::NET_LIQUIDATION => Self::NetLiquidation,
+            tags::TOTAL_CASH_VALUE => Self::TotalCashValue,
+            tags::SETTLED_CASH => Self::SettledCash,
+            tags::ACCRUED_CASH => Self::AccruedCash,
+            tags::BUYING_POWER => Self::BuyingPower,
+            tags::EQUITY_WITH_LOAN_VALUE => Self::EquityWithLoanValue,
+            tags::PREVIOUS_EQUITY_WITH_LOAN_VALUE => Self::PreviousEquityWithLoanValue,
+            tags::GROSS_POSITION_VALUE => Self::GrossPositionValue,
+            tags::INIT_MARGIN_REQ => Self::InitMarginReq,
+            tags::MAINT_MARGIN_REQ => Self::MaintMarginReq,
+            tags::AVAILABLE_FUNDS => Self::AvailableFunds,
+            tags::EXCESS_LIQUIDITY => Self::ExcessLiquidity,
+            tags::CUSHION => Self::Cushion,
+            tags::DAY_TRADES_REMAINING => Self::DayTradesRemaining,
+            tags::LEVERAGE => Self::Leverage,
+            "CashBalance" => Self::CashBalance,
+            "UnrealizedPnL" => Self::UnrealizedPnL,
+            "RealizedPnL" => Self::RealizedPnL,
+            "NetDividend" => Self::NetDividend,
+            "StockMarketValue" => Self::StockMarketValue,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+// ============================================================================
+// AccountValue
+// ============================================================================
+
+/// One account value from `req_account_updates`, as carried by
+/// `IBEvent::UpdateAccountValue` (see `IBEvent::account_value`).
+///
+/// `value` keeps the string TWS sent; [`as_decimal`](Self::as_decimal)
+/// parses it without going through `f64`, so currency amounts keep their
+/// cents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AccountValue {
+    pub key: String,
+    pub value: String,
+    /// Empty for values that are not monetary.
+    pub currency: String,
+    pub account_name: String,
+}
+
+impl AccountValue {
+    /// `key` as an [`AccountTag`].
+    pub fn tag(&self) -> AccountTag {
+        let Ok(tag) = self.key.parse();
+        tag
+    }
+
+    /// `value` parsed as a decimal. `None` if it is not numeric, e.g. for
+    /// `AccountType`.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        parse_decimal(&self.value)
+    }
+}
+
+/// Parse a plain or scientific-notation decimal string.
+fn parse_decimal(value: &str) -> Option<Decimal> {
+    value.parse().ok().or_else(|| Decimal::from_scientific(value).ok())
+}
+
+// ============================================================================
+// AccountSummaryValue
+// ============================================================================
+
+/// One tag value from an account summary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AccountSummaryValue {
+    pub value: String,
+    /// Empty for tags that are not monetary, e.g. `AccountType`.
+    pub currency: String,
+}
+
+// ============================================================================
+// AccountSummaryValues
+// ============================================================================
+
+/// Account summary tags of a single account, keyed by tag name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct AccountSummaryValues {
+    pub tags: BTreeMap<String, AccountSummaryValue>,
+}
+
+impl AccountSummaryValues {
+    /// Value and currency of `tag`, if the server sent it.
+    pub fn get(&self, tag: &str) -> Option<&AccountSummaryValue> {
+        self.tags.get(tag)
+    }
+
+    /// Value of `tag` parsed as a decimal. `None` if the tag is missing or
+    /// not numeric.
+    pub fn decimal(&self, tag: &str) -> Option<Decimal> {
+        parse_decimal(&self.get(tag)?.value)
+    }
+
+    pub fn net_liquidation(&self) -> Option<Decimal> {
+        self.decimal(tags::NET_LIQUIDATION)
+    }
+
+    pub fn total_cash_value(&self) -> Option<Decimal> {
+        self.decimal(tags::TOTAL_CASH_VALUE)
+    }
+
+    pub fn buying_power(&self) -> Option<Decimal> {
+        self.decimal(tags::BUYING_POWER)
+    }
+
+    pub fn available_funds(&self) -> Option<Decimal> {
+        self.decimal(tags::AVAILABLE_FUNDS)
+    }
+
+    pub fn excess_liquidity(&self) -> Option<Decimal> {
+        self.decimal(tags::EXCESS_LIQUIDITY)
+    }
+
+    pub fn gross_position_value(&self) -> Option<Decimal> {
+        self.decimal(tags::GROSS_POSITION_VALUE)
+    }
+
+    pub fn init_margin_req(&self) -> Option<Decimal> {
+        self.decimal(tags::INIT_MARGIN_REQ)
+    }
+
+    pub fn maint_margin_req(&self) -> Option<Decimal> {
+        self.decimal(tags::MAINT_MARGIN_REQ)
+    }
+}
+
+// ============================================================================
+// AccountSummary
+// ============================================================================
+
+/// Result of one account summary request, keyed by account ID.
+///
+/// Returned by `IBClient::account_summary`. A request for group `"All"`
+/// covers every account the login can see, so several accounts may be
+/// present. The typed accessors here read the only account and return
+/// `None` when there are several; use [`account`](Self::account) then.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct AccountSummary {
+    pub accounts: BTreeMap<String, AccountSummaryValues>,
+}
+
+impl AccountSummary {
+    /// Record one `AccountSummary` row.
+    pub fn insert(&mut self, account: String, tag: String, value: String, currency: String) {
+        self.accounts
+            .entry(account)
+            .or_default()
+            .tags
+            .insert(tag, AccountSummaryValue { value, currency });
+    }
+
+    /// Tags of `account`.
+    pub fn account(&self, account: &str) -> Option<&AccountSummaryValues> {
+        self.accounts.get(account)
+    }
+
+    /// Tags of the only account in the summary.
+    pub fn single(&self) -> Option<&AccountSummaryValues> {
+        match self.accounts.len() {
+            1 => self.accounts.values().next(),
+            _ => None,
+        }
+    }
+
+    pub fn net_liquidation(&self) -> Option<Decimal> {
+        self.single()?.net_liquidation()
+    }
+
+    pub fn total_cash_value(&self) -> Option<Decimal> {
+        self.single()?.total_cash_value()
+    }
+
+    pub fn buying_power(&self) -> Option<Decimal> {
+        self.single()?.buying_power()
+    }
+
+    pub fn available_funds(&self) -> Option<Decimal> {
+        self.single()?.available_funds()
+    }
+
+    pub fn excess_liquidity(&self) -> Option<Decimal> {
+        self.single()?.excess_liquidity()
+    }
+}
+
+// ============================================================================
+// PositionRecord
+// ============================================================================
+
+/// One position from the `req_positions` snapshot, as returned by
+/// `IBClient::positions`.
+///
+/// There is no C++ equivalent -- `EWrapper::position` passes the fields as
+/// separate arguments.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PositionRecord {
+    pub account: String,
+    pub contract: Contract,
+    pub position: Decimal,
+    pub avg_cost: Decimal,
+}
+
+// ============================================================================
+// PortfolioItem
+// ============================================================================
+
+/// One portfolio position from `req_account_updates`, as carried by
+/// `IBEvent::UpdatePortfolio`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct PortfolioItem {
+    pub contract: Contract,
+    pub position: Decimal,
+    pub market_price: f64,
+    pub market_value: f64,
+    pub average_cost: Decimal,
+    pub unrealized_pnl: f64,
+    pub realized_pnl: f64,
+    pub account_name: String,
+}
+
+// ============================================================================
+// AccountSnapshot
+// ============================================================================
+
+/// Everything `req_account_updates` sends for one account up to
+/// `AccountDownloadEnd`, as returned by `IBClient::account_snapshot`.
+///
+/// There is no C++ equivalent -- `EWrapper` receives each value and
+/// position through its own callback.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct AccountSnapshot {
+    /// Account code from `AccountDownloadEnd`; the resolved account when
+    /// the request named none.
+    pub account: String,
+    /// Account values in the order received. A key may appear once per
+    /// currency (and once with currency `"BASE"`).
+    pub values: Vec<AccountValue>,
+    pub portfolio: Vec<PortfolioItem>,
+    /// Last `UpdateAccountTime` timestamp, e.g. `"14:32"`.
+    pub update_time: String,
+}
+
+impl AccountSnapshot {
+    /// The value of `key` in `currency` (`""` for values that are not
+    /// monetary).
+    pub fn value(&self, key: &str, currency: &str) -> Option<&AccountValue> {
+        self.values.iter().rev().find(|v| v.key == key && v.currency == currency)
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_value_parses_tag_and_decimal() {
+        let value = AccountValue {
+            key: "NetLiquidation".into(),
+            value: "100000.55".into(),
+            currency: "USD".into(),
+            account_name: "DU123".into(),
+        };
+        assert_eq!(value.tag(), AccountTag::NetLiquidation);
+        assert_eq!(value.as_decimal(), Some(Decimal::new(10000055, 2)));
+        assert_eq!(value.as_decimal().unwrap().to_string(), "100000.55");
+
+        let account_type =
+            AccountValue { key: "AccountType".into(), value: "INDIVIDUAL".into(), ..value };
+        assert_eq!(account_type.tag(), AccountTag::AccountType);
+        assert_eq!(account_type.as_decimal(), None);
+
+        let segment: AccountTag = "NetLiquidation-S".parse().unwrap();
+        assert_eq!(segment, AccountTag::Other("NetLiquidation-S".into()));
+        assert_eq!(segment.to_string(), "NetLiquidation-S");
+        assert_eq!(AccountTag::UnrealizedPnL.as_str(), "UnrealizedPnL");
+    }
+}
@@ -3,10 +3,19 @@
 //! Ported from: `bar.h`, `HistoricalTick.h`, `HistoricalTickBidAsk.h`,
 //! `HistoricalTickLast.h`, `HistoricalSession.h`.
 
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "chrono")]
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
+#[cfg(feature = "chrono")]
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use super::market_data::{TickAttribBidAsk, TickAttribLast};
+use crate::errors::{IBApiError, Result};
 
 // ============================================================================
 // Bar
@@ -15,21 +24,59 @@ use super::market_data::{TickAttribBidAsk, TickAttribLast};
 /// OHLCV bar for historical and real-time data.
 ///
 /// C++ source: `struct Bar` in `bar.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Bar {
     pub time: String,
     pub open: f64,
     pub high: f64,
     pub low: f64,
     pub close: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub volume: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub wap: Option<Decimal>,
     pub count: i32,
 }
 
+#[cfg(feature = "chrono")]
+impl Bar {
+    /// Parse [`time`](Self::time) into Unix epoch seconds.
+    ///
+    /// `format_date` is the value passed to `req_historical_data`. With
+    /// `2`, intraday bars carry epoch seconds already. With `1`, they carry
+    /// `"20240225 09:30:00"`, optionally followed by a zone name, and the
+    /// time is interpreted in that zone or else in `tz` (the TWS login
+    /// zone). Daily and longer bars are a bare `"20240225"` in either
+    /// format and resolve to midnight in `tz`.
+    ///
+    /// Returns `None` if the string or zone name is malformed, or the local
+    /// time does not exist in the zone (DST gap). Ambiguous local times
+    /// resolve to the earlier instant. There is no C++ equivalent.
+    pub fn parsed_time(&self, format_date: i32, tz: &str) -> Option<i64> {
+        let mut parts = self.time.split_whitespace();
+        let date = parts.next()?;
+        // An eight-digit value is a `YYYYMMDD` date, never 1970s epoch seconds.
+        if format_date == 2 && date.len() != 8 {
+            if let Ok(secs) = date.parse::<i64>() {
+                return parts.next().is_none().then_some(secs);
+            }
+        }
+        let naive = match parts.next() {
+            Some(time) => {
+                NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y%m%d %H:%M:%S").ok()?
+            }
+            None => NaiveDate::parse_from_str(date, "%Y%m%d").ok()?.and_hms_opt(0, 0, 0)?,
+        };
+        let zone: Tz = parts.next().unwrap_or(tz).parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        zone.from_local_datetime(&naive).earliest().map(|t| t.timestamp())
+    }
+}
+
 // ============================================================================
 // HistoricalTick
 // ============================================================================
@@ -37,12 +84,13 @@ pub struct Bar {
 /// A single historical tick (trade or midpoint).
 ///
 /// C++ source: `struct HistoricalTick` in `HistoricalTick.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HistoricalTick {
     pub time: i64,
     pub price: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub size: Option<Decimal>,
 }
 
@@ -53,16 +101,17 @@ pub struct HistoricalTick {
 /// A single historical bid/ask tick.
 ///
 /// C++ source: `struct HistoricalTickBidAsk` in `HistoricalTickBidAsk.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HistoricalTickBidAsk {
     pub time: i64,
     pub tick_attrib_bid_ask: TickAttribBidAsk,
     pub price_bid: f64,
     pub price_ask: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub size_bid: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub size_ask: Option<Decimal>,
 }
 
@@ -73,13 +122,14 @@ pub struct HistoricalTickBidAsk {
 /// A single historical last-trade tick.
 ///
 /// C++ source: `struct HistoricalTickLast` in `HistoricalTickLast.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HistoricalTickLast {
     pub time: i64,
     pub tick_attrib_last: TickAttribLast,
     pub price: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub size: Option<Decimal>,
     pub exchange: String,
     pub special_conditions: String,
@@ -92,10 +142,301 @@ pub struct HistoricalTickLast {
 /// Trading session schedule entry.
 ///
 /// C++ source: `struct HistoricalSession` in `HistoricalSession.h`.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HistoricalSession {
     pub start_date_time: String,
     pub end_date_time: String,
     pub ref_date: String,
 }
+
+/// Trading schedule answering a `SCHEDULE` historical data request.
+///
+/// Returned by `IBClient::historical_schedule`. There is no C++ equivalent
+/// -- these are the arguments of the `historicalSchedule` callback.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct HistoricalSchedule {
+    pub start_date_time: String,
+    pub end_date_time: String,
+    pub time_zone: String,
+    pub sessions: Vec<HistoricalSession>,
+}
+
+// ============================================================================
+// TickKind / HistoricalTickSeries
+// ============================================================================
+
+/// Tick type of a historical ticks request; selects both the `whatToShow`
+/// string and the tick struct TWS answers with. There is no C++
+/// equivalent -- C++ takes the string as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TickKind {
+    /// `MIDPOINT`, answered with [`HistoricalTick`]s.
+    Midpoint,
+    /// `BID_ASK`, answered with [`HistoricalTickBidAsk`]s.
+    BidAsk,
+    /// `TRADES`, answered with [`HistoricalTickLast`]s.
+    Trades,
+}
+
+impl TickKind {
+    /// The `whatToShow` value for `IBClient::req_historical_ticks`.
+    pub fn what_to_show(self) -> &'static str {
+        match self {
+            Self::Midpoint => "MIDPOINT",
+            Self::BidAsk => "BID_ASK",
+            Self::Trades => "TRADES",
+        }
+    }
+}
+
+/// Historical ticks of one [`TickKind`], oldest first.
+///
+/// Returned by `IBClient::historical_ticks_all`. There is no C++ equivalent.
+#[derive(Debug, Clone)]
+pub enum HistoricalTickSeries {
+    Midpoint(Vec<HistoricalTick>),
+    BidAsk(Vec<HistoricalTickBidAsk>),
+    Trades(Vec<HistoricalTickLast>),
+}
+
+impl HistoricalTickSeries {
+    /// An empty series of `kind`.
+    pub fn new(kind: TickKind) -> Self {
+        match kind {
+            TickKind::Midpoint => Self::Midpoint(Vec::new()),
+            TickKind::BidAsk => Self::BidAsk(Vec::new()),
+            TickKind::Trades => Self::Trades(Vec::new()),
+        }
+    }
+
+    pub fn kind(&self) -> TickKind {
+        match self {
+            Self::Midpoint(_) => TickKind::Midpoint,
+            Self::BidAsk(_) => TickKind::BidAsk,
+            Self::Trades(_) => TickKind::Trades,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Midpoint(ticks) => ticks.len(),
+            Self::BidAsk(ticks) => ticks.len(),
+            Self::Trades(ticks) => ticks.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Unix time of the newest tick.
+    pub fn last_time(&self) -> Option<i64> {
+        match self {
+            Self::Midpoint(ticks) => ticks.last().map(|t| t.time),
+            Self::BidAsk(ticks) => ticks.last().map(|t| t.time),
+            Self::Trades(ticks) => ticks.last().map(|t| t.time),
+        }
+    }
+
+    /// Unix time of each tick, oldest first.
+    fn times(&self) -> Vec<i64> {
+        match self {
+            Self::Midpoint(ticks) => ticks.iter().map(|t| t.time).collect(),
+            Self::BidAsk(ticks) => ticks.iter().map(|t| t.time).collect(),
+            Self::Trades(ticks) => ticks.iter().map(|t| t.time).collect(),
+        }
+    }
+
+    /// Number of ticks at unix time `time` at the end of the series.
+    pub(crate) fn trailing_at(&self, time: i64) -> usize {
+        self.times().iter().rev().take_while(|&&t| t == time).count()
+    }
+
+    /// Remove up to `n` ticks at unix time `time` from the start.
+    pub(crate) fn skip_leading_at(&mut self, time: i64, n: usize) {
+        let skip = self.times().iter().take(n).take_while(|&&t| t == time).count();
+        match self {
+            Self::Midpoint(ticks) => drop(ticks.drain(..skip)),
+            Self::BidAsk(ticks) => drop(ticks.drain(..skip)),
+            Self::Trades(ticks) => drop(ticks.drain(..skip)),
+        }
+    }
+
+    /// Append the ticks of `other` (of the same kind) up to and including
+    /// unix time `end`. Ticks of another kind are ignored.
+    pub fn extend_until(&mut self, other: Self, end: i64) {
+        match (self, other) {
+            (Self::Midpoint(a), Self::Midpoint(b)) => {
+                a.extend(b.into_iter().filter(|t| t.time <= end))
+            }
+            (Self::BidAsk(a), Self::BidAsk(b)) => a.extend(b.into_iter().filter(|t| t.time <= end)),
+            (Self::Trades(a), Self::Trades(b)) => a.extend(b.into_iter().filter(|t| t.time <= end)),
+            _ => {}
+        }
+    }
+}
+
+/// Format unix time `secs` as `YYYYMMDD-HH:MM:SS` in UTC, the form TWS
+/// accepts for request date/times without a time zone name.
+pub(crate) fn format_utc_date_time(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // Civil-from-days (H. Hinnant), days since 1970-01-01.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}:{:02}:{:02}",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+// ============================================================================
+// Duration
+// ============================================================================
+
+/// Time span of a historical data request, e.g. `"1 D"` or `"2 W"`.
+///
+/// IB rejects duration strings without the space (`"1D"`) or with a zero
+/// count; the constructors and [`FromStr`] only produce well-formed values.
+/// Converts into the `String` taken by `IBClient::req_historical_data`.
+/// There is no C++ equivalent -- C++ takes the string as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Duration {
+    count: u32,
+    unit: char,
+}
+
+impl Duration {
+    fn new(count: u32, unit: char) -> Result<Self> {
+        if count == 0 {
+            return Err(IBApiError::Encoding(format!(
+                "historical duration must be positive, got 0 {unit}"
+            )));
+        }
+        Ok(Self { count, unit })
+    }
+
+    pub fn seconds(n: u32) -> Result<Self> {
+        Self::new(n, 'S')
+    }
+
+    pub fn days(n: u32) -> Result<Self> {
+        Self::new(n, 'D')
+    }
+
+    pub fn weeks(n: u32) -> Result<Self> {
+        Self::new(n, 'W')
+    }
+
+    pub fn months(n: u32) -> Result<Self> {
+        Self::new(n, 'M')
+    }
+
+    pub fn years(n: u32) -> Result<Self> {
+        Self::new(n, 'Y')
+    }
+}
+
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.count, self.unit)
+    }
+}
+
+impl From<Duration> for String {
+    fn from(duration: Duration) -> Self {
+        duration.to_string()
+    }
+}
+
+impl FromStr for Duration {
+    type Err = IBApiError;
+
+    /// Parse `"<count> <unit>"` with unit one of `S`, `D`, `W`, `M`, `Y`.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || IBApiError::Encoding(format!("invalid historical duration {s:?}"));
+        let (count, unit) = s.split_once(' ').ok_or_else(invalid)?;
+        let count = count.parse().map_err(|_| invalid())?;
+        match unit {
+            "S" => Self::seconds(count),
+            "D" => Self::days(count),
+            "W" => Self::weeks(count),
+            "M" => Self::months(count),
+            "Y" => Self::years(count),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_utc_date_time_matches_tws_format() {
+        assert_eq!(format_utc_date_time(0), "19700101-00:00:00");
+        assert_eq!(format_utc_date_time(1_767_225_600), "20260101-00:00:00");
+        assert_eq!(format_utc_date_time(951_825_599), "20000229-11:59:59");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn bar_parsed_time_handles_both_date_formats() {
+        let bar = |time: &str| Bar { time: time.into(), ..Bar::default() };
+
+        // formatDate=2: epoch seconds, independent of `tz`.
+        assert_eq!(bar("1708871400").parsed_time(2, "US/Eastern"), Some(1_708_871_400));
+
+        // formatDate=1: local time in `tz`, or in the zone TWS appends.
+        assert_eq!(bar("20240225 09:30:00").parsed_time(1, "US/Eastern"), Some(1_708_871_400));
+        assert_eq!(bar("20240225  09:30:00").parsed_time(1, "US/Eastern"), Some(1_708_871_400));
+        assert_eq!(
+            bar("20240225 15:30:00 Europe/Berlin").parsed_time(1, "US/Eastern"),
+            Some(1_708_871_400)
+        );
+
+        // Daily bars are a bare date in either format.
+        assert_eq!(bar("20240225").parsed_time(2, "UTC"), Some(1_708_819_200));
+        assert_eq!(bar("20240225").parsed_time(1, "UTC"), Some(1_708_819_200));
+
+        // Epoch seconds are not a format-1 date, and zones must be known.
+        assert_eq!(bar("1708871400").parsed_time(1, "UTC"), None);
+        assert_eq!(bar("20240225 09:30:00").parsed_time(1, "Mars/Olympus"), None);
+        assert_eq!(bar("").parsed_time(2, "UTC"), None);
+    }
+
+    #[test]
+    fn duration_formats_with_space() {
+        assert_eq!(Duration::seconds(30).unwrap().to_string(), "30 S");
+        assert_eq!(Duration::days(1).unwrap().to_string(), "1 D");
+        assert_eq!(Duration::weeks(2).unwrap().to_string(), "2 W");
+        assert_eq!(Duration::months(6).unwrap().to_string(), "6 M");
+        assert_eq!(String::from(Duration::years(1).unwrap()), "1 Y");
+        assert!(matches!(Duration::days(0), Err(IBApiError::Encoding(_))));
+    }
+
+    #[test]
+    fn duration_parse_rejects_malformed_strings() {
+        assert_eq!("1 D".parse::<Duration>().unwrap(), Duration::days(1).unwrap());
+        assert_eq!("3600 S".parse::<Duration>().unwrap(), Duration::seconds(3600).unwrap());
+        for bad in ["1D", "0 D", "1 d", "D 1", "-1 W", "1 H", ""] {
+            assert!(bad.parse::<Duration>().is_err(), "{bad:?} should be rejected");
+        }
+    }
+}
@@ -3,9 +3,13 @@
 //! Ported from: `TagValue.h`, `SoftDollarTier.h`, `FamilyCode.h`, `NewsProvider.h`,
 //! `HistogramEntry.h`, `PriceIncrement.h`, `IneligibilityReason.h`, `WshEventData.h`.
 
+use base64::Engine;
 use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::errors::{IBApiError, Result};
+
 // ============================================================================
 // TagValue
 // ============================================================================
@@ -13,8 +17,9 @@ use serde::{Deserialize, Serialize};
 /// Generic key-value pair used for algo parameters, misc options, etc.
 ///
 /// C++ source: `struct TagValue` in `TagValue.h`.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct TagValue {
     pub tag: String,
     pub value: String,
@@ -36,8 +41,9 @@ impl TagValue {
 /// Soft dollar tier for institutional orders.
 ///
 /// C++ source: `class SoftDollarTier` in `SoftDollarTier.h`.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct SoftDollarTier {
     pub name: String,
     pub val: String,
@@ -51,8 +57,9 @@ pub struct SoftDollarTier {
 /// Family code linking accounts under the same family.
 ///
 /// C++ source: `struct FamilyCode` in `FamilyCode.h`.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct FamilyCode {
     pub account_id: String,
     pub family_code_str: String,
@@ -65,13 +72,43 @@ pub struct FamilyCode {
 /// News provider information.
 ///
 /// C++ source: `struct NewsProvider` in `NewsProvider.h`.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct NewsProvider {
     pub provider_code: String,
     pub provider_name: String,
 }
 
+// ============================================================================
+// NewsArticleContent
+// ============================================================================
+
+/// Body of a news article returned by `IBClient::get_news_article`.
+///
+/// There is no C++ equivalent -- `EWrapper::newsArticle` passes the
+/// `articleType` code and raw text through unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NewsArticleContent {
+    /// Plain text or HTML (`articleType` 0).
+    Text(String),
+    /// Binary document such as a PDF (`articleType` 1), base64-decoded.
+    Binary(Vec<u8>),
+}
+
+impl NewsArticleContent {
+    /// Interpret a `NEWS_ARTICLE` payload, decoding base64 for binary articles.
+    pub fn from_article(article_type: i32, article_text: String) -> Result<Self> {
+        if article_type != 1 {
+            return Ok(Self::Text(article_text));
+        }
+        base64::engine::general_purpose::STANDARD
+            .decode(article_text.trim())
+            .map(Self::Binary)
+            .map_err(|e| IBApiError::Decoding(format!("invalid base64 news article: {e}")))
+    }
+}
+
 // ============================================================================
 // HistogramEntry
 // ============================================================================
@@ -79,11 +116,12 @@ pub struct NewsProvider {
 /// A single entry in histogram data.
 ///
 /// C++ source: `struct HistogramEntry` in `HistogramEntry.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct HistogramEntry {
     pub price: f64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub size: Option<Decimal>,
 }
 
@@ -94,8 +132,9 @@ pub struct HistogramEntry {
 /// Price increment rule defining tick sizes for price ranges.
 ///
 /// C++ source: `struct PriceIncrement` in `PriceIncrement.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct PriceIncrement {
     pub low_edge: f64,
     pub increment: f64,
@@ -108,8 +147,9 @@ pub struct PriceIncrement {
 /// Reason why a contract is ineligible for certain operations.
 ///
 /// C++ source: `struct IneligibilityReason` in `IneligibilityReason.h`.
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct IneligibilityReason {
     pub id: String,
     pub description: String,
@@ -122,8 +162,9 @@ pub struct IneligibilityReason {
 /// Wall Street Horizon event data request parameters.
 ///
 /// C++ source: `struct WshEventData` in `WshEventData.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct WshEventData {
     pub con_id: i32,
     pub filter: String,
@@ -142,10 +183,91 @@ pub struct WshEventData {
 /// Smart routing component information.
 ///
 /// C++ uses: `std::map<int, std::tuple<std::string, char>>` in EWrapper callbacks.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct SmartComponent {
     pub bit_number: i32,
     pub exchange: String,
     pub exchange_letter: char,
 }
+
+/// The component list of one `SmartComponents` response.
+///
+/// There is no C++ equivalent; C++ passes a `SmartComponentsMap` keyed by
+/// bit number.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct SmartComponents {
+    pub components: Vec<SmartComponent>,
+}
+
+impl SmartComponents {
+    /// Components whose `bit_number` is set in `mask`, in list order.
+    /// Bit numbers outside 0..32 never match.
+    pub fn exchanges_for_mask(&self, mask: i32) -> Vec<&SmartComponent> {
+        let mask = mask as u32;
+        self.components
+            .iter()
+            .filter(|c| {
+                u32::try_from(c.bit_number)
+                    .ok()
+                    .and_then(|bit| 1u32.checked_shl(bit))
+                    .is_some_and(|bit| mask & bit != 0)
+            })
+            .collect()
+    }
+
+    /// Component for the exchange letter used in tick data, e.g. `'Q'`.
+    pub fn by_letter(&self, letter: char) -> Option<&SmartComponent> {
+        self.components.iter().find(|c| c.exchange_letter == letter)
+    }
+}
+
+impl From<Vec<SmartComponent>> for SmartComponents {
+    fn from(components: Vec<SmartComponent>) -> Self {
+        Self { components }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_components_exchanges_for_mask() {
+        let component = |bit_number, exchange: &str, exchange_letter| SmartComponent {
+            bit_number,
+            exchange: exchange.to_string(),
+            exchange_letter,
+        };
+        let components = SmartComponents::from(vec![
+            component(0, "NYSE", 'N'),
+            component(1, "ARCA", 'P'),
+            component(4, "NASDAQ", 'Q'),
+            component(31, "IEX", 'V'),
+            component(40, "BOGUS", 'X'),
+        ]);
+
+        let names = |mask| {
+            components
+                .exchanges_for_mask(mask)
+                .into_iter()
+                .map(|c| c.exchange.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(0b10011), ["NYSE", "ARCA", "NASDAQ"]);
+        assert_eq!(names(0b10000), ["NASDAQ"]);
+        assert_eq!(names(i32::MIN), ["IEX"]);
+        assert_eq!(names(-1), ["NYSE", "ARCA", "NASDAQ", "IEX"]);
+        assert!(names(0).is_empty());
+
+        assert_eq!(components.by_letter('Q').unwrap().bit_number, 4);
+        assert!(components.by_letter('Z').is_none());
+    }
+}
@@ -2,11 +2,17 @@
 //!
 //! Ported from: `ibtws-cpp/client/Contract.h`.
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+#[cfg(feature = "chrono")]
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use super::common::{IneligibilityReason, TagValue};
 use super::enums::*;
+use crate::errors::{IBApiError, Result};
 
 // ============================================================================
 // ComboLeg
@@ -15,12 +21,16 @@ use super::enums::*;
 /// A single leg of a combo/spread order.
 ///
 /// C++ source: `struct ComboLeg` in `Contract.h`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ComboLeg {
     pub con_id: i64,
-    pub ratio: i64,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Units of this leg per unit of the combo. A `Decimal` so fractional
+    /// ratios can be expressed, but every server version so far takes an
+    /// integer (C++ `long ratio`); see [`wire_ratio`](Self::wire_ratio).
+    pub ratio: Decimal,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub action: Option<Action>,
     pub exchange: String,
     pub open_close: LegOpenClose,
@@ -35,7 +45,7 @@ impl Default for ComboLeg {
     fn default() -> Self {
         Self {
             con_id: 0,
-            ratio: 0,
+            ratio: Decimal::ZERO,
             action: None,
             exchange: String::new(),
             open_close: LegOpenClose::Same,
@@ -46,6 +56,26 @@ impl Default for ComboLeg {
     }
 }
 
+impl ComboLeg {
+    /// `ratio` as sent to a server of version `server_version`.
+    ///
+    /// Whole ratios are normalized (`2.0` is sent as `2`), so integer
+    /// ratios encode exactly as before. A fractional ratio fails with
+    /// `IBApiError::Encoding`: no server version up to
+    /// [`MAX_CLIENT_VER`](crate::protocol::MAX_CLIENT_VER) accepts one,
+    /// and TWS would otherwise truncate it or reject the whole request.
+    pub fn wire_ratio(&self, server_version: i32) -> Result<Decimal> {
+        if !self.ratio.fract().is_zero() {
+            return Err(IBApiError::Encoding(format!(
+                "fractional combo leg ratio {} (con_id {}) not supported by server version \
+                 {server_version}",
+                self.ratio, self.con_id
+            )));
+        }
+        Ok(self.ratio.normalize())
+    }
+}
+
 // ============================================================================
 // DeltaNeutralContract
 // ============================================================================
@@ -53,8 +83,9 @@ impl Default for ComboLeg {
 /// Delta neutral contract info attached to a combo.
 ///
 /// C++ source: `struct DeltaNeutralContract` in `Contract.h`.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct DeltaNeutralContract {
     pub con_id: i64,
     pub delta: f64,
@@ -68,19 +99,20 @@ pub struct DeltaNeutralContract {
 /// Defines a financial instrument (stock, option, future, forex, etc.).
 ///
 /// C++ source: `struct Contract` in `Contract.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Contract {
     pub con_id: i64,
     pub symbol: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub sec_type: Option<SecType>,
     pub last_trade_date_or_contract_month: String,
     pub last_trade_date: String,
     /// C++ default: `UNSET_DOUBLE`.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub strike: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub right: Option<Right>,
     pub multiplier: String,
     pub exchange: String,
@@ -89,18 +121,260 @@ pub struct Contract {
     pub local_symbol: String,
     pub trading_class: String,
     pub include_expired: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub sec_id_type: Option<SecIdType>,
     pub sec_id: String,
     pub description: String,
     pub issuer_id: String,
     pub combo_legs_descrip: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub combo_legs: Option<Vec<ComboLeg>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub delta_neutral_contract: Option<DeltaNeutralContract>,
 }
 
+impl Contract {
+    /// US stock routed through SMART, quoted in USD.
+    ///
+    /// Shorthand for `ContractBuilder::new().stock(symbol, "SMART", "USD")`.
+    pub fn stock(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            sec_type: Some(SecType::Stock),
+            exchange: "SMART".to_string(),
+            currency: "USD".to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether `other` describes the same instrument, e.g. a contract in a
+    /// response compared with the one that was requested.
+    ///
+    /// When both sides carry a `con_id`, only the IDs are compared.
+    /// Otherwise the identifying fields are compared -- symbol, security
+    /// type, currency, expiry, strike, right and multiplier -- and a field
+    /// left empty on either side matches anything. An expiry given as a
+    /// contract month (`202603`) matches a full date in that month
+    /// (`20260320`). Routing-only fields (`exchange`, `primary_exchange`,
+    /// `local_symbol`, `trading_class`) are ignored, so a `SMART` request
+    /// matches a response naming the exchange the order was routed to.
+    pub fn matches(&self, other: &Contract) -> bool {
+        if self.con_id > 0 && other.con_id > 0 {
+            return self.con_id == other.con_id;
+        }
+
+        fn both<T: PartialEq>(a: Option<T>, b: Option<T>) -> bool {
+            match (a, b) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            }
+        }
+        fn non_empty(s: &str) -> Option<&str> {
+            Some(s).filter(|s| !s.is_empty())
+        }
+        fn right(c: &Contract) -> Option<&Right> {
+            c.right.as_ref().filter(|r| **r != Right::Undefined)
+        }
+        let expiry_matches = match (
+            non_empty(&self.last_trade_date_or_contract_month),
+            non_empty(&other.last_trade_date_or_contract_month),
+        ) {
+            (Some(a), Some(b)) => a.starts_with(b) || b.starts_with(a),
+            _ => true,
+        };
+
+        self.symbol.eq_ignore_ascii_case(&other.symbol)
+            && both(self.sec_type.as_ref(), other.sec_type.as_ref())
+            && both(non_empty(&self.currency), non_empty(&other.currency))
+            && expiry_matches
+            && both(self.strike, other.strike)
+            && both(right(self), right(other))
+            && both(non_empty(&self.multiplier), non_empty(&other.multiplier))
+    }
+}
+
+// ============================================================================
+// ContractBuilder
+// ============================================================================
+
+/// Fluent builder for `Contract`.
+///
+/// Start from a preset (`stock`, `option`, `future`, `continuous_future`,
+/// `forex`) and refine it with the field setters; `build()` checks that the
+/// contract identifies an instrument. There is no C++ equivalent.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let call = ContractBuilder::new()
+///     .option("AAPL", "20260116", 200.0, Right::Call)
+///     .trading_class("AAPL")
+///     .build()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContractBuilder {
+    contract: Contract,
+}
+
+impl ContractBuilder {
+    /// Empty builder; every field starts at its `Contract::default()` value.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stock (`STK`) on `exchange`, quoted in `currency`.
+    pub fn stock(mut self, symbol: &str, exchange: &str, currency: &str) -> Self {
+        self.contract.symbol = symbol.to_string();
+        self.contract.sec_type = Some(SecType::Stock);
+        self.contract.exchange = exchange.to_string();
+        self.contract.currency = currency.to_string();
+        self
+    }
+
+    /// US equity option (`OPT`) routed through SMART, quoted in USD.
+    ///
+    /// `expiry` is `YYYYMMDD` (or `YYYYMM` for the contract month).
+    pub fn option(mut self, symbol: &str, expiry: &str, strike: f64, right: Right) -> Self {
+        self.contract.symbol = symbol.to_string();
+        self.contract.sec_type = Some(SecType::Option);
+        self.contract.last_trade_date_or_contract_month = expiry.to_string();
+        self.contract.strike = Some(strike);
+        self.contract.right = Some(right);
+        self.contract.multiplier = "100".to_string();
+        self.contract.exchange = "SMART".to_string();
+        self.contract.currency = "USD".to_string();
+        self
+    }
+
+    /// Future (`FUT`) on `exchange` for contract month `expiry`, quoted in USD.
+    pub fn future(mut self, symbol: &str, expiry: &str, exchange: &str) -> Self {
+        self.contract.symbol = symbol.to_string();
+        self.contract.sec_type = Some(SecType::Future);
+        self.contract.last_trade_date_or_contract_month = expiry.to_string();
+        self.contract.exchange = exchange.to_string();
+        self.contract.currency = "USD".to_string();
+        self
+    }
+
+    /// Continuous future (`CONTFUT`) on `exchange`, quoted in USD.
+    ///
+    /// No contract month is needed: TWS resolves the contract to the current
+    /// front month, so `req_contract_details` answers with the details of
+    /// that `FUT` contract (use its `con_id` to place orders), while
+    /// `req_mkt_data` and historical data requests follow the roll.
+    pub fn continuous_future(mut self, symbol: &str, exchange: &str) -> Self {
+        self.contract.symbol = symbol.to_string();
+        self.contract.sec_type = Some(SecType::ContinuousFuture);
+        self.contract.exchange = exchange.to_string();
+        self.contract.currency = "USD".to_string();
+        self
+    }
+
+    /// Currency pair (`CASH`) on IDEALPRO, e.g. `forex("EUR", "USD")`.
+    pub fn forex(mut self, base: &str, quote: &str) -> Self {
+        self.contract.symbol = base.to_string();
+        self.contract.sec_type = Some(SecType::Forex);
+        self.contract.exchange = "IDEALPRO".to_string();
+        self.contract.currency = quote.to_string();
+        self
+    }
+
+    /// IB contract ID. Enough on its own to identify the instrument.
+    pub fn con_id(mut self, con_id: i64) -> Self {
+        self.contract.con_id = con_id;
+        self
+    }
+
+    pub fn symbol(mut self, symbol: &str) -> Self {
+        self.contract.symbol = symbol.to_string();
+        self
+    }
+
+    pub fn sec_type(mut self, sec_type: SecType) -> Self {
+        self.contract.sec_type = Some(sec_type);
+        self
+    }
+
+    pub fn exchange(mut self, exchange: &str) -> Self {
+        self.contract.exchange = exchange.to_string();
+        self
+    }
+
+    pub fn primary_exchange(mut self, primary_exchange: &str) -> Self {
+        self.contract.primary_exchange = primary_exchange.to_string();
+        self
+    }
+
+    pub fn currency(mut self, currency: &str) -> Self {
+        self.contract.currency = currency.to_string();
+        self
+    }
+
+    pub fn multiplier(mut self, multiplier: &str) -> Self {
+        self.contract.multiplier = multiplier.to_string();
+        self
+    }
+
+    pub fn local_symbol(mut self, local_symbol: &str) -> Self {
+        self.contract.local_symbol = local_symbol.to_string();
+        self
+    }
+
+    pub fn trading_class(mut self, trading_class: &str) -> Self {
+        self.contract.trading_class = trading_class.to_string();
+        self
+    }
+
+    pub fn include_expired(mut self, include_expired: bool) -> Self {
+        self.contract.include_expired = include_expired;
+        self
+    }
+
+    /// Validate and return the contract.
+    ///
+    /// A non-zero `con_id` is always accepted. Otherwise the contract needs
+    /// a symbol (or local symbol), a security type and an exchange, and
+    /// derivatives need their expiry -- plus strike and right for options.
+    pub fn build(self) -> Result<Contract> {
+        let c = self.contract;
+        if c.con_id != 0 {
+            return Ok(c);
+        }
+        let invalid = |msg: &str| Err(IBApiError::Encoding(format!("invalid contract: {msg}")));
+
+        if c.symbol.is_empty() && c.local_symbol.is_empty() {
+            return invalid("symbol, local_symbol or con_id is required");
+        }
+        let Some(sec_type) = &c.sec_type else {
+            return invalid("sec_type is required");
+        };
+        if c.exchange.is_empty() {
+            return invalid("exchange is required");
+        }
+        let has_expiry = !c.last_trade_date_or_contract_month.is_empty() || !c.local_symbol.is_empty();
+        match sec_type {
+            SecType::Option | SecType::FutureOption | SecType::Warrant => {
+                if !has_expiry {
+                    return invalid("options need an expiry");
+                }
+                if c.local_symbol.is_empty() {
+                    if c.strike.is_none() {
+                        return invalid("options need a strike");
+                    }
+                    if matches!(c.right, None | Some(Right::Undefined)) {
+                        return invalid("options need a right (call/put)");
+                    }
+                }
+            }
+            SecType::Future if !has_expiry => {
+                return invalid("futures need a contract month");
+            }
+            _ => {}
+        }
+        Ok(c)
+    }
+}
+
 // ============================================================================
 // ContractDetails
 // ============================================================================
@@ -108,8 +382,9 @@ pub struct Contract {
 /// Extended contract information returned by `reqContractDetails`.
 ///
 /// C++ source: `struct ContractDetails` in `Contract.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ContractDetails {
     pub contract: Contract,
     pub market_name: String,
@@ -129,7 +404,7 @@ pub struct ContractDetails {
     pub ev_rule: String,
     pub ev_multiplier: f64,
     /// C++ default: `UNSET_INTEGER`.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub agg_group: Option<i32>,
     pub under_symbol: String,
     pub under_sec_type: String,
@@ -138,13 +413,13 @@ pub struct ContractDetails {
     pub last_trade_time: String,
     pub stock_type: String,
     /// C++ default: `UNSET_DECIMAL`.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub min_size: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub size_increment: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub suggested_size_increment: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub sec_id_list: Option<Vec<TagValue>>,
 
     // ----- Bond-specific fields -----
@@ -183,10 +458,178 @@ pub struct ContractDetails {
     pub fund_distribution_policy_indicator: FundDistributionPolicyIndicator,
     pub fund_asset_type: FundAssetType,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub ineligibility_reason_list: Option<Vec<IneligibilityReason>>,
 }
 
+impl ContractDetails {
+    /// The bond-specific fields, or `None` unless `contract.sec_type` is
+    /// `BOND`.
+    ///
+    /// There is no C++ equivalent -- a view over what
+    /// `decode_bond_contract_data` fills in.
+    pub fn as_bond(&self) -> Option<BondDetails> {
+        if self.contract.sec_type != Some(SecType::Bond) {
+            return None;
+        }
+        // BOND_CONTRACT_DATA carries the maturity in the contract's
+        // `lastTradeDateOrContractMonth` field.
+        let maturity = if self.maturity.is_empty() {
+            &self.contract.last_trade_date_or_contract_month
+        } else {
+            &self.maturity
+        };
+        Some(BondDetails {
+            con_id: self.contract.con_id,
+            symbol: self.contract.symbol.clone(),
+            exchange: self.contract.exchange.clone(),
+            currency: self.contract.currency.clone(),
+            cusip: self.cusip.clone(),
+            ratings: self.ratings.clone(),
+            desc_append: self.desc_append.clone(),
+            bond_type: self.bond_type.clone(),
+            coupon_type: self.coupon_type.clone(),
+            coupon: self.coupon,
+            maturity: maturity.clone(),
+            issue_date: self.issue_date.clone(),
+            callable: self.callable,
+            putable: self.putable,
+            convertible: self.convertible,
+            next_option_date: self.next_option_date.clone(),
+            next_option_type: self.next_option_type.clone(),
+            next_option_partial: self.next_option_partial,
+            notes: self.notes.clone(),
+        })
+    }
+
+    /// [`trading_hours`](Self::trading_hours) split into sessions.
+    ///
+    /// Understands the current `"20240225:0930-20240225:1600"` form, the
+    /// older `"20240225:0930-1600"` form (times on the listed day, several
+    /// ranges separated by `,`), and `"20240226:CLOSED"`. Times are in
+    /// [`time_zone_id`](Self::time_zone_id). Malformed entries are skipped.
+    ///
+    /// There is no C++ equivalent -- `tradingHours` is a plain string.
+    pub fn parse_trading_hours(&self) -> Vec<TradingSession> {
+        parse_sessions(&self.trading_hours, &self.time_zone_id)
+    }
+
+    /// [`liquid_hours`](Self::liquid_hours) split into sessions, like
+    /// [`parse_trading_hours`](Self::parse_trading_hours).
+    pub fn parse_liquid_hours(&self) -> Vec<TradingSession> {
+        parse_sessions(&self.liquid_hours, &self.time_zone_id)
+    }
+}
+
+/// One session (or closed day) from `ContractDetails::trading_hours` or
+/// `liquid_hours`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct TradingSession {
+    /// Day the entry is listed under, `YYYYMMDD`.
+    pub date: String,
+    /// Session open as `YYYYMMDD:HHMM`; empty for a closed day.
+    pub start: String,
+    /// Session close as `YYYYMMDD:HHMM`; empty for a closed day. Overnight
+    /// sessions close on a later day than they open.
+    pub end: String,
+    /// Time zone of `start` and `end`, the contract's `time_zone_id`.
+    pub time_zone: String,
+}
+
+impl TradingSession {
+    /// Whether the market is closed on [`date`](Self::date).
+    pub fn is_closed(&self) -> bool {
+        self.start.is_empty()
+    }
+
+    /// [`start`](Self::start) as a timezone-aware timestamp, or `None` for a
+    /// closed day or a zone name chrono-tz does not know.
+    #[cfg(feature = "chrono")]
+    pub fn start_time(&self) -> Option<DateTime<Tz>> {
+        self.parse_bound(&self.start)
+    }
+
+    /// [`end`](Self::end) as a timezone-aware timestamp, like
+    /// [`start_time`](Self::start_time).
+    #[cfg(feature = "chrono")]
+    pub fn end_time(&self) -> Option<DateTime<Tz>> {
+        self.parse_bound(&self.end)
+    }
+
+    #[cfg(feature = "chrono")]
+    fn parse_bound(&self, bound: &str) -> Option<DateTime<Tz>> {
+        let tz: Tz = self.time_zone.parse().ok()?;
+        let naive = NaiveDateTime::parse_from_str(bound, "%Y%m%d:%H%M").ok()?;
+        tz.from_local_datetime(&naive).earliest()
+    }
+}
+
+/// Parse a `tradingHours` / `liquidHours` string.
+fn parse_sessions(hours: &str, time_zone: &str) -> Vec<TradingSession> {
+    let mut sessions = Vec::new();
+    for entry in hours.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((date, ranges)) = entry.split_once(':') else {
+            continue;
+        };
+        let session = |start: String, end: String| TradingSession {
+            date: date.to_string(),
+            start,
+            end,
+            time_zone: time_zone.to_string(),
+        };
+        if ranges == "CLOSED" {
+            sessions.push(session(String::new(), String::new()));
+            continue;
+        }
+        for range in ranges.split(',') {
+            let Some((start, end)) = range.split_once('-') else {
+                continue;
+            };
+            // A bare `HHMM` is on the listed day.
+            let bound = |t: &str| {
+                if t.contains(':') {
+                    t.to_string()
+                } else {
+                    format!("{date}:{t}")
+                }
+            };
+            sessions.push(session(bound(start), bound(end)));
+        }
+    }
+    sessions
+}
+
+/// The bond-relevant part of a [`ContractDetails`], from
+/// [`ContractDetails::as_bond`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct BondDetails {
+    pub con_id: i64,
+    /// Issuer ticker, e.g. `"IBCID123456789"` or `"T"`.
+    pub symbol: String,
+    pub exchange: String,
+    pub currency: String,
+    pub cusip: String,
+    pub ratings: String,
+    pub desc_append: String,
+    pub bond_type: String,
+    pub coupon_type: String,
+    pub coupon: f64,
+    /// `YYYYMMDD`.
+    pub maturity: String,
+    pub issue_date: String,
+    pub callable: bool,
+    pub putable: bool,
+    pub convertible: bool,
+    pub next_option_date: String,
+    pub next_option_type: String,
+    pub next_option_partial: bool,
+    pub notes: String,
+}
+
 // ============================================================================
 // ContractDescription
 // ============================================================================
@@ -196,9 +639,179 @@ pub struct ContractDetails {
 /// Returned by `reqMatchingSymbols`.
 ///
 /// C++ source: `struct ContractDescription` in `Contract.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ContractDescription {
     pub contract: Contract,
     pub derivative_sec_types: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stock_preset_matches_hand_built_contract() {
+        let built = ContractBuilder::new().stock("AAPL", "SMART", "USD").build().unwrap();
+        let hand = Contract {
+            symbol: "AAPL".into(),
+            sec_type: Some(SecType::Stock),
+            exchange: "SMART".into(),
+            currency: "USD".into(),
+            ..Contract::default()
+        };
+        assert_eq!(format!("{built:?}"), format!("{hand:?}"));
+        assert_eq!(format!("{:?}", Contract::stock("AAPL")), format!("{hand:?}"));
+    }
+
+    #[test]
+    fn matches_ignores_routing_and_prefers_con_id() {
+        let requested = Contract { con_id: 265598, ..Contract::stock("AAPL") };
+        let response = Contract {
+            con_id: 265598,
+            symbol: "AAPL".into(),
+            sec_type: Some(SecType::Stock),
+            exchange: "NASDAQ".into(),
+            primary_exchange: "NASDAQ".into(),
+            currency: "USD".into(),
+            local_symbol: "AAPL".into(),
+            trading_class: "NMS".into(),
+            ..Contract::default()
+        };
+        assert!(requested.matches(&response));
+        assert!(response.matches(&requested));
+        assert!(!requested.matches(&Contract { con_id: 1, ..response.clone() }));
+
+        // Without a con_id on the request, the descriptive fields decide.
+        assert!(Contract::stock("AAPL").matches(&response));
+        assert!(!Contract::stock("MSFT").matches(&response));
+        let eur = Contract { currency: "EUR".into(), ..Contract::stock("AAPL") };
+        assert!(!eur.matches(&response));
+
+        let month = ContractBuilder::new().future("ES", "202603", "CME").build().unwrap();
+        let routed = Contract {
+            last_trade_date_or_contract_month: "20260320".into(),
+            exchange: "GLOBEX".into(),
+            multiplier: "50".into(),
+            currency: "USD".into(),
+            ..month.clone()
+        };
+        assert!(month.matches(&routed));
+        let june = Contract { last_trade_date_or_contract_month: "20260618".into(), ..routed };
+        assert!(!month.matches(&june));
+    }
+
+    #[test]
+    fn option_and_forex_presets() {
+        let opt = ContractBuilder::new()
+            .option("AAPL", "20260116", 200.0, Right::Call)
+            .build()
+            .unwrap();
+        assert_eq!(opt.sec_type, Some(SecType::Option));
+        assert_eq!(opt.strike, Some(200.0));
+        assert_eq!(opt.right, Some(Right::Call));
+        assert_eq!(opt.multiplier, "100");
+        assert_eq!(opt.exchange, "SMART");
+
+        let fx = ContractBuilder::new().forex("EUR", "USD").build().unwrap();
+        assert_eq!(fx.symbol, "EUR");
+        assert_eq!(fx.currency, "USD");
+        assert_eq!(fx.exchange, "IDEALPRO");
+    }
+
+    #[test]
+    fn build_rejects_incomplete_contracts() {
+        assert!(ContractBuilder::new().build().is_err());
+        assert!(ContractBuilder::new().symbol("AAPL").exchange("SMART").build().is_err());
+        assert!(ContractBuilder::new().future("ES", "", "CME").build().is_err());
+        assert!(ContractBuilder::new()
+            .option("AAPL", "20260116", 200.0, Right::Undefined)
+            .build()
+            .is_err());
+
+        // A con_id alone identifies the instrument.
+        let c = ContractBuilder::new().con_id(265598).exchange("SMART").build().unwrap();
+        assert_eq!(c.con_id, 265598);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn contract_serde_round_trip() {
+        let contract = ContractBuilder::new()
+            .option("AAPL", "20260116", 200.0, Right::Call)
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&contract).unwrap();
+        assert_eq!(json["secType"], serde_json::json!(SecType::Option));
+        assert_eq!(json["strike"], 200.0);
+
+        let back: Contract = serde_json::from_value(json).unwrap();
+        assert_eq!(format!("{back:?}"), format!("{contract:?}"));
+
+        // Unset fields are omitted rather than sent as C++ sentinels.
+        let json = serde_json::to_value(Contract::stock("AAPL")).unwrap();
+        assert!(json.get("strike").is_none());
+        let back: Contract = serde_json::from_value(json).unwrap();
+        assert_eq!(back.strike, None);
+    }
+
+    fn details_with_hours(trading_hours: &str) -> ContractDetails {
+        ContractDetails {
+            time_zone_id: "US/Eastern".into(),
+            trading_hours: trading_hours.into(),
+            ..ContractDetails::default()
+        }
+    }
+
+    #[test]
+    fn parse_trading_hours_current_format() {
+        let details = details_with_hours(
+            "20240225:CLOSED;20240226:0400-20240226:2000;20240227:1800-20240228:1700",
+        );
+        let sessions = details.parse_trading_hours();
+        assert_eq!(sessions.len(), 3);
+        assert!(sessions[0].is_closed());
+        assert_eq!(sessions[0].date, "20240225");
+        assert_eq!(sessions[1].start, "20240226:0400");
+        assert_eq!(sessions[1].end, "20240226:2000");
+        assert_eq!(sessions[1].time_zone, "US/Eastern");
+        assert!(!sessions[1].is_closed());
+        // Overnight session listed under the day it opens.
+        assert_eq!(sessions[2].date, "20240227");
+        assert_eq!(sessions[2].end, "20240228:1700");
+    }
+
+    #[test]
+    fn parse_trading_hours_old_format() {
+        let details =
+            details_with_hours("20240225:0930-1600;20240226:CLOSED;20240227:0930-1200,1300-1600");
+        let sessions = details.parse_trading_hours();
+        let bounds: Vec<_> =
+            sessions.iter().map(|s| (s.date.as_str(), s.start.as_str(), s.end.as_str())).collect();
+        assert_eq!(
+            bounds,
+            [
+                ("20240225", "20240225:0930", "20240225:1600"),
+                ("20240226", "", ""),
+                ("20240227", "20240227:0930", "20240227:1200"),
+                ("20240227", "20240227:1300", "20240227:1600"),
+            ]
+        );
+        assert!(details_with_hours("").parse_trading_hours().is_empty());
+        assert!(details_with_hours("garbage;20240225").parse_trading_hours().is_empty());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn trading_session_times_in_contract_zone() {
+        use chrono::Utc;
+        let session = &details_with_hours("20240226:0930-20240226:1600").parse_trading_hours()[0];
+        let start = session.start_time().unwrap().with_timezone(&Utc);
+        assert_eq!(start.to_rfc3339(), "2024-02-26T14:30:00+00:00");
+        let end = session.end_time().unwrap().with_timezone(&Utc);
+        assert_eq!(end.to_rfc3339(), "2024-02-26T21:00:00+00:00");
+        let closed = &details_with_hours("20240225:CLOSED").parse_trading_hours()[0];
+        assert_eq!(closed.start_time(), None);
+    }
+}
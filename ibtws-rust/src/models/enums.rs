@@ -4,6 +4,7 @@
 //! variants for extensible types. Serde `rename` attributes match the IB wire
 //! protocol strings exactly.
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
@@ -13,36 +14,40 @@ use std::str::FromStr;
 // ============================================================================
 
 /// Security type (C++: `string secType` field in `Contract`).
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SecType {
-    #[serde(rename = "STK")]
+    #[cfg_attr(feature = "serde", serde(rename = "STK"))]
     Stock,
-    #[serde(rename = "OPT")]
+    #[cfg_attr(feature = "serde", serde(rename = "OPT"))]
     Option,
-    #[serde(rename = "FUT")]
+    #[cfg_attr(feature = "serde", serde(rename = "FUT"))]
     Future,
-    #[serde(rename = "CASH")]
+    /// Continuous future: the front-month contract, rolled by TWS.
+    #[cfg_attr(feature = "serde", serde(rename = "CONTFUT"))]
+    ContinuousFuture,
+    #[cfg_attr(feature = "serde", serde(rename = "CASH"))]
     Forex,
-    #[serde(rename = "IND")]
+    #[cfg_attr(feature = "serde", serde(rename = "IND"))]
     Index,
-    #[serde(rename = "FOP")]
+    #[cfg_attr(feature = "serde", serde(rename = "FOP"))]
     FutureOption,
-    #[serde(rename = "BOND")]
+    #[cfg_attr(feature = "serde", serde(rename = "BOND"))]
     Bond,
-    #[serde(rename = "FUND")]
+    #[cfg_attr(feature = "serde", serde(rename = "FUND"))]
     Fund,
-    #[serde(rename = "WAR")]
+    #[cfg_attr(feature = "serde", serde(rename = "WAR"))]
     Warrant,
-    #[serde(rename = "CMDTY")]
+    #[cfg_attr(feature = "serde", serde(rename = "CMDTY"))]
     Commodity,
-    #[serde(rename = "BAG")]
+    #[cfg_attr(feature = "serde", serde(rename = "BAG"))]
     Combo,
-    #[serde(rename = "NEWS")]
+    #[cfg_attr(feature = "serde", serde(rename = "NEWS"))]
     News,
-    #[serde(rename = "CRYPTO")]
+    #[cfg_attr(feature = "serde", serde(rename = "CRYPTO"))]
     Crypto,
     /// Unrecognized security type from the server.
-    #[serde(untagged)]
+    #[cfg_attr(feature = "serde", serde(untagged))]
     Other(String),
 }
 
@@ -52,6 +57,7 @@ impl fmt::Display for SecType {
             Self::Stock => write!(f, "STK"),
             Self::Option => write!(f, "OPT"),
             Self::Future => write!(f, "FUT"),
+            Self::ContinuousFuture => write!(f, "CONTFUT"),
             Self::Forex => write!(f, "CASH"),
             Self::Index => write!(f, "IND"),
             Self::FutureOption => write!(f, "FOP"),
@@ -74,6 +80,7 @@ impl FromStr for SecType {
             "STK" => Self::Stock,
             "OPT" => Self::Option,
             "FUT" => Self::Future,
+            "CONTFUT" => Self::ContinuousFuture,
             "CASH" => Self::Forex,
             "IND" => Self::Index,
             "FOP" => Self::FutureOption,
@@ -90,13 +97,14 @@ impl FromStr for SecType {
 }
 
 /// Option right (C++: `string right` field in `Contract`).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Right {
-    #[serde(rename = "C")]
+    #[cfg_attr(feature = "serde", serde(rename = "C"))]
     Call,
-    #[serde(rename = "P")]
+    #[cfg_attr(feature = "serde", serde(rename = "P"))]
     Put,
-    #[serde(rename = "")]
+    #[cfg_attr(feature = "serde", serde(rename = ""))]
     Undefined,
 }
 
@@ -122,17 +130,18 @@ impl FromStr for Right {
 }
 
 /// Security ID type (C++: `string secIdType` field in `Contract`).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SecIdType {
-    #[serde(rename = "CUSIP")]
+    #[cfg_attr(feature = "serde", serde(rename = "CUSIP"))]
     Cusip,
-    #[serde(rename = "SEDOL")]
+    #[cfg_attr(feature = "serde", serde(rename = "SEDOL"))]
     Sedol,
-    #[serde(rename = "ISIN")]
+    #[cfg_attr(feature = "serde", serde(rename = "ISIN"))]
     Isin,
-    #[serde(rename = "RIC")]
+    #[cfg_attr(feature = "serde", serde(rename = "RIC"))]
     Ric,
-    #[serde(untagged)]
+    #[cfg_attr(feature = "serde", serde(untagged))]
     Other(String),
 }
 
@@ -166,13 +175,14 @@ impl FromStr for SecIdType {
 // ============================================================================
 
 /// Order action (C++: `string action` field in `Order`).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Action {
-    #[serde(rename = "BUY")]
+    #[cfg_attr(feature = "serde", serde(rename = "BUY"))]
     Buy,
-    #[serde(rename = "SELL")]
+    #[cfg_attr(feature = "serde", serde(rename = "SELL"))]
     Sell,
-    #[serde(rename = "SSHORT")]
+    #[cfg_attr(feature = "serde", serde(rename = "SSHORT"))]
     SellShort,
 }
 
@@ -199,54 +209,55 @@ impl FromStr for Action {
 }
 
 /// Order type (C++: `string orderType` field in `Order`).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OrderType {
-    #[serde(rename = "MKT")]
+    #[cfg_attr(feature = "serde", serde(rename = "MKT"))]
     Market,
-    #[serde(rename = "LMT")]
+    #[cfg_attr(feature = "serde", serde(rename = "LMT"))]
     Limit,
-    #[serde(rename = "STP")]
+    #[cfg_attr(feature = "serde", serde(rename = "STP"))]
     Stop,
-    #[serde(rename = "STP LMT")]
+    #[cfg_attr(feature = "serde", serde(rename = "STP LMT"))]
     StopLimit,
-    #[serde(rename = "TRAIL")]
+    #[cfg_attr(feature = "serde", serde(rename = "TRAIL"))]
     TrailingStop,
-    #[serde(rename = "TRAIL LIMIT")]
+    #[cfg_attr(feature = "serde", serde(rename = "TRAIL LIMIT"))]
     TrailingStopLimit,
-    #[serde(rename = "REL")]
+    #[cfg_attr(feature = "serde", serde(rename = "REL"))]
     Relative,
-    #[serde(rename = "MOC")]
+    #[cfg_attr(feature = "serde", serde(rename = "MOC"))]
     MarketOnClose,
-    #[serde(rename = "LOC")]
+    #[cfg_attr(feature = "serde", serde(rename = "LOC"))]
     LimitOnClose,
-    #[serde(rename = "MOO")]
+    #[cfg_attr(feature = "serde", serde(rename = "MOO"))]
     MarketOnOpen,
-    #[serde(rename = "LOO")]
+    #[cfg_attr(feature = "serde", serde(rename = "LOO"))]
     LimitOnOpen,
-    #[serde(rename = "PEG MKT")]
+    #[cfg_attr(feature = "serde", serde(rename = "PEG MKT"))]
     PeggedToMarket,
-    #[serde(rename = "PEG MID")]
+    #[cfg_attr(feature = "serde", serde(rename = "PEG MID"))]
     PeggedToMidpoint,
-    #[serde(rename = "PEG BENCH")]
+    #[cfg_attr(feature = "serde", serde(rename = "PEG BENCH"))]
     PeggedToBenchmark,
-    #[serde(rename = "VOL")]
+    #[cfg_attr(feature = "serde", serde(rename = "VOL"))]
     Volatility,
-    #[serde(rename = "MIT")]
+    #[cfg_attr(feature = "serde", serde(rename = "MIT"))]
     MarketIfTouched,
-    #[serde(rename = "LIT")]
+    #[cfg_attr(feature = "serde", serde(rename = "LIT"))]
     LimitIfTouched,
-    #[serde(rename = "MKT PRT")]
+    #[cfg_attr(feature = "serde", serde(rename = "MKT PRT"))]
     MarketWithProtection,
-    #[serde(rename = "MIDPRICE")]
+    #[cfg_attr(feature = "serde", serde(rename = "MIDPRICE"))]
     MidPrice,
-    #[serde(rename = "SNAP MKT")]
+    #[cfg_attr(feature = "serde", serde(rename = "SNAP MKT"))]
     SnapToMarket,
-    #[serde(rename = "SNAP MID")]
+    #[cfg_attr(feature = "serde", serde(rename = "SNAP MID"))]
     SnapToMidpoint,
-    #[serde(rename = "PEG PRIM")]
+    #[cfg_attr(feature = "serde", serde(rename = "PEG PRIM"))]
     PeggedToPrimary,
     /// Unrecognized order type from the server.
-    #[serde(untagged)]
+    #[cfg_attr(feature = "serde", serde(untagged))]
     Other(String),
 }
 
@@ -312,23 +323,24 @@ impl FromStr for OrderType {
 }
 
 /// Time in force (C++: `string tif` field in `Order`).
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TimeInForce {
-    #[serde(rename = "DAY")]
+    #[cfg_attr(feature = "serde", serde(rename = "DAY"))]
     Day,
-    #[serde(rename = "GTC")]
+    #[cfg_attr(feature = "serde", serde(rename = "GTC"))]
     GoodTilCancelled,
-    #[serde(rename = "IOC")]
+    #[cfg_attr(feature = "serde", serde(rename = "IOC"))]
     ImmediateOrCancel,
-    #[serde(rename = "GTD")]
+    #[cfg_attr(feature = "serde", serde(rename = "GTD"))]
     GoodTilDate,
-    #[serde(rename = "OPG")]
+    #[cfg_attr(feature = "serde", serde(rename = "OPG"))]
     AtTheOpening,
-    #[serde(rename = "FOK")]
+    #[cfg_attr(feature = "serde", serde(rename = "FOK"))]
     FillOrKill,
-    #[serde(rename = "DTC")]
+    #[cfg_attr(feature = "serde", serde(rename = "DTC"))]
     DayTilCancelled,
-    #[serde(untagged)]
+    #[cfg_attr(feature = "serde", serde(untagged))]
     Other(String),
 }
 
@@ -363,12 +375,370 @@ impl FromStr for TimeInForce {
     }
 }
 
+// ============================================================================
+// Historical Data Enums
+// ============================================================================
+
+/// Data type of a historical data request (C++: `whatToShow` string in
+/// `EClient::reqHistoricalData`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WhatToShow {
+    #[cfg_attr(feature = "serde", serde(rename = "TRADES"))]
+    Trades,
+    #[cfg_attr(feature = "serde", serde(rename = "MIDPOINT"))]
+    Midpoint,
+    #[cfg_attr(feature = "serde", serde(rename = "BID"))]
+    Bid,
+    #[cfg_attr(feature = "serde", serde(rename = "ASK"))]
+    Ask,
+    #[cfg_attr(feature = "serde", serde(rename = "BID_ASK"))]
+    BidAsk,
+    #[cfg_attr(feature = "serde", serde(rename = "ADJUSTED_LAST"))]
+    AdjustedLast,
+    #[cfg_attr(feature = "serde", serde(rename = "HISTORICAL_VOLATILITY"))]
+    HistoricalVolatility,
+    #[cfg_attr(feature = "serde", serde(rename = "OPTION_IMPLIED_VOLATILITY"))]
+    OptionImpliedVolatility,
+    #[cfg_attr(feature = "serde", serde(rename = "REBATE_RATE"))]
+    RebateRate,
+    #[cfg_attr(feature = "serde", serde(rename = "FEE_RATE"))]
+    FeeRate,
+    #[cfg_attr(feature = "serde", serde(rename = "YIELD_BID"))]
+    YieldBid,
+    #[cfg_attr(feature = "serde", serde(rename = "YIELD_ASK"))]
+    YieldAsk,
+    #[cfg_attr(feature = "serde", serde(rename = "YIELD_BID_ASK"))]
+    YieldBidAsk,
+    #[cfg_attr(feature = "serde", serde(rename = "YIELD_LAST"))]
+    YieldLast,
+    #[cfg_attr(feature = "serde", serde(rename = "SCHEDULE"))]
+    Schedule,
+    #[cfg_attr(feature = "serde", serde(rename = "AGGTRADES"))]
+    AggTrades,
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String),
+}
+
+impl fmt::Display for WhatToShow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trades => write!(f, "TRADES"),
+            Self::Midpoint => write!(f, "MIDPOINT"),
+            Self::Bid => write!(f, "BID"),
+            Self::Ask => write!(f, "ASK"),
+            Self::BidAsk => write!(f, "BID_ASK"),
+            Self::AdjustedLast => write!(f, "ADJUSTED_LAST"),
+            Self::HistoricalVolatility => write!(f, "HISTORICAL_VOLATILITY"),
+            Self::OptionImpliedVolatility => write!(f, "OPTION_IMPLIED_VOLATILITY"),
+            Self::RebateRate => write!(f, "REBATE_RATE"),
+            Self::FeeRate => write!(f, "FEE_RATE"),
+            Self::YieldBid => write!(f, "YIELD_BID"),
+            Self::YieldAsk => write!(f, "YIELD_ASK"),
+            Self::YieldBidAsk => write!(f, "YIELD_BID_ASK"),
+            Self::YieldLast => write!(f, "YIELD_LAST"),
+            Self::Schedule => write!(f, "SCHEDULE"),
+            Self::AggTrades => write!(f, "AGGTRADES"),
+            Self::Other(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl FromStr for WhatToShow {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "TRADES" => Self::Trades,
+            "MIDPOINT" => Self::Midpoint,
+            "BID" => Self::Bid,
+            "ASK" => Self::Ask,
+            "BID_ASK" => Self::BidAsk,
+            "ADJUSTED_LAST" => Self::AdjustedLast,
+            "HISTORICAL_VOLATILITY" => Self::HistoricalVolatility,
+            "OPTION_IMPLIED_VOLATILITY" => Self::OptionImpliedVolatility,
+            "REBATE_RATE" => Self::RebateRate,
+            "FEE_RATE" => Self::FeeRate,
+            "YIELD_BID" => Self::YieldBid,
+            "YIELD_ASK" => Self::YieldAsk,
+            "YIELD_BID_ASK" => Self::YieldBidAsk,
+            "YIELD_LAST" => Self::YieldLast,
+            "SCHEDULE" => Self::Schedule,
+            "AGGTRADES" => Self::AggTrades,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+/// String escape hatch: known values map to their variant, anything else
+/// is sent as-is via `Other`.
+impl From<&str> for WhatToShow {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_else(|never| match never {})
+    }
+}
+
+/// Bar size of a historical data request (C++: `barSizeSetting` string in
+/// `EClient::reqHistoricalData`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BarSize {
+    #[cfg_attr(feature = "serde", serde(rename = "1 secs"))]
+    OneSec,
+    #[cfg_attr(feature = "serde", serde(rename = "5 secs"))]
+    FiveSecs,
+    #[cfg_attr(feature = "serde", serde(rename = "10 secs"))]
+    TenSecs,
+    #[cfg_attr(feature = "serde", serde(rename = "15 secs"))]
+    FifteenSecs,
+    #[cfg_attr(feature = "serde", serde(rename = "30 secs"))]
+    ThirtySecs,
+    #[cfg_attr(feature = "serde", serde(rename = "1 min"))]
+    OneMin,
+    #[cfg_attr(feature = "serde", serde(rename = "2 mins"))]
+    TwoMins,
+    #[cfg_attr(feature = "serde", serde(rename = "3 mins"))]
+    ThreeMins,
+    #[cfg_attr(feature = "serde", serde(rename = "5 mins"))]
+    FiveMins,
+    #[cfg_attr(feature = "serde", serde(rename = "10 mins"))]
+    TenMins,
+    #[cfg_attr(feature = "serde", serde(rename = "15 mins"))]
+    FifteenMins,
+    #[cfg_attr(feature = "serde", serde(rename = "20 mins"))]
+    TwentyMins,
+    #[cfg_attr(feature = "serde", serde(rename = "30 mins"))]
+    ThirtyMins,
+    #[cfg_attr(feature = "serde", serde(rename = "1 hour"))]
+    OneHour,
+    #[cfg_attr(feature = "serde", serde(rename = "2 hours"))]
+    TwoHours,
+    #[cfg_attr(feature = "serde", serde(rename = "3 hours"))]
+    ThreeHours,
+    #[cfg_attr(feature = "serde", serde(rename = "4 hours"))]
+    FourHours,
+    #[cfg_attr(feature = "serde", serde(rename = "8 hours"))]
+    EightHours,
+    #[cfg_attr(feature = "serde", serde(rename = "1 day"))]
+    OneDay,
+    #[cfg_attr(feature = "serde", serde(rename = "1 week"))]
+    OneWeek,
+    #[cfg_attr(feature = "serde", serde(rename = "1 month"))]
+    OneMonth,
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String),
+}
+
+impl fmt::Display for BarSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OneSec => write!(f, "1 secs"),
+            Self::FiveSecs => write!(f, "5 secs"),
+            Self::TenSecs => write!(f, "10 secs"),
+            Self::FifteenSecs => write!(f, "15 secs"),
+            Self::ThirtySecs => write!(f, "30 secs"),
+            Self::OneMin => write!(f, "1 min"),
+            Self::TwoMins => write!(f, "2 mins"),
+            Self::ThreeMins => write!(f, "3 mins"),
+            Self::FiveMins => write!(f, "5 mins"),
+            Self::TenMins => write!(f, "10 mins"),
+            Self::FifteenMins => write!(f, "15 mins"),
+            Self::TwentyMins => write!(f, "20 mins"),
+            Self::ThirtyMins => write!(f, "30 mins"),
+            Self::OneHour => write!(f, "1 hour"),
+            Self::TwoHours => write!(f, "2 hours"),
+            Self::ThreeHours => write!(f, "3 hours"),
+            Self::FourHours => write!(f, "4 hours"),
+            Self::EightHours => write!(f, "8 hours"),
+            Self::OneDay => write!(f, "1 day"),
+            Self::OneWeek => write!(f, "1 week"),
+            Self::OneMonth => write!(f, "1 month"),
+            Self::Other(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+impl FromStr for BarSize {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "1 secs" => Self::OneSec,
+            "5 secs" => Self::FiveSecs,
+            "10 secs" => Self::TenSecs,
+            "15 secs" => Self::FifteenSecs,
+            "30 secs" => Self::ThirtySecs,
+            "1 min" => Self::OneMin,
+            "2 mins" => Self::TwoMins,
+            "3 mins" => Self::ThreeMins,
+            "5 mins" => Self::FiveMins,
+            "10 mins" => Self::TenMins,
+            "15 mins" => Self::FifteenMins,
+            "20 mins" => Self::TwentyMins,
+            "30 mins" => Self::ThirtyMins,
+            "1 hour" => Self::OneHour,
+            "2 hours" => Self::TwoHours,
+            "3 hours" => Self::ThreeHours,
+            "4 hours" => Self::FourHours,
+            "8 hours" => Self::EightHours,
+            "1 day" => Self::OneDay,
+            "1 week" => Self::OneWeek,
+            "1 month" => Self::OneMonth,
+            other => Self::Other(other.to_string()),
+        })
+    }
+}
+
+impl From<&str> for BarSize {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_else(|never| match never {})
+    }
+}
+
+// ============================================================================
+// Market Data Enums
+// ============================================================================
+
+/// Generic tick types requested through `req_mkt_data`'s `generic_ticks`.
+///
+/// Each value asks TWS for extra ticks beyond the default bid/ask/last set;
+/// the ticks that arrive are listed per variant. Build the request string
+/// with [`GenericTickList::to_generic_string`]. There is no C++ equivalent --
+/// `EClient::reqMktData` takes the comma-separated codes as a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(i32)]
+pub enum GenericTick {
+    /// Option call/put volume (ticks 29, 30).
+    OptionVolume = 100,
+    /// Option call/put open interest (ticks 27, 28).
+    OptionOpenInterest = 101,
+    /// 30-day historical volatility (tick 23).
+    HistoricalVolatility = 104,
+    /// Average option volume (tick 87).
+    AverageOptionVolume = 105,
+    /// 30-day implied volatility of the underlying (tick 24).
+    OptionImpliedVolatility = 106,
+    /// Index future premium (tick 31).
+    IndexFuturePremium = 162,
+    /// 13/26/52-week highs and lows, average volume (ticks 15-21).
+    MiscellaneousStats = 165,
+    /// Mark price used in TWS P&L (tick 37).
+    MarkPrice = 221,
+    /// Auction volume, price and imbalance (ticks 34-36, 61).
+    AuctionValues = 225,
+    /// Last trade price, size, time and VWAP as one string (tick 48).
+    RtVolume = 233,
+    /// Shortability indicator and shortable shares (ticks 46, 89).
+    Shortable = 236,
+    /// Fundamental ratios (tick 47).
+    FundamentalRatios = 258,
+    /// News headlines (tick 62).
+    News = 292,
+    /// Trade count for the day (tick 54).
+    TradeCount = 293,
+    /// Trades per minute (tick 55).
+    TradeRate = 294,
+    /// Shares per minute (tick 56).
+    VolumeRate = 295,
+    /// Last regular-hours trade price (tick 57).
+    LastRthTrade = 318,
+    /// RTVolume restricted to reportable trades (tick 77).
+    RtTradeVolume = 375,
+    /// Real-time historical volatility (tick 58).
+    RtHistoricalVolatility = 411,
+    /// Past and projected dividends (tick 59).
+    Dividends = 456,
+    /// Bond factor multiplier (tick 60).
+    BondFactorMultiplier = 460,
+    /// ETF NAV bid and ask (ticks 96, 97).
+    EtfNavBidAsk = 576,
+    /// ETF NAV last (tick 98).
+    EtfNavLast = 577,
+    /// ETF NAV close (tick 92).
+    EtfNavClose = 578,
+    /// Estimated and final IPO prices (ticks 101, 102).
+    IpoPrices = 586,
+    /// Futures open interest (tick 86).
+    FuturesOpenInterest = 588,
+    /// Volume over the last 3, 5 and 10 minutes (ticks 63-65).
+    ShortTermVolume = 595,
+    /// ETF NAV high and low (ticks 93, 94).
+    EtfNavHighLow = 614,
+    /// Creditman slow mark price (tick 79).
+    CreditmanSlowMarkPrice = 619,
+    /// ETF NAV frozen last (tick 99).
+    EtfNavFrozenLast = 623,
+}
+
+impl fmt::Display for GenericTick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", *self as i32)
+    }
+}
+
+impl From<GenericTick> for i32 {
+    fn from(tick: GenericTick) -> i32 {
+        tick as i32
+    }
+}
+
+impl TryFrom<i32> for GenericTick {
+    type Error = i32;
+    fn try_from(v: i32) -> Result<Self, Self::Error> {
+        match v {
+            100 => Ok(Self::OptionVolume),
+            101 => Ok(Self::OptionOpenInterest),
+            104 => Ok(Self::HistoricalVolatility),
+            105 => Ok(Self::AverageOptionVolume),
+            106 => Ok(Self::OptionImpliedVolatility),
+            162 => Ok(Self::IndexFuturePremium),
+            165 => Ok(Self::MiscellaneousStats),
+            221 => Ok(Self::MarkPrice),
+            225 => Ok(Self::AuctionValues),
+            233 => Ok(Self::RtVolume),
+            236 => Ok(Self::Shortable),
+            258 => Ok(Self::FundamentalRatios),
+            292 => Ok(Self::News),
+            293 => Ok(Self::TradeCount),
+            294 => Ok(Self::TradeRate),
+            295 => Ok(Self::VolumeRate),
+            318 => Ok(Self::LastRthTrade),
+            375 => Ok(Self::RtTradeVolume),
+            411 => Ok(Self::RtHistoricalVolatility),
+            456 => Ok(Self::Dividends),
+            460 => Ok(Self::BondFactorMultiplier),
+            576 => Ok(Self::EtfNavBidAsk),
+            577 => Ok(Self::EtfNavLast),
+            578 => Ok(Self::EtfNavClose),
+            586 => Ok(Self::IpoPrices),
+            588 => Ok(Self::FuturesOpenInterest),
+            595 => Ok(Self::ShortTermVolume),
+            614 => Ok(Self::EtfNavHighLow),
+            619 => Ok(Self::CreditmanSlowMarkPrice),
+            623 => Ok(Self::EtfNavFrozenLast),
+            _ => Err(v),
+        }
+    }
+}
+
+/// Joins generic tick types into the `generic_ticks` string of `req_mkt_data`.
+pub trait GenericTickList {
+    /// Comma-separated codes, e.g. `"100,233"`.
+    fn to_generic_string(&self) -> String;
+}
+
+impl GenericTickList for [GenericTick] {
+    fn to_generic_string(&self) -> String {
+        self.iter().map(|tick| tick.to_string()).collect::<Vec<_>>().join(",")
+    }
+}
+
 // ============================================================================
 // Integer-Based Enums (from CommonDefs.h, Order.h)
 // ============================================================================
 
 /// Order origin (C++: `enum Origin` in `Order.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum Origin {
     #[default]
@@ -378,7 +748,8 @@ pub enum Origin {
 }
 
 /// Auction strategy (C++: `enum AuctionStrategy` in `Order.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum AuctionStrategy {
     #[default]
@@ -389,7 +760,8 @@ pub enum AuctionStrategy {
 }
 
 /// Combo leg open/close (C++: `enum LegOpenClose` in `Contract.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum LegOpenClose {
     #[default]
@@ -400,7 +772,8 @@ pub enum LegOpenClose {
 }
 
 /// Market data type (C++: `enum MarketDataType` in `CommonDefs.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum MarketDataType {
     RealTime = 1,
@@ -410,7 +783,8 @@ pub enum MarketDataType {
 }
 
 /// FA data type (C++: `enum faDataType` in `CommonDefs.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum FaDataType {
     Groups = 1,
@@ -418,7 +792,8 @@ pub enum FaDataType {
 }
 
 /// Fund asset type (C++: `enum class FundAssetType` in `CommonDefs.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FundAssetType {
     #[default]
     None,
@@ -432,8 +807,32 @@ pub enum FundAssetType {
     Alternative,
 }
 
+impl FromStr for FundAssetType {
+    type Err = std::convert::Infallible;
+
+    /// Parse the `fundAssetType` code of contract details, `"001"` (others)
+    /// through `"008"` (alternative). Unknown codes give `None`.
+    ///
+    /// Note: C++ `Utils::getFundAssetType` numbers the same types from
+    /// `"000"` to `"007"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "001" => Self::Others,
+            "002" => Self::MoneyMarket,
+            "003" => Self::FixedIncome,
+            "004" => Self::MultiAsset,
+            "005" => Self::Equity,
+            "006" => Self::Sector,
+            "007" => Self::Guaranteed,
+            "008" => Self::Alternative,
+            _ => Self::None,
+        })
+    }
+}
+
 /// Fund distribution policy indicator (C++: `enum class FundDistributionPolicyIndicator`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FundDistributionPolicyIndicator {
     #[default]
     None,
@@ -441,8 +840,27 @@ pub enum FundDistributionPolicyIndicator {
     IncomeFund,
 }
 
+impl FromStr for FundDistributionPolicyIndicator {
+    type Err = std::convert::Infallible;
+
+    /// Parse the `fundDistributionPolicyIndicator` of contract details:
+    /// `"Y"` or `"1"` for accumulation, `"D"` or `"2"` for income. Anything
+    /// else gives `None`.
+    ///
+    /// Note: C++ `Utils::getFundDistributionPolicyIndicator` maps `"N"` to
+    /// accumulation and `"Y"` to income.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "Y" | "1" => Self::AccumulationFund,
+            "D" | "2" => Self::IncomeFund,
+            _ => Self::None,
+        })
+    }
+}
+
 /// Option exercise type (C++: `enum class OptionExerciseType` in `CommonDefs.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OptionExerciseType {
     #[default]
     None,
@@ -457,7 +875,8 @@ pub enum OptionExerciseType {
 }
 
 /// Use price management algorithm (C++: `enum UsePriceMmgtAlgo` in `Order.h`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum UsePriceMgmtAlgo {
     DontUse = 0,
@@ -467,7 +886,8 @@ pub enum UsePriceMgmtAlgo {
 }
 
 /// Trigger method for price conditions and orders (C++: `PriceCondition::Method`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum TriggerMethod {
     #[default]
@@ -481,7 +901,8 @@ pub enum TriggerMethod {
 }
 
 /// Order condition type discriminant (C++: `OrderCondition::OrderConditionType`).
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum OrderConditionType {
     Price = 1,
@@ -623,12 +1044,58 @@ impl TryFrom<i32> for OptionExerciseType {
 mod tests {
     use super::*;
 
+    #[test]
+    fn fund_asset_type_from_code() {
+        let codes = [
+            ("001", FundAssetType::Others),
+            ("002", FundAssetType::MoneyMarket),
+            ("003", FundAssetType::FixedIncome),
+            ("004", FundAssetType::MultiAsset),
+            ("005", FundAssetType::Equity),
+            ("006", FundAssetType::Sector),
+            ("007", FundAssetType::Guaranteed),
+            ("008", FundAssetType::Alternative),
+            ("", FundAssetType::None),
+            ("009", FundAssetType::None),
+        ];
+        for (code, expected) in codes {
+            assert_eq!(code.parse::<FundAssetType>(), Ok(expected), "{code:?}");
+        }
+    }
+
+    #[test]
+    fn fund_distribution_policy_indicator_from_code() {
+        use FundDistributionPolicyIndicator::*;
+        let codes = [
+            ("Y", AccumulationFund),
+            ("1", AccumulationFund),
+            ("D", IncomeFund),
+            ("2", IncomeFund),
+            ("", None),
+            ("N", None),
+        ];
+        for (code, expected) in codes {
+            assert_eq!(code.parse::<FundDistributionPolicyIndicator>(), Ok(expected), "{code:?}");
+        }
+    }
+
+    #[test]
+    fn generic_tick_string() {
+        let ticks = [GenericTick::OptionVolume, GenericTick::RtVolume];
+        assert_eq!(ticks.to_generic_string(), "100,233");
+        let none: [GenericTick; 0] = [];
+        assert_eq!(none.to_generic_string(), "");
+        assert_eq!(GenericTick::try_from(236), Ok(GenericTick::Shortable));
+        assert_eq!(GenericTick::try_from(7), Err(7));
+    }
+
     #[test]
     fn sec_type_display_round_trip() {
         let types = vec![
             (SecType::Stock, "STK"),
             (SecType::Option, "OPT"),
             (SecType::Future, "FUT"),
+            (SecType::ContinuousFuture, "CONTFUT"),
             (SecType::Forex, "CASH"),
             (SecType::Index, "IND"),
             (SecType::Crypto, "CRYPTO"),
@@ -679,4 +1146,28 @@ mod tests {
         assert_eq!(Right::Put.to_string(), "P");
         assert_eq!(Right::from_str("CALL").unwrap(), Right::Call);
     }
+
+    #[test]
+    fn bar_size_display_round_trip() {
+        assert_eq!(BarSize::OneMin.to_string(), "1 min");
+        assert_eq!(BarSize::OneSec.to_string(), "1 secs");
+        assert_eq!(BarSize::FiveMins.to_string(), "5 mins");
+        assert_eq!(BarSize::OneHour.to_string(), "1 hour");
+        assert_eq!(BarSize::from("1 day"), BarSize::OneDay);
+        assert_eq!(BarSize::from("1 min"), BarSize::OneMin);
+        assert_eq!(BarSize::from("7 mins"), BarSize::Other("7 mins".into()));
+    }
+
+    #[test]
+    fn what_to_show_display_round_trip() {
+        assert_eq!(WhatToShow::Trades.to_string(), "TRADES");
+        assert_eq!(WhatToShow::BidAsk.to_string(), "BID_ASK");
+        assert_eq!(
+            WhatToShow::OptionImpliedVolatility.to_string(),
+            "OPTION_IMPLIED_VOLATILITY"
+        );
+        assert_eq!(WhatToShow::from("MIDPOINT"), WhatToShow::Midpoint);
+        assert_eq!(WhatToShow::from("TRADE"), WhatToShow::Other("TRADE".into()));
+        assert_eq!(WhatToShow::from("TRADE").to_string(), "TRADE");
+    }
 }
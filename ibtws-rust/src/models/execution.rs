@@ -2,10 +2,17 @@
 //!
 //! Ported from: `ibtws-cpp/client/Execution.h`, `CommissionAndFeesReport.h`.
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, NaiveDateTime, TimeZone};
+#[cfg(feature = "chrono")]
+use chrono_tz::Tz;
 use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use super::contract::Contract;
 use super::enums::OptionExerciseType;
+use crate::errors::{IBApiError, Result};
 
 // ============================================================================
 // Execution
@@ -14,8 +21,9 @@ use super::enums::OptionExerciseType;
 /// Details of a trade execution (fill).
 ///
 /// C++ source: `struct Execution` in `Execution.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Execution {
     pub exec_id: String,
     pub time: String,
@@ -23,7 +31,7 @@ pub struct Execution {
     pub exchange: String,
     /// "BUY", "SELL", or "SSHORT".
     pub side: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub shares: Option<Decimal>,
     pub price: f64,
     pub perm_id: i64,
@@ -31,7 +39,7 @@ pub struct Execution {
     pub order_id: i64,
     /// 0 = normal, 1 = liquidation.
     pub liquidation: i32,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub cum_qty: Option<Decimal>,
     pub avg_price: f64,
     pub order_ref: String,
@@ -44,6 +52,41 @@ pub struct Execution {
     pub opt_exercise_or_lapse_type: OptionExerciseType,
 }
 
+#[cfg(feature = "chrono")]
+impl Execution {
+    /// Parse [`time`](Self::time) into a timezone-aware timestamp.
+    ///
+    /// Understands `"20240225 14:30:00 America/New_York"`, the older
+    /// `"20240225  14:30:00"` without a zone (interpreted in `account_tz`),
+    /// and the UTC form `"20240225-14:30:00"`. Returns `None` if the string
+    /// or zone name is malformed, or the local time does not exist in the
+    /// zone (DST gap). Ambiguous local times resolve to the earlier instant.
+    ///
+    /// There is no C++ equivalent -- `Execution::time` is a plain string.
+    pub fn parsed_time(&self, account_tz: Tz) -> Option<DateTime<Tz>> {
+        let mut parts = self.time.split_whitespace();
+        let date = parts.next()?;
+        if let Some((date, time)) = date.split_once('-') {
+            let naive = parse_naive(date, time)?;
+            return parts.next().is_none().then(|| Tz::UTC.from_utc_datetime(&naive));
+        }
+        let naive = parse_naive(date, parts.next()?)?;
+        let tz = match parts.next() {
+            Some(name) => name.parse().ok()?,
+            None => account_tz,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        tz.from_local_datetime(&naive).earliest()
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn parse_naive(date: &str, time: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(&format!("{date} {time}"), "%Y%m%d %H:%M:%S").ok()
+}
+
 // ============================================================================
 // ExecutionFilter
 // ============================================================================
@@ -52,8 +95,9 @@ pub struct Execution {
 ///
 /// C++ source: `struct ExecutionFilter` in `Execution.h`.
 /// Note: C++ uses `m_` prefix for members; Rust drops it.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ExecutionFilter {
     pub client_id: i64,
     pub acct_code: String,
@@ -63,12 +107,156 @@ pub struct ExecutionFilter {
     pub exchange: String,
     pub side: String,
     /// C++ default: `UNSET_INTEGER`.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub last_n_days: Option<i32>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
     pub specific_dates: Vec<i64>,
 }
 
+// ============================================================================
+// ExecutionFilterBuilder
+// ============================================================================
+
+/// Fluent builder for `ExecutionFilter`.
+///
+/// `build()` rejects combinations TWS would refuse. There is no C++
+/// equivalent.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let filter = ExecutionFilterBuilder::new()
+///     .acct_code("DU123")
+///     .symbol("AAPL")
+///     .time("20260105 09:30:00 US/Eastern")
+///     .build()?;
+/// let fills = client.executions(&filter).await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionFilterBuilder {
+    filter: ExecutionFilter,
+}
+
+impl ExecutionFilterBuilder {
+    /// Empty builder; an empty filter matches every execution.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn client_id(mut self, client_id: i64) -> Self {
+        self.filter.client_id = client_id;
+        self
+    }
+
+    pub fn acct_code(mut self, acct_code: &str) -> Self {
+        self.filter.acct_code = acct_code.to_string();
+        self
+    }
+
+    /// Only executions after `time`: `"yyyymmdd hh:mm:ss"`, optionally
+    /// followed by a time zone name, or `"yyyymmdd-hh:mm:ss"` in UTC.
+    pub fn time(mut self, time: &str) -> Self {
+        self.filter.time = time.to_string();
+        self
+    }
+
+    pub fn symbol(mut self, symbol: &str) -> Self {
+        self.filter.symbol = symbol.to_string();
+        self
+    }
+
+    pub fn sec_type(mut self, sec_type: &str) -> Self {
+        self.filter.sec_type = sec_type.to_string();
+        self
+    }
+
+    pub fn exchange(mut self, exchange: &str) -> Self {
+        self.filter.exchange = exchange.to_string();
+        self
+    }
+
+    /// `"BUY"` or `"SELL"`.
+    pub fn side(mut self, side: &str) -> Self {
+        self.filter.side = side.to_string();
+        self
+    }
+
+    /// Executions of the last `days` days.
+    pub fn last_n_days(mut self, days: i32) -> Self {
+        self.filter.last_n_days = Some(days);
+        self
+    }
+
+    /// Executions on these dates, each as `yyyymmdd`.
+    pub fn specific_dates(mut self, dates: &[i64]) -> Self {
+        self.filter.specific_dates = dates.to_vec();
+        self
+    }
+
+    /// Validate and return the filter.
+    ///
+    /// Fails if both `last_n_days` and `specific_dates` are set, if
+    /// `last_n_days` is not positive, if a specific date is not a
+    /// `yyyymmdd` date, or if `time` is set but malformed.
+    pub fn build(self) -> Result<ExecutionFilter> {
+        let f = self.filter;
+        let invalid =
+            |msg: String| Err(IBApiError::Encoding(format!("invalid execution filter: {msg}")));
+
+        if f.last_n_days.is_some() && !f.specific_dates.is_empty() {
+            return invalid("last_n_days and specific_dates are mutually exclusive".into());
+        }
+        if let Some(days) = f.last_n_days.filter(|d| *d <= 0) {
+            return invalid(format!("last_n_days must be positive, got {days}"));
+        }
+        if let Some(date) = f.specific_dates.iter().find(|d| !is_yyyymmdd(&d.to_string())) {
+            return invalid(format!("specific date {date} is not yyyymmdd"));
+        }
+        if !f.time.is_empty() && !is_filter_time(&f.time) {
+            return invalid(format!("time {:?} is not \"yyyymmdd hh:mm:ss\"", f.time));
+        }
+        Ok(f)
+    }
+}
+
+/// `yyyymmdd hh:mm:ss` with an optional zone name, or `yyyymmdd-hh:mm:ss`.
+fn is_filter_time(time: &str) -> bool {
+    let (date, clock) = match time.get(8..9) {
+        Some("-") => (&time[..8], &time[9..]),
+        _ => {
+            let mut parts = time.split_whitespace();
+            let (Some(date), Some(clock)) = (parts.next(), parts.next()) else {
+                return false;
+            };
+            // At most a zone name may follow.
+            if parts.nth(1).is_some() {
+                return false;
+            }
+            (date, clock)
+        }
+    };
+    let clock: Vec<&str> = clock.split(':').collect();
+    let [h, m, s] = clock[..] else {
+        return false;
+    };
+    is_yyyymmdd(date) && number(h, 2, 0..=23) && number(m, 2, 0..=59) && number(s, 2, 0..=59)
+}
+
+fn is_yyyymmdd(date: &str) -> bool {
+    date.len() == 8
+        && date.is_ascii()
+        && number(&date[..4], 4, 1900..=9999)
+        && number(&date[4..6], 2, 1..=12)
+        && number(&date[6..], 2, 1..=31)
+}
+
+/// `s` is exactly `len` digits with a value within `range`.
+fn number(s: &str, len: usize, range: std::ops::RangeInclusive<u32>) -> bool {
+    s.len() == len
+        && s.bytes().all(|b| b.is_ascii_digit())
+        && s.parse().is_ok_and(|v| range.contains(&v))
+}
+
 // ============================================================================
 // CommissionAndFeesReport
 // ============================================================================
@@ -76,8 +264,9 @@ pub struct ExecutionFilter {
 /// Commission and fees for an execution.
 ///
 /// C++ source: `struct CommissionAndFeesReport` in `CommissionAndFeesReport.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct CommissionAndFeesReport {
     pub exec_id: String,
     pub commission_and_fees: f64,
@@ -87,3 +276,115 @@ pub struct CommissionAndFeesReport {
     /// YYYYMMDD format.
     pub yield_redemption_date: i32,
 }
+
+// ============================================================================
+// ExecutionWithCommission
+// ============================================================================
+
+/// An execution joined with its commission report, as returned by
+/// `IBClient::executions`.
+///
+/// There is no C++ equivalent -- `execDetails` and
+/// `commissionAndFeesReport` arrive as separate callbacks linked by
+/// `exec_id`.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ExecutionWithCommission {
+    pub contract: Contract,
+    pub execution: Execution,
+    /// `None` if TWS did not send the report in time.
+    pub commission: Option<CommissionAndFeesReport>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "chrono")]
+    use chrono::{NaiveDate, Utc};
+
+    #[test]
+    fn execution_filter_builder_validates_fields() {
+        let filter = ExecutionFilterBuilder::new()
+            .client_id(3)
+            .acct_code("DU123")
+            .symbol("AAPL")
+            .side("BUY")
+            .time("20260105 09:30:00 US/Eastern")
+            .last_n_days(2)
+            .build()
+            .unwrap();
+        assert_eq!(filter.client_id, 3);
+        assert_eq!(filter.last_n_days, Some(2));
+        assert!(filter.specific_dates.is_empty());
+
+        let err = ExecutionFilterBuilder::new()
+            .last_n_days(2)
+            .specific_dates(&[20260105])
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("mutually exclusive"), "{err}");
+
+        for time in ["20260105 09:30:00", "20260105-14:30:00", "20260105 09:30:00 Etc/GMT-5"] {
+            assert!(ExecutionFilterBuilder::new().time(time).build().is_ok(), "{time:?}");
+        }
+        for time in [
+            "20260105",
+            "2026-01-05 09:30:00",
+            "20260105 9:30:00",
+            "20261305 09:30:00",
+            "20260105 24:00:00",
+            "20260105 09:30:00 US/Eastern extra",
+        ] {
+            assert!(ExecutionFilterBuilder::new().time(time).build().is_err(), "{time:?}");
+        }
+        assert!(ExecutionFilterBuilder::new().last_n_days(0).build().is_err());
+        assert!(ExecutionFilterBuilder::new().specific_dates(&[2026015]).build().is_err());
+        assert!(ExecutionFilterBuilder::new().specific_dates(&[20260105]).build().is_ok());
+    }
+
+    #[cfg(feature = "chrono")]
+    fn exec_at(time: &str) -> Execution {
+        Execution { time: time.to_string(), ..Default::default() }
+    }
+
+    #[cfg(feature = "chrono")]
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> DateTime<Utc> {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap().and_hms_opt(h, min, s).unwrap().and_utc()
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parsed_time_with_zone_suffix() {
+        let exec = exec_at("20240225 14:30:00 America/New_York");
+        let t = exec.parsed_time(Tz::Asia__Tokyo).unwrap();
+        assert_eq!(t.timezone(), Tz::America__New_York);
+        assert_eq!(t.with_timezone(&Utc), utc(2024, 2, 25, 19, 30, 0));
+        // Raw string is untouched.
+        assert_eq!(exec.time, "20240225 14:30:00 America/New_York");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parsed_time_without_zone_uses_account_tz() {
+        let t = exec_at("20240701  09:15:30").parsed_time(Tz::Europe__London).unwrap();
+        assert_eq!(t.timezone(), Tz::Europe__London);
+        assert_eq!(t.with_timezone(&Utc), utc(2024, 7, 1, 8, 15, 30));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parsed_time_utc_form() {
+        let t = exec_at("20240225-14:30:00").parsed_time(Tz::America__Chicago).unwrap();
+        assert_eq!(t.timezone(), Tz::UTC);
+        assert_eq!(t.with_timezone(&Utc), utc(2024, 2, 25, 14, 30, 0));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn parsed_time_rejects_malformed() {
+        for raw in ["", "20240225", "2024-02-25 14:30:00", "20240225 14:30:00 Mars/Base"] {
+            assert_eq!(exec_at(raw).parsed_time(Tz::UTC), None, "{raw:?}");
+        }
+    }
+}
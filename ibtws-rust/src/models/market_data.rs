@@ -1,10 +1,14 @@
-//! Market data tick attributes and depth descriptions.
+//! Market data tick attributes, depth descriptions, and snapshots.
 //!
 //! Ported from: `TickAttrib.h`, `TickAttribBidAsk.h`, `TickAttribLast.h`,
 //! `DepthMktDataDescription.h`.
 
+use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::protocol::TickType;
+
 // ============================================================================
 // TickAttrib
 // ============================================================================
@@ -12,8 +16,9 @@ use serde::{Deserialize, Serialize};
 /// Tick attributes for price ticks.
 ///
 /// C++ source: `struct TickAttrib` in `TickAttrib.h`.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct TickAttrib {
     pub can_auto_execute: bool,
     pub past_limit: bool,
@@ -27,8 +32,9 @@ pub struct TickAttrib {
 /// Tick attributes for bid/ask ticks.
 ///
 /// C++ source: `struct TickAttribBidAsk` in `TickAttribBidAsk.h`.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct TickAttribBidAsk {
     pub bid_past_low: bool,
     pub ask_past_high: bool,
@@ -41,8 +47,9 @@ pub struct TickAttribBidAsk {
 /// Tick attributes for last-trade ticks.
 ///
 /// C++ source: `struct TickAttribLast` in `TickAttribLast.h`.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct TickAttribLast {
     pub past_limit: bool,
     pub unreported: bool,
@@ -55,13 +62,164 @@ pub struct TickAttribLast {
 /// Description of available market depth data for an exchange.
 ///
 /// C++ source: `struct DepthMktDataDescription` in `DepthMktDataDescription.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct DepthMktDataDescription {
     pub exchange: String,
     pub sec_type: String,
     pub listing_exch: String,
     pub service_data_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub agg_group: Option<i32>,
 }
+
+// ============================================================================
+// SnapshotPermissions
+// ============================================================================
+
+/// The `snapshot_permissions` bit set of a `TickReqParams` event.
+///
+/// TWS reports which kinds of snapshot the account may request for the
+/// instrument. The C++ API passes the raw `int` through; the bits below
+/// are the ones TWS sets in practice. Unknown bits are kept in
+/// [`bits`](Self::bits).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SnapshotPermissions(pub i32);
+
+impl SnapshotPermissions {
+    /// Snapshot market data (`req_mkt_data` with `snapshot = true`).
+    pub const SNAPSHOT: i32 = 1;
+    /// Regulatory snapshots (`req_mkt_data` with `regulatory_snapshot =
+    /// true`), charged per request.
+    pub const REGULATORY_SNAPSHOT: i32 = 2;
+
+    pub fn bits(self) -> i32 {
+        self.0
+    }
+
+    /// Whether every bit of `mask` is set.
+    pub fn contains(self, mask: i32) -> bool {
+        self.0 & mask == mask
+    }
+
+    pub fn can_snapshot(self) -> bool {
+        self.contains(Self::SNAPSHOT)
+    }
+
+    pub fn can_regulatory_snapshot(self) -> bool {
+        self.contains(Self::REGULATORY_SNAPSHOT)
+    }
+}
+
+// ============================================================================
+// MarketSnapshot
+// ============================================================================
+
+/// Ticks of one snapshot market data request, folded into a single quote.
+///
+/// Returned by `IBClient::req_mkt_data_snapshot`. Delayed tick types
+/// (`DelayedBid`, ...) fill the same fields as their real-time counterparts.
+/// Fields the server did not send stay `None`. There is no C++ equivalent.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct MarketSnapshot {
+    pub bid: Option<f64>,
+    pub ask: Option<f64>,
+    pub last: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub open: Option<f64>,
+    pub close: Option<f64>,
+    pub bid_size: Option<Decimal>,
+    pub ask_size: Option<Decimal>,
+    pub last_size: Option<Decimal>,
+    pub volume: Option<Decimal>,
+    /// Unix time of the last trade, from the `LastTimestamp` string tick.
+    pub last_timestamp: Option<i64>,
+    pub halted: Option<bool>,
+}
+
+impl MarketSnapshot {
+    /// Apply a TICK_PRICE, including the size that accompanies bid, ask
+    /// and last prices. Prices of -1 mean "not available" and are ignored.
+    pub fn apply_price(&mut self, tick_type: TickType, price: f64, size: Decimal) {
+        use TickType::*;
+        if let Some(size_type) = tick_type.size_tick() {
+            if size >= Decimal::ZERO {
+                self.apply_size(size_type, size);
+            }
+        }
+        if price < 0.0 {
+            return;
+        }
+        let slot = match tick_type {
+            Bid | DelayedBid => &mut self.bid,
+            Ask | DelayedAsk => &mut self.ask,
+            Last | DelayedLast => &mut self.last,
+            High | DelayedHigh => &mut self.high,
+            Low | DelayedLow => &mut self.low,
+            Open | DelayedOpen => &mut self.open,
+            Close | DelayedClose => &mut self.close,
+            _ => return,
+        };
+        *slot = Some(price);
+    }
+
+    /// Apply a TICK_SIZE.
+    pub fn apply_size(&mut self, tick_type: TickType, size: Decimal) {
+        use TickType::*;
+        let slot = match tick_type {
+            BidSize | DelayedBidSize => &mut self.bid_size,
+            AskSize | DelayedAskSize => &mut self.ask_size,
+            LastSize | DelayedLastSize => &mut self.last_size,
+            Volume | DelayedVolume => &mut self.volume,
+            _ => return,
+        };
+        *slot = Some(size);
+    }
+
+    /// Apply a TICK_STRING.
+    pub fn apply_string(&mut self, tick_type: TickType, value: &str) {
+        if matches!(tick_type, TickType::LastTimestamp | TickType::DelayedLastTimestamp) {
+            self.last_timestamp = value.parse().ok().or(self.last_timestamp);
+        }
+    }
+
+    /// Apply a TICK_GENERIC.
+    pub fn apply_generic(&mut self, tick_type: TickType, value: f64) {
+        if matches!(tick_type, TickType::Halted | TickType::DelayedHalted) && value >= 0.0 {
+            self.halted = Some(value > 0.0);
+        }
+    }
+}
+
+// ============================================================================
+// OptionComputation
+// ============================================================================
+
+/// Result of an option calculation or model computation tick.
+///
+/// Returned by `IBClient::implied_volatility` and `IBClient::option_price`.
+/// The wire sentinels for "not computed" (-1, -2 and `DBL_MAX`) are
+/// decoded as `None`. There is no C++ equivalent -- `EWrapper::tickOptionComputation`
+/// passes the values as separate arguments.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct OptionComputation {
+    /// `CustOptionComputation` for calculation requests.
+    pub tick_type: TickType,
+    /// Whether volatility is price-based (1) rather than return-based (0).
+    pub price_based: bool,
+    pub implied_vol: Option<f64>,
+    pub delta: Option<f64>,
+    pub opt_price: Option<f64>,
+    pub pv_dividend: Option<f64>,
+    pub gamma: Option<f64>,
+    pub vega: Option<f64>,
+    pub theta: Option<f64>,
+    pub und_price: Option<f64>,
+}
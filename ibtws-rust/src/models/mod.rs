@@ -2,6 +2,7 @@
 //!
 //! Ported from the C++ TWS API header files in `ibtws-cpp/client/`.
 
+pub mod account;
 pub mod bar;
 pub mod common;
 pub mod contract;
@@ -10,3 +11,4 @@ pub mod execution;
 pub mod market_data;
 pub mod order;
 pub mod scanner;
+pub mod unset;
@@ -6,9 +6,11 @@
 //! `PercentChangeCondition.h`, `ExecutionCondition.h`).
 
 use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use super::common::{SoftDollarTier, TagValue};
+use super::contract::Contract;
 use super::enums::*;
 
 // ============================================================================
@@ -20,8 +22,9 @@ use super::enums::*;
 /// C++ uses inheritance: `OrderCondition` -> `OperatorCondition` ->
 /// `ContractCondition` -> `PriceCondition`. Rust flattens this into a single
 /// enum with named fields per variant.
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum OrderCondition {
     Price {
         is_conjunction_connection: bool,
@@ -61,6 +64,20 @@ pub enum OrderCondition {
         exchange: String,
         change_percent: Option<f64>,
     },
+    /// A condition type this library does not know, kept so that a
+    /// protobuf open order carrying it still decodes. There is no C++
+    /// equivalent.
+    ///
+    /// `fields` are the text protocol fields after the conjunction, written
+    /// verbatim when encoding; placing an order with empty `fields` fails.
+    /// The protobuf decoder leaves them empty. The text protocol does not
+    /// delimit condition payloads, so the text decoder fails an order with
+    /// an unknown condition rather than misread the fields after it.
+    Unknown {
+        is_conjunction_connection: bool,
+        raw_type: i32,
+        fields: Vec<String>,
+    },
 }
 
 // ============================================================================
@@ -70,11 +87,12 @@ pub enum OrderCondition {
 /// Per-leg price for combo orders.
 ///
 /// C++ source: `struct OrderComboLeg` in `Order.h`.
-#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct OrderComboLeg {
     /// C++ default: `UNSET_DOUBLE`.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub price: Option<f64>,
 }
 
@@ -89,8 +107,9 @@ pub struct OrderComboLeg {
 /// `UNSET_INTEGER`) are represented as `Option<T>`.
 ///
 /// C++ source: `struct Order` in `Order.h`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct Order {
     // ----- Order Identification -----
     pub order_id: i64,
@@ -98,19 +117,19 @@ pub struct Order {
     pub perm_id: i64,
 
     // ----- Main Order Fields -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub action: Option<Action>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub total_quantity: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub order_type: Option<OrderType>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub lmt_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub aux_price: Option<f64>,
 
     // ----- Extended Order Fields -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub tif: Option<TimeInForce>,
     pub active_start_time: String,
     pub active_stop_time: String,
@@ -129,14 +148,14 @@ pub struct Order {
     pub good_till_date: String,
     pub rule_80a: String,
     pub all_or_none: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub min_qty: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub percent_offset: Option<f64>,
     pub override_percentage_constraints: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub trail_stop_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub trailing_percent: Option<f64>,
 
     // ----- Financial Advisors -----
@@ -158,28 +177,28 @@ pub struct Order {
 
     // ----- BOX Exchange -----
     pub auction_strategy: AuctionStrategy,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub starting_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub stock_ref_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub delta: Option<f64>,
 
     // ----- Pegged to Stock / VOL -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub stock_range_lower: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub stock_range_upper: Option<f64>,
     pub randomize_size: bool,
     pub randomize_price: bool,
 
     // ----- Volatility Orders -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub volatility: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub volatility_type: Option<i32>,
     pub delta_neutral_order_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub delta_neutral_aux_price: Option<f64>,
     pub delta_neutral_con_id: i64,
     pub delta_neutral_settling_firm: String,
@@ -190,32 +209,32 @@ pub struct Order {
     pub delta_neutral_short_sale_slot: i32,
     pub delta_neutral_designated_location: String,
     pub continuous_update: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub reference_price_type: Option<i32>,
 
     // ----- Combo Orders -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub basis_points: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub basis_points_type: Option<i32>,
 
     // ----- Scale Orders -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_init_level_size: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_subs_level_size: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_price_increment: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_price_adjust_value: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_price_adjust_interval: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_profit_offset: Option<f64>,
     pub scale_auto_reset: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_init_position: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub scale_init_fill_qty: Option<i32>,
     pub scale_random_percent: bool,
     pub scale_table: String,
@@ -232,9 +251,9 @@ pub struct Order {
 
     // ----- Algo Orders -----
     pub algo_strategy: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub algo_params: Option<Vec<TagValue>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub smart_combo_routing_params: Option<Vec<TagValue>>,
     pub algo_id: String,
 
@@ -247,36 +266,36 @@ pub struct Order {
     pub model_code: String,
 
     // ----- Order Combo Legs -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub order_combo_legs: Option<Vec<OrderComboLeg>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub order_misc_options: Option<Vec<TagValue>>,
 
     // ----- Pegged to Benchmark -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub reference_contract_id: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub pegged_change_amount: Option<f64>,
     pub is_pegged_change_amount_decrease: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub reference_change_amount: Option<f64>,
     pub reference_exchange_id: String,
     pub adjusted_order_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub trigger_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub adjusted_stop_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub adjusted_stop_limit_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub adjusted_trailing_amount: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub adjustable_trailing_unit: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub lmt_price_offset: Option<f64>,
 
     // ----- Conditions -----
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
     pub conditions: Vec<OrderCondition>,
     pub conditions_cancel_order: bool,
     pub conditions_ignore_rth: bool,
@@ -288,7 +307,7 @@ pub struct Order {
     pub soft_dollar_tier: SoftDollarTier,
 
     // ----- Cash Quantity -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub cash_qty: Option<f64>,
 
     // ----- MiFID II -----
@@ -302,38 +321,38 @@ pub struct Order {
     pub is_oms_container: bool,
     pub discretionary_up_to_limit_price: bool,
     pub auto_cancel_date: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub filled_quantity: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub ref_futures_con_id: Option<i32>,
     pub auto_cancel_parent: bool,
     pub shareholder: String,
     pub imbalance_only: bool,
     pub route_marketable_to_bbo: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub parent_perm_id: Option<i64>,
     pub use_price_mgmt_algo: UsePriceMgmtAlgo,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub duration: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub post_to_ats: Option<i32>,
     pub advanced_error_override: String,
     pub manual_order_time: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub min_trade_qty: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub min_compete_size: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub compete_against_best_offset: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub mid_offset_at_whole: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub mid_offset_at_half: Option<f64>,
     pub customer_account: String,
     pub professional_customer: bool,
     pub bond_accrued_interest: String,
     pub include_overnight: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub manual_order_indicator: Option<i32>,
     pub submitter: String,
 }
@@ -510,6 +529,292 @@ impl Default for Order {
     }
 }
 
+// ============================================================================
+// OrderBuilder
+// ============================================================================
+
+/// Fluent builder for the common `Order` types.
+///
+/// Each constructor fills in `action`, `order_type`, `total_quantity` and
+/// the prices that order type needs; everything else keeps its C++ default,
+/// including `transmit = true`. There is no C++ equivalent.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let order = OrderBuilder::limit(Action::Buy, 100, 150.25)
+///     .tif(TimeInForce::GoodTilCancelled)
+///     .outside_rth(true)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrderBuilder {
+    order: Order,
+}
+
+impl OrderBuilder {
+    fn new(action: Action, quantity: Decimal, order_type: OrderType) -> Self {
+        Self {
+            order: Order {
+                action: Some(action),
+                total_quantity: Some(quantity),
+                order_type: Some(order_type),
+                ..Order::default()
+            },
+        }
+    }
+
+    /// Market order (`MKT`).
+    pub fn market(action: Action, quantity: impl Into<Decimal>) -> Self {
+        Self::new(action, quantity.into(), OrderType::Market)
+    }
+
+    /// Limit order (`LMT`) at `limit_price`.
+    pub fn limit(action: Action, quantity: impl Into<Decimal>, limit_price: f64) -> Self {
+        let mut b = Self::new(action, quantity.into(), OrderType::Limit);
+        b.order.lmt_price = Some(limit_price);
+        b
+    }
+
+    /// Stop order (`STP`) triggered at `stop_price`.
+    pub fn stop(action: Action, quantity: impl Into<Decimal>, stop_price: f64) -> Self {
+        let mut b = Self::new(action, quantity.into(), OrderType::Stop);
+        b.order.aux_price = Some(stop_price);
+        b
+    }
+
+    /// Stop-limit order (`STP LMT`): becomes a limit order at `limit_price`
+    /// once `stop_price` trades.
+    pub fn stop_limit(
+        action: Action,
+        quantity: impl Into<Decimal>,
+        limit_price: f64,
+        stop_price: f64,
+    ) -> Self {
+        let mut b = Self::new(action, quantity.into(), OrderType::StopLimit);
+        b.order.lmt_price = Some(limit_price);
+        b.order.aux_price = Some(stop_price);
+        b
+    }
+
+    /// Trailing stop order (`TRAIL`) trailing the market by
+    /// `trailing_percent` percent. Use [`trail_stop_price`](Self::trail_stop_price)
+    /// to set the initial stop.
+    pub fn trailing_stop(
+        action: Action,
+        quantity: impl Into<Decimal>,
+        trailing_percent: f64,
+    ) -> Self {
+        let mut b = Self::new(action, quantity.into(), OrderType::TrailingStop);
+        b.order.trailing_percent = Some(trailing_percent);
+        b
+    }
+
+    /// Initial stop price of a trailing stop.
+    pub fn trail_stop_price(mut self, price: f64) -> Self {
+        self.order.trail_stop_price = Some(price);
+        self
+    }
+
+    pub fn account(mut self, account: &str) -> Self {
+        self.order.account = account.to_string();
+        self
+    }
+
+    pub fn tif(mut self, tif: TimeInForce) -> Self {
+        self.order.tif = Some(tif);
+        self
+    }
+
+    /// Allow the order to trigger or fill outside regular trading hours.
+    pub fn outside_rth(mut self, outside_rth: bool) -> Self {
+        self.order.outside_rth = outside_rth;
+        self
+    }
+
+    pub fn order_ref(mut self, order_ref: &str) -> Self {
+        self.order.order_ref = order_ref.to_string();
+        self
+    }
+
+    /// Attach to a parent order (bracket / OCA child).
+    pub fn parent_id(mut self, parent_id: i64) -> Self {
+        self.order.parent_id = parent_id;
+        self
+    }
+
+    /// Set `transmit = false` to stage the order in TWS without sending it.
+    pub fn transmit(mut self, transmit: bool) -> Self {
+        self.order.transmit = transmit;
+        self
+    }
+
+    /// Return the finished order.
+    pub fn build(self) -> Order {
+        self.order
+    }
+}
+
+// ============================================================================
+// Order validation
+// ============================================================================
+
+/// A field combination TWS would reject, found by [`Order::validate`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum OrderValidationError {
+    #[error("action (BUY/SELL) is required")]
+    MissingAction,
+    #[error("order_type is required")]
+    MissingOrderType,
+    #[error("total_quantity or cash_qty is required")]
+    MissingQuantity,
+    #[error("total_quantity must be positive, got {0}")]
+    NonPositiveQuantity(Decimal),
+    #[error("{0} orders need lmt_price")]
+    MissingLimitPrice(OrderType),
+    #[error("{0} orders need the stop/trigger price in aux_price")]
+    MissingStopPrice(OrderType),
+    #[error("{0} orders need a trailing amount (aux_price) or trailing_percent")]
+    MissingTrailingAmount(OrderType),
+    #[error("{0} orders take either a trailing amount (aux_price) or trailing_percent, not both")]
+    ConflictingTrailingAmount(OrderType),
+    #[error("trailing_percent must be in (0, 100], got {0}")]
+    InvalidTrailingPercent(f64),
+    #[error("{field} must be a finite number, got {value}")]
+    NonFinitePrice { field: &'static str, value: f64 },
+    #[error("VOL orders need volatility and volatility_type")]
+    MissingVolatility,
+    #[error("PEG BENCH orders need reference_contract_id")]
+    MissingReferenceContract,
+    #[error("GTD orders need good_till_date")]
+    MissingGoodTillDate,
+    #[error("min_qty {min_qty} exceeds total_quantity {quantity}")]
+    MinQtyAboveQuantity { min_qty: i32, quantity: Decimal },
+    #[error("combo (BAG) contracts need combo_legs")]
+    ComboWithoutLegs,
+    #[error("order_combo_legs has {prices} leg prices for {legs} combo legs")]
+    ComboLegPriceCount { prices: usize, legs: usize },
+}
+
+impl Order {
+    /// Check field combinations TWS would reject, without a round trip.
+    ///
+    /// Returns every problem found, not just the first. The checks cover
+    /// the common order types (prices each type needs, trailing amounts),
+    /// quantities, `GTD` expiry, volatility and pegged-to-benchmark fields,
+    /// and combo legs; passing them does not guarantee TWS accepts the
+    /// order. There is no C++ equivalent. `IBClient::set_validate_orders`
+    /// makes `place_order` run this before sending.
+    pub fn validate(&self, contract: &Contract) -> Result<(), Vec<OrderValidationError>> {
+        use OrderValidationError as E;
+        let mut errors = Vec::new();
+
+        if self.action.is_none() {
+            errors.push(E::MissingAction);
+        }
+        match self.total_quantity {
+            Some(q) if q <= Decimal::ZERO => errors.push(E::NonPositiveQuantity(q)),
+            None if self.cash_qty.is_none() => errors.push(E::MissingQuantity),
+            _ => {}
+        }
+        if let (Some(min_qty), Some(quantity)) = (self.min_qty, self.total_quantity) {
+            if Decimal::from(min_qty) > quantity {
+                errors.push(E::MinQtyAboveQuantity { min_qty, quantity });
+            }
+        }
+
+        let prices = [
+            ("lmt_price", self.lmt_price),
+            ("aux_price", self.aux_price),
+            ("trail_stop_price", self.trail_stop_price),
+        ];
+        for (field, value) in prices {
+            if let Some(value) = value.filter(|v| !v.is_finite()) {
+                errors.push(E::NonFinitePrice { field, value });
+            }
+        }
+
+        match &self.order_type {
+            None => errors.push(E::MissingOrderType),
+            Some(order_type) => self.validate_type_fields(order_type, &mut errors),
+        }
+        if let Some(p) = self.trailing_percent.filter(|p| !(*p > 0.0 && *p <= 100.0)) {
+            errors.push(E::InvalidTrailingPercent(p));
+        }
+        if self.tif == Some(TimeInForce::GoodTilDate) && self.good_till_date.is_empty() {
+            errors.push(E::MissingGoodTillDate);
+        }
+
+        if contract.sec_type == Some(SecType::Combo) {
+            let legs = contract.combo_legs.as_deref().unwrap_or_default();
+            if legs.is_empty() {
+                errors.push(E::ComboWithoutLegs);
+            }
+            if let Some(prices) = self.order_combo_legs.as_deref().filter(|p| !p.is_empty()) {
+                if prices.len() != legs.len() {
+                    errors.push(E::ComboLegPriceCount { prices: prices.len(), legs: legs.len() });
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The prices and parameters `order_type` needs.
+    fn validate_type_fields(&self, order_type: &OrderType, errors: &mut Vec<OrderValidationError>) {
+        use OrderValidationError as E;
+        let needs_limit = matches!(
+            order_type,
+            OrderType::Limit
+                | OrderType::StopLimit
+                | OrderType::LimitOnClose
+                | OrderType::LimitOnOpen
+                | OrderType::LimitIfTouched
+        );
+        if needs_limit && self.lmt_price.is_none() {
+            errors.push(E::MissingLimitPrice(order_type.clone()));
+        }
+        let needs_stop = matches!(
+            order_type,
+            OrderType::Stop
+                | OrderType::StopLimit
+                | OrderType::MarketIfTouched
+                | OrderType::LimitIfTouched
+        );
+        if needs_stop && self.aux_price.is_none() {
+            errors.push(E::MissingStopPrice(order_type.clone()));
+        }
+        match order_type {
+            OrderType::TrailingStop | OrderType::TrailingStopLimit => {
+                match (self.aux_price, self.trailing_percent) {
+                    (None, None) => errors.push(E::MissingTrailingAmount(order_type.clone())),
+                    (Some(_), Some(_)) => {
+                        errors.push(E::ConflictingTrailingAmount(order_type.clone()))
+                    }
+                    _ => {}
+                }
+                let has_limit = self.lmt_price.is_some() || self.lmt_price_offset.is_some();
+                if *order_type == OrderType::TrailingStopLimit && !has_limit {
+                    errors.push(E::MissingLimitPrice(order_type.clone()));
+                }
+            }
+            OrderType::Volatility
+                if self.volatility.is_none() || self.volatility_type.is_none() =>
+            {
+                errors.push(E::MissingVolatility);
+            }
+            OrderType::PeggedToBenchmark if self.reference_contract_id.is_none() => {
+                errors.push(E::MissingReferenceContract);
+            }
+            _ => {}
+        }
+    }
+}
+
 // ============================================================================
 // OrderAllocation
 // ============================================================================
@@ -517,19 +822,20 @@ impl Default for Order {
 /// Per-account allocation info within an order (FA orders).
 ///
 /// C++ source: `struct OrderAllocation` in `OrderState.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct OrderAllocation {
     pub account: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub position: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub position_desired: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub position_after: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub desired_alloc_qty: Option<Decimal>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub allowed_alloc_qty: Option<Decimal>,
     pub is_monetary: bool,
 }
@@ -541,8 +847,9 @@ pub struct OrderAllocation {
 /// Order state including margin impact and commission info.
 ///
 /// C++ source: `struct OrderState` in `OrderState.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct OrderState {
     pub status: String,
 
@@ -562,39 +869,39 @@ pub struct OrderState {
     pub equity_with_loan_after: String,
 
     // ----- Commission -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub commission_and_fees: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub min_commission_and_fees: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub max_commission_and_fees: Option<f64>,
     pub commission_and_fees_currency: String,
     pub margin_currency: String,
 
     // ----- Outside RTH margin -----
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub init_margin_before_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub maint_margin_before_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub equity_with_loan_before_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub init_margin_change_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub maint_margin_change_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub equity_with_loan_change_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub init_margin_after_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub maint_margin_after_outside_rth: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub equity_with_loan_after_outside_rth: Option<f64>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub suggested_size: Option<Decimal>,
     pub reject_reason: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub order_allocations: Option<Vec<OrderAllocation>>,
     pub warning_text: String,
     pub completed_time: String,
@@ -608,12 +915,13 @@ pub struct OrderState {
 /// Parameters for cancelling an order.
 ///
 /// C++ source: `struct OrderCancel` in `OrderCancel.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct OrderCancel {
     pub manual_order_cancel_time: String,
     pub ext_operator: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub manual_order_indicator: Option<i32>,
 }
 
@@ -636,6 +944,7 @@ mod tests {
         assert_eq!(order.order_id, 0);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn order_condition_serde_round_trip() {
         let cond = OrderCondition::Price {
@@ -651,6 +960,155 @@ mod tests {
         assert_eq!(cond, deserialized);
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn order_serde_round_trip() {
+        let order = OrderBuilder::limit(Action::Buy, Decimal::new(15, 1), 101.25)
+            .account("DU123")
+            .build();
+        let json = serde_json::to_value(&order).unwrap();
+        // Decimals go over the wire as strings; unset prices are left out.
+        assert_eq!(json["totalQuantity"], "1.5");
+        assert_eq!(json["lmtPrice"], 101.25);
+        assert!(json.get("auxPrice").is_none());
+
+        let back: Order = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(back.total_quantity, Some(Decimal::new(15, 1)));
+        assert_eq!(back.aux_price, None);
+        assert_eq!(serde_json::to_value(&back).unwrap(), json);
+    }
+
+    #[test]
+    fn order_builder_presets() {
+        let stop = OrderBuilder::stop_limit(Action::Sell, 10, 99.0, 100.0).build();
+        assert_eq!(stop.order_type, Some(OrderType::StopLimit));
+        assert_eq!(stop.lmt_price, Some(99.0));
+        assert_eq!(stop.aux_price, Some(100.0));
+
+        let trail = OrderBuilder::trailing_stop(Action::Sell, Decimal::new(5, 1), 2.0)
+            .trail_stop_price(95.0)
+            .build();
+        assert_eq!(trail.total_quantity, Some(Decimal::new(5, 1)));
+        assert_eq!(trail.trailing_percent, Some(2.0));
+        assert_eq!(trail.trail_stop_price, Some(95.0));
+        assert!(trail.transmit);
+    }
+
+    #[test]
+    fn order_builder_limit_encodes_like_hand_built_order() {
+        use prost::Message;
+
+        let contract = crate::models::contract::Contract::stock("AAPL");
+        let built = OrderBuilder::limit(Action::Buy, 100, 150.25)
+            .account("DU123")
+            .tif(TimeInForce::Day)
+            .outside_rth(true)
+            .build();
+
+        let hand = Order {
+            action: Some(Action::Buy),
+            total_quantity: Some(Decimal::from(100)),
+            order_type: Some(OrderType::Limit),
+            lmt_price: Some(150.25),
+            account: "DU123".to_string(),
+            tif: Some(TimeInForce::Day),
+            outside_rth: true,
+            ..Order::default()
+        };
+
+        let encode = |order: &Order| {
+            crate::proto_encode::build_place_order_request(1, &contract, order).encode_to_vec()
+        };
+        assert_eq!(encode(&built), encode(&hand));
+    }
+
+    #[test]
+    fn validate_accepts_builder_orders() {
+        let stock = Contract::stock("AAPL");
+        let orders = [
+            OrderBuilder::market(Action::Buy, 100).build(),
+            OrderBuilder::limit(Action::Buy, 100, 150.25).build(),
+            OrderBuilder::stop_limit(Action::Sell, 10, 99.0, 100.0).build(),
+            OrderBuilder::trailing_stop(Action::Sell, 10, 2.0).trail_stop_price(95.0).build(),
+        ];
+        for order in orders {
+            assert_eq!(order.validate(&stock), Ok(()), "{:?}", order.order_type);
+        }
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        use OrderValidationError as E;
+        let stock = Contract::stock("AAPL");
+
+        let errors = Order::default().validate(&stock).unwrap_err();
+        assert_eq!(errors, [E::MissingAction, E::MissingQuantity, E::MissingOrderType]);
+
+        let order = Order {
+            lmt_price: None,
+            aux_price: Some(f64::NAN),
+            total_quantity: Some(Decimal::ZERO),
+            ..OrderBuilder::stop_limit(Action::Buy, 1, 0.0, 0.0).build()
+        };
+        let errors = order.validate(&stock).unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], E::NonPositiveQuantity(Decimal::ZERO));
+        assert!(matches!(errors[1], E::NonFinitePrice { field: "aux_price", .. }));
+        assert_eq!(errors[2], E::MissingLimitPrice(OrderType::StopLimit));
+        assert_eq!(errors[2].to_string(), "STP LMT orders need lmt_price");
+
+        let mut trail = OrderBuilder::trailing_stop(Action::Sell, 10, 2.0).build();
+        trail.aux_price = Some(1.5);
+        trail.trailing_percent = Some(150.0);
+        assert_eq!(
+            trail.validate(&stock).unwrap_err(),
+            [
+                E::ConflictingTrailingAmount(OrderType::TrailingStop),
+                E::InvalidTrailingPercent(150.0),
+            ]
+        );
+        trail.aux_price = None;
+        trail.trailing_percent = None;
+        assert_eq!(
+            trail.validate(&stock).unwrap_err(),
+            [E::MissingTrailingAmount(OrderType::TrailingStop)]
+        );
+
+        let gtd = OrderBuilder::limit(Action::Buy, 5, 10.0).tif(TimeInForce::GoodTilDate).build();
+        assert_eq!(gtd.validate(&stock).unwrap_err(), [E::MissingGoodTillDate]);
+
+        let min_qty = Order { min_qty: Some(10), ..OrderBuilder::market(Action::Buy, 5).build() };
+        assert_eq!(
+            min_qty.validate(&stock).unwrap_err(),
+            [E::MinQtyAboveQuantity { min_qty: 10, quantity: Decimal::from(5) }]
+        );
+
+        let vol = Order { volatility: Some(0.3), ..OrderBuilder::market(Action::Buy, 1).build() };
+        let vol = Order { order_type: Some(OrderType::Volatility), ..vol };
+        assert_eq!(vol.validate(&stock).unwrap_err(), [E::MissingVolatility]);
+    }
+
+    #[test]
+    fn validate_checks_combo_legs() {
+        use crate::models::contract::ComboLeg;
+        use OrderValidationError as E;
+
+        let order = OrderBuilder::limit(Action::Buy, 1, -0.5).build();
+        let mut bag = Contract { sec_type: Some(SecType::Combo), ..Contract::stock("SPY") };
+        assert_eq!(order.validate(&bag).unwrap_err(), [E::ComboWithoutLegs]);
+
+        bag.combo_legs = Some(vec![ComboLeg::default(), ComboLeg::default()]);
+        assert_eq!(order.validate(&bag), Ok(()));
+        let order = Order {
+            order_combo_legs: Some(vec![OrderComboLeg { price: Some(1.0) }]),
+            ..order
+        };
+        assert_eq!(
+            order.validate(&bag).unwrap_err(),
+            [E::ComboLegPriceCount { prices: 1, legs: 2 }]
+        );
+    }
+
     #[test]
     fn order_state_default() {
         let state = OrderState::default();
@@ -2,8 +2,13 @@
 //!
 //! Ported from: `ScannerSubscription.h`.
 
+use rust_decimal::Decimal;
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use super::contract::ComboLeg;
+use super::enums::Action;
+
 // ============================================================================
 // ScannerSubscription
 // ============================================================================
@@ -13,23 +18,24 @@ use serde::{Deserialize, Serialize};
 /// All C++ sentinel values (`DBL_MAX`, `INT_MAX`) are mapped to `Option<T>`.
 ///
 /// C++ source: `struct ScannerSubscription` in `ScannerSubscription.h`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 pub struct ScannerSubscription {
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub number_of_rows: Option<i32>,
     pub instrument: String,
     pub location_code: String,
     pub scan_code: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub above_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub below_price: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub above_volume: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub market_cap_above: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub market_cap_below: Option<f64>,
     pub moody_rating_above: String,
     pub moody_rating_below: String,
@@ -37,14 +43,236 @@ pub struct ScannerSubscription {
     pub sp_rating_below: String,
     pub maturity_date_above: String,
     pub maturity_date_below: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub coupon_rate_above: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub coupon_rate_below: Option<f64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub exclude_convertible: Option<i32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub average_option_volume_above: Option<i32>,
     pub scanner_setting_pairs: String,
     pub stock_type_filter: String,
 }
+
+// ============================================================================
+// ScannerSubscriptionBuilder
+// ============================================================================
+
+/// Fluent builder for `ScannerSubscription`.
+///
+/// Starts from a top-percent-gainers scan of major US stocks returning 50
+/// rows (the most TWS allows); every filter starts unset. There is no C++
+/// equivalent.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let sub = ScannerSubscriptionBuilder::new()
+///     .scan_code("HOT_BY_VOLUME")
+///     .number_of_rows(10)
+///     .above_price(5.0)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScannerSubscriptionBuilder {
+    subscription: ScannerSubscription,
+}
+
+impl Default for ScannerSubscriptionBuilder {
+    fn default() -> Self {
+        Self {
+            subscription: ScannerSubscription {
+                number_of_rows: Some(50),
+                instrument: "STK".to_string(),
+                location_code: "STK.US.MAJOR".to_string(),
+                scan_code: "TOP_PERC_GAIN".to_string(),
+                ..ScannerSubscription::default()
+            },
+        }
+    }
+}
+
+impl ScannerSubscriptionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maximum number of results (TWS caps this at 50).
+    pub fn number_of_rows(mut self, rows: i32) -> Self {
+        self.subscription.number_of_rows = Some(rows);
+        self
+    }
+
+    /// Instrument type, e.g. `"STK"`, `"FUT.US"`. See `req_scanner_parameters`.
+    pub fn instrument(mut self, instrument: &str) -> Self {
+        self.subscription.instrument = instrument.to_string();
+        self
+    }
+
+    /// Location, e.g. `"STK.US.MAJOR"`, `"STK.NASDAQ"`.
+    pub fn location_code(mut self, location_code: &str) -> Self {
+        self.subscription.location_code = location_code.to_string();
+        self
+    }
+
+    /// Scan type, e.g. `"TOP_PERC_GAIN"`, `"MOST_ACTIVE"`.
+    pub fn scan_code(mut self, scan_code: &str) -> Self {
+        self.subscription.scan_code = scan_code.to_string();
+        self
+    }
+
+    pub fn above_price(mut self, price: f64) -> Self {
+        self.subscription.above_price = Some(price);
+        self
+    }
+
+    pub fn below_price(mut self, price: f64) -> Self {
+        self.subscription.below_price = Some(price);
+        self
+    }
+
+    pub fn above_volume(mut self, volume: i32) -> Self {
+        self.subscription.above_volume = Some(volume);
+        self
+    }
+
+    pub fn market_cap_above(mut self, market_cap: f64) -> Self {
+        self.subscription.market_cap_above = Some(market_cap);
+        self
+    }
+
+    pub fn market_cap_below(mut self, market_cap: f64) -> Self {
+        self.subscription.market_cap_below = Some(market_cap);
+        self
+    }
+
+    pub fn average_option_volume_above(mut self, volume: i32) -> Self {
+        self.subscription.average_option_volume_above = Some(volume);
+        self
+    }
+
+    /// `"ALL"`, `"STOCK"` or `"ETF"`.
+    pub fn stock_type_filter(mut self, filter: &str) -> Self {
+        self.subscription.stock_type_filter = filter.to_string();
+        self
+    }
+
+    pub fn build(self) -> ScannerSubscription {
+        self.subscription
+    }
+}
+
+// ============================================================================
+// ScannerLeg
+// ============================================================================
+
+/// One leg of a combo scanner result, parsed from
+/// `ScannerDataItem::legs_str` by [`parse_combo_legs_str`].
+///
+/// There is no C++ equivalent -- `EWrapper::scannerData` passes `legsStr`
+/// through unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct ScannerLeg {
+    pub con_id: i64,
+    /// Always positive; the sign on the wire is carried by `action`.
+    pub ratio: i64,
+    pub action: Action,
+}
+
+impl From<ScannerLeg> for ComboLeg {
+    /// A combo leg for the scanner leg, e.g. to build a `BAG` contract that
+    /// trades the scanner hit. Exchange and the other leg settings keep
+    /// their defaults.
+    fn from(leg: ScannerLeg) -> Self {
+        Self {
+            con_id: leg.con_id,
+            ratio: Decimal::from(leg.ratio),
+            action: Some(leg.action),
+            ..Self::default()
+        }
+    }
+}
+
+/// Parse the `legs_str` of a combo scanner result.
+///
+/// TWS describes the legs like a contract's `comboLegsDescrip`: comma
+/// separated `conId|ratio` pairs, where a negative ratio is a sell leg,
+/// e.g. `"265598|1,8314|-1"`. Blank entries and entries that don't follow
+/// this format are skipped; an empty string (non-combo hits) yields no legs.
+pub fn parse_combo_legs_str(legs_str: &str) -> Vec<ScannerLeg> {
+    legs_str
+        .split(',')
+        .map(str::trim)
+        .filter(|leg| !leg.is_empty())
+        .filter_map(|leg| {
+            let parsed = leg.split_once('|').and_then(|(con_id, ratio)| {
+                Some((con_id.trim().parse().ok()?, ratio.trim().parse::<i64>().ok()?))
+            });
+            match parsed {
+                Some((con_id, ratio)) if ratio != 0 => Some(ScannerLeg {
+                    con_id,
+                    ratio: ratio.abs(),
+                    action: if ratio > 0 { Action::Buy } else { Action::Sell },
+                }),
+                _ => {
+                    tracing::debug!(leg, "skipping malformed scanner combo leg");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_defaults_and_overrides() {
+        let sub = ScannerSubscriptionBuilder::new().build();
+        assert_eq!(sub.number_of_rows, Some(50));
+        assert_eq!(sub.instrument, "STK");
+        assert_eq!(sub.location_code, "STK.US.MAJOR");
+        assert_eq!(sub.scan_code, "TOP_PERC_GAIN");
+        assert_eq!(sub.above_price, None);
+
+        let sub = ScannerSubscriptionBuilder::new()
+            .scan_code("MOST_ACTIVE")
+            .number_of_rows(10)
+            .above_price(5.0)
+            .stock_type_filter("ETF")
+            .build();
+        assert_eq!(sub.scan_code, "MOST_ACTIVE");
+        assert_eq!(sub.number_of_rows, Some(10));
+        assert_eq!(sub.above_price, Some(5.0));
+        assert_eq!(sub.stock_type_filter, "ETF");
+        assert_eq!(sub.location_code, "STK.US.MAJOR");
+    }
+
+    #[test]
+    fn parse_combo_legs_str_splits_legs() {
+        let legs = parse_combo_legs_str("265598|1,8314|-2, 76792991|3");
+        assert_eq!(
+            legs,
+            [
+                ScannerLeg { con_id: 265598, ratio: 1, action: Action::Buy },
+                ScannerLeg { con_id: 8314, ratio: 2, action: Action::Sell },
+                ScannerLeg { con_id: 76792991, ratio: 3, action: Action::Buy },
+            ]
+        );
+        let leg = ComboLeg::from(legs[1].clone());
+        assert_eq!((leg.con_id, leg.action), (8314, Some(Action::Sell)));
+        assert_eq!(leg.ratio, Decimal::from(2));
+
+        assert!(parse_combo_legs_str("").is_empty());
+        let legs = parse_combo_legs_str("265598|1,,garbage,8314|0,8314|x");
+        assert_eq!(legs.len(), 1);
+    }
+}
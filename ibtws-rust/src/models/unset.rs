@@ -0,0 +1,80 @@
+//! Sentinel values the C++ API uses for "not set".
+//!
+//! Ported from: `Decimal.h` (`UNSET_DECIMAL`) and `CommonDefs.h`
+//! (`UNSET_DOUBLE`, `UNSET_INTEGER`, `UNSET_LONG`).
+//!
+//! The public models represent unset fields as `Option<T>`; these constants
+//! only appear while decoding, where the wire format (or the C++ decoder
+//! logic being mirrored) still works with sentinels. Use
+//! [`Unset::unset_to_none`] to cross that boundary.
+
+use rust_decimal::Decimal;
+
+/// C++ `UNSET_DOUBLE` (`DBL_MAX`).
+pub const UNSET_DOUBLE: f64 = f64::MAX;
+
+/// C++ `UNSET_INTEGER` (`INT_MAX`).
+pub const UNSET_INTEGER: i32 = i32::MAX;
+
+/// C++ `UNSET_LONG` (`LLONG_MAX`).
+pub const UNSET_LONG: i64 = i64::MAX;
+
+/// C++ `UNSET_DECIMAL`. The C++ value is the largest representable
+/// `Decimal`; here it is `Decimal::MAX`.
+pub const UNSET_DECIMAL: Decimal = Decimal::MAX;
+
+/// Types with a C++ "not set" sentinel.
+pub trait Unset: Sized + PartialEq {
+    /// The sentinel value.
+    const UNSET: Self;
+
+    /// Whether `self` is the sentinel.
+    fn is_unset(&self) -> bool {
+        *self == Self::UNSET
+    }
+
+    /// `None` for the sentinel, `Some(self)` otherwise.
+    fn unset_to_none(self) -> Option<Self> {
+        if self.is_unset() {
+            None
+        } else {
+            Some(self)
+        }
+    }
+}
+
+impl Unset for f64 {
+    const UNSET: Self = UNSET_DOUBLE;
+}
+
+impl Unset for i32 {
+    const UNSET: Self = UNSET_INTEGER;
+}
+
+impl Unset for i64 {
+    const UNSET: Self = UNSET_LONG;
+}
+
+impl Unset for Decimal {
+    const UNSET: Self = UNSET_DECIMAL;
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unset_to_none_maps_only_the_sentinel() {
+        assert_eq!(UNSET_DOUBLE.unset_to_none(), None);
+        assert_eq!(0.0f64.unset_to_none(), Some(0.0));
+        assert!(UNSET_INTEGER.is_unset());
+        assert!(!(-1i32).is_unset());
+        assert_eq!(UNSET_LONG.unset_to_none(), None);
+        assert!(UNSET_DECIMAL.is_unset());
+        assert_eq!(Decimal::ONE.unset_to_none(), Some(Decimal::ONE));
+    }
+}
@@ -0,0 +1,400 @@
+//! Request/response correlation for the IB TWS API.
+//!
+//! Most IB requests carry a caller-chosen `req_id` that the server echoes
+//! back on every response message. `PendingRequests` keeps a map of
+//! in-flight request IDs to per-request channels; the reader task consults
+//! it for every decoded event and routes matching events to the waiting
+//! collector instead of the global event channel. Order events, which are
+//! keyed by order ID instead, are routed through a second map, and
+//! commission reports follow the execution they belong to. Streams without
+//! any ID (e.g. positions) have at most one collector each.
+//!
+//! There is no direct C++ equivalent -- `EWrapper` callbacks receive every
+//! response and leave correlation to the application.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::mpsc;
+
+use crate::errors::{IBApiError, Result};
+use crate::wrapper::IBEvent;
+
+// ============================================================================
+// PendingRequests
+// ============================================================================
+
+type Routes<K> = Arc<Mutex<HashMap<K, mpsc::UnboundedSender<IBEvent>>>>;
+
+/// Server streams whose events carry no request or order ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Stream {
+    /// `Position` and `PositionEnd` from `req_positions`.
+    Positions,
+    /// The one `ScannerParameters` answer to `req_scanner_parameters`.
+    ScannerParameters,
+    /// The one `MktDepthExchanges` answer to `req_mkt_depth_exchanges`.
+    MktDepthExchanges,
+    /// The next `CurrentTime` answer.
+    CurrentTime,
+    /// The next `CurrentTimeInMillis` answer. Kept apart from
+    /// [`CurrentTime`](Self::CurrentTime), which keepalive probes and
+    /// `req_current_time` also answer.
+    CurrentTimeInMillis,
+    /// Account values and portfolio from `req_account_updates`, up to
+    /// `AccountDownloadEnd`.
+    AccountUpdates,
+}
+
+impl Stream {
+    /// Stream `event` belongs to, if any.
+    fn of(event: &IBEvent) -> Option<Self> {
+        match event {
+            IBEvent::Position { .. } | IBEvent::PositionEnd => Some(Self::Positions),
+            IBEvent::ScannerParameters { .. } => Some(Self::ScannerParameters),
+            IBEvent::MktDepthExchanges { .. } => Some(Self::MktDepthExchanges),
+            IBEvent::CurrentTime { .. } => Some(Self::CurrentTime),
+            IBEvent::CurrentTimeInMillis { .. } => Some(Self::CurrentTimeInMillis),
+            IBEvent::UpdateAccountValue { .. }
+            | IBEvent::UpdatePortfolio { .. }
+            | IBEvent::UpdateAccountTime { .. }
+            | IBEvent::AccountDownloadEnd { .. } => Some(Self::AccountUpdates),
+            _ => None,
+        }
+    }
+
+    /// Whether `event` is the last one of its stream.
+    fn is_end(event: &IBEvent) -> bool {
+        matches!(
+            event,
+            IBEvent::PositionEnd
+                | IBEvent::ScannerParameters { .. }
+                | IBEvent::MktDepthExchanges { .. }
+                | IBEvent::CurrentTime { .. }
+                | IBEvent::CurrentTimeInMillis { .. }
+                | IBEvent::AccountDownloadEnd { .. }
+        )
+    }
+}
+
+/// Shared map of in-flight request IDs to their response channels.
+///
+/// Cloning is cheap: all clones share the same underlying map. `IBClient`
+/// registers a request before sending it, and the `MessageReader` task
+/// dispatches incoming events into it.
+#[derive(Debug, Clone, Default)]
+pub struct PendingRequests {
+    inner: Routes<i32>,
+    orders: Routes<i64>,
+    /// Request ID of each execution routed to a pending request, so its
+    /// `CommissionReport` (which has no request ID) can follow it.
+    exec_ids: Arc<Mutex<HashMap<String, i32>>>,
+    streams: Routes<Stream>,
+}
+
+impl PendingRequests {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `req_id` and return the receiver its responses are routed to.
+    ///
+    /// Registering an ID that is already pending replaces the previous
+    /// registration; the old receiver sees its channel close.
+    pub fn register(&self, req_id: i32) -> mpsc::UnboundedReceiver<IBEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.lock().insert(req_id, tx);
+        rx
+    }
+
+    /// Stop routing events for `req_id`.
+    pub fn remove(&self, req_id: i32) {
+        self.lock().remove(&req_id);
+        self.exec_ids().retain(|_, id| *id != req_id);
+    }
+
+    /// Whether `req_id` is currently registered.
+    pub fn contains(&self, req_id: i32) -> bool {
+        self.lock().contains_key(&req_id)
+    }
+
+    /// Number of registered requests.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no requests are registered.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Register `order_id` and return the receiver its `OpenOrder`,
+    /// `OrderStatus` and `Error` events are routed to.
+    ///
+    /// Like [`register`](Self::register), a second registration of the same
+    /// ID replaces the first.
+    pub fn register_order(&self, order_id: i64) -> mpsc::UnboundedReceiver<IBEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        lock(&self.orders).insert(order_id, tx);
+        rx
+    }
+
+    /// Stop routing events for `order_id`.
+    pub fn remove_order(&self, order_id: i64) {
+        lock(&self.orders).remove(&order_id);
+    }
+
+    /// Whether `order_id` is currently registered.
+    pub fn contains_order(&self, order_id: i64) -> bool {
+        lock(&self.orders).contains_key(&order_id)
+    }
+
+    /// Route the events of `stream` to the returned receiver until its end
+    /// marker (e.g. `PositionEnd`), which is the last event routed; later
+    /// updates go to the event channel.
+    ///
+    /// Fails with `IBApiError::InProgress` while another receiver for
+    /// `stream` is still open, since the answers could not be told apart.
+    pub fn register_stream(&self, stream: Stream) -> Result<mpsc::UnboundedReceiver<IBEvent>> {
+        let mut streams = lock(&self.streams);
+        if streams.get(&stream).is_some_and(|tx| !tx.is_closed()) {
+            return Err(IBApiError::InProgress(format!("{stream:?} request already in progress")));
+        }
+        let (tx, rx) = mpsc::unbounded_channel();
+        streams.insert(stream, tx);
+        Ok(rx)
+    }
+
+    /// Deliver the events of `stream` to the event channel again.
+    pub fn remove_stream(&self, stream: Stream) {
+        lock(&self.streams).remove(&stream);
+    }
+
+    /// Drop all request, order and stream registrations. Waiting collectors
+    /// observe a closed channel.
+    pub fn clear(&self) {
+        self.lock().clear();
+        lock(&self.orders).clear();
+        self.exec_ids().clear();
+        lock(&self.streams).clear();
+    }
+
+    /// Route `event` to its pending request, if any.
+    ///
+    /// `Error` events whose ID matches a registered request are routed too,
+    /// so collectors can fail fast; connection-level errors (`req_id == -1`)
+    /// are never consumed. `OpenOrder` and `OrderStatus` events go to their
+    /// registered order, as does an `Error` whose ID is no pending request
+    /// but a registered order ID. A `CommissionReport` goes to the request
+    /// that received the `ExecDetails` with the same `exec_id`. Events of a
+    /// [`Stream`] go to its registered collector.
+    ///
+    /// Returns `None` when the event was consumed by a registered request,
+    /// or gives the event back when nobody is waiting for it. If the
+    /// collector has gone away, the stale registration is removed and the
+    /// event is handed back.
+    pub fn dispatch(&self, event: IBEvent) -> Option<IBEvent> {
+        if let Some(stream) = Stream::of(&event) {
+            let end = Stream::is_end(&event);
+            let mut streams = lock(&self.streams);
+            let Some(tx) = streams.get(&stream) else {
+                return Some(event);
+            };
+            let sent = tx.send(event);
+            if end || sent.is_err() {
+                streams.remove(&stream);
+            }
+            return sent.err().map(|mpsc::error::SendError(event)| event);
+        }
+        match &event {
+            IBEvent::OpenOrder { order_id, .. } | IBEvent::OrderStatus { order_id, .. } => {
+                return route(&self.orders, *order_id, event);
+            }
+            IBEvent::Error { req_id, .. } if *req_id >= 0 => {
+                let order_id = i64::from(*req_id);
+                let event = route(&self.inner, *req_id, event)?;
+                return route(&self.orders, order_id, event);
+            }
+            IBEvent::ExecDetails { req_id, execution, .. } if self.contains(*req_id) => {
+                self.exec_ids().insert(execution.exec_id.clone(), *req_id);
+            }
+            IBEvent::CommissionReport { report } => {
+                let Some(req_id) = self.exec_ids().get(&report.exec_id).copied() else {
+                    return Some(event);
+                };
+                return route(&self.inner, req_id, event);
+            }
+            _ => {}
+        }
+        match event.req_id() {
+            Some(req_id) => route(&self.inner, req_id, event),
+            None => Some(event),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<i32, mpsc::UnboundedSender<IBEvent>>> {
+        lock(&self.inner)
+    }
+
+    fn exec_ids(&self) -> MutexGuard<'_, HashMap<String, i32>> {
+        self.exec_ids.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Send `event` to the channel registered under `key`; see
+/// [`PendingRequests::dispatch`].
+fn route<K: Hash + Eq>(routes: &Routes<K>, key: K, event: IBEvent) -> Option<IBEvent> {
+    let mut map = lock(routes);
+    let Some(tx) = map.get(&key) else {
+        return Some(event);
+    };
+    match tx.send(event) {
+        Ok(()) => None,
+        Err(mpsc::error::SendError(event)) => {
+            map.remove(&key);
+            Some(event)
+        }
+    }
+}
+
+fn lock<K>(routes: &Routes<K>) -> MutexGuard<'_, HashMap<K, mpsc::UnboundedSender<IBEvent>>> {
+    // A poisoned lock only means another thread panicked mid-insert;
+    // the map itself is still usable.
+    routes.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dispatch_routes_registered_req_id() {
+        let pending = PendingRequests::new();
+        let mut rx = pending.register(7);
+
+        assert!(pending.dispatch(IBEvent::ContractDetailsEnd { req_id: 7 }).is_none());
+        match rx.try_recv().unwrap() {
+            IBEvent::ContractDetailsEnd { req_id } => assert_eq!(req_id, 7),
+            other => panic!("expected ContractDetailsEnd, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn dispatch_returns_unmatched_events() {
+        let pending = PendingRequests::new();
+        let _rx = pending.register(7);
+
+        // Different req_id
+        assert!(pending.dispatch(IBEvent::ContractDetailsEnd { req_id: 8 }).is_some());
+        // No req_id at all
+        assert!(pending.dispatch(IBEvent::ConnectionClosed).is_some());
+    }
+
+    #[test]
+    fn dispatch_routes_request_errors_only() {
+        let pending = PendingRequests::new();
+        let mut rx = pending.register(7);
+        let error = |req_id| IBEvent::Error {
+            req_id,
+            error_time: 0,
+            code: 354,
+            message: "Requested market data is not subscribed".into(),
+            advanced_order_reject_json: String::new(),
+        };
+
+        assert!(pending.dispatch(error(7)).is_none());
+        assert!(matches!(rx.try_recv().unwrap(), IBEvent::Error { code: 354, .. }));
+        assert!(pending.dispatch(error(-1)).is_some());
+    }
+
+    #[test]
+    fn dispatch_drops_stale_registration() {
+        let pending = PendingRequests::new();
+        drop(pending.register(7));
+
+        assert!(pending.dispatch(IBEvent::ContractDetailsEnd { req_id: 7 }).is_some());
+        assert!(!pending.contains(7));
+    }
+
+    #[test]
+    fn dispatch_routes_order_events_by_order_id() {
+        let pending = PendingRequests::new();
+        let mut order_rx = pending.register_order(5);
+        let mut req_rx = pending.register(6);
+        let status = |order_id| IBEvent::OrderStatus {
+            order_id,
+            status: "Submitted".into(),
+            filled: Default::default(),
+            remaining: Default::default(),
+            avg_fill_price: 0.0,
+            perm_id: 0,
+            parent_id: 0,
+            last_fill_price: 0.0,
+            client_id: 0,
+            why_held: String::new(),
+            mkt_cap_price: None,
+        };
+        let error = |req_id| IBEvent::Error {
+            req_id,
+            error_time: 0,
+            code: 201,
+            message: "Order rejected".into(),
+            advanced_order_reject_json: String::new(),
+        };
+
+        assert!(pending.dispatch(status(5)).is_none());
+        assert!(pending.dispatch(status(6)).is_some());
+        assert!(matches!(order_rx.try_recv().unwrap(), IBEvent::OrderStatus { order_id: 5, .. }));
+
+        // Errors go to a pending request with their ID, else to the order.
+        assert!(pending.dispatch(error(5)).is_none());
+        assert!(pending.dispatch(error(6)).is_none());
+        assert!(matches!(order_rx.try_recv().unwrap(), IBEvent::Error { req_id: 5, .. }));
+        assert!(matches!(req_rx.try_recv().unwrap(), IBEvent::Error { req_id: 6, .. }));
+
+        pending.clear();
+        assert!(!pending.contains_order(5));
+    }
+
+    #[test]
+    fn dispatch_routes_stream_until_end_marker() {
+        let pending = PendingRequests::new();
+        let mut rx = pending.register_stream(Stream::Positions).unwrap();
+        let position = || IBEvent::Position {
+            account: "DU123".into(),
+            contract: Box::default(),
+            position: Default::default(),
+            avg_cost: Default::default(),
+        };
+
+        assert!(pending.dispatch(position()).is_none());
+        assert!(pending.dispatch(IBEvent::PositionEnd).is_none());
+        // Updates after the end marker go to the event channel.
+        assert!(pending.dispatch(position()).is_some());
+        assert!(matches!(rx.try_recv().unwrap(), IBEvent::Position { .. }));
+        assert!(matches!(rx.try_recv().unwrap(), IBEvent::PositionEnd));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn register_stream_rejects_a_second_open_receiver() {
+        let pending = PendingRequests::new();
+        let rx = pending.register_stream(Stream::Positions).unwrap();
+        assert!(matches!(
+            pending.register_stream(Stream::Positions),
+            Err(IBApiError::InProgress(_))
+        ));
+        assert!(pending.register_stream(Stream::CurrentTime).is_ok());
+
+        // A receiver dropped without remove_stream does not block the stream.
+        drop(rx);
+        assert!(pending.register_stream(Stream::Positions).is_ok());
+    }
+}
@@ -0,0 +1,220 @@
+//! Several `IBClient` connections to one TWS, behind one event stream.
+//!
+//! TWS accepts up to 32 API connections with distinct client IDs, which
+//! lets an application keep e.g. order management and market data on
+//! separate sockets. `ClientPool` owns those clients, keyed by client ID,
+//! and merges their event channels into one stream whose events are tagged
+//! with the client ID they arrived on.
+//!
+//! There is no C++ equivalent -- each C++ `EClientSocket` is a separate
+//! connection with its own `EWrapper`.
+
+use std::collections::BTreeMap;
+
+use tokio::sync::mpsc;
+
+use crate::client::IBClient;
+use crate::errors::{IBApiError, Result};
+use crate::wrapper::IBEvent;
+
+/// An event from one of the connections of a [`ClientPool`].
+#[derive(Debug)]
+pub struct PoolEvent {
+    /// Client ID of the connection the event arrived on.
+    pub client_id: i32,
+    pub event: IBEvent,
+}
+
+/// What the per-connection forwarding tasks send to the pool.
+enum Forwarded {
+    Event(PoolEvent),
+    /// The connection's event channel closed; no more events follow.
+    Ended,
+}
+
+// ============================================================================
+// ClientPool
+// ============================================================================
+
+/// A set of `IBClient`s keyed by client ID, with a merged event stream.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let mut pool = ClientPool::new();
+/// pool.connect("127.0.0.1", 4002, 1, None).await?; // orders
+/// pool.connect("127.0.0.1", 4002, 2, None).await?; // market data
+///
+/// pool.client(2).unwrap().req_mkt_data(1, &contract, "", false, false, &[]).await?;
+/// while let Some(PoolEvent { client_id, event }) = pool.recv().await {
+///     println!("[{client_id}] {event}");
+/// }
+/// ```
+pub struct ClientPool {
+    clients: BTreeMap<i32, IBClient>,
+    tx: mpsc::UnboundedSender<Forwarded>,
+    rx: mpsc::UnboundedReceiver<Forwarded>,
+    /// Connections whose event channel has not closed yet.
+    open: usize,
+}
+
+impl ClientPool {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self { clients: BTreeMap::new(), tx, rx, open: 0 }
+    }
+
+    /// Connect a new client with [`IBClient::connect`] and add it to the
+    /// pool.
+    ///
+    /// Fails with `IBApiError::Connection` if `client_id` is already in the
+    /// pool, without connecting.
+    pub async fn connect(
+        &mut self,
+        host: &str,
+        port: u16,
+        client_id: i32,
+        optional_capabilities: Option<&str>,
+    ) -> Result<()> {
+        self.check_free(client_id)?;
+        let (client, events) =
+            IBClient::connect(host, port, client_id, optional_capabilities).await?;
+        self.add(client, events)
+    }
+
+    /// Add a client connected by other means (e.g. with a transport config
+    /// or reconnect policy), together with its event receiver.
+    ///
+    /// Fails with `IBApiError::Connection` if a client with the same client
+    /// ID is already in the pool.
+    pub fn add(
+        &mut self,
+        client: IBClient,
+        mut events: mpsc::UnboundedReceiver<IBEvent>,
+    ) -> Result<()> {
+        let client_id = client.client_id();
+        self.check_free(client_id)?;
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.recv().await {
+                if tx.send(Forwarded::Event(PoolEvent { client_id, event })).is_err() {
+                    return; // pool dropped
+                }
+            }
+            let _ = tx.send(Forwarded::Ended);
+        });
+        self.clients.insert(client_id, client);
+        self.open += 1;
+        Ok(())
+    }
+
+    /// The client connected with `client_id`, to send requests on that
+    /// connection.
+    pub fn client(&mut self, client_id: i32) -> Option<&mut IBClient> {
+        self.clients.get_mut(&client_id)
+    }
+
+    /// Client IDs in the pool, in ascending order.
+    pub fn client_ids(&self) -> impl Iterator<Item = i32> + '_ {
+        self.clients.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+
+    /// Receive the next event from any connection.
+    ///
+    /// Events of one connection arrive in order; events of different
+    /// connections are interleaved as they arrive. Returns `None` once the
+    /// event channel of every connection has closed (and immediately for an
+    /// empty pool).
+    pub async fn recv(&mut self) -> Option<PoolEvent> {
+        while self.open > 0 {
+            match self.rx.recv().await? {
+                Forwarded::Event(event) => return Some(event),
+                Forwarded::Ended => self.open -= 1,
+            }
+        }
+        None
+    }
+
+    /// Disconnect every client in the pool.
+    pub async fn disconnect_all(&mut self) {
+        for client in self.clients.values_mut() {
+            client.disconnect().await;
+        }
+    }
+
+    fn check_free(&self, client_id: i32) -> Result<()> {
+        if self.clients.contains_key(&client_id) {
+            return Err(IBApiError::Connection(format!(
+                "client id {client_id} is already in the pool"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ClientPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{frame, MockTws};
+
+    #[tokio::test]
+    async fn pool_routes_requests_and_tags_merged_events() {
+        let mut orders_tws = MockTws::start(176).await;
+        let mut data_tws = MockTws::start(176).await;
+        let mut pool = ClientPool::new();
+        pool.connect("127.0.0.1", orders_tws.port(), 1, None).await.unwrap();
+        pool.connect("127.0.0.1", data_tws.port(), 2, None).await.unwrap();
+        assert_eq!(pool.client_ids().collect::<Vec<_>>(), [1, 2]);
+
+        let err = pool.connect("127.0.0.1", data_tws.port(), 2, None).await.unwrap_err();
+        assert!(matches!(err, IBApiError::Connection(_)), "got {err:?}");
+
+        // Requests go out on the chosen connection only.
+        pool.client(2).unwrap().req_current_time().await.unwrap();
+        assert_eq!(data_tws.expect_message().await[0], "49");
+        pool.client(1).unwrap().req_ids().await.unwrap();
+        assert_eq!(orders_tws.expect_message().await[0], "8");
+        assert!(pool.client(3).is_none());
+
+        orders_tws.push_event(frame(&["9", "1", "100"])); // NEXT_VALID_ID
+        match pool.recv().await.unwrap() {
+            PoolEvent { client_id: 1, event: IBEvent::NextValidId { order_id: 100 } } => {}
+            other => panic!("expected NextValidId from client 1, got {other:?}"),
+        }
+        data_tws.push_event(frame(&["49", "1", "1767225600"])); // CURRENT_TIME
+        match pool.recv().await.unwrap() {
+            PoolEvent { client_id: 2, event: IBEvent::CurrentTime { time: 1767225600 } } => {}
+            other => panic!("expected CurrentTime from client 2, got {other:?}"),
+        }
+
+        // Each closed connection reports its own ConnectionClosed.
+        data_tws.close();
+        match pool.recv().await.unwrap() {
+            PoolEvent { client_id: 2, event: IBEvent::ConnectionClosed } => {}
+            other => panic!("expected ConnectionClosed from client 2, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_pool_recv_returns_none() {
+        assert!(ClientPool::new().recv().await.is_none());
+    }
+}
@@ -6,13 +6,16 @@
 //! if `msg_id > PROTOBUF_MSG_ID` (200), subtract 200 to get the real message
 //! type, and decode the remaining bytes as a protobuf message.
 //!
-//! Currently 6 incoming message types support protobuf encoding:
+//! Currently 9 incoming message types support protobuf encoding:
 //! - ORDER_STATUS (3)
 //! - ERR_MSG (4)
 //! - OPEN_ORDER (5)
 //! - EXECUTION_DATA (11)
+//! - HISTORICAL_DATA (17)
 //! - OPEN_ORDER_END (53)
 //! - EXECUTION_DATA_END (55)
+//! - HISTORICAL_DATA_UPDATE (90)
+//! - HISTORICAL_DATA_END (108)
 //!
 //! Ported from: `EDecoder.cpp` (processXxxProtoBuf functions) and
 //! `EDecoderUtils.cpp` (decodeContract, decodeOrder, decodeOrderState,
@@ -25,6 +28,7 @@ use rust_decimal::Decimal;
 use std::str::FromStr;
 
 use crate::errors::{IBApiError, Result};
+use crate::models::bar::Bar;
 use crate::models::common::{SoftDollarTier, TagValue};
 use crate::models::contract::{ComboLeg, Contract, DeltaNeutralContract};
 use crate::models::enums::*;
@@ -32,8 +36,12 @@ use crate::models::execution::Execution;
 use crate::models::order::{
     Order, OrderAllocation, OrderComboLeg, OrderCondition, OrderState,
 };
+use crate::models::unset::{UNSET_DECIMAL, UNSET_DOUBLE};
 use crate::wrapper::IBEvent;
 
+/// Request ID of messages that answer no request. C++ `NO_VALID_ID`.
+const NO_VALID_ID: i32 = -1;
+
 // Include the prost-generated protobuf types.
 #[allow(clippy::derive_partial_eq_without_eq)]
 pub mod pb {
@@ -60,6 +68,9 @@ pub fn decode_protobuf_msg(real_msg_id: i32, data: &[u8]) -> Result<IBEvent> {
         incoming::EXECUTION_DATA => decode_execution_details_pb(data),
         incoming::OPEN_ORDER_END => decode_open_order_end_pb(data),
         incoming::EXECUTION_DATA_END => decode_execution_details_end_pb(data),
+        incoming::HISTORICAL_DATA => decode_historical_data_pb(data),
+        incoming::HISTORICAL_DATA_UPDATE => decode_historical_data_update_pb(data),
+        incoming::HISTORICAL_DATA_END => decode_historical_data_end_pb(data),
         _ => Err(IBApiError::Decoding(format!(
             "unknown protobuf message id: {real_msg_id}"
         ))),
@@ -81,13 +92,13 @@ fn decode_order_status_pb(data: &[u8]) -> Result<IBEvent> {
         status: proto.status.unwrap_or_default(),
         filled: parse_decimal_opt(&proto.filled),
         remaining: parse_decimal_opt(&proto.remaining),
-        avg_fill_price: proto.avg_fill_price.unwrap_or(f64::MAX),
+        avg_fill_price: proto.avg_fill_price.unwrap_or(UNSET_DOUBLE),
         perm_id: proto.perm_id.unwrap_or(0),
         parent_id: proto.parent_id.unwrap_or(0),
-        last_fill_price: proto.last_fill_price.unwrap_or(f64::MAX),
+        last_fill_price: proto.last_fill_price.unwrap_or(UNSET_DOUBLE),
         client_id: proto.client_id.unwrap_or(0),
         why_held: proto.why_held.unwrap_or_default(),
-        mkt_cap_price: proto.mkt_cap_price.unwrap_or(f64::MAX),
+        mkt_cap_price: proto.mkt_cap_price,
     })
 }
 
@@ -97,13 +108,13 @@ fn decode_error_msg_pb(data: &[u8]) -> Result<IBEvent> {
     let proto = pb::ErrorMessage::decode(data)
         .map_err(|e| IBApiError::Decoding(format!("protobuf ErrorMessage: {e}")))?;
 
-    Ok(IBEvent::Error {
-        req_id: proto.id.unwrap_or(0),
-        error_time: proto.error_time.unwrap_or(0),
-        code: proto.error_code.unwrap_or(0),
-        message: proto.error_msg.unwrap_or_default(),
-        advanced_order_reject_json: proto.advanced_order_reject_json.unwrap_or_default(),
-    })
+    Ok(IBEvent::from_error(
+        proto.id.unwrap_or(0),
+        proto.error_time.unwrap_or(0),
+        proto.error_code.unwrap_or(0),
+        proto.error_msg.unwrap_or_default(),
+        proto.advanced_order_reject_json.unwrap_or_default(),
+    ))
 }
 
 /// Decode protobuf OPEN_ORDER message.
@@ -147,7 +158,7 @@ fn decode_execution_details_pb(data: &[u8]) -> Result<IBEvent> {
     let proto = pb::ExecutionDetails::decode(data)
         .map_err(|e| IBApiError::Decoding(format!("protobuf ExecutionDetails: {e}")))?;
 
-    let req_id = proto.req_id.unwrap_or(0);
+    let req_id = proto.req_id.unwrap_or(NO_VALID_ID);
 
     let contract = match proto.contract {
         Some(ref c) => decode_contract_pb(c),
@@ -183,7 +194,44 @@ fn decode_execution_details_end_pb(data: &[u8]) -> Result<IBEvent> {
         .map_err(|e| IBApiError::Decoding(format!("protobuf ExecutionDetailsEnd: {e}")))?;
 
     Ok(IBEvent::ExecDetailsEnd {
+        req_id: proto.req_id.unwrap_or(NO_VALID_ID),
+    })
+}
+
+/// Decode protobuf HISTORICAL_DATA message.
+/// C++: `EDecoder::processHistoricalDataMsgProtoBuf`
+fn decode_historical_data_pb(data: &[u8]) -> Result<IBEvent> {
+    let proto = pb::HistoricalData::decode(data)
+        .map_err(|e| IBApiError::Decoding(format!("protobuf HistoricalData: {e}")))?;
+
+    Ok(IBEvent::HistoricalData {
+        req_id: proto.req_id.unwrap_or(0),
+        bars: proto.historical_data_bars.iter().map(decode_bar_pb).collect(),
+    })
+}
+
+/// Decode protobuf HISTORICAL_DATA_UPDATE message.
+/// C++: `EDecoder::processHistoricalDataUpdateMsgProtoBuf`
+fn decode_historical_data_update_pb(data: &[u8]) -> Result<IBEvent> {
+    let proto = pb::HistoricalDataUpdate::decode(data)
+        .map_err(|e| IBApiError::Decoding(format!("protobuf HistoricalDataUpdate: {e}")))?;
+
+    Ok(IBEvent::HistoricalDataUpdate {
+        req_id: proto.req_id.unwrap_or(0),
+        bar: proto.historical_data_bar.as_ref().map(decode_bar_pb).unwrap_or_default(),
+    })
+}
+
+/// Decode protobuf HISTORICAL_DATA_END message.
+/// C++: `EDecoder::processHistoricalDataEndMsgProtoBuf`
+fn decode_historical_data_end_pb(data: &[u8]) -> Result<IBEvent> {
+    let proto = pb::HistoricalDataEnd::decode(data)
+        .map_err(|e| IBApiError::Decoding(format!("protobuf HistoricalDataEnd: {e}")))?;
+
+    Ok(IBEvent::HistoricalDataEnd {
         req_id: proto.req_id.unwrap_or(0),
+        start: proto.start_date_str.unwrap_or_default(),
+        end: proto.end_date_str.unwrap_or_default(),
     })
 }
 
@@ -218,7 +266,7 @@ fn decode_contract_pb(cp: &pb::Contract) -> Contract {
         let legs: Vec<ComboLeg> = cp.combo_legs.iter().map(|leg| {
             let mut cl = ComboLeg::default();
             if let Some(v) = leg.con_id { cl.con_id = v as i64; }
-            if let Some(v) = leg.ratio { cl.ratio = v as i64; }
+            if let Some(v) = leg.ratio { cl.ratio = Decimal::from(v); }
             if let Some(ref v) = leg.action { cl.action = Action::from_str(v).ok(); }
             if let Some(ref v) = leg.exchange { cl.exchange.clone_from(v); }
             if let Some(v) = leg.open_close {
@@ -545,8 +593,28 @@ fn decode_order_state_pb(os: &pb::OrderState) -> OrderState {
 /// Parse an optional string field as Decimal, defaulting to UNSET_DECIMAL on missing/invalid.
 fn parse_decimal_opt(s: &Option<String>) -> Decimal {
     match s {
-        Some(v) => Decimal::from_str(v).unwrap_or(Decimal::MAX),
-        None => Decimal::MAX,
+        Some(v) => Decimal::from_str(v).unwrap_or(UNSET_DECIMAL),
+        None => UNSET_DECIMAL,
+    }
+}
+
+/// Convert a protobuf `HistoricalDataBar` → `Bar`.
+///
+/// Volume and WAP follow the text decoder: absent or empty means `None`.
+/// C++: `EDecoderUtils::decodeHistoricalDataBar`
+fn decode_bar_pb(b: &pb::HistoricalDataBar) -> Bar {
+    let decimal = |s: &Option<String>| {
+        s.as_deref().filter(|v| !v.is_empty()).and_then(|v| Decimal::from_str(v).ok())
+    };
+    Bar {
+        time: b.date.clone().unwrap_or_default(),
+        open: b.open.unwrap_or(0.0),
+        high: b.high.unwrap_or(0.0),
+        low: b.low.unwrap_or(0.0),
+        close: b.close.unwrap_or(0.0),
+        volume: decimal(&b.volume),
+        wap: decimal(&b.wap),
+        count: b.bar_count.unwrap_or(0),
     }
 }
 
@@ -565,12 +633,19 @@ fn decode_tag_value_map(m: &std::collections::HashMap<String, String>) -> Vec<Ta
 fn decode_conditions_pb(conditions: &[pb::OrderCondition]) -> Vec<OrderCondition> {
     conditions
         .iter()
-        .filter_map(|c| {
-            let cond_type = OrderConditionType::try_from(c.r#type.unwrap_or(0)).ok()?;
+        .map(|c| {
+            let raw_type = c.r#type.unwrap_or(0);
             let conj = c.is_conjunction_connection.unwrap_or(true);
             let is_more = c.is_more.unwrap_or(false);
+            let Ok(cond_type) = OrderConditionType::try_from(raw_type) else {
+                return OrderCondition::Unknown {
+                    is_conjunction_connection: conj,
+                    raw_type,
+                    fields: Vec::new(),
+                };
+            };
 
-            Some(match cond_type {
+            match cond_type {
                 OrderConditionType::Price => OrderCondition::Price {
                     is_conjunction_connection: conj,
                     is_more,
@@ -612,7 +687,7 @@ fn decode_conditions_pb(conditions: &[pb::OrderCondition]) -> Vec<OrderCondition
                     exchange: c.exchange.clone().unwrap_or_default(),
                     change_percent: c.change_percent,
                 },
-            })
+            }
         })
         .collect()
 }
@@ -824,6 +899,63 @@ mod tests {
         }
     }
 
+    /// `ExecutionDetails` for req_id 7: 10 AAPL bought at 150.5 on NASDAQ by
+    /// order 42 (perm_id 9001), order_ref "rebalance", last_liquidity 1.
+    const GOLDEN_EXECUTION_DETAILS: &[u8] = &[
+        0x08, 0x07, 0x12, 0x26, 0x08, 0xfe, 0x9a, 0x10, 0x12, 0x04, 0x41, 0x41,
+        0x50, 0x4c, 0x1a, 0x03, 0x53, 0x54, 0x4b, 0x42, 0x05, 0x53, 0x4d, 0x41,
+        0x52, 0x54, 0x52, 0x03, 0x55, 0x53, 0x44, 0x5a, 0x04, 0x41, 0x41, 0x50,
+        0x4c, 0x62, 0x03, 0x4e, 0x4d, 0x53, 0x1a, 0x7f, 0x08, 0x2a, 0x12, 0x17,
+        0x30, 0x30, 0x30, 0x30, 0x65, 0x30, 0x64, 0x35, 0x2e, 0x36, 0x37, 0x38,
+        0x39, 0x61, 0x62, 0x63, 0x64, 0x2e, 0x30, 0x31, 0x2e, 0x30, 0x31, 0x1a,
+        0x1c, 0x32, 0x30, 0x32, 0x36, 0x30, 0x31, 0x30, 0x35, 0x20, 0x31, 0x30,
+        0x3a, 0x33, 0x30, 0x3a, 0x30, 0x30, 0x20, 0x55, 0x53, 0x2f, 0x45, 0x61,
+        0x73, 0x74, 0x65, 0x72, 0x6e, 0x22, 0x05, 0x44, 0x55, 0x31, 0x32, 0x33,
+        0x2a, 0x06, 0x4e, 0x41, 0x53, 0x44, 0x41, 0x51, 0x32, 0x03, 0x42, 0x4f,
+        0x54, 0x3a, 0x02, 0x31, 0x30, 0x41, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd0,
+        0x62, 0x40, 0x48, 0xa9, 0x46, 0x50, 0x00, 0x58, 0x00, 0x62, 0x02, 0x31,
+        0x30, 0x69, 0x00, 0x00, 0x00, 0x00, 0x00, 0xd0, 0x62, 0x40, 0x72, 0x09,
+        0x72, 0x65, 0x62, 0x61, 0x6c, 0x61, 0x6e, 0x63, 0x65, 0x90, 0x01, 0x01,
+        0x98, 0x01, 0x00,
+    ];
+
+    #[test]
+    fn decode_execution_details_protobuf_matches_text_decoder() {
+        let IBEvent::ExecDetails { req_id, contract, execution } =
+            decode_protobuf_msg(11, GOLDEN_EXECUTION_DETAILS).unwrap()
+        else {
+            panic!("expected ExecDetails");
+        };
+        assert_eq!(req_id, 7);
+        assert_eq!(execution.order_id, 42);
+        assert_eq!(execution.shares, Some(Decimal::from(10)));
+        assert_eq!(execution.order_ref, "rebalance");
+
+        // The same execution as EXECUTION_DATA (11) text at sv 176.
+        let text = [
+            "11", "7", "42", "265598", "AAPL", "STK", "", "", "", "", "SMART", "USD", "AAPL",
+            "NMS", "0000e0d5.6789abcd.01.01", "20260105 10:30:00 US/Eastern", "DU123",
+            "NASDAQ", "BOT", "10", "150.5", "9001", "0", "0", "10", "150.5", "rebalance", "",
+            "", "", "1",
+        ];
+        let data: Vec<u8> = text.iter().flat_map(|f| f.bytes().chain([0])).collect();
+        let IBEvent::ExecDetails { req_id: text_req_id, contract: text, execution: text_exec } =
+            crate::decoder::decode_server_msg(&data, 176)
+        else {
+            panic!("expected text ExecDetails");
+        };
+        assert_eq!(req_id, text_req_id);
+        assert_eq!(format!("{contract:?}"), format!("{text:?}"));
+        assert_eq!(format!("{execution:?}"), format!("{text_exec:?}"));
+
+        // Without a request ID (executions pushed after a fill) both use -1.
+        let data = encode_pb(&pb::ExecutionDetails::default());
+        assert!(matches!(
+            decode_protobuf_msg(11, &data).unwrap(),
+            IBEvent::ExecDetails { req_id: -1, .. }
+        ));
+    }
+
     #[test]
     fn decode_open_order_with_conditions_protobuf() {
         let proto = pb::OpenOrder {
@@ -854,6 +986,11 @@ mod tests {
                         time: Some("20260301 09:30:00".into()),
                         ..Default::default()
                     },
+                    pb::OrderCondition {
+                        r#type: Some(2), // not assigned by IB
+                        is_conjunction_connection: Some(true),
+                        ..Default::default()
+                    },
                 ],
                 conditions_cancel_order: Some(true),
                 conditions_ignore_rth: Some(false),
@@ -865,7 +1002,7 @@ mod tests {
 
         match decode_protobuf_msg(5, &data).unwrap() {
             IBEvent::OpenOrder { order, .. } => {
-                assert_eq!(order.conditions.len(), 2);
+                assert_eq!(order.conditions.len(), 3);
                 match &order.conditions[0] {
                     OrderCondition::Price { price, con_id, .. } => {
                         assert!((price - 400.0).abs() < f64::EPSILON);
@@ -879,6 +1016,16 @@ mod tests {
                     }
                     other => panic!("expected Time condition, got {other:?}"),
                 }
+                // Protobuf conditions are self-delimiting, so an unknown
+                // type is kept instead of failing the order.
+                assert_eq!(
+                    order.conditions[2],
+                    OrderCondition::Unknown {
+                        is_conjunction_connection: true,
+                        raw_type: 2,
+                        fields: vec![],
+                    }
+                );
                 assert!(order.conditions_cancel_order);
                 assert!(!order.conditions_ignore_rth);
             }
@@ -1011,4 +1158,91 @@ mod tests {
             other => panic!("expected ExecDetails, got {other:?}"),
         }
     }
+
+    /// HISTORICAL_DATA protobuf payload (req_id 7, two 5-minute bars) as
+    /// sent by a server with protobuf historical data.
+    const HISTORICAL_DATA_PB: &[u8] = &[
+        0x08, 0x07, 0x12, 0x49, 0x0a, 0x11, 0x32, 0x30, 0x32, 0x36, 0x30, 0x31,
+        0x30, 0x32, 0x20, 0x30, 0x39, 0x3a, 0x33, 0x30, 0x3a, 0x30, 0x30, 0x11,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x62, 0x40, 0x19, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0xf0, 0x62, 0x40, 0x21, 0x00, 0x00, 0x00, 0x00, 0x00, 0xb8,
+        0x62, 0x40, 0x29, 0x00, 0x00, 0x00, 0x00, 0x00, 0xe8, 0x62, 0x40, 0x32,
+        0x05, 0x31, 0x32, 0x33, 0x34, 0x35, 0x3a, 0x06, 0x31, 0x35, 0x30, 0x2e,
+        0x36, 0x32, 0x40, 0xc1, 0x02, 0x12, 0x47, 0x0a, 0x11, 0x32, 0x30, 0x32,
+        0x36, 0x30, 0x31, 0x30, 0x32, 0x20, 0x30, 0x39, 0x3a, 0x33, 0x35, 0x3a,
+        0x30, 0x30, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0xe8, 0x62, 0x40, 0x19,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x63, 0x40, 0x21, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0xe0, 0x62, 0x40, 0x29, 0x00, 0x00, 0x00, 0x00, 0x00, 0xf8,
+        0x62, 0x40, 0x32, 0x04, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x05, 0x31, 0x35,
+        0x31, 0x2e, 0x34, 0x40, 0xd2, 0x01,
+    ];
+
+    #[test]
+    fn decode_historical_data_protobuf_matches_text_decoder() {
+        let pb_event = decode_protobuf_msg(17, HISTORICAL_DATA_PB).unwrap();
+
+        // Same bars in the text format at sv 176:
+        // msg_id, req_id, startDate, endDate, count, then 8 fields per bar.
+        let mut text = Vec::new();
+        for f in [
+            "17", "7", "20260102 09:30:00", "20260102 09:40:00", "2",
+            "20260102 09:30:00", "150", "151.5", "149.75", "151.25", "12345", "150.62", "321",
+            "20260102 09:35:00", "151.25", "152", "151", "151.75", "6789", "151.4", "210",
+        ] {
+            text.extend_from_slice(f.as_bytes());
+            text.push(0);
+        }
+        let text_event = crate::decoder::decode_server_msg(&text, 176);
+
+        match (pb_event, text_event) {
+            (
+                IBEvent::HistoricalData { req_id, bars },
+                IBEvent::HistoricalData { req_id: text_req_id, bars: text_bars },
+            ) => {
+                assert_eq!(req_id, 7);
+                assert_eq!(req_id, text_req_id);
+                assert_eq!(bars.len(), 2);
+                assert_eq!(bars[0].volume, Some(Decimal::from(12345)));
+                assert_eq!(format!("{bars:?}"), format!("{text_bars:?}"));
+            }
+            other => panic!("expected two HistoricalData events, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_historical_data_update_and_end_protobuf() {
+        let update = pb::HistoricalDataUpdate {
+            req_id: Some(7),
+            historical_data_bar: Some(pb::HistoricalDataBar {
+                date: Some("20260102 09:40:00".into()),
+                close: Some(151.9),
+                volume: Some("".into()),
+                bar_count: Some(12),
+                ..Default::default()
+            }),
+        };
+        match decode_protobuf_msg(90, &encode_pb(&update)).unwrap() {
+            IBEvent::HistoricalDataUpdate { req_id, bar } => {
+                assert_eq!(req_id, 7);
+                assert_eq!(bar.close, 151.9);
+                assert_eq!(bar.volume, None);
+                assert_eq!(bar.count, 12);
+            }
+            other => panic!("expected HistoricalDataUpdate, got {other:?}"),
+        }
+
+        let end = pb::HistoricalDataEnd {
+            req_id: Some(7),
+            start_date_str: Some("20260102 09:30:00".into()),
+            end_date_str: Some("20260102 09:40:00".into()),
+        };
+        match decode_protobuf_msg(108, &encode_pb(&end)).unwrap() {
+            IBEvent::HistoricalDataEnd { req_id, start, end } => {
+                assert_eq!(req_id, 7);
+                assert_eq!(start, "20260102 09:30:00");
+                assert_eq!(end, "20260102 09:40:00");
+            }
+            other => panic!("expected HistoricalDataEnd, got {other:?}"),
+        }
+    }
 }
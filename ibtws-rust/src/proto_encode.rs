@@ -7,12 +7,15 @@
 //! - reqExecutions (sv >= 201)
 //! - reqGlobalCancel (sv >= 203)
 
+use rust_decimal::prelude::ToPrimitive;
+
 use crate::models::contract::Contract;
 use crate::models::execution::ExecutionFilter;
 use crate::models::order::{Order, OrderCancel, OrderCondition};
 
 // Include the prost-generated protobuf types.
-#[allow(clippy::derive_partial_eq_without_eq)]
+// Decode-only message types are never constructed on the encode side.
+#[allow(clippy::derive_partial_eq_without_eq, dead_code)]
 mod pb {
     include!("generated/protobuf.rs");
 }
@@ -117,7 +120,7 @@ fn contract_to_proto(c: &Contract) -> pb::Contract {
                 legs.iter()
                     .map(|leg| pb::ComboLeg {
                         con_id: Some(leg.con_id as i32),
-                        ratio: Some(leg.ratio as i32),
+                        ratio: leg.ratio.to_i32(),
                         action: leg.action.as_ref().map(|a| a.to_string()),
                         exchange: opt_str(&leg.exchange),
                         open_close: Some(leg.open_close as i32),
@@ -432,5 +435,53 @@ fn condition_to_proto(cond: &OrderCondition) -> pb::OrderCondition {
             time: None,
             volume: None,
         },
+        // The protobuf message has no slot for untyped fields.
+        OrderCondition::Unknown { is_conjunction_connection, raw_type, .. } => {
+            pb::OrderCondition {
+                r#type: Some(*raw_type),
+                is_conjunction_connection: Some(*is_conjunction_connection),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order_cancel() -> OrderCancel {
+        OrderCancel {
+            manual_order_cancel_time: "20260105 09:30:00".into(),
+            ext_operator: "desk7".into(),
+            manual_order_indicator: Some(1),
+        }
+    }
+
+    #[test]
+    fn global_cancel_maps_every_order_cancel_field() {
+        let request = build_global_cancel_request(&order_cancel());
+        let proto = request.order_cancel.unwrap();
+        assert_eq!(proto.manual_order_cancel_time.as_deref(), Some("20260105 09:30:00"));
+        assert_eq!(proto.ext_operator.as_deref(), Some("desk7"));
+        assert_eq!(proto.manual_order_indicator, Some(1));
+
+        // Empty strings and unset indicators are left out of the message.
+        let proto = build_global_cancel_request(&OrderCancel::default()).order_cancel.unwrap();
+        assert_eq!(proto.manual_order_cancel_time, None);
+        assert_eq!(proto.ext_operator, None);
+        assert_eq!(proto.manual_order_indicator, None);
+    }
+
+    #[test]
+    fn cancel_order_maps_id_and_order_cancel() {
+        let request = build_cancel_order_request(42, &order_cancel());
+        assert_eq!(request.order_id, Some(42));
+        let global = build_global_cancel_request(&order_cancel());
+        assert_eq!(request.order_cancel, global.order_cancel);
     }
 }
@@ -4,6 +4,7 @@
 //! and `CommonDefs.h`. These constants define the binary wire protocol between
 //! the client and TWS/Gateway.
 
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -378,6 +379,149 @@ pub mod server_version {
     pub const PROTOBUF_PLACE_ORDER: i32 = 203;
 }
 
+// ============================================================================
+// Feature
+// ============================================================================
+
+/// API features gated on the negotiated server version.
+///
+/// `IBClient::supports` checks a feature against the connected server;
+/// [`min_server_version`](Self::min_server_version) gives the gate itself.
+/// There is no C++ equivalent -- `EClient` compares `m_serverVersion`
+/// against the `MIN_SERVER_VER_*` constants inline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// `req_global_cancel`.
+    GlobalCancel,
+    /// `req_market_data_type`.
+    MarketDataType,
+    /// `calculate_implied_volatility`.
+    CalcImpliedVolatility,
+    /// `cancel_calculate_implied_volatility`.
+    CancelCalcImpliedVolatility,
+    /// `calculate_option_price`.
+    CalcOptionPrice,
+    /// `cancel_calculate_option_price`.
+    CancelCalcOptionPrice,
+    /// `req_fundamental_data` and its cancel.
+    FundamentalData,
+    /// `req_positions` and its cancel.
+    Positions,
+    /// `req_account_summary` and its cancel.
+    AccountSummary,
+    /// Display groups and `verify_request` / `verify_message`.
+    Linking,
+    /// `verify_and_auth_request` / `verify_and_auth_message`.
+    LinkingAuth,
+    /// Fractional order quantities and position sizes.
+    FractionalPositions,
+    /// Model codes: `req_positions_multi`, `req_account_updates_multi`.
+    Models,
+    /// `req_sec_def_opt_params`.
+    SecDefOptParams,
+    /// `req_soft_dollar_tiers`.
+    SoftDollarTiers,
+    /// `req_family_codes`.
+    FamilyCodes,
+    /// `req_matching_symbols`.
+    MatchingSymbols,
+    /// `req_mkt_depth_exchanges`.
+    MktDepthExchanges,
+    /// News ticks on market data streams.
+    TickNews,
+    /// `req_smart_components`.
+    SmartComponents,
+    /// `req_news_providers`.
+    NewsProviders,
+    /// `req_news_article`.
+    NewsArticle,
+    /// `req_historical_news`.
+    HistoricalNews,
+    /// `req_head_timestamp`.
+    HeadTimestamp,
+    /// `req_histogram_data` and its cancel.
+    Histogram,
+    /// `cancel_head_timestamp`.
+    CancelHeadTimestamp,
+    /// `req_market_rule`.
+    MarketRules,
+    /// `req_pnl` / `req_pnl_single` and their cancels.
+    Pnl,
+    /// `req_historical_ticks`.
+    HistoricalTicks,
+    /// `req_tick_by_tick_data` and its cancel.
+    TickByTick,
+    /// `ignore_size` on tick-by-tick requests.
+    TickByTickIgnoreSize,
+    /// Scanner filter options.
+    ScannerGenericOpts,
+    /// Smart-routed market depth.
+    SmartDepth,
+    /// `req_completed_orders`.
+    CompletedOrders,
+    /// Wall Street Horizon meta and event data.
+    WshCalendar,
+    /// Historical data with `WhatToShow::Schedule`.
+    HistoricalSchedule,
+    /// `req_user_info`.
+    UserInfo,
+    /// `req_current_time_in_millis`.
+    CurrentTimeInMillis,
+    /// Protobuf-encoded messages.
+    Protobuf,
+    /// Protobuf-encoded order placement and cancels.
+    ProtobufPlaceOrder,
+}
+
+impl Feature {
+    /// Lowest server version that supports the feature.
+    pub fn min_server_version(self) -> i32 {
+        match self {
+            Self::GlobalCancel => server_version::REQ_GLOBAL_CANCEL,
+            Self::MarketDataType => server_version::REQ_MARKET_DATA_TYPE,
+            Self::CalcImpliedVolatility => server_version::REQ_CALC_IMPLIED_VOLAT,
+            Self::CancelCalcImpliedVolatility => server_version::CANCEL_CALC_IMPLIED_VOLAT,
+            Self::CalcOptionPrice => server_version::REQ_CALC_OPTION_PRICE,
+            Self::CancelCalcOptionPrice => server_version::CANCEL_CALC_OPTION_PRICE,
+            Self::FundamentalData => server_version::FUNDAMENTAL_DATA,
+            Self::Positions => server_version::POSITIONS,
+            Self::AccountSummary => server_version::ACCOUNT_SUMMARY,
+            Self::Linking => server_version::LINKING,
+            Self::LinkingAuth => server_version::LINKING_AUTH,
+            Self::FractionalPositions => server_version::FRACTIONAL_POSITIONS,
+            Self::Models => server_version::MODELS_SUPPORT,
+            Self::SecDefOptParams => server_version::SEC_DEF_OPT_PARAMS_REQ,
+            Self::SoftDollarTiers => server_version::SOFT_DOLLAR_TIER,
+            Self::FamilyCodes => server_version::REQ_FAMILY_CODES,
+            Self::MatchingSymbols => server_version::REQ_MATCHING_SYMBOLS,
+            Self::MktDepthExchanges => server_version::REQ_MKT_DEPTH_EXCHANGES,
+            Self::TickNews => server_version::TICK_NEWS,
+            Self::SmartComponents => server_version::REQ_SMART_COMPONENTS,
+            Self::NewsProviders => server_version::REQ_NEWS_PROVIDERS,
+            Self::NewsArticle => server_version::REQ_NEWS_ARTICLE,
+            Self::HistoricalNews => server_version::REQ_HISTORICAL_NEWS,
+            Self::HeadTimestamp => server_version::REQ_HEAD_TIMESTAMP,
+            Self::Histogram => server_version::REQ_HISTOGRAM,
+            Self::CancelHeadTimestamp => server_version::CANCEL_HEADTIMESTAMP,
+            Self::MarketRules => server_version::MARKET_RULES,
+            Self::Pnl => server_version::PNL,
+            Self::HistoricalTicks => server_version::HISTORICAL_TICKS,
+            Self::TickByTick => server_version::TICK_BY_TICK,
+            Self::TickByTickIgnoreSize => server_version::TICK_BY_TICK_IGNORE_SIZE,
+            Self::ScannerGenericOpts => server_version::SCANNER_GENERIC_OPTS,
+            Self::SmartDepth => server_version::SMART_DEPTH,
+            Self::CompletedOrders => server_version::COMPLETED_ORDERS,
+            Self::WshCalendar => server_version::WSHE_CALENDAR,
+            Self::HistoricalSchedule => server_version::HISTORICAL_SCHEDULE,
+            Self::UserInfo => server_version::USER_INFO,
+            Self::CurrentTimeInMillis => server_version::CURRENT_TIME_IN_MILLIS,
+            Self::Protobuf => server_version::PROTOBUF,
+            Self::ProtobufPlaceOrder => server_version::PROTOBUF_PLACE_ORDER,
+        }
+    }
+}
+
 // ============================================================================
 // TickType Enum
 // ============================================================================
@@ -386,7 +530,8 @@ pub mod server_version {
 ///
 /// Maps to C++ `enum TickType` (106 values, `BID_SIZE`=0 through `NOT_SET`=105).
 /// Ported from `EWrapper.h`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[repr(i32)]
 pub enum TickType {
     BidSize = 0,
@@ -497,6 +642,164 @@ pub enum TickType {
     NotSet = 105,
 }
 
+impl TickType {
+    /// Size tick that accompanies this price tick: `BidSize` for `Bid`,
+    /// and so on for ask, last and their delayed variants. `None` for
+    /// every other tick type.
+    ///
+    /// Mirrors the `sizeTickType` switch in C++ `processTickPriceMsg`.
+    pub fn size_tick(self) -> Option<TickType> {
+        match self {
+            Self::Bid => Some(Self::BidSize),
+            Self::Ask => Some(Self::AskSize),
+            Self::Last => Some(Self::LastSize),
+            Self::DelayedBid => Some(Self::DelayedBidSize),
+            Self::DelayedAsk => Some(Self::DelayedAskSize),
+            Self::DelayedLast => Some(Self::DelayedLastSize),
+            _ => None,
+        }
+    }
+
+    /// Every tick type, in wire-code order.
+    pub fn all() -> impl Iterator<Item = TickType> {
+        (0..=Self::NotSet as i32).filter_map(|code| Self::try_from(code).ok())
+    }
+
+    /// Name of the C++ `TickType` enumerator, e.g. `"BID"` or
+    /// `"OPTION_IMPLIED_VOL"`. Also used by `Display`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::BidSize => "BID_SIZE",
+            Self::Bid => "BID",
+            Self::Ask => "ASK",
+            Self::AskSize => "ASK_SIZE",
+            Self::Last => "LAST",
+            Self::LastSize => "LAST_SIZE",
+            Self::High => "HIGH",
+            Self::Low => "LOW",
+            Self::Volume => "VOLUME",
+            Self::Close => "CLOSE",
+            Self::BidOptionComputation => "BID_OPTION_COMPUTATION",
+            Self::AskOptionComputation => "ASK_OPTION_COMPUTATION",
+            Self::LastOptionComputation => "LAST_OPTION_COMPUTATION",
+            Self::ModelOption => "MODEL_OPTION",
+            Self::Open => "OPEN",
+            Self::Low13Week => "LOW_13_WEEK",
+            Self::High13Week => "HIGH_13_WEEK",
+            Self::Low26Week => "LOW_26_WEEK",
+            Self::High26Week => "HIGH_26_WEEK",
+            Self::Low52Week => "LOW_52_WEEK",
+            Self::High52Week => "HIGH_52_WEEK",
+            Self::AvgVolume => "AVG_VOLUME",
+            Self::OpenInterest => "OPEN_INTEREST",
+            Self::OptionHistoricalVol => "OPTION_HISTORICAL_VOL",
+            Self::OptionImpliedVol => "OPTION_IMPLIED_VOL",
+            Self::OptionBidExch => "OPTION_BID_EXCH",
+            Self::OptionAskExch => "OPTION_ASK_EXCH",
+            Self::OptionCallOpenInterest => "OPTION_CALL_OPEN_INTEREST",
+            Self::OptionPutOpenInterest => "OPTION_PUT_OPEN_INTEREST",
+            Self::OptionCallVolume => "OPTION_CALL_VOLUME",
+            Self::OptionPutVolume => "OPTION_PUT_VOLUME",
+            Self::IndexFuturePremium => "INDEX_FUTURE_PREMIUM",
+            Self::BidExch => "BID_EXCH",
+            Self::AskExch => "ASK_EXCH",
+            Self::AuctionVolume => "AUCTION_VOLUME",
+            Self::AuctionPrice => "AUCTION_PRICE",
+            Self::AuctionImbalance => "AUCTION_IMBALANCE",
+            Self::MarkPrice => "MARK_PRICE",
+            Self::BidEfpComputation => "BID_EFP_COMPUTATION",
+            Self::AskEfpComputation => "ASK_EFP_COMPUTATION",
+            Self::LastEfpComputation => "LAST_EFP_COMPUTATION",
+            Self::OpenEfpComputation => "OPEN_EFP_COMPUTATION",
+            Self::HighEfpComputation => "HIGH_EFP_COMPUTATION",
+            Self::LowEfpComputation => "LOW_EFP_COMPUTATION",
+            Self::CloseEfpComputation => "CLOSE_EFP_COMPUTATION",
+            Self::LastTimestamp => "LAST_TIMESTAMP",
+            Self::Shortable => "SHORTABLE",
+            Self::FundamentalRatios => "FUNDAMENTAL_RATIOS",
+            Self::RtVolume => "RT_VOLUME",
+            Self::Halted => "HALTED",
+            Self::BidYield => "BID_YIELD",
+            Self::AskYield => "ASK_YIELD",
+            Self::LastYield => "LAST_YIELD",
+            Self::CustOptionComputation => "CUST_OPTION_COMPUTATION",
+            Self::TradeCount => "TRADE_COUNT",
+            Self::TradeRate => "TRADE_RATE",
+            Self::VolumeRate => "VOLUME_RATE",
+            Self::LastRthTrade => "LAST_RTH_TRADE",
+            Self::RtHistoricalVol => "RT_HISTORICAL_VOL",
+            Self::IbDividends => "IB_DIVIDENDS",
+            Self::BondFactorMultiplier => "BOND_FACTOR_MULTIPLIER",
+            Self::RegulatoryImbalance => "REGULATORY_IMBALANCE",
+            Self::NewsTick => "NEWS_TICK",
+            Self::ShortTermVolume3Min => "SHORT_TERM_VOLUME_3_MIN",
+            Self::ShortTermVolume5Min => "SHORT_TERM_VOLUME_5_MIN",
+            Self::ShortTermVolume10Min => "SHORT_TERM_VOLUME_10_MIN",
+            Self::DelayedBid => "DELAYED_BID",
+            Self::DelayedAsk => "DELAYED_ASK",
+            Self::DelayedLast => "DELAYED_LAST",
+            Self::DelayedBidSize => "DELAYED_BID_SIZE",
+            Self::DelayedAskSize => "DELAYED_ASK_SIZE",
+            Self::DelayedLastSize => "DELAYED_LAST_SIZE",
+            Self::DelayedHigh => "DELAYED_HIGH",
+            Self::DelayedLow => "DELAYED_LOW",
+            Self::DelayedVolume => "DELAYED_VOLUME",
+            Self::DelayedClose => "DELAYED_CLOSE",
+            Self::DelayedOpen => "DELAYED_OPEN",
+            Self::RtTrdVolume => "RT_TRD_VOLUME",
+            Self::CreditmanMarkPrice => "CREDITMAN_MARK_PRICE",
+            Self::CreditmanSlowMarkPrice => "CREDITMAN_SLOW_MARK_PRICE",
+            Self::DelayedBidOptionComputation => "DELAYED_BID_OPTION_COMPUTATION",
+            Self::DelayedAskOptionComputation => "DELAYED_ASK_OPTION_COMPUTATION",
+            Self::DelayedLastOptionComputation => "DELAYED_LAST_OPTION_COMPUTATION",
+            Self::DelayedModelOptionComputation => "DELAYED_MODEL_OPTION_COMPUTATION",
+            Self::LastExch => "LAST_EXCH",
+            Self::LastRegTime => "LAST_REG_TIME",
+            Self::FuturesOpenInterest => "FUTURES_OPEN_INTEREST",
+            Self::AvgOptVolume => "AVG_OPT_VOLUME",
+            Self::DelayedLastTimestamp => "DELAYED_LAST_TIMESTAMP",
+            Self::ShortableShares => "SHORTABLE_SHARES",
+            Self::DelayedHalted => "DELAYED_HALTED",
+            Self::Reuters2MutualFunds => "REUTERS_2_MUTUAL_FUNDS",
+            Self::EtfNavClose => "ETF_NAV_CLOSE",
+            Self::EtfNavPriorClose => "ETF_NAV_PRIOR_CLOSE",
+            Self::EtfNavBid => "ETF_NAV_BID",
+            Self::EtfNavAsk => "ETF_NAV_ASK",
+            Self::EtfNavLast => "ETF_NAV_LAST",
+            Self::EtfFrozenNavLast => "ETF_FROZEN_NAV_LAST",
+            Self::EtfNavHigh => "ETF_NAV_HIGH",
+            Self::EtfNavLow => "ETF_NAV_LOW",
+            Self::SocialMarketAnalytics => "SOCIAL_MARKET_ANALYTICS",
+            Self::EstimatedIpoMidpoint => "ESTIMATED_IPO_MIDPOINT",
+            Self::FinalIpoLast => "FINAL_IPO_LAST",
+            Self::DelayedYieldBid => "DELAYED_YIELD_BID",
+            Self::DelayedYieldAsk => "DELAYED_YIELD_ASK",
+            Self::NotSet => "NOT_SET",
+        }
+    }
+
+    /// Whether this is one of the `DELAYED_*` tick types sent for delayed
+    /// (market data type 3 or 4) data.
+    pub fn is_delayed(self) -> bool {
+        matches!(
+            self,
+            Self::DelayedBid | Self::DelayedAsk | Self::DelayedLast | Self::DelayedBidSize |
+                Self::DelayedAskSize | Self::DelayedLastSize | Self::DelayedHigh |
+                Self::DelayedLow | Self::DelayedVolume | Self::DelayedClose |
+                Self::DelayedOpen | Self::DelayedBidOptionComputation |
+                Self::DelayedAskOptionComputation | Self::DelayedLastOptionComputation |
+                Self::DelayedModelOptionComputation | Self::DelayedLastTimestamp |
+                Self::DelayedHalted | Self::DelayedYieldBid | Self::DelayedYieldAsk
+        )
+    }
+}
+
+impl std::fmt::Display for TickType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
 impl TryFrom<i32> for TickType {
     type Error = i32;
 
@@ -661,6 +964,17 @@ mod tests {
         assert_eq!(i32::from(TickType::NotSet), 105);
     }
 
+    #[test]
+    fn tick_type_round_trips_every_value() {
+        // The enum has no gaps, so every C++ tick type 0..=105 must convert
+        // both ways -- in particular the DELAYED_* values sent for delayed
+        // market data.
+        for raw in 0..=105 {
+            let tt = TickType::try_from(raw).unwrap_or_else(|e| panic!("tick type {e} missing"));
+            assert_eq!(i32::from(tt), raw);
+        }
+    }
+
     #[test]
     fn protocol_constants_sanity() {
         assert_eq!(CLIENT_VERSION, 66);
@@ -688,4 +1002,23 @@ mod tests {
         assert_eq!(outgoing::PLACE_ORDER, 3);
         assert_eq!(outgoing::START_API, 71);
     }
+
+    #[test]
+    fn tick_type_names_match_cpp_enumerators() {
+        assert_eq!(TickType::Bid.name(), "BID");
+        assert_eq!(TickType::Ask.name(), "ASK");
+        assert_eq!(TickType::Last.name(), "LAST");
+        assert_eq!(TickType::OptionImpliedVol.name(), "OPTION_IMPLIED_VOL");
+        assert_eq!(TickType::DelayedBid.name(), "DELAYED_BID");
+        assert_eq!(TickType::DelayedYieldAsk.name(), "DELAYED_YIELD_ASK");
+        assert_eq!(TickType::NotSet.to_string(), "NOT_SET");
+
+        assert!(TickType::DelayedLastSize.is_delayed());
+        assert!(TickType::DelayedModelOptionComputation.is_delayed());
+        assert!(!TickType::Last.is_delayed());
+        assert!(!TickType::RtTrdVolume.is_delayed());
+
+        assert_eq!(TickType::all().count(), 106);
+        assert_eq!(TickType::all().filter(|t| t.is_delayed()).count(), 19);
+    }
 }
@@ -6,14 +6,130 @@
 //! Replaces C++ `EReader` (pthread + message queue + signal mechanism)
 //! with Rust async/await + tokio::spawn + mpsc channel.
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
-use crate::decoder::decode_server_msg;
+use crate::channel::{self, BoundedReceiver, EventSink, OverflowPolicy};
+use crate::decoder::decode_server_msgs;
 use crate::errors::IBApiError;
+use crate::keepalive::Heartbeat;
+use crate::pending::PendingRequests;
+use crate::state::{ConnectionState, ConnectionStatus};
 use crate::transport::TransportReader;
 use crate::wrapper::IBEvent;
 
+// ============================================================================
+// AccountsCache
+// ============================================================================
+
+/// Accounts from the last `ManagedAccounts` event.
+///
+/// TWS sends the list right after START_API (and again after a reconnect);
+/// the reader task records it here so `IBClient` can answer without a round
+/// trip.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct AccountsCache {
+    accounts: Arc<RwLock<Vec<String>>>,
+}
+
+impl AccountsCache {
+    /// Record `event` if it is a `ManagedAccounts` list.
+    pub(crate) fn observe(&self, event: &IBEvent) {
+        if let IBEvent::ManagedAccounts { accounts } = event {
+            *self.accounts.write().unwrap_or_else(|e| e.into_inner()) =
+                parse_accounts_list(accounts);
+        }
+    }
+
+    pub(crate) fn get(&self) -> Vec<String> {
+        self.accounts.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Split a comma-joined account list, e.g. `"DU123,DU456,"`.
+fn parse_accounts_list(accounts: &str) -> Vec<String> {
+    accounts
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// ============================================================================
+// OrderIds
+// ============================================================================
+
+/// The client-side order ID sequence, seeded from `NextValidId`.
+///
+/// TWS keeps a single order ID sequence per client ID: every order placed
+/// on the connection must use an ID above all earlier ones, and
+/// `NextValidId` reports the next free one. The reader task raises the
+/// sequence to each `NextValidId` it sees (never lowers it), so IDs handed
+/// out by [`next`](Self::next) stay monotonic across `req_ids` calls and
+/// reconnects.
+#[derive(Debug, Clone)]
+pub(crate) struct OrderIds {
+    next: Arc<AtomicI64>,
+}
+
+impl Default for OrderIds {
+    fn default() -> Self {
+        Self { next: Arc::new(AtomicI64::new(1)) }
+    }
+}
+
+impl OrderIds {
+    /// Raise the sequence if `event` is a `NextValidId` ahead of it.
+    pub(crate) fn observe(&self, event: &IBEvent) {
+        if let IBEvent::NextValidId { order_id } = event {
+            self.next.fetch_max(*order_id, Ordering::Relaxed);
+        }
+    }
+
+    /// Take the next order ID.
+    pub(crate) fn next(&self) -> i64 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+// ============================================================================
+// DepthFlags
+// ============================================================================
+
+/// `is_smart_depth` of each open `req_mkt_depth`, by ticker ID.
+///
+/// TWS rejects a `CANCEL_MKT_DEPTH` whose flag differs from the request's,
+/// so `IBClient::cancel_mkt_depth` looks it up here. The reader task clears
+/// the map when the connection ends, along with the pending requests, and
+/// the reconnect supervisor also clears it on each new connection.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DepthFlags {
+    flags: Arc<Mutex<HashMap<i32, bool>>>,
+}
+
+impl DepthFlags {
+    pub(crate) fn insert(&self, ticker_id: i32, is_smart_depth: bool) {
+        self.lock().insert(ticker_id, is_smart_depth);
+    }
+
+    pub(crate) fn remove(&self, ticker_id: i32) -> Option<bool> {
+        self.lock().remove(&ticker_id)
+    }
+
+    pub(crate) fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<i32, bool>> {
+        self.flags.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
 // ============================================================================
 // MessageReader
 // ============================================================================
@@ -22,7 +138,7 @@ use crate::wrapper::IBEvent;
 ///
 /// Reads complete framed messages from the `TransportReader`, decodes them
 /// into `IBEvent` variants, and sends them to the consumer through an
-/// unbounded mpsc channel.
+/// unbounded mpsc channel (or a bounded one, see [`spawn_bounded`](Self::spawn_bounded)).
 ///
 /// ## Usage
 ///
@@ -39,6 +155,13 @@ use crate::wrapper::IBEvent;
 pub struct MessageReader {
     transport_reader: TransportReader,
     server_version: i32,
+    pending: Option<PendingRequests>,
+    heartbeat: Option<Heartbeat>,
+    accounts: Option<AccountsCache>,
+    order_ids: Option<OrderIds>,
+    depth_flags: Option<DepthFlags>,
+    state: Option<ConnectionStatus>,
+    synthetic_tick_size: bool,
 }
 
 impl MessageReader {
@@ -47,56 +170,177 @@ impl MessageReader {
         Self {
             transport_reader,
             server_version,
+            pending: None,
+            heartbeat: None,
+            accounts: None,
+            order_ids: None,
+            depth_flags: None,
+            state: None,
+            synthetic_tick_size: false,
         }
     }
 
+    /// Route responses for registered request IDs into `pending` instead of
+    /// the event channel. Events nobody is waiting for still go to the channel.
+    pub fn with_pending(mut self, pending: PendingRequests) -> Self {
+        self.pending = Some(pending);
+        self
+    }
+
+    /// Record keepalive answers in `heartbeat` (see `IBClient::start_keepalive`).
+    pub(crate) fn with_heartbeat(mut self, heartbeat: Heartbeat) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Record the account list of `ManagedAccounts` events in `accounts`
+    /// (see `IBClient::managed_accounts`).
+    pub(crate) fn with_accounts(mut self, accounts: AccountsCache) -> Self {
+        self.accounts = Some(accounts);
+        self
+    }
+
+    /// Seed `order_ids` from `NextValidId` events (see
+    /// `IBClient::next_order_id`).
+    pub(crate) fn with_order_ids(mut self, order_ids: OrderIds) -> Self {
+        self.order_ids = Some(order_ids);
+        self
+    }
+
+    /// Forget the market depth flags in `depth_flags` when the connection
+    /// ends (see `IBClient::cancel_mkt_depth`).
+    pub(crate) fn with_depth_flags(mut self, depth_flags: DepthFlags) -> Self {
+        self.depth_flags = Some(depth_flags);
+        self
+    }
+
+    /// Move `state` from `Connected` to `Disconnected` when the server
+    /// closes the connection (see `IBClient::connection_state`).
+    pub(crate) fn with_state(mut self, state: ConnectionStatus) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Follow each bid, ask or last `TickPrice` with the `TickSize` event
+    /// C++ `EDecoder` synthesizes from it (see
+    /// [`decode_server_msgs`](crate::decoder::decode_server_msgs)). Off by
+    /// default.
+    pub fn with_synthetic_tick_size(mut self, enabled: bool) -> Self {
+        self.synthetic_tick_size = enabled;
+        self
+    }
+
     /// Spawn the reader task and return the event receiver + task handle.
     ///
-    /// The spawned task runs until the connection closes or the receiver
-    /// is dropped. Returns:
+    /// The spawned task runs until the connection closes. Dropping the
+    /// receiver does not stop it; unrouted events are then discarded.
+    /// Returns:
     /// - An unbounded receiver for consuming `IBEvent`s
     /// - A `JoinHandle` for waiting on or aborting the reader task
     pub fn spawn(self) -> (mpsc::UnboundedReceiver<IBEvent>, JoinHandle<()>) {
         let (tx, rx) = mpsc::unbounded_channel();
+        (rx, self.spawn_with(tx.into()))
+    }
 
-        let handle = tokio::spawn(async move {
-            self.run(tx).await;
-        });
+    /// Spawn the reader task with a bounded event channel of `capacity`
+    /// events.
+    ///
+    /// When the consumer falls behind, `policy` decides whether the oldest or
+    /// newest event is dropped, or whether the reader stops reading from the
+    /// socket until there is room (TCP flow control then throttles TWS).
+    /// Dropped events are logged and counted in
+    /// [`BoundedReceiver::dropped_events`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn spawn_bounded(
+        self,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> (BoundedReceiver, JoinHandle<()>) {
+        let (tx, rx) = channel::bounded(capacity, policy, Arc::default());
+        (rx, self.spawn_with(EventSink::Bounded(tx)))
+    }
 
-        (rx, handle)
+    /// Spawn the reader task, sending events into an existing channel.
+    pub(crate) fn spawn_with(self, tx: EventSink) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            self.run(tx).await;
+        })
     }
 
-    /// Main read loop. Runs until connection closes or receiver is dropped.
-    async fn run(mut self, tx: mpsc::UnboundedSender<IBEvent>) {
+    /// Main read loop. Runs until the connection closes.
+    ///
+    /// Dropping the event receiver does not stop it: events routed to
+    /// pending requests and subscriptions keep flowing, the rest are
+    /// discarded.
+    async fn run(mut self, tx: EventSink) {
+        let mut receiver_alive = true;
         loop {
             match self.transport_reader.read_message().await {
                 Ok(msg) => {
-                    let event = decode_server_msg(&msg, self.server_version);
-                    if tx.send(event).is_err() {
-                        // Receiver dropped — stop reading
-                        tracing::debug!("event receiver dropped, reader stopping");
-                        break;
+                    let events =
+                        decode_server_msgs(&msg, self.server_version, self.synthetic_tick_size);
+                    for event in events {
+                        if let Some(heartbeat) = &self.heartbeat {
+                            heartbeat.observe(&event);
+                        }
+                        if let Some(accounts) = &self.accounts {
+                            accounts.observe(&event);
+                        }
+                        if let Some(order_ids) = &self.order_ids {
+                            order_ids.observe(&event);
+                        }
+                        let event = match &self.pending {
+                            Some(pending) => match pending.dispatch(event) {
+                                Some(event) => event,
+                                None => continue,
+                            },
+                            None => event,
+                        };
+                        if receiver_alive && !tx.send(event).await {
+                            tracing::debug!("event receiver dropped, discarding unrouted events");
+                            receiver_alive = false;
+                        }
                     }
                 }
                 Err(IBApiError::Disconnected(reason)) => {
                     tracing::info!("server disconnected: {reason}");
-                    let _ = tx.send(IBEvent::ConnectionClosed);
+                    self.mark_disconnected();
+                    tx.send(IBEvent::ConnectionClosed).await;
                     break;
                 }
                 Err(e) => {
                     tracing::error!("reader error: {e}");
-                    let _ = tx.send(IBEvent::Error {
+                    tx.send(IBEvent::Error {
                         req_id: -1,
                         error_time: 0,
                         code: 0,
                         message: format!("reader error: {e}"),
                         advanced_order_reject_json: String::new(),
-                    });
-                    let _ = tx.send(IBEvent::ConnectionClosed);
+                    })
+                    .await;
+                    self.mark_disconnected();
+                    tx.send(IBEvent::ConnectionClosed).await;
                     break;
                 }
             }
         }
+
+        // Wake any collectors still waiting on a response.
+        if let Some(pending) = &self.pending {
+            pending.clear();
+        }
+        if let Some(depth_flags) = &self.depth_flags {
+            depth_flags.clear();
+        }
+    }
+
+    fn mark_disconnected(&self) {
+        if let Some(state) = &self.state {
+            state.replace_if(ConnectionState::Connected, ConnectionState::Disconnected);
+        }
     }
 }
 
@@ -107,64 +351,22 @@ impl MessageReader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
-    use tokio::net::TcpListener;
-
-    /// Build a framed message from null-terminated fields.
-    fn build_framed_msg(fields: &[&str]) -> Vec<u8> {
-        let mut body = Vec::new();
-        for f in fields {
-            body.extend_from_slice(f.as_bytes());
-            body.push(0);
-        }
-        let mut frame = Vec::new();
-        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
-        frame.extend(body);
-        frame
-    }
-
-    /// Create a mock TWS that completes handshake and sends given messages.
-    async fn mock_tws_with_messages(
-        sv: i32,
-        messages: Vec<Vec<u8>>,
-    ) -> u16 {
-        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
-        let port = listener.local_addr().unwrap().port();
-
-        tokio::spawn(async move {
-            let (mut stream, _) = listener.accept().await.unwrap();
-
-            // Read connect request
-            let mut buf = vec![0u8; 512];
-            let _ = stream.read(&mut buf).await.unwrap();
-
-            // Send handshake response
-            let handshake = build_framed_msg(&[&sv.to_string(), "20260101 12:00:00"]);
-            stream.write_all(&handshake).await.unwrap();
-
-            // Read start_api
-            let _ = stream.read(&mut buf).await.unwrap();
+    use crate::testutil::{frame, MockTws};
 
-            // Send test messages
-            for msg in messages {
-                stream.write_all(&msg).await.unwrap();
-            }
-
-            // Close connection (reader will get EOF)
-            drop(stream);
-        });
-
-        tokio::task::yield_now().await;
-        port
+    /// Create a mock TWS that completes handshake, sends the given messages
+    /// and closes the connection.
+    async fn mock_tws_with_messages(sv: i32, messages: Vec<Vec<u8>>) -> u16 {
+        let builder = MockTws::builder().server_version(sv).close_after_messages();
+        messages.into_iter().fold(builder, |b, msg| b.message(msg)).start().await.port()
     }
 
     #[tokio::test]
     async fn reader_receives_events() {
         let messages = vec![
             // NEXT_VALID_ID: msg_id=9, version=1, orderId=100
-            build_framed_msg(&["9", "1", "100"]),
+            frame(&["9", "1", "100"]),
             // MANAGED_ACCTS: msg_id=15, version=1, accounts="DU123"
-            build_framed_msg(&["15", "1", "DU123"]),
+            frame(&["15", "1", "DU123"]),
         ];
 
         let port = mock_tws_with_messages(176, messages).await;
@@ -202,6 +404,38 @@ mod tests {
         handle.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn reader_survives_truncated_message() {
+        let messages = vec![
+            // OPEN_ORDER cut off after the order id and symbol
+            frame(&["5", "100", "0", "AAPL"]),
+            frame(&["9", "1", "101"]),
+        ];
+
+        let port = mock_tws_with_messages(176, messages).await;
+
+        let mut transport =
+            crate::transport::Transport::connect("127.0.0.1", port, None)
+                .await
+                .unwrap();
+        transport.start_api(0, None).await.unwrap();
+        let sv = transport.server_version();
+        let (reader_half, _writer_half) = transport.into_split();
+
+        let (mut rx, handle) = MessageReader::new(reader_half, sv).spawn();
+
+        match rx.recv().await.unwrap() {
+            IBEvent::Unknown { msg_id, .. } => assert_eq!(msg_id, -1),
+            other => panic!("expected Unknown, got {other:?}"),
+        }
+        match rx.recv().await.unwrap() {
+            IBEvent::NextValidId { order_id } => assert_eq!(order_id, 101),
+            other => panic!("expected NextValidId, got {other:?}"),
+        }
+
+        handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn reader_sends_connection_closed_on_disconnect() {
         // Empty messages = server immediately closes
@@ -229,11 +463,12 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn reader_stops_when_receiver_dropped() {
+    async fn reader_keeps_routing_after_receiver_dropped() {
         let messages = vec![
-            build_framed_msg(&["9", "1", "100"]),
-            build_framed_msg(&["9", "1", "101"]),
-            build_framed_msg(&["9", "1", "102"]),
+            frame(&["9", "1", "100"]),
+            frame(&["9", "1", "101"]),
+            frame(&["52", "1", "7"]), // CONTRACT_DATA_END
+            frame(&["9", "1", "102"]),
         ];
 
         let port = mock_tws_with_messages(176, messages).await;
@@ -246,13 +481,19 @@ mod tests {
         let sv = transport.server_version();
         let (reader_half, _writer_half) = transport.into_split();
 
-        let reader = MessageReader::new(reader_half, sv);
+        let pending = PendingRequests::new();
+        let mut details = pending.register(7);
+        let order_ids = OrderIds::default();
+        let reader = MessageReader::new(reader_half, sv)
+            .with_pending(pending)
+            .with_order_ids(order_ids.clone());
         let (rx, handle) = reader.spawn();
-
-        // Drop the receiver immediately
         drop(rx);
 
-        // Reader task should finish cleanly
+        // The reader reads on until the server closes the connection,
+        // feeding the pending request and the order ID observer.
         handle.await.unwrap();
+        assert!(matches!(details.try_recv(), Ok(IBEvent::ContractDetailsEnd { req_id: 7 })));
+        assert_eq!(order_ids.next(), 102);
     }
 }
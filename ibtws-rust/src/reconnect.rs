@@ -0,0 +1,425 @@
+//! Automatic reconnection for `IBClient`.
+//!
+//! When a client is created with `IBClient::connect_with_reconnect`, the
+//! reader task is wrapped in a supervisor. If the server closes the
+//! connection without the client asking for it, the supervisor re-runs the
+//! TCP connect + handshake + START_API sequence with exponential backoff,
+//! swaps the new write half into the client, replays the recorded streaming
+//! subscriptions, and emits `IBEvent::ConnectionReestablished`.
+//!
+//! There is no C++ equivalent -- `EClientSocket` leaves reconnection to the
+//! application.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rust_decimal::Decimal;
+use tokio::task::JoinHandle;
+
+use crate::channel::EventSink;
+use crate::errors::Result;
+use crate::keepalive::Heartbeat;
+use crate::pending::PendingRequests;
+use crate::reader::{AccountsCache, DepthFlags, MessageReader, OrderIds};
+use crate::state::{ConnectionState, ConnectionStatus};
+use crate::subscription::SharedWriter;
+use crate::transport::{Transport, TransportConfig, TransportReader};
+use crate::wrapper::IBEvent;
+
+// ============================================================================
+// ReconnectPolicy
+// ============================================================================
+
+/// How `IBClient` retries after an unexpected disconnect.
+///
+/// The delay before attempt `n` (0-based) is `base_backoff * 2^n`, capped at
+/// `max_backoff`, then stretched by a random factor in `[1, 1 + jitter)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Attempts before giving up and emitting `ConnectionClosed`.
+    pub max_retries: u32,
+    /// Delay before the first attempt.
+    pub base_backoff: Duration,
+    /// Upper bound on the delay between attempts (before jitter).
+    pub max_backoff: Duration,
+    /// Random stretch applied to each delay, as a fraction (0.0 = none).
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            jitter: 0.2,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before reconnect attempt `attempt` (0-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt);
+        let delay = self.base_backoff.saturating_mul(factor).min(self.max_backoff);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        // Cheap randomness is enough to keep several clients from
+        // reconnecting in lockstep.
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let r = f64::from(nanos % 1_000_000) / 1_000_000.0;
+        delay.mul_f64(1.0 + self.jitter * r)
+    }
+}
+
+// ============================================================================
+// ReplayLog
+// ============================================================================
+
+/// Streaming request that is re-sent after a reconnect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum ReplayKey {
+    MktData(i32),
+    Positions,
+    AccountUpdates,
+}
+
+/// Encoded requests for the subscriptions that are currently active.
+///
+/// `IBClient` records a request when it starts a subscription and removes
+/// it when the subscription is cancelled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ReplayLog {
+    inner: Arc<Mutex<HashMap<ReplayKey, Vec<u8>>>>,
+}
+
+impl ReplayLog {
+    pub(crate) fn record(&self, key: ReplayKey, msg: &[u8]) {
+        self.lock().insert(key, msg.to_vec());
+    }
+
+    pub(crate) fn remove(&self, key: ReplayKey) {
+        self.lock().remove(&key);
+    }
+
+    fn messages(&self) -> Vec<Vec<u8>> {
+        self.lock().values().cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ReplayKey, Vec<u8>>> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ============================================================================
+// OrderTracker
+// ============================================================================
+
+/// Last known state of an order, from its most recent `OrderStatus`.
+///
+/// Returned by `IBClient::order_state`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrder {
+    pub perm_id: i64,
+    pub order_id: i64,
+    pub client_id: i32,
+    pub status: String,
+    pub filled: Decimal,
+    pub remaining: Decimal,
+    pub avg_fill_price: f64,
+}
+
+/// Current state of every order seen on the connection, keyed by `perm_id`.
+///
+/// After START_API, TWS replays the current status of every open order,
+/// which would otherwise show up as duplicate updates after a reconnect.
+/// Between `ConnectionReestablished` and the first `OpenOrderEnd`, the
+/// supervisor drops `OrderStatus` events whose `(status, filled,
+/// remaining)` match the tracked state. Outside that window every status is
+/// delivered, so answers to `req_open_orders` are complete. Statuses without
+/// a `perm_id` (0) are neither tracked nor dropped.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OrderTracker {
+    orders: Arc<Mutex<HashMap<i64, TrackedOrder>>>,
+}
+
+impl OrderTracker {
+    /// Record `event` if it is an `OrderStatus`. Returns true if it only
+    /// repeats the tracked state of its order.
+    pub(crate) fn observe(&self, event: &IBEvent) -> bool {
+        let IBEvent::OrderStatus {
+            order_id,
+            status,
+            filled,
+            remaining,
+            avg_fill_price,
+            perm_id,
+            client_id,
+            ..
+        } = event
+        else {
+            return false;
+        };
+        if *perm_id == 0 {
+            return false;
+        }
+        let order = TrackedOrder {
+            perm_id: *perm_id,
+            order_id: *order_id,
+            client_id: *client_id,
+            status: status.clone(),
+            filled: *filled,
+            remaining: *remaining,
+            avg_fill_price: *avg_fill_price,
+        };
+        let previous = self.lock().insert(*perm_id, order);
+        previous.is_some_and(|p| {
+            p.status == *status && p.filled == *filled && p.remaining == *remaining
+        })
+    }
+
+    pub(crate) fn get(&self, perm_id: i64) -> Option<TrackedOrder> {
+        self.lock().get(&perm_id).cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<i64, TrackedOrder>> {
+        self.orders.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ============================================================================
+// Supervisor
+// ============================================================================
+
+/// Connection state shared between `IBClient` and the supervisor task.
+pub(crate) struct Supervisor {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) client_id: i32,
+    pub(crate) optional_capabilities: Option<String>,
+    pub(crate) transport_config: TransportConfig,
+    pub(crate) policy: ReconnectPolicy,
+    pub(crate) writer: SharedWriter,
+    pub(crate) pending: PendingRequests,
+    pub(crate) replay: ReplayLog,
+    pub(crate) heartbeat: Heartbeat,
+    pub(crate) accounts: AccountsCache,
+    pub(crate) order_ids: OrderIds,
+    pub(crate) depth_flags: DepthFlags,
+    pub(crate) orders: OrderTracker,
+    pub(crate) server_version: Arc<AtomicI32>,
+    pub(crate) state: ConnectionStatus,
+    pub(crate) closing: Arc<AtomicBool>,
+}
+
+impl Supervisor {
+    /// Spawn the supervisor task, starting from an already-connected reader.
+    pub(crate) fn spawn_with(
+        self,
+        reader: TransportReader,
+        tx: EventSink,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            self.run(reader, tx).await;
+        })
+    }
+
+    async fn run(self, mut reader: TransportReader, tx: EventSink) {
+        let mut receiver_alive = true;
+        // Set from a reconnect until TWS has finished replaying open orders.
+        let mut replaying = false;
+        loop {
+            let sv = self.server_version.load(Ordering::Relaxed);
+            let (mut events, handle) = MessageReader::new(reader, sv)
+                .with_heartbeat(self.heartbeat.clone())
+                .with_accounts(self.accounts.clone())
+                .with_order_ids(self.order_ids.clone())
+                .spawn();
+
+            while let Some(event) = events.recv().await {
+                if matches!(event, IBEvent::ConnectionClosed) {
+                    break;
+                }
+                // Track every status, including those routed to order handles.
+                let repeated = self.orders.observe(&event);
+                if matches!(event, IBEvent::OpenOrderEnd) {
+                    replaying = false;
+                } else if replaying && repeated {
+                    continue;
+                }
+                let Some(event) = self.pending.dispatch(event) else {
+                    continue;
+                };
+                if receiver_alive && !tx.send(event).await {
+                    tracing::debug!("event receiver dropped, discarding unrouted events");
+                    receiver_alive = false;
+                }
+            }
+            let _ = handle.await;
+
+            if self.closing.load(Ordering::Relaxed) {
+                break;
+            }
+            self.set_state(ConnectionState::Reconnecting);
+            tracing::warn!("connection lost, attempting to reconnect");
+
+            match self.reconnect().await {
+                Some(new_reader) => {
+                    reader = new_reader;
+                    // Depth streams are not replayed, so their flags are stale.
+                    self.depth_flags.clear();
+                    replaying = true;
+                    let server_version = self.server_version.load(Ordering::Relaxed);
+                    self.set_state(ConnectionState::Connected);
+                    tx.send(IBEvent::ConnectionReestablished { server_version }).await;
+                    self.replay_subscriptions().await;
+                }
+                None => {
+                    self.set_state(ConnectionState::Failed);
+                    break;
+                }
+            }
+        }
+
+        self.pending.clear();
+        self.depth_flags.clear();
+        tx.send(IBEvent::ConnectionClosed).await;
+    }
+
+    /// Retry the connection according to the policy. Returns the new read
+    /// half on success, after the new write half has been installed.
+    async fn reconnect(&self) -> Option<TransportReader> {
+        for attempt in 0..self.policy.max_retries {
+            tokio::time::sleep(self.policy.backoff(attempt)).await;
+            if self.closing.load(Ordering::Relaxed) {
+                return None;
+            }
+            match self.try_connect().await {
+                Ok(reader) => {
+                    tracing::info!(attempt, "reconnected");
+                    return Some(reader);
+                }
+                Err(e) => {
+                    tracing::warn!(attempt, "reconnect failed: {e}");
+                    self.set_state(ConnectionState::Reconnecting);
+                }
+            }
+        }
+        tracing::error!(
+            max_retries = self.policy.max_retries,
+            "giving up on reconnect"
+        );
+        None
+    }
+
+    async fn try_connect(&self) -> Result<TransportReader> {
+        self.set_state(ConnectionState::Connecting);
+        let mut transport =
+            Transport::connect_observed(&self.host, self.port, &self.transport_config, || {
+                self.set_state(ConnectionState::Handshaking);
+            })
+            .await?;
+        transport.start_api(self.client_id, self.optional_capabilities.as_deref()).await?;
+        let previous = self.server_version.swap(transport.server_version(), Ordering::Relaxed);
+        if previous != transport.server_version() {
+            tracing::warn!(
+                previous,
+                current = transport.server_version(),
+                "server version changed across reconnect"
+            );
+        }
+        let (reader, writer) = transport.into_split();
+        *self.writer.lock().await = writer;
+        Ok(reader)
+    }
+
+    /// Publish `state` unless `disconnect` has taken over.
+    fn set_state(&self, state: ConnectionState) {
+        if !self.closing.load(Ordering::Relaxed) {
+            self.state.set(state);
+        }
+    }
+
+    async fn replay_subscriptions(&self) {
+        for msg in self.replay.messages() {
+            if let Err(e) = self.writer.lock().await.send_message(&msg).await {
+                tracing::warn!("failed to replay subscription: {e}");
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_and_caps() {
+        let policy = ReconnectPolicy {
+            max_retries: 10,
+            base_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            jitter: 0.0,
+        };
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn backoff_jitter_stays_in_range() {
+        let policy = ReconnectPolicy {
+            base_backoff: Duration::from_millis(100),
+            jitter: 0.5,
+            ..ReconnectPolicy::default()
+        };
+        let delay = policy.backoff(0);
+        assert!(delay >= Duration::from_millis(100));
+        assert!(delay < Duration::from_millis(150));
+    }
+
+    #[test]
+    fn order_tracker_drops_repeats_only() {
+        let status = |status: &str, filled: i64, avg_fill_price: f64, perm_id: i64| {
+            IBEvent::OrderStatus {
+                order_id: 1,
+                status: status.into(),
+                filled: Decimal::from(filled),
+                remaining: Decimal::from(10 - filled),
+                avg_fill_price,
+                perm_id,
+                parent_id: 0,
+                last_fill_price: 0.0,
+                client_id: 0,
+                why_held: String::new(),
+                mkt_cap_price: None,
+            }
+        };
+        let tracker = OrderTracker::default();
+        assert!(!tracker.observe(&status("Submitted", 0, 0.0, 42)));
+        assert!(tracker.observe(&status("Submitted", 0, 0.0, 42)));
+        assert!(!tracker.observe(&status("Submitted", 5, 1.5, 42)));
+        // Only (status, filled, remaining) count as a change.
+        assert!(tracker.observe(&status("Submitted", 5, 1.25, 42)));
+        assert_eq!(tracker.get(42).unwrap().avg_fill_price, 1.25);
+        // Same state, different order.
+        assert!(!tracker.observe(&status("Submitted", 5, 1.25, 43)));
+        // No perm_id: never dropped.
+        assert!(!tracker.observe(&status("Submitted", 0, 0.0, 0)));
+        assert!(!tracker.observe(&status("Submitted", 0, 0.0, 0)));
+        assert_eq!(tracker.get(0), None);
+        assert!(!tracker.observe(&IBEvent::ConnectionClosed));
+    }
+}
@@ -0,0 +1,346 @@
+//! Catalog of the market scanner parameters.
+//!
+//! `IBEvent::ScannerParameters` (the answer to `req_scanner_parameters`)
+//! carries a large XML document describing every instrument, location and
+//! scan type TWS supports. [`ScannerParameters::parse`] extracts the values
+//! a [`ScannerSubscription`](crate::ScannerSubscription) needs --
+//! `instrument`, `location_code` and `scan_code` -- so valid combinations
+//! can be looked up instead of hard-coded. Filters and display settings in
+//! the document are skipped.
+//!
+//! There is no C++ equivalent -- `EWrapper::scannerParameters` passes the
+//! XML string through unchanged. Requires the `xml` feature.
+
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use crate::errors::{IBApiError, Result};
+use crate::models::scanner::ScannerSubscription;
+
+// ============================================================================
+// Catalog entries
+// ============================================================================
+
+/// An `<Instrument>` of the `InstrumentList`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScannerInstrument {
+    /// Display name, e.g. `"US Stocks"`.
+    pub name: String,
+    /// Value for `ScannerSubscription::instrument`, e.g. `"STK"`.
+    pub instrument_type: String,
+}
+
+/// A `<Location>` of the `LocationTree`. Nested locations are listed after
+/// their parent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScannerLocation {
+    pub display_name: String,
+    /// Value for `ScannerSubscription::location_code`, e.g. `"STK.US.MAJOR"`.
+    pub location_code: String,
+    /// Instrument types available at this location.
+    pub instruments: Vec<String>,
+}
+
+/// A `<ScanType>` of the `ScanTypeList`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanType {
+    pub display_name: String,
+    /// Value for `ScannerSubscription::scan_code`, e.g. `"TOP_PERC_GAIN"`.
+    pub scan_code: String,
+    /// Instrument types the scan supports.
+    pub instruments: Vec<String>,
+}
+
+// ============================================================================
+// ScannerParameters
+// ============================================================================
+
+/// Instruments, locations and scan types from a scanner parameters XML
+/// document, in document order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScannerParameters {
+    pub instruments: Vec<ScannerInstrument>,
+    pub locations: Vec<ScannerLocation>,
+    pub scan_types: Vec<ScanType>,
+}
+
+impl ScannerParameters {
+    /// Parse the `xml` of an `IBEvent::ScannerParameters` event.
+    pub fn parse(xml: &str) -> Result<Self> {
+        let mut reader = Reader::from_str(xml);
+        let mut params = Self::default();
+        // Open elements, and the index of each open `<Location>` in
+        // `params.locations` (the tree nests them).
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut open_locations: Vec<usize> = Vec::new();
+
+        loop {
+            let event = reader.read_event().map_err(|e| {
+                IBApiError::Decoding(format!(
+                    "invalid scanner parameters XML at byte {}: {e}",
+                    reader.error_position()
+                ))
+            })?;
+            match event {
+                Event::Start(start) => {
+                    let name = start.name().as_ref().to_vec();
+                    match name.as_slice() {
+                        b"Instrument" => params.instruments.push(ScannerInstrument::default()),
+                        b"Location" => {
+                            open_locations.push(params.locations.len());
+                            params.locations.push(ScannerLocation::default());
+                        }
+                        b"ScanType" => params.scan_types.push(ScanType::default()),
+                        _ => {}
+                    }
+                    path.push(name);
+                }
+                Event::End(end) => {
+                    path.pop();
+                    if end.name().as_ref() == b"Location" {
+                        open_locations.pop();
+                    }
+                }
+                Event::Text(text) => {
+                    let [.., parent, field] = path.as_slice() else {
+                        continue;
+                    };
+                    let value = text
+                        .unescape()
+                        .map_err(|e| {
+                            IBApiError::Decoding(format!("invalid scanner parameters XML: {e}"))
+                        })?
+                        .trim()
+                        .to_string();
+                    params.set_field(parent, field, value, open_locations.last().copied());
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(params)
+    }
+
+    /// Store the text of `<parent><field>` in the entry being read.
+    fn set_field(&mut self, parent: &[u8], field: &[u8], value: String, location: Option<usize>) {
+        match (parent, field) {
+            (b"Instrument", b"name") => {
+                if let Some(i) = self.instruments.last_mut() {
+                    i.name = value;
+                }
+            }
+            (b"Instrument", b"type") => {
+                if let Some(i) = self.instruments.last_mut() {
+                    i.instrument_type = value;
+                }
+            }
+            (b"Location", _) => {
+                let Some(l) = location.and_then(|i| self.locations.get_mut(i)) else {
+                    return;
+                };
+                match field {
+                    b"displayName" => l.display_name = value,
+                    b"locationCode" => l.location_code = value,
+                    b"instruments" => l.instruments = split_list(&value),
+                    _ => {}
+                }
+            }
+            (b"ScanType", _) => {
+                let Some(s) = self.scan_types.last_mut() else {
+                    return;
+                };
+                match field {
+                    b"displayName" => s.display_name = value,
+                    b"scanCode" => s.scan_code = value,
+                    b"instruments" => s.instruments = split_list(&value),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Every instrument type, e.g. `"STK"`.
+    pub fn instrument_types(&self) -> impl Iterator<Item = &str> {
+        self.instruments.iter().map(|i| i.instrument_type.as_str())
+    }
+
+    /// Every location code, e.g. `"STK.US.MAJOR"`.
+    pub fn location_codes(&self) -> impl Iterator<Item = &str> {
+        self.locations.iter().map(|l| l.location_code.as_str())
+    }
+
+    /// Every scan code, e.g. `"TOP_PERC_GAIN"`.
+    pub fn scan_codes(&self) -> impl Iterator<Item = &str> {
+        self.scan_types.iter().map(|s| s.scan_code.as_str())
+    }
+
+    /// Scan types that support `instrument_type`.
+    pub fn scan_types_for<'a>(
+        &'a self,
+        instrument_type: &'a str,
+    ) -> impl Iterator<Item = &'a ScanType> {
+        self.scan_types.iter().filter(move |s| s.instruments.iter().any(|i| i == instrument_type))
+    }
+
+    /// Locations where `instrument_type` is available.
+    pub fn locations_for<'a>(
+        &'a self,
+        instrument_type: &'a str,
+    ) -> impl Iterator<Item = &'a ScannerLocation> {
+        self.locations.iter().filter(move |l| l.instruments.iter().any(|i| i == instrument_type))
+    }
+
+    /// Check that the `instrument`, `location_code` and `scan_code` of
+    /// `subscription` are in the catalog, and that the scan supports the
+    /// instrument.
+    ///
+    /// Fails with `IBApiError::Encoding` naming the first bad value and
+    /// listing the valid ones (narrowed to the instrument where possible).
+    pub fn validate(&self, subscription: &ScannerSubscription) -> Result<()> {
+        let invalid = |what: &str, value: &str, valid: Vec<&str>| {
+            Err(IBApiError::Encoding(format!(
+                "invalid scanner subscription: unknown {what} {value:?}; valid values: {}",
+                valid.join(", ")
+            )))
+        };
+        let instrument = subscription.instrument.as_str();
+        if !self.instrument_types().any(|i| i == instrument) {
+            return invalid("instrument", instrument, self.instrument_types().collect());
+        }
+        let location = subscription.location_code.as_str();
+        if !self.location_codes().any(|l| l == location) {
+            let valid = self.locations_for(instrument).map(|l| l.location_code.as_str());
+            return invalid("location code", location, valid.collect());
+        }
+        let scan_code = subscription.scan_code.as_str();
+        let valid = || self.scan_types_for(instrument).map(|s| s.scan_code.as_str());
+        if !self.scan_codes().any(|s| s == scan_code) {
+            return invalid("scan code", scan_code, valid().collect());
+        }
+        if !valid().any(|s| s == scan_code) {
+            return Err(IBApiError::Encoding(format!(
+                "invalid scanner subscription: scan code {scan_code:?} does not support \
+                 instrument {instrument:?}; valid values: {}",
+                valid().collect::<Vec<_>>().join(", ")
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Split a comma-separated list such as `"STK,ETF.EQ.US"`.
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|v| !v.is_empty()).map(String::from).collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed from a paper account's scanner parameters response.
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ScanParameterResponse>
+  <InstrumentList varName="instrumentList">
+    <Instrument>
+      <name>US Stocks</name>
+      <type>STK</type>
+      <filters>AFTERHRSCHANGEPERC,AVGOPTVOLUME</filters>
+    </Instrument>
+    <Instrument>
+      <name>US Futures</name>
+      <type>FUT.US</type>
+    </Instrument>
+  </InstrumentList>
+  <LocationTree varName="locationTree">
+    <Location varName="usStocks">
+      <displayName>US Stocks</displayName>
+      <locationCode>STK.US</locationCode>
+      <instruments>STK</instruments>
+      <LocationTree varName="locationTree">
+        <Location varName="usMajor">
+          <displayName>Listed/NASDAQ</displayName>
+          <locationCode>STK.US.MAJOR</locationCode>
+          <instruments>STK</instruments>
+        </Location>
+      </LocationTree>
+      <routeExchange>SMART</routeExchange>
+    </Location>
+    <Location varName="usFutures">
+      <displayName>US Futures</displayName>
+      <locationCode>FUT.US</locationCode>
+      <instruments>FUT.US</instruments>
+    </Location>
+  </LocationTree>
+  <ScanTypeList varName="scanTypeList">
+    <ScanType>
+      <displayName>Top % Gainers</displayName>
+      <scanCode>TOP_PERC_GAIN</scanCode>
+      <instruments>STK,FUT.US</instruments>
+      <absoluteColumns>false</absoluteColumns>
+    </ScanType>
+    <ScanType>
+      <displayName>Hot Contracts by Volume &amp; Price</displayName>
+      <scanCode>HOT_BY_VOLUME</scanCode>
+      <instruments>STK</instruments>
+    </ScanType>
+  </ScanTypeList>
+</ScanParameterResponse>"#;
+
+    #[test]
+    fn parse_scanner_parameters_catalog() {
+        let params = ScannerParameters::parse(SAMPLE).unwrap();
+
+        assert_eq!(params.instrument_types().collect::<Vec<_>>(), ["STK", "FUT.US"]);
+        assert_eq!(params.instruments[0].name, "US Stocks");
+        // Nested locations follow their parent; routeExchange after the
+        // nested tree still belongs to the outer location and is ignored.
+        let locations: Vec<_> = params.location_codes().collect();
+        assert_eq!(locations, ["STK.US", "STK.US.MAJOR", "FUT.US"]);
+        assert_eq!(params.locations[1].display_name, "Listed/NASDAQ");
+        assert_eq!(params.scan_codes().collect::<Vec<_>>(), ["TOP_PERC_GAIN", "HOT_BY_VOLUME"]);
+        assert_eq!(params.scan_types[1].display_name, "Hot Contracts by Volume & Price");
+
+        let futures: Vec<_> = params.scan_types_for("FUT.US").map(|s| &s.scan_code).collect();
+        assert_eq!(futures, ["TOP_PERC_GAIN"]);
+        let futures: Vec<_> = params.locations_for("FUT.US").map(|l| &l.location_code).collect();
+        assert_eq!(futures, ["FUT.US"]);
+    }
+
+    #[test]
+    fn validate_scanner_subscription_against_catalog() {
+        let params = ScannerParameters::parse(SAMPLE).unwrap();
+        let sub = |instrument: &str, location_code: &str, scan_code: &str| ScannerSubscription {
+            instrument: instrument.into(),
+            location_code: location_code.into(),
+            scan_code: scan_code.into(),
+            ..ScannerSubscription::default()
+        };
+        params.validate(&sub("STK", "STK.US.MAJOR", "HOT_BY_VOLUME")).unwrap();
+        params.validate(&sub("FUT.US", "FUT.US", "TOP_PERC_GAIN")).unwrap();
+
+        let message = |sub| match params.validate(&sub) {
+            Err(IBApiError::Encoding(msg)) => msg,
+            other => panic!("expected Encoding error, got {other:?}"),
+        };
+        let msg = message(sub("STK", "STK.US.MAJOR", "TOP_PERC_LOSE"));
+        assert!(msg.contains("\"TOP_PERC_LOSE\""), "{msg}");
+        assert!(msg.ends_with("valid values: TOP_PERC_GAIN, HOT_BY_VOLUME"), "{msg}");
+        let msg = message(sub("FUT.US", "FUT.US", "HOT_BY_VOLUME"));
+        assert!(msg.contains("does not support"), "{msg}");
+        let msg = message(sub("STK", "STK.EU", "TOP_PERC_GAIN"));
+        assert!(msg.ends_with("valid values: STK.US, STK.US.MAJOR"), "{msg}");
+        let msg = message(sub("BOND", "STK.US", "TOP_PERC_GAIN"));
+        assert!(msg.ends_with("valid values: STK, FUT.US"), "{msg}");
+    }
+
+    #[test]
+    fn parse_scanner_parameters_rejects_malformed_xml() {
+        let err = ScannerParameters::parse("<ScanTypeList><ScanType></ScanTypeList>").unwrap_err();
+        assert!(matches!(err, IBApiError::Decoding(_)), "got {err:?}");
+    }
+}
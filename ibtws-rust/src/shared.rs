@@ -0,0 +1,324 @@
+//! Market data subscriptions shared between several consumers.
+//!
+//! Every `IBClient::subscribe_mkt_data` call allocates its own ticker ID, so
+//! two parts of an application watching the same instrument open two
+//! upstream streams -- and code that instead reuses one ticker ID cancels
+//! both with the first `cancel_mkt_data`. `SharedMarketData` opens one
+//! `REQ_MKT_DATA` per contract and generic tick list, fans its events out to
+//! every subscriber, and sends `CANCEL_MKT_DATA` only when the last
+//! subscriber's handle is dropped.
+//!
+//! There is no C++ equivalent.
+
+use std::collections::HashMap;
+use std::mem::Discriminant;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::client::IBClient;
+use crate::errors::Result;
+use crate::models::contract::Contract;
+use crate::protocol::TickType;
+use crate::subscription::MarketDataSubscription;
+use crate::wrapper::IBEvent;
+
+type Streams = Arc<Mutex<HashMap<String, Upstream>>>;
+
+/// One upstream `REQ_MKT_DATA` and the task fanning it out.
+struct Upstream {
+    req_id: i32,
+    fanout: Arc<Mutex<Fanout>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct Fanout {
+    next_id: u64,
+    subscribers: HashMap<u64, mpsc::UnboundedSender<IBEvent>>,
+    /// Latest event per tick kind and type, replayed to late subscribers so
+    /// they need not wait for each value to change.
+    latest: Vec<IBEvent>,
+}
+
+impl Fanout {
+    fn remember(&mut self, event: &IBEvent) {
+        let Some(key) = latest_key(event) else {
+            return;
+        };
+        match self.latest.iter_mut().find(|e| latest_key(e) == Some(key)) {
+            Some(slot) => *slot = event.clone(),
+            None => self.latest.push(event.clone()),
+        }
+    }
+}
+
+fn latest_key(event: &IBEvent) -> Option<(Discriminant<IBEvent>, Option<TickType>)> {
+    let tick_type = match event {
+        IBEvent::TickPrice { tick_type, .. }
+        | IBEvent::TickSize { tick_type, .. }
+        | IBEvent::TickGeneric { tick_type, .. }
+        | IBEvent::TickString { tick_type, .. }
+        | IBEvent::TickOptionComputation { tick_type, .. } => Some(*tick_type),
+        IBEvent::MarketDataType { .. } => None,
+        _ => return None,
+    };
+    Some((std::mem::discriminant(event), tick_type))
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // A panicking subscriber must not wedge the stream for the others, and
+    // `Drop` must not panic on a poisoned lock.
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// ============================================================================
+// SharedMarketData
+// ============================================================================
+
+/// Reference-counted market data streams over one `IBClient`.
+///
+/// Subscriptions are keyed by the contract's identifying fields (including
+/// `exchange`, which selects the data source) and the set of generic ticks.
+/// A subscriber joining a running stream first receives the latest value of
+/// each tick seen so far, then the live events. Clones share the same
+/// streams.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let shared = SharedMarketData::new(&client);
+/// let mut chart = shared.subscribe(&Contract::stock("AAPL"), "").await?;
+/// let mut blotter = shared.subscribe(&Contract::stock("AAPL"), "").await?; // same ticker ID
+/// drop(chart); // stream stays open for `blotter`
+/// ```
+pub struct SharedMarketData {
+    client: IBClient,
+    streams: Streams,
+}
+
+impl SharedMarketData {
+    /// Share streams opened on a handle of `client`.
+    pub fn new(client: &IBClient) -> Self {
+        Self { client: client.handle(), streams: Streams::default() }
+    }
+
+    /// Join the stream for `contract` and `generic_ticks`, opening it with
+    /// `IBClient::subscribe_mkt_data` if no subscriber holds it yet.
+    ///
+    /// A stream whose upstream ended (the connection closed) is reopened.
+    pub async fn subscribe(
+        &self,
+        contract: &Contract,
+        generic_ticks: &str,
+    ) -> Result<SharedMarketDataSubscription> {
+        let key = stream_key(contract, generic_ticks);
+        if let Some(sub) = self.join(&key, None) {
+            return Ok(sub);
+        }
+        let upstream = self.client.subscribe_mkt_data(contract, generic_ticks, false).await?;
+        // If another caller opened the stream meanwhile, joining it drops
+        // (and cancels) `upstream`.
+        Ok(self.join(&key, Some(upstream)).expect("stream opened"))
+    }
+
+    /// Add a subscriber to the running stream for `key`, or to one started
+    /// from `upstream`.
+    fn join(
+        &self,
+        key: &str,
+        upstream: Option<MarketDataSubscription>,
+    ) -> Option<SharedMarketDataSubscription> {
+        let mut streams = lock(&self.streams);
+        if streams.get(key).is_some_and(|s| s.task.is_finished()) {
+            streams.remove(key);
+        }
+        if !streams.contains_key(key) {
+            let upstream = upstream?;
+            let fanout = Arc::new(Mutex::new(Fanout::default()));
+            let stream = Upstream {
+                req_id: upstream.req_id(),
+                fanout: fanout.clone(),
+                task: tokio::spawn(forward(upstream, fanout)),
+            };
+            streams.insert(key.to_string(), stream);
+        }
+        let stream = &streams[key];
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut fanout = lock(&stream.fanout);
+        for event in &fanout.latest {
+            let _ = tx.send(event.clone());
+        }
+        let id = fanout.next_id;
+        fanout.next_id += 1;
+        fanout.subscribers.insert(id, tx);
+        Some(SharedMarketDataSubscription {
+            req_id: stream.req_id,
+            key: key.to_string(),
+            id,
+            rx,
+            fanout: stream.fanout.clone(),
+            streams: self.streams.clone(),
+        })
+    }
+}
+
+impl Clone for SharedMarketData {
+    fn clone(&self) -> Self {
+        Self { client: self.client.handle(), streams: self.streams.clone() }
+    }
+}
+
+/// Copy each upstream event to every subscriber.
+async fn forward(mut upstream: MarketDataSubscription, fanout: Arc<Mutex<Fanout>>) {
+    while let Some(event) = upstream.recv().await {
+        let mut fanout = lock(&fanout);
+        fanout.remember(&event);
+        fanout.subscribers.retain(|_, tx| tx.send(event.clone()).is_ok());
+    }
+    // Connection closed: end every subscriber's stream.
+    lock(&fanout).subscribers.clear();
+}
+
+/// Identifying fields of `contract` plus the sorted, deduplicated ticks.
+fn stream_key(contract: &Contract, generic_ticks: &str) -> String {
+    let mut ticks: Vec<&str> =
+        generic_ticks.split(',').map(str::trim).filter(|t| !t.is_empty()).collect();
+    ticks.sort_unstable();
+    ticks.dedup();
+    let c = contract;
+    format!(
+        "{}|{}|{:?}|{}|{:?}|{:?}|{}|{}|{}|{}|{}|{}|{:?}|{}",
+        c.con_id,
+        c.symbol.to_ascii_uppercase(),
+        c.sec_type,
+        c.last_trade_date_or_contract_month,
+        c.strike,
+        c.right,
+        c.multiplier,
+        c.exchange.to_ascii_uppercase(),
+        c.primary_exchange.to_ascii_uppercase(),
+        c.currency.to_ascii_uppercase(),
+        c.local_symbol,
+        c.trading_class,
+        c.combo_legs,
+        ticks.join(","),
+    )
+}
+
+// ============================================================================
+// SharedMarketDataSubscription
+// ============================================================================
+
+/// Handle returned by [`SharedMarketData::subscribe`].
+///
+/// Yields the events of the shared stream, including `Error`s for its ticker
+/// ID. Dropping the last handle of a stream sends `CANCEL_MKT_DATA`.
+pub struct SharedMarketDataSubscription {
+    req_id: i32,
+    key: String,
+    id: u64,
+    rx: mpsc::UnboundedReceiver<IBEvent>,
+    fanout: Arc<Mutex<Fanout>>,
+    streams: Streams,
+}
+
+impl SharedMarketDataSubscription {
+    /// Ticker ID of the shared upstream request.
+    pub fn req_id(&self) -> i32 {
+        self.req_id
+    }
+
+    /// Receive the next event of the stream.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<IBEvent> {
+        self.rx.recv().await
+    }
+
+    /// Receive the next event, turning an `Error` for the ticker ID into
+    /// `Err` (see [`IBEvent::api_error`]). Warnings are yielded as events.
+    ///
+    /// Returns `Ok(None)` once the connection has closed.
+    pub async fn try_next(&mut self) -> Result<Option<IBEvent>> {
+        let Some(event) = self.rx.recv().await else {
+            return Ok(None);
+        };
+        match event.api_error() {
+            Some(err) => Err(err),
+            None => Ok(Some(event)),
+        }
+    }
+}
+
+impl Drop for SharedMarketDataSubscription {
+    fn drop(&mut self) {
+        let mut streams = lock(&self.streams);
+        let mut fanout = lock(&self.fanout);
+        fanout.subscribers.remove(&self.id);
+        if !fanout.subscribers.is_empty() {
+            return;
+        }
+        drop(fanout);
+        // Only tear down the stream this handle belongs to, not a newer one
+        // reopened under the same key.
+        if streams.get(&self.key).is_some_and(|s| Arc::ptr_eq(&s.fanout, &self.fanout)) {
+            // Aborting the task drops the upstream handle, which sends
+            // CANCEL_MKT_DATA.
+            if let Some(stream) = streams.remove(&self.key) {
+                stream.task.abort();
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{frame, MockTws};
+
+    #[tokio::test]
+    async fn shared_market_data_opens_one_request_per_contract() {
+        let mut tws = MockTws::start(176).await;
+        let (client, _rx) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+        let shared = SharedMarketData::new(&client);
+        let aapl = Contract::stock("AAPL");
+
+        let mut first = shared.subscribe(&aapl, "233,100").await.unwrap();
+        let mut second = shared.clone().subscribe(&aapl, "100,233").await.unwrap();
+        assert_eq!(first.req_id(), second.req_id());
+        let id = first.req_id().to_string();
+        let request = tws.expect_message().await;
+        assert_eq!(request[..3], ["1", "11", id.as_str()]);
+
+        tws.push_event(frame(&["1", "6", &id, "1", "150.25", "300", "0"]));
+        for sub in [&mut first, &mut second] {
+            match sub.recv().await.unwrap() {
+                IBEvent::TickPrice { price, .. } => assert_eq!(price, 150.25),
+                other => panic!("expected TickPrice, got {other:?}"),
+            }
+        }
+
+        // A late subscriber starts from the latest tick.
+        let mut third = shared.subscribe(&aapl, "100,233").await.unwrap();
+        let replayed = third.recv().await;
+        assert!(matches!(replayed, Some(IBEvent::TickPrice { price, .. }) if price == 150.25));
+
+        // Dropping some subscribers keeps the stream: the next message TWS
+        // sees is the request for another contract, not a cancel.
+        drop(first);
+        drop(third);
+        let msft = shared.subscribe(&Contract::stock("MSFT"), "").await.unwrap();
+        assert_eq!(tws.expect_message().await[0], "1");
+
+        drop(second);
+        assert_eq!(tws.expect_message().await, ["2", "2", id.as_str()]);
+        drop(msft);
+        assert_eq!(tws.expect_message().await[0], "2");
+    }
+}
@@ -0,0 +1,118 @@
+//! Observable connection state of `IBClient`.
+//!
+//! The client publishes its lifecycle through a `tokio::sync::watch`
+//! channel: `IBClient::watch_connection_state` hands out receivers that see
+//! every transition made by `connect`, `disconnect`, the keepalive watchdog
+//! and the reconnect supervisor.
+//!
+//! There is no C++ equivalent -- `EClient::isConnected` only reports a bool.
+
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Lifecycle state of a client connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// No connection; the initial and final state.
+    Disconnected,
+    /// Opening the TCP (or TLS) connection.
+    Connecting,
+    /// Socket open; exchanging the API version handshake.
+    Handshaking,
+    /// Handshake done and START_API sent; requests can be sent.
+    Connected,
+    /// The connection was lost and the supervisor is retrying.
+    Reconnecting,
+    /// The connection was lost and will not be re-established: the reconnect
+    /// policy gave up, or the keepalive watchdog timed out.
+    Failed,
+}
+
+impl ConnectionState {
+    /// True only for [`Connected`](Self::Connected).
+    pub fn is_connected(self) -> bool {
+        self == Self::Connected
+    }
+}
+
+/// Shared, watchable [`ConnectionState`] cell.
+///
+/// Cloned into the reader task, the reconnect supervisor and the keepalive
+/// watchdog. Receivers are only notified when the state actually changes.
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionStatus {
+    tx: Arc<watch::Sender<ConnectionState>>,
+}
+
+impl ConnectionStatus {
+    pub(crate) fn new(initial: ConnectionState) -> Self {
+        Self { tx: Arc::new(watch::Sender::new(initial)) }
+    }
+
+    pub(crate) fn get(&self) -> ConnectionState {
+        *self.tx.borrow()
+    }
+
+    pub(crate) fn is_connected(&self) -> bool {
+        self.get().is_connected()
+    }
+
+    /// Move to `state`, returning the previous state.
+    pub(crate) fn set(&self, state: ConnectionState) -> ConnectionState {
+        let mut previous = state;
+        self.tx.send_if_modified(|current| {
+            previous = *current;
+            *current = state;
+            previous != state
+        });
+        if previous != state {
+            tracing::debug!(from = ?previous, to = ?state, "connection state changed");
+        }
+        previous
+    }
+
+    /// Move to `to` only if the current state is `from`.
+    pub(crate) fn replace_if(&self, from: ConnectionState, to: ConnectionState) -> bool {
+        let replaced = self.tx.send_if_modified(|current| {
+            if *current == from && from != to {
+                *current = to;
+                true
+            } else {
+                false
+            }
+        });
+        if replaced {
+            tracing::debug!(from = ?from, to = ?to, "connection state changed");
+        }
+        replaced
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<ConnectionState> {
+        self.tx.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_only_notifies_on_change() {
+        let status = ConnectionStatus::new(ConnectionState::Connecting);
+        let mut rx = status.subscribe();
+
+        assert_eq!(status.set(ConnectionState::Connecting), ConnectionState::Connecting);
+        assert!(!rx.has_changed().unwrap());
+
+        assert_eq!(status.set(ConnectionState::Connected), ConnectionState::Connecting);
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), ConnectionState::Connected);
+        assert!(status.is_connected());
+
+        assert!(!status.replace_if(ConnectionState::Reconnecting, ConnectionState::Failed));
+        assert!(!rx.has_changed().unwrap());
+        assert!(status.replace_if(ConnectionState::Connected, ConnectionState::Disconnected));
+        assert_eq!(*rx.borrow_and_update(), ConnectionState::Disconnected);
+    }
+}
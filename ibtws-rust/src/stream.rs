@@ -0,0 +1,236 @@
+//! `futures::Stream` adapter for the event channel.
+//!
+//! [`IBEventStream`] wraps the receiver returned by `IBClient::connect` so
+//! events can be consumed with `StreamExt` combinators from `futures` or
+//! `tokio-stream` and composed with other streams (`select`, `merge`, ...).
+//!
+//! There is no C++ equivalent -- `EWrapper` callbacks are the C++ delivery
+//! mechanism. Requires the `stream` feature.
+
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tokio_stream::StreamExt;
+
+use crate::client::IBClient;
+use crate::errors::Result;
+use crate::wrapper::IBEvent;
+
+/// The event channel of an `IBClient` as a `Stream<Item = IBEvent>`.
+///
+/// Ends after `ConnectionClosed`, once the reader task has stopped.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// use tokio_stream::StreamExt;
+///
+/// let (client, events) = IBClient::connect_stream("127.0.0.1", 4002, 0, None).await?;
+/// let req_id = client.next_req_id();
+/// client.req_mkt_data(req_id, &contract, "", false, false, &[]).await?;
+/// let mut ticks = events.filter_req_id(req_id);
+/// while let Some(event) = ticks.next().await {
+///     println!("{event}");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct IBEventStream {
+    inner: UnboundedReceiverStream<IBEvent>,
+}
+
+impl IBEventStream {
+    /// Wrap an event receiver, e.g. the one returned by `IBClient::connect`.
+    pub fn new(rx: mpsc::UnboundedReceiver<IBEvent>) -> Self {
+        Self { inner: UnboundedReceiverStream::new(rx) }
+    }
+
+    /// The underlying receiver.
+    pub fn into_inner(self) -> mpsc::UnboundedReceiver<IBEvent> {
+        self.inner.into_inner()
+    }
+
+    /// Only the events answering request `req_id` (see
+    /// [`IBEvent::req_id`]), including `Error`s for it.
+    pub fn filter_req_id(self, req_id: i32) -> impl Stream<Item = IBEvent> + Unpin {
+        self.filter(move |event| event.req_id() == Some(req_id))
+    }
+
+    /// Only the order events of orders tagged `order_ref`: `OpenOrder`,
+    /// `CompletedOrder` and `ExecDetails` with that `order_ref`, and the
+    /// `OrderStatus` updates of orders already seen in such an `OpenOrder`
+    /// (`OrderStatus` itself carries no `order_ref`; TWS sends the
+    /// `OpenOrder` first).
+    pub fn filter_order_ref(self, order_ref: &str) -> impl Stream<Item = IBEvent> + Unpin {
+        let order_ref = order_ref.to_string();
+        // (client_id, order_id) of the matching orders
+        let mut orders = HashSet::new();
+        self.filter(move |event| match event {
+            IBEvent::OrderStatus { order_id, client_id, .. } => {
+                orders.contains(&(i64::from(*client_id), *order_id))
+            }
+            _ if event.order_ref() == Some(order_ref.as_str()) => {
+                if let (IBEvent::OpenOrder { order_id, .. }, Some(client_id)) =
+                    (event, event.client_id())
+                {
+                    orders.insert((client_id, *order_id));
+                }
+                true
+            }
+            _ => false,
+        })
+    }
+
+    /// Only the order events (see [`IBEvent::client_id`]) of orders placed
+    /// by API client `client_id`.
+    pub fn filter_client_id(self, client_id: i32) -> impl Stream<Item = IBEvent> + Unpin {
+        self.filter(move |event| event.client_id() == Some(i64::from(client_id)))
+    }
+
+    /// Only the events for which `keep` returns true.
+    pub fn filter_events<F>(self, keep: F) -> impl Stream<Item = IBEvent> + Unpin
+    where
+        F: FnMut(&IBEvent) -> bool + Unpin,
+    {
+        self.filter(keep)
+    }
+}
+
+impl From<mpsc::UnboundedReceiver<IBEvent>> for IBEventStream {
+    fn from(rx: mpsc::UnboundedReceiver<IBEvent>) -> Self {
+        Self::new(rx)
+    }
+}
+
+impl Stream for IBEventStream {
+    type Item = IBEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<IBEvent>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl IBClient {
+    /// Connect like [`connect`](IBClient::connect), delivering events as an
+    /// [`IBEventStream`]. Requires the `stream` feature.
+    pub async fn connect_stream(
+        host: &str,
+        port: u16,
+        client_id: i32,
+        optional_capabilities: Option<&str>,
+    ) -> Result<(Self, IBEventStream)> {
+        let (client, rx) = Self::connect(host, port, client_id, optional_capabilities).await?;
+        Ok((client, IBEventStream::new(rx)))
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{frame, MockTws};
+
+    #[tokio::test]
+    async fn event_stream_yields_events_and_filters_by_req_id() {
+        let tws = MockTws::start(176).await;
+        let (_client, mut events) =
+            IBClient::connect_stream("127.0.0.1", tws.port(), 0, None).await.unwrap();
+
+        tws.push_event(frame(&["9", "1", "100"])); // NEXT_VALID_ID
+        assert!(matches!(events.next().await, Some(IBEvent::NextValidId { order_id: 100 })));
+
+        tws.push_event(frame(&["57", "1", "7"])); // TICK_SNAPSHOT_END, req 7
+        tws.push_event(frame(&["57", "1", "8"])); // TICK_SNAPSHOT_END, req 8
+        tws.push_event(frame(&["49", "1", "1767225600"])); // CURRENT_TIME
+        tws.push_event(frame(&["57", "1", "8"]));
+        tws.close();
+
+        let mut req_8 = events.filter_req_id(8);
+        assert!(matches!(req_8.next().await, Some(IBEvent::TickSnapshotEnd { req_id: 8 })));
+        assert!(matches!(req_8.next().await, Some(IBEvent::TickSnapshotEnd { req_id: 8 })));
+        assert!(req_8.next().await.is_none(), "stream ends when the connection closes");
+    }
+
+    #[tokio::test]
+    async fn event_stream_filters_order_events_by_order_ref_and_client_id() {
+        use crate::models::execution::Execution;
+        use crate::models::order::Order;
+
+        let open_order = |order_id: i64, client_id: i64, order_ref: &str| IBEvent::OpenOrder {
+            order_id,
+            contract: Box::default(),
+            order: Box::new(Order { client_id, order_ref: order_ref.into(), ..Order::default() }),
+            order_state: Box::default(),
+        };
+        let status = |order_id: i64, client_id: i32| IBEvent::OrderStatus {
+            order_id,
+            status: "Submitted".into(),
+            filled: Default::default(),
+            remaining: Default::default(),
+            avg_fill_price: 0.0,
+            perm_id: 0,
+            parent_id: 0,
+            last_fill_price: 0.0,
+            client_id,
+            why_held: String::new(),
+            mkt_cap_price: None,
+        };
+        let events = || {
+            vec![
+                open_order(1, 0, "alpha"),
+                open_order(2, 0, "beta"),
+                status(1, 0),
+                status(2, 0),
+                // Same order ID, other client: a different order.
+                status(1, 7),
+                IBEvent::ExecDetails {
+                    req_id: -1,
+                    contract: Box::default(),
+                    execution: Box::new(Execution {
+                        order_id: 1,
+                        client_id: 0,
+                        order_ref: "alpha".into(),
+                        ..Execution::default()
+                    }),
+                },
+                IBEvent::CurrentTime { time: 1_767_225_600 },
+                open_order(3, 7, "beta"),
+            ]
+        };
+        let stream = || {
+            let (tx, rx) = mpsc::unbounded_channel();
+            events().into_iter().for_each(|event| tx.send(event).unwrap());
+            IBEventStream::new(rx)
+        };
+        let summary = |event: IBEvent| {
+            let kind = event.to_string().split_whitespace().next().unwrap().to_string();
+            (kind, event.order_id(), event.client_id())
+        };
+
+        let alpha: Vec<_> = stream().filter_order_ref("alpha").map(summary).collect().await;
+        assert_eq!(
+            alpha,
+            [
+                ("OpenOrder".into(), Some(1), Some(0)),
+                ("OrderStatus".into(), Some(1), Some(0)),
+                ("ExecDetails".into(), Some(1), Some(0)),
+            ]
+        );
+
+        let client_7: Vec<_> = stream().filter_client_id(7).map(summary).collect().await;
+        assert_eq!(
+            client_7,
+            [("OrderStatus".into(), Some(1), Some(7)), ("OpenOrder".into(), Some(3), Some(7))]
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! Typed subscription handles for streaming requests.
+//!
+//! A subscription owns the request ID allocated for a streaming request,
+//! receives only the events routed to that ID by the reader task, and sends
+//! the matching cancel message when it is dropped. An order handle does the
+//! same for the status updates of one placed order.
+//!
+//! There is no direct C++ equivalent -- in `EClient` the application keeps
+//! track of ticker IDs and calls the `cancelXxx` method itself.
+
+use std::sync::Arc;
+
+use tokio::sync::{mpsc, Mutex};
+
+use crate::errors::Result;
+use crate::models::bar::Bar;
+use crate::pending::PendingRequests;
+use crate::transport::TransportWriter;
+use crate::wrapper::IBEvent;
+
+/// Write half shared between `IBClient` and its subscription handles.
+pub(crate) type SharedWriter = Arc<Mutex<TransportWriter>>;
+
+/// Runs once when a subscription ends: does any client-side bookkeeping and
+/// returns the encoded cancel message to send.
+pub(crate) type CancelFn = Box<dyn FnOnce() -> Option<Vec<u8>> + Send>;
+
+// ============================================================================
+// Subscription
+// ============================================================================
+
+/// Handle for a streaming request started by one of the `IBClient::subscribe_*`
+/// methods.
+///
+/// Yields the events -- and any `Error` -- for its own request ID only. Those
+/// events are not delivered to the global event channel. Dropping the handle
+/// (or calling [`cancel`](Self::cancel)) sends the request's cancel message,
+/// e.g. `CANCEL_MKT_DATA` or `CANCEL_SCANNER_SUBSCRIPTION`.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let mut sub = client.subscribe_mkt_data(&contract, "", false).await?;
+/// while let Some(event) = sub.recv().await {
+///     if let IBEvent::TickPrice { tick_type, price, .. } = event {
+///         println!("{tick_type:?} {price}");
+///     }
+/// }
+/// ```
+pub struct Subscription {
+    req_id: i32,
+    rx: mpsc::UnboundedReceiver<IBEvent>,
+    pending: PendingRequests,
+    writer: SharedWriter,
+    on_cancel: Option<CancelFn>,
+}
+
+/// Handle returned by `IBClient::subscribe_mkt_data`.
+pub type MarketDataSubscription = Subscription;
+
+/// Handle returned by `IBClient::subscribe_implied_volatility` and
+/// `IBClient::subscribe_option_price`.
+pub type CalcSubscription = Subscription;
+
+impl Subscription {
+    pub(crate) fn new(
+        req_id: i32,
+        rx: mpsc::UnboundedReceiver<IBEvent>,
+        pending: PendingRequests,
+        writer: SharedWriter,
+        on_cancel: CancelFn,
+    ) -> Self {
+        Self { req_id, rx, pending, writer, on_cancel: Some(on_cancel) }
+    }
+
+    /// Request (or ticker) ID allocated for this subscription.
+    pub fn req_id(&self) -> i32 {
+        self.req_id
+    }
+
+    /// Receive the next event for this subscription.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<IBEvent> {
+        self.rx.recv().await
+    }
+
+    /// Receive the next event, turning an `Error` for this request into
+    /// `Err` (see [`IBEvent::api_error`]). Warnings are yielded as events.
+    ///
+    /// Returns `Ok(None)` once the connection has closed.
+    pub async fn try_next(&mut self) -> Result<Option<IBEvent>> {
+        let Some(event) = self.rx.recv().await else {
+            return Ok(None);
+        };
+        match event.api_error() {
+            Some(err) => Err(err),
+            None => Ok(Some(event)),
+        }
+    }
+
+    /// Cancel the subscription and wait for the cancel message to be sent.
+    pub async fn cancel(mut self) -> Result<()> {
+        match self.finish() {
+            Some(msg) => self.writer.lock().await.send_message(&msg).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Stop routing events and run the cancel closure (at most once).
+    fn finish(&mut self) -> Option<Vec<u8>> {
+        self.pending.remove(self.req_id);
+        self.on_cancel.take().and_then(|on_cancel| on_cancel())
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let Some(msg) = self.finish() else {
+            return;
+        };
+        // Drop can't await; hand the send to the runtime if there is one.
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            let writer = self.writer.clone();
+            runtime.spawn(async move {
+                let _ = writer.lock().await.send_message(&msg).await;
+            });
+        }
+    }
+}
+
+// ============================================================================
+// NewsSubscription
+// ============================================================================
+
+/// Handle returned by `IBClient::subscribe_tick_news`.
+///
+/// A market data request with generic tick 292 streams `TickNews` headlines,
+/// but TWS may send other ticks for the same ticker ID alongside them. This
+/// handle yields only the `TickNews` events (and, from
+/// [`try_next`](Self::try_next), request errors) and sends `CANCEL_MKT_DATA`
+/// when dropped.
+pub struct NewsSubscription {
+    inner: Subscription,
+}
+
+impl NewsSubscription {
+    pub(crate) fn new(inner: Subscription) -> Self {
+        Self { inner }
+    }
+
+    /// Ticker ID allocated for this subscription.
+    pub fn req_id(&self) -> i32 {
+        self.inner.req_id()
+    }
+
+    /// Receive the next `TickNews` event, skipping everything else.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<IBEvent> {
+        loop {
+            let event = self.inner.recv().await?;
+            if matches!(event, IBEvent::TickNews { .. }) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Receive the next `TickNews` event, turning an `Error` for this request
+    /// into `Err`. Warnings and other ticks are skipped.
+    ///
+    /// Returns `Ok(None)` once the connection has closed.
+    pub async fn try_next(&mut self) -> Result<Option<IBEvent>> {
+        loop {
+            match self.inner.try_next().await? {
+                Some(event @ IBEvent::TickNews { .. }) => return Ok(Some(event)),
+                Some(_) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Cancel the subscription and wait for the cancel message to be sent.
+    pub async fn cancel(self) -> Result<()> {
+        self.inner.cancel().await
+    }
+}
+
+// ============================================================================
+// PnlSubscription
+// ============================================================================
+
+/// Handle returned by `IBClient::subscribe_pnl` and
+/// `IBClient::subscribe_pnl_single`.
+///
+/// Yields only the `Pnl` (or `PnlSingle`) updates for its request ID -- and,
+/// from [`try_next`](Self::try_next), request errors -- and sends
+/// `CANCEL_PNL` (or `CANCEL_PNL_SINGLE`) when dropped. Values TWS has not
+/// computed, or that the server version does not send, are `None`.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let mut sub = client.subscribe_pnl("DU123", "").await?;
+/// while let Some(IBEvent::Pnl { daily_pnl, unrealized_pnl, .. }) = sub.recv().await {
+///     println!("daily {daily_pnl:?} unrealized {unrealized_pnl:?}");
+/// }
+/// ```
+pub struct PnlSubscription {
+    inner: Subscription,
+}
+
+impl PnlSubscription {
+    pub(crate) fn new(inner: Subscription) -> Self {
+        Self { inner }
+    }
+
+    /// Request ID allocated for this subscription.
+    pub fn req_id(&self) -> i32 {
+        self.inner.req_id()
+    }
+
+    /// Receive the next `Pnl` or `PnlSingle` event, skipping everything else.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<IBEvent> {
+        loop {
+            let event = self.inner.recv().await?;
+            if is_pnl(&event) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Receive the next `Pnl` or `PnlSingle` event, turning an `Error` for
+    /// this request into `Err`. Warnings are skipped.
+    ///
+    /// Returns `Ok(None)` once the connection has closed.
+    pub async fn try_next(&mut self) -> Result<Option<IBEvent>> {
+        loop {
+            match self.inner.try_next().await? {
+                Some(event) if is_pnl(&event) => return Ok(Some(event)),
+                Some(_) => {}
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Cancel the subscription and wait for the cancel message to be sent.
+    pub async fn cancel(self) -> Result<()> {
+        self.inner.cancel().await
+    }
+}
+
+fn is_pnl(event: &IBEvent) -> bool {
+    matches!(event, IBEvent::Pnl { .. } | IBEvent::PnlSingle { .. })
+}
+
+// ============================================================================
+// HistoricalSubscription
+// ============================================================================
+
+/// One step of a `keep_up_to_date` historical data stream.
+#[derive(Debug, Clone)]
+pub enum HistoricalUpdate {
+    /// The initial series, up to the time of the request.
+    Initial(Vec<Bar>),
+    /// A `HistoricalDataUpdate` bar. TWS resends the latest bar each time
+    /// it changes, so consecutive updates often share the same `time`.
+    Update(Bar),
+}
+
+/// Handle returned by `IBClient::subscribe_historical_data`.
+///
+/// A `REQ_HISTORICAL_DATA` with `keep_up_to_date = true` first sends the
+/// initial bars -- one or more `HistoricalData` messages closed by
+/// `HistoricalDataEnd`, or a single `HistoricalData` before server version
+/// `HISTORICAL_DATA_END` -- and then streams `HistoricalDataUpdate` bars.
+/// This handle yields the two phases as [`HistoricalUpdate`] values and
+/// sends `CANCEL_HISTORICAL_DATA` when dropped.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let mut sub = client
+///     .subscribe_historical_data(&contract, "1 D", BarSize::OneMin, "TRADES", true, &[])
+///     .await?;
+/// while let Some(update) = sub.try_next().await? {
+///     match update {
+///         HistoricalUpdate::Initial(bars) => println!("{} bars", bars.len()),
+///         HistoricalUpdate::Update(bar) => println!("{} {}", bar.time, bar.close),
+///     }
+/// }
+/// ```
+pub struct HistoricalSubscription {
+    inner: Subscription,
+    separate_end: bool,
+    initial: Vec<Bar>,
+}
+
+impl HistoricalSubscription {
+    pub(crate) fn new(inner: Subscription, separate_end: bool) -> Self {
+        Self { inner, separate_end, initial: Vec::new() }
+    }
+
+    /// Request ID allocated for this subscription.
+    pub fn req_id(&self) -> i32 {
+        self.inner.req_id()
+    }
+
+    /// Receive the next phase of the stream, skipping errors and warnings.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<HistoricalUpdate> {
+        loop {
+            let event = self.inner.recv().await?;
+            if let Some(update) = self.absorb(event) {
+                return Some(update);
+            }
+        }
+    }
+
+    /// Receive the next phase of the stream, turning an `Error` for this
+    /// request into `Err`. Warnings are skipped.
+    ///
+    /// Returns `Ok(None)` once the connection has closed.
+    pub async fn try_next(&mut self) -> Result<Option<HistoricalUpdate>> {
+        loop {
+            let Some(event) = self.inner.try_next().await? else {
+                return Ok(None);
+            };
+            if let Some(update) = self.absorb(event) {
+                return Ok(Some(update));
+            }
+        }
+    }
+
+    /// Cancel the subscription and wait for the cancel message to be sent.
+    pub async fn cancel(self) -> Result<()> {
+        self.inner.cancel().await
+    }
+
+    /// Buffer initial bars until the series is complete.
+    fn absorb(&mut self, event: IBEvent) -> Option<HistoricalUpdate> {
+        match event {
+            IBEvent::HistoricalData { bars, .. } if !self.separate_end => {
+                Some(HistoricalUpdate::Initial(bars))
+            }
+            IBEvent::HistoricalData { bars, .. } => {
+                self.initial.extend(bars);
+                None
+            }
+            IBEvent::HistoricalDataEnd { .. } => {
+                Some(HistoricalUpdate::Initial(std::mem::take(&mut self.initial)))
+            }
+            IBEvent::HistoricalDataUpdate { bar, .. } => Some(HistoricalUpdate::Update(bar)),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// OrderHandle
+// ============================================================================
+
+/// Handle for an order placed with `IBClient::place_order_ack`.
+///
+/// Holds the event that acknowledged the order and yields the later
+/// `OpenOrder`, `OrderStatus` and `Error` events for its order ID. Those
+/// events are not delivered to the global event channel while the handle
+/// lives. Dropping the handle only stops the routing; it does not cancel
+/// the order.
+///
+/// ## Usage
+///
+/// ```rust,ignore
+/// let mut handle = client.place_order_ack(order_id, &contract, &order).await?;
+/// while let Some(event) = handle.recv().await {
+///     if let IBEvent::OrderStatus { status, filled, .. } = event {
+///         println!("{status} {filled}");
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OrderHandle {
+    order_id: i64,
+    ack: IBEvent,
+    rx: mpsc::UnboundedReceiver<IBEvent>,
+    pending: PendingRequests,
+}
+
+impl OrderHandle {
+    pub(crate) fn new(
+        order_id: i64,
+        ack: IBEvent,
+        rx: mpsc::UnboundedReceiver<IBEvent>,
+        pending: PendingRequests,
+    ) -> Self {
+        Self { order_id, ack, rx, pending }
+    }
+
+    pub fn order_id(&self) -> i64 {
+        self.order_id
+    }
+
+    /// The first `OpenOrder` or `OrderStatus` event for the order. For a
+    /// what-if order this is the `OpenOrder` carrying the margin impact in
+    /// its `order_state`; no status updates follow.
+    pub fn ack(&self) -> &IBEvent {
+        &self.ack
+    }
+
+    /// Receive the next event for this order.
+    ///
+    /// Returns `None` once the connection has closed.
+    pub async fn recv(&mut self) -> Option<IBEvent> {
+        self.rx.recv().await
+    }
+
+    /// Receive the next event, turning an `Error` for this order into `Err`
+    /// (see [`IBEvent::api_error`]). Warnings are yielded as events.
+    ///
+    /// Returns `Ok(None)` once the connection has closed.
+    pub async fn try_next(&mut self) -> Result<Option<IBEvent>> {
+        let Some(event) = self.rx.recv().await else {
+            return Ok(None);
+        };
+        match event.api_error() {
+            Some(err) => Err(err),
+            None => Ok(Some(event)),
+        }
+    }
+}
+
+impl Drop for OrderHandle {
+    fn drop(&mut self) {
+        self.pending.remove_order(self.order_id);
+    }
+}
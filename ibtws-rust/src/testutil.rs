@@ -0,0 +1,326 @@
+//! Scriptable mock TWS server for tests.
+//!
+//! `MockTws` listens on a local port, performs the V100+ handshake with the
+//! client, consumes `START_API`, then records every request the client
+//! sends and writes whatever responses the test scripted. Point an
+//! `IBClient` (or your own code) at [`MockTws::port`].
+//!
+//! Available in this crate's tests and, for downstream crates, with the
+//! `testing` feature. There is no C++ equivalent.
+//!
+//! ## Usage
+//!
+//! ```rust,ignore
+//! let mut tws = MockTws::builder()
+//!     .respond(|fields| match fields[0].as_str() {
+//!         "49" => vec![frame(&["49", "1", "1767225600"])], // CURRENT_TIME
+//!         _ => Vec::new(),
+//!     })
+//!     .start()
+//!     .await;
+//! let (client, mut events) = IBClient::connect("127.0.0.1", tws.port(), 0, None).await?;
+//! client.req_current_time().await?;
+//! assert_eq!(tws.expect_message().await[0], "49");
+//! tws.push_event(frame(&["9", "1", "100"])); // NEXT_VALID_ID
+//! ```
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+/// How long [`MockTws::expect_message`] waits before failing the test.
+const EXPECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Responder = Box<dyn Fn(&[u8]) -> Vec<Vec<u8>> + Send>;
+
+/// Frame null-terminated text fields as a server message
+/// (`[4-byte BE length][fields]`), ready for [`MockTws::push_event`].
+pub fn frame(fields: &[&str]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for f in fields {
+        body.extend_from_slice(f.as_bytes());
+        body.push(0);
+    }
+    let mut framed = (body.len() as u32).to_be_bytes().to_vec();
+    framed.extend(body);
+    framed
+}
+
+/// Split a text-encoded message body into its fields. Empty fields are
+/// kept; only the terminator of the last field is dropped.
+pub fn fields(body: &[u8]) -> Vec<String> {
+    let body = body.strip_suffix(&[0]).unwrap_or(body);
+    if body.is_empty() {
+        return Vec::new();
+    }
+    body.split(|b| *b == 0).map(|f| String::from_utf8_lossy(f).into_owned()).collect()
+}
+
+// ============================================================================
+// MockTwsBuilder
+// ============================================================================
+
+/// Configuration of a [`MockTws`]; see [`MockTws::builder`].
+pub struct MockTwsBuilder {
+    server_version: i32,
+    tws_time: String,
+    optional_capabilities: Option<String>,
+    messages: Vec<Vec<u8>>,
+    close_after_messages: bool,
+    responder: Option<Responder>,
+}
+
+impl MockTwsBuilder {
+    /// Server version sent in the handshake. Default 176.
+    pub fn server_version(mut self, server_version: i32) -> Self {
+        self.server_version = server_version;
+        self
+    }
+
+    /// TWS time sent in the handshake.
+    pub fn tws_time(mut self, tws_time: &str) -> Self {
+        self.tws_time = tws_time.to_string();
+        self
+    }
+
+    /// Optional capabilities to echo after the TWS time in the handshake.
+    /// Not sent by default, like standard TWS builds.
+    pub fn optional_capabilities(mut self, capabilities: &str) -> Self {
+        self.optional_capabilities = Some(capabilities.to_string());
+        self
+    }
+
+    /// Framed message to send right after `START_API`, e.g. from [`frame`].
+    pub fn message(mut self, framed: Vec<u8>) -> Self {
+        self.messages.push(framed);
+        self
+    }
+
+    /// Close the connection once the [`message`](Self::message)s are sent.
+    pub fn close_after_messages(mut self) -> Self {
+        self.close_after_messages = true;
+        self
+    }
+
+    /// Answer each client request with the framed messages `respond`
+    /// returns for its text fields (see [`fields`]).
+    pub fn respond<F>(self, respond: F) -> Self
+    where
+        F: Fn(&[String]) -> Vec<Vec<u8>> + Send + 'static,
+    {
+        self.respond_raw(move |body| respond(&fields(body)))
+    }
+
+    /// Like [`respond`](Self::respond), with the raw request body, for
+    /// binary (raw int message ID or protobuf) requests.
+    pub fn respond_raw<F>(mut self, respond: F) -> Self
+    where
+        F: Fn(&[u8]) -> Vec<Vec<u8>> + Send + 'static,
+    {
+        self.responder = Some(Box::new(respond));
+        self
+    }
+
+    /// Start listening on a free local port. Accepts a single connection.
+    pub async fn start(self) -> MockTws {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind mock TWS");
+        let port = listener.local_addr().expect("mock TWS address").port();
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let (seen_tx, seen_rx) = mpsc::unbounded_channel();
+        let (push_tx, push_rx) = mpsc::unbounded_channel();
+
+        tokio::spawn(serve(listener, self, sent.clone(), seen_tx, push_rx));
+        tokio::task::yield_now().await;
+        MockTws { port, sent, seen_rx, push_tx }
+    }
+}
+
+// ============================================================================
+// MockTws
+// ============================================================================
+
+/// Handle to a running mock TWS server.
+///
+/// Dropping the handle stops [`push_event`](Self::push_event)s, but the
+/// server keeps answering requests until the client disconnects.
+pub struct MockTws {
+    port: u16,
+    sent: Arc<Mutex<Vec<Vec<u8>>>>,
+    seen_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    /// `None` asks the server task to close the connection.
+    push_tx: mpsc::UnboundedSender<Option<Vec<u8>>>,
+}
+
+impl MockTws {
+    pub fn builder() -> MockTwsBuilder {
+        MockTwsBuilder {
+            server_version: 176,
+            tws_time: "20260101 12:00:00".to_string(),
+            optional_capabilities: None,
+            messages: Vec::new(),
+            close_after_messages: false,
+            responder: None,
+        }
+    }
+
+    /// Start a server that handshakes at `server_version` and sends nothing
+    /// on its own.
+    pub async fn start(server_version: i32) -> Self {
+        Self::builder().server_version(server_version).start().await
+    }
+
+    /// Port to connect to on 127.0.0.1.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Send a framed server message (see [`frame`]) to the client.
+    pub fn push_event(&self, framed: Vec<u8>) {
+        let _ = self.push_tx.send(Some(framed));
+    }
+
+    /// Close the connection, as TWS does when it drops a client.
+    pub fn close(&self) {
+        let _ = self.push_tx.send(None);
+    }
+
+    /// Text fields of the next request the client sends (after `START_API`).
+    ///
+    /// Panics if none arrives within 5 seconds or the connection closes.
+    pub async fn expect_message(&mut self) -> Vec<String> {
+        fields(&self.expect_raw_message().await)
+    }
+
+    /// Raw body of the next request, for binary-encoded messages.
+    pub async fn expect_raw_message(&mut self) -> Vec<u8> {
+        match tokio::time::timeout(EXPECT_TIMEOUT, self.seen_rx.recv()).await {
+            Ok(Some(body)) => body,
+            Ok(None) => panic!("mock TWS connection closed before the expected message"),
+            Err(_) => panic!("no message from the client within {EXPECT_TIMEOUT:?}"),
+        }
+    }
+
+    /// Raw bodies of every request received so far, in order. Not consumed
+    /// by [`expect_message`](Self::expect_message).
+    pub fn sent_messages(&self) -> Vec<Vec<u8>> {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Read one length-prefixed frame.
+async fn read_frame(reader: &mut OwnedReadHalf) -> Option<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len).await.ok()?;
+    let mut body = vec![0u8; u32::from_be_bytes(len) as usize];
+    reader.read_exact(&mut body).await.ok()?;
+    Some(body)
+}
+
+/// Server task: handshake, then record requests and write responses until
+/// either side closes.
+async fn serve(
+    listener: TcpListener,
+    config: MockTwsBuilder,
+    sent: Arc<Mutex<Vec<Vec<u8>>>>,
+    seen_tx: mpsc::UnboundedSender<Vec<u8>>,
+    mut push_rx: mpsc::UnboundedReceiver<Option<Vec<u8>>>,
+) {
+    let Ok((stream, _)) = listener.accept().await else {
+        return;
+    };
+    let (mut reader, mut writer) = stream.into_split();
+
+    // "API\0" prefix + version range
+    let mut prefix = [0u8; 4];
+    if reader.read_exact(&mut prefix).await.is_err() || read_frame(&mut reader).await.is_none() {
+        return;
+    }
+    let server_version = config.server_version.to_string();
+    let mut handshake_fields = vec![server_version.as_str(), config.tws_time.as_str()];
+    handshake_fields.extend(config.optional_capabilities.as_deref());
+    let handshake = frame(&handshake_fields);
+    if writer.write_all(&handshake).await.is_err() {
+        return;
+    }
+    // START_API
+    if read_frame(&mut reader).await.is_none() {
+        return;
+    }
+    for msg in &config.messages {
+        if writer.write_all(msg).await.is_err() {
+            return;
+        }
+    }
+    if config.close_after_messages {
+        return;
+    }
+
+    let mut pushes_open = true;
+    loop {
+        tokio::select! {
+            body = read_frame(&mut reader) => {
+                let Some(body) = body else {
+                    return;
+                };
+                sent.lock().unwrap_or_else(|e| e.into_inner()).push(body.clone());
+                let responses = config.responder.as_ref().map(|r| r(&body)).unwrap_or_default();
+                let _ = seen_tx.send(body);
+                for msg in responses {
+                    if writer.write_all(&msg).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            push = push_rx.recv(), if pushes_open => match push {
+                Some(Some(msg)) => {
+                    if writer.write_all(&msg).await.is_err() {
+                        return;
+                    }
+                }
+                Some(None) => return,
+                None => pushes_open = false,
+            },
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::IBClient;
+    use crate::wrapper::IBEvent;
+
+    #[test]
+    fn fields_keep_empty_values() {
+        assert_eq!(fields(b"1\0\0AAPL\0"), ["1", "", "AAPL"]);
+        assert!(fields(b"").is_empty());
+        assert_eq!(frame(&["9", "1"]), [0, 0, 0, 4, b'9', 0, b'1', 0]);
+    }
+
+    #[tokio::test]
+    async fn mock_tws_records_requests_and_pushes_events() {
+        let mut tws = MockTws::start(176).await;
+        let (client, mut rx) =
+            IBClient::connect("127.0.0.1", tws.port(), 0, None).await.unwrap();
+
+        client.req_current_time().await.unwrap();
+        client.req_ids().await.unwrap();
+        assert_eq!(tws.expect_message().await[0], "49");
+        assert_eq!(tws.expect_message().await[0], "8");
+        assert_eq!(tws.sent_messages().len(), 2);
+
+        tws.push_event(frame(&["9", "1", "42"])); // NEXT_VALID_ID
+        assert!(matches!(rx.recv().await, Some(IBEvent::NextValidId { order_id: 42 })));
+
+        tws.close();
+        assert!(matches!(rx.recv().await, Some(IBEvent::ConnectionClosed)));
+    }
+}
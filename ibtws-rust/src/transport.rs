@@ -1,14 +1,18 @@
 //! Async TCP transport for the IB TWS API protocol.
 //!
 //! Handles V100+ message framing (4-byte big-endian length prefix), the
-//! connection handshake, and reading/writing complete messages.
+//! connection handshake, and reading/writing complete messages. The byte
+//! stream is either a plain `TcpStream` or, with the `tls` feature, a
+//! TLS session over TCP; framing is identical for both.
 //!
 //! Ported from: `EClientSocket` (connect, framing), `ESocket` (TCP send/recv),
 //! `EReader` (message reading), `EClient::sendConnectRequest` / `startApi`.
 
+use std::future::Future;
+use std::time::Duration;
+
 use bytes::{Buf, BytesMut};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::net::TcpStream;
 
 use crate::decoder::MessageDecoder;
@@ -17,6 +21,13 @@ use crate::errors::{IBApiError, Result};
 use crate::protocol::{
     HEADER_LEN, MAX_CLIENT_VER, MAX_MSG_LEN, MIN_CLIENT_VER, outgoing, server_version,
 };
+use crate::wiretap::{WireDirection, WireTap};
+
+/// Read half of the underlying byte stream (plain TCP or TLS).
+type BoxedRead = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Write half of the underlying byte stream (plain TCP or TLS).
+type BoxedWrite = Box<dyn AsyncWrite + Send + Unpin>;
 
 // ============================================================================
 // Connection State
@@ -32,6 +43,205 @@ pub enum ConnState {
     Connected,
 }
 
+// ============================================================================
+// Transport Configuration
+// ============================================================================
+
+/// Connection settings for `Transport::connect_with_config`.
+///
+/// There is no C++ equivalent -- `EClientSocket::eConnect` blocks on the
+/// socket for as long as the OS allows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportConfig {
+    /// Limit on establishing the TCP connection (and TLS session, if any).
+    pub connect_timeout: Duration,
+    /// Limit on the V100+ handshake, from sending the version range to
+    /// receiving the server version. Guards against a peer that accepts
+    /// the connection but never answers.
+    pub handshake_timeout: Duration,
+    /// Extra connect options sent with the version range.
+    pub connect_options: Option<String>,
+    /// Most messages per second the write half sends; further sends wait.
+    /// TWS disconnects clients that exceed about 50. `None` disables the
+    /// limit.
+    pub max_messages_per_sec: Option<u32>,
+    /// How many times the handshake may be redirected to another gateway
+    /// before connecting fails. Mirrors C++ `REDIRECT_COUNT_MAX`.
+    pub max_redirects: u32,
+    /// Called with every framed message sent or received after the
+    /// handshake (see [`WireTap`]). `None` by default.
+    pub wire_tap: Option<WireTap>,
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            handshake_timeout: Duration::from_secs(10),
+            connect_options: None,
+            max_messages_per_sec: Some(45),
+            max_redirects: 2,
+            wire_tap: None,
+        }
+    }
+}
+
+/// Outcome of the V100+ handshake.
+enum Handshake {
+    Ready(Transport),
+    /// The server sent a negative version and the `host[:port]` of the
+    /// gateway to use instead.
+    Redirect(String),
+}
+
+/// Split a redirect target into host and port. A missing host means
+/// localhost, a missing port keeps `current_port`.
+///
+/// Mirrors C++ `EDecoder::processConnectAck` / `EClientSocket::redirect`.
+fn parse_redirect(target: &str, current_port: u16) -> Result<(String, u16)> {
+    let (host, port) = match target.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port.parse().map_err(|_| {
+                IBApiError::Protocol(format!("invalid redirect target {target:?}"))
+            })?;
+            (host, port)
+        }
+        None => (target, current_port),
+    };
+    let host = if host.is_empty() { "127.0.0.1" } else { host };
+    Ok((host.to_string(), port))
+}
+
+/// Run `fut`, failing with `IBApiError::Timeout` after `limit`.
+async fn with_timeout<T>(
+    limit: Duration,
+    what: impl FnOnce() -> String,
+    fut: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(limit, fut)
+        .await
+        .unwrap_or_else(|_| {
+            Err(IBApiError::Timeout(format!("{} timed out after {limit:?}", what())))
+        })
+}
+
+// ============================================================================
+// Framing
+// ============================================================================
+
+/// Take one complete V100+ frame off the front of `buf` and return its
+/// body (without the length header).
+///
+/// Returns `Ok(None)` while `buf` holds only part of a frame. Fails with
+/// `IBApiError::Protocol` as soon as the header announces an impossible
+/// length.
+pub(crate) fn take_frame(buf: &mut BytesMut) -> Result<Option<Vec<u8>>> {
+    if buf.len() < HEADER_LEN {
+        return Ok(None);
+    }
+    let len_bytes: [u8; 4] = buf[..HEADER_LEN].try_into().unwrap();
+    let msg_len = u32::from_be_bytes(len_bytes) as usize;
+    if msg_len == 0 || msg_len > MAX_MSG_LEN {
+        return Err(IBApiError::Protocol(format!(
+            "invalid message length: {msg_len}"
+        )));
+    }
+    if buf.len() < HEADER_LEN + msg_len {
+        return Ok(None);
+    }
+    buf.advance(HEADER_LEN);
+    Ok(Some(buf.split_to(msg_len).to_vec()))
+}
+
+/// Read from `reader` into `buf` until a complete frame is available.
+///
+/// Handles TCP fragmentation: bytes past the frame stay in `buf` for the
+/// next call. Mirrors C++ `EReader::readSingleMsg`.
+async fn read_frame(reader: &mut BoxedRead, buf: &mut BytesMut) -> Result<Vec<u8>> {
+    loop {
+        if let Some(msg) = take_frame(buf)? {
+            return Ok(msg);
+        }
+        let n = reader.read_buf(buf).await?;
+        if n == 0 {
+            let part = if buf.len() < HEADER_LEN { "header" } else { "body" };
+            return Err(IBApiError::Disconnected(format!(
+                "connection closed while reading message {part}"
+            )));
+        }
+    }
+}
+
+// ============================================================================
+// Rate Limiting
+// ============================================================================
+
+/// Token bucket pacing outgoing messages.
+///
+/// Holds at most a tenth of a second's worth of tokens, so a burst followed
+/// by sustained sending never puts more than `rate * 1.1` messages into any
+/// one-second window.
+#[derive(Debug)]
+struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last: tokio::time::Instant,
+}
+
+impl RateLimiter {
+    fn new(per_sec: u32) -> Self {
+        let rate = f64::from(per_sec.max(1));
+        let capacity = (rate / 10.0).max(1.0);
+        Self { rate, capacity, tokens: capacity, last: tokio::time::Instant::now() }
+    }
+
+    /// Wait until a message may be sent, then take its token.
+    async fn acquire(&mut self) {
+        let now = tokio::time::Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.rate;
+        self.tokens = (self.tokens + refill).min(self.capacity);
+        self.last = now;
+        if self.tokens < 1.0 {
+            let wait = Duration::from_secs_f64((1.0 - self.tokens) / self.rate);
+            tokio::time::sleep(wait).await;
+            self.tokens = 1.0;
+            self.last = tokio::time::Instant::now();
+        }
+        self.tokens -= 1.0;
+    }
+}
+
+// ============================================================================
+// TLS Configuration
+// ============================================================================
+
+/// TLS settings for `Transport::connect_tls`.
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct TlsConfig {
+    /// rustls client configuration (root certificates, client auth, ...).
+    pub client_config: std::sync::Arc<tokio_rustls::rustls::ClientConfig>,
+    /// Name to verify the server certificate against. Defaults to the host.
+    pub server_name: Option<String>,
+    /// Extra connect options sent with the version range.
+    pub connect_options: Option<String>,
+}
+
+#[cfg(feature = "tls")]
+impl TlsConfig {
+    /// Use `client_config`, verifying the certificate against the host name.
+    pub fn new(client_config: std::sync::Arc<tokio_rustls::rustls::ClientConfig>) -> Self {
+        Self { client_config, server_name: None, connect_options: None }
+    }
+
+    /// Verify the server certificate against `name` instead of the host.
+    pub fn with_server_name(mut self, name: impl Into<String>) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+}
+
 // ============================================================================
 // Transport
 // ============================================================================
@@ -44,12 +254,15 @@ pub enum ConnState {
 ///
 /// Replaces C++ `EClientSocket` + `ESocket` + `EReader` (read loop).
 pub struct Transport {
-    reader: OwnedReadHalf,
-    writer: OwnedWriteHalf,
+    reader: BoxedRead,
+    writer: BoxedWrite,
     read_buf: BytesMut,
     server_version: i32,
     tws_time: String,
+    optional_capabilities: Option<String>,
     conn_state: ConnState,
+    max_messages_per_sec: Option<u32>,
+    wire_tap: Option<WireTap>,
 }
 
 impl Transport {
@@ -62,35 +275,168 @@ impl Transport {
     /// - C++ `EDecoder::processConnectAck` (read server version + time)
     ///
     /// The `start_api()` call (sending client ID) is separate — call it
-    /// after connect returns.
+    /// after connect returns. Uses the default timeouts of
+    /// [`TransportConfig`].
     pub async fn connect(
         host: &str,
         port: u16,
         connect_options: Option<&str>,
     ) -> Result<Self> {
-        // 1. TCP connect
+        let config = TransportConfig {
+            connect_options: connect_options.map(String::from),
+            ..TransportConfig::default()
+        };
+        Self::connect_with_config(host, port, &config).await
+    }
+
+    /// Connect like [`connect`](Self::connect), with the timeouts and
+    /// connect options from `config`.
+    ///
+    /// Fails with `IBApiError::Timeout` if the TCP connect or the handshake
+    /// does not complete in time.
+    ///
+    /// If the server redirects the handshake to another gateway (accounts
+    /// routed through regional gateways), reconnects there, up to
+    /// `config.max_redirects` times; each hop gets the full timeouts.
+    pub async fn connect_with_config(
+        host: &str,
+        port: u16,
+        config: &TransportConfig,
+    ) -> Result<Self> {
+        Self::connect_observed(host, port, config, || {}).await
+    }
+
+    /// [`connect_with_config`](Self::connect_with_config), calling
+    /// `on_handshake` each time a socket is open and the version exchange
+    /// is about to start.
+    pub(crate) async fn connect_observed(
+        host: &str,
+        port: u16,
+        config: &TransportConfig,
+        on_handshake: impl Fn(),
+    ) -> Result<Self> {
+        let mut host = host.to_string();
+        let mut port = port;
+        let mut redirects = 0;
+        loop {
+            // 1. TCP connect
+            let addr = format!("{host}:{port}");
+            let stream =
+                with_timeout(config.connect_timeout, || format!("connect to {addr}"), async {
+                    TcpStream::connect(&addr).await.map_err(|e| {
+                        IBApiError::Connection(format!("failed to connect to {addr}: {e}"))
+                    })
+                })
+                .await?;
+
+            on_handshake();
+            let (reader, writer) = stream.into_split();
+            let handshake = with_timeout(
+                config.handshake_timeout,
+                || format!("handshake with {addr}"),
+                Self::handshake(
+                    Box::new(reader),
+                    Box::new(writer),
+                    config.connect_options.as_deref(),
+                ),
+            )
+            .await?;
+
+            match handshake {
+                Handshake::Ready(mut transport) => {
+                    transport.max_messages_per_sec = config.max_messages_per_sec;
+                    transport.wire_tap = config.wire_tap.clone();
+                    return Ok(transport);
+                }
+                Handshake::Redirect(target) => {
+                    if redirects == config.max_redirects {
+                        return Err(IBApiError::Connection(format!(
+                            "server redirect to {target} exceeds the limit of {} redirects",
+                            config.max_redirects
+                        )));
+                    }
+                    redirects += 1;
+                    (host, port) = parse_redirect(&target, port)?;
+                    tracing::info!(from = %addr, to = %target, "IB TWS API handshake redirected");
+                }
+            }
+        }
+    }
+
+    /// Connect over TLS, perform the V100+ handshake inside the encrypted
+    /// session, and return a ready-to-use Transport.
+    ///
+    /// Use this when IB Gateway sits behind a TLS terminator. Everything
+    /// after the TLS handshake -- `"API\0"`, the length-prefixed version
+    /// negotiation, and message framing -- is the same as for `connect`,
+    /// including the default timeouts of [`TransportConfig`].
+    #[cfg(feature = "tls")]
+    pub async fn connect_tls(host: &str, port: u16, config: TlsConfig) -> Result<Self> {
+        use tokio_rustls::rustls::pki_types::ServerName;
+        use tokio_rustls::TlsConnector;
+
+        let timeouts = TransportConfig::default();
         let addr = format!("{host}:{port}");
-        let stream = TcpStream::connect(&addr).await.map_err(|e| {
-            IBApiError::Connection(format!("failed to connect to {addr}: {e}"))
+        let name = config.server_name.as_deref().unwrap_or(host).to_string();
+        let server_name = ServerName::try_from(name).map_err(|e| {
+            IBApiError::Connection(format!("invalid TLS server name: {e}"))
         })?;
+        let stream = with_timeout(timeouts.connect_timeout, || format!("connect to {addr}"), async {
+            let stream = TcpStream::connect(&addr).await.map_err(|e| {
+                IBApiError::Connection(format!("failed to connect to {addr}: {e}"))
+            })?;
+            TlsConnector::from(config.client_config)
+                .connect(server_name, stream)
+                .await
+                .map_err(|e| {
+                    IBApiError::Connection(format!("TLS handshake with {addr} failed: {e}"))
+                })
+        })
+        .await?;
+
+        let (reader, writer) = tokio::io::split(stream);
+        let handshake = with_timeout(
+            timeouts.handshake_timeout,
+            || format!("handshake with {addr}"),
+            Self::handshake(Box::new(reader), Box::new(writer), config.connect_options.as_deref()),
+        )
+        .await?;
+        match handshake {
+            Handshake::Ready(transport) => Ok(transport),
+            // The new gateway would need its own TLS server name.
+            Handshake::Redirect(target) => {
+                Err(IBApiError::Protocol(format!("server redirect to {target}")))
+            }
+        }
+    }
 
-        let (reader, writer) = stream.into_split();
+    /// Run the V100+ handshake over an established byte stream.
+    async fn handshake(
+        reader: BoxedRead,
+        writer: BoxedWrite,
+        connect_options: Option<&str>,
+    ) -> Result<Handshake> {
         let mut transport = Self {
             reader,
             writer,
             read_buf: BytesMut::with_capacity(8192),
             server_version: 0,
             tws_time: String::new(),
+            optional_capabilities: None,
             conn_state: ConnState::Connecting,
+            max_messages_per_sec: TransportConfig::default().max_messages_per_sec,
+            wire_tap: None,
         };
 
         // 2. Send connect request: "API\0" + [4-byte length] + "v100..203"
         transport.send_connect_request(connect_options).await?;
 
         // 3. Read handshake response and validate
-        transport.process_connect_ack().await?;
+        if let Some(target) = transport.process_connect_ack().await? {
+            return Ok(Handshake::Redirect(target));
+        }
 
-        Ok(transport)
+        Ok(Handshake::Ready(transport))
     }
 
     // ========================================================================
@@ -111,16 +457,24 @@ impl Transport {
                 "failed to send connect request: {e}"
             ))
         })?;
+        self.writer.flush().await.map_err(|e| {
+            IBApiError::Connection(format!(
+                "failed to send connect request: {e}"
+            ))
+        })?;
         Ok(())
     }
 
     /// Read and process the connect acknowledgment from the server.
     ///
-    /// Server sends: `[4-byte length][server_version\0][tws_time\0]`
+    /// Server sends: `[4-byte length][server_version\0][tws_time\0]`,
+    /// optionally followed by `[optional_capabilities\0]`,
     /// or for redirect: `[4-byte length][negative_version\0][host:port\0]`.
     ///
+    /// Returns the redirect target, if any.
+    ///
     /// Mirrors C++ `EDecoder::processConnectAck`.
-    async fn process_connect_ack(&mut self) -> Result<()> {
+    async fn process_connect_ack(&mut self) -> Result<Option<String>> {
         let msg = self.read_message().await?;
         let mut dec = MessageDecoder::new(&msg, 0);
 
@@ -130,9 +484,7 @@ impl Transport {
         if sv < 0 {
             // Redirect: read host:port
             let hostport = dec.decode_string()?;
-            return Err(IBApiError::Protocol(format!(
-                "server redirect to {hostport}"
-            )));
+            return Ok(Some(hostport));
         }
 
         // Validate server version is within our supported range
@@ -145,6 +497,12 @@ impl Transport {
         // Read TWS time (always present for V100+ servers)
         let tws_time = dec.decode_string()?;
 
+        // Capabilities the server acknowledged (not sent by standard TWS builds)
+        if dec.has_remaining() {
+            let capabilities = dec.decode_string()?;
+            self.optional_capabilities = (!capabilities.is_empty()).then_some(capabilities);
+        }
+
         self.server_version = sv;
         self.tws_time = tws_time;
         self.conn_state = ConnState::Connected;
@@ -155,7 +513,7 @@ impl Transport {
             "IB TWS API handshake complete"
         );
 
-        Ok(())
+        Ok(None)
     }
 
     // ========================================================================
@@ -172,40 +530,10 @@ impl Transport {
     ///
     /// Mirrors C++ `EReader::readSingleMsg`.
     pub async fn read_message(&mut self) -> Result<Vec<u8>> {
-        // Ensure we have at least 4 bytes for the length header
-        while self.read_buf.len() < HEADER_LEN {
-            let n = self.reader.read_buf(&mut self.read_buf).await?;
-            if n == 0 {
-                return Err(IBApiError::Disconnected(
-                    "connection closed while reading message header".into(),
-                ));
-            }
-        }
-
-        // Parse the message length
-        let len_bytes: [u8; 4] = self.read_buf[..4].try_into().unwrap();
-        let msg_len = u32::from_be_bytes(len_bytes) as usize;
-
-        if msg_len == 0 || msg_len > MAX_MSG_LEN {
-            return Err(IBApiError::Protocol(format!(
-                "invalid message length: {msg_len}"
-            )));
-        }
-
-        // Read until we have the complete message
-        let total_needed = HEADER_LEN + msg_len;
-        while self.read_buf.len() < total_needed {
-            let n = self.reader.read_buf(&mut self.read_buf).await?;
-            if n == 0 {
-                return Err(IBApiError::Disconnected(
-                    "connection closed while reading message body".into(),
-                ));
-            }
+        let msg = read_frame(&mut self.reader, &mut self.read_buf).await?;
+        if let Some(tap) = &self.wire_tap {
+            tap.record_incoming(&msg);
         }
-
-        // Extract the message body, advancing the buffer
-        self.read_buf.advance(HEADER_LEN);
-        let msg = self.read_buf.split_to(msg_len).to_vec();
         Ok(msg)
     }
 
@@ -221,6 +549,13 @@ impl Transport {
         self.writer.write_all(data).await.map_err(|e| {
             IBApiError::Connection(format!("failed to send message: {e}"))
         })?;
+        // No-op for plain TCP; pushes buffered records out over TLS.
+        self.writer.flush().await.map_err(|e| {
+            IBApiError::Connection(format!("failed to send message: {e}"))
+        })?;
+        if let Some(tap) = &self.wire_tap {
+            tap.record(WireDirection::Outgoing, data);
+        }
         Ok(())
     }
 
@@ -262,6 +597,11 @@ impl Transport {
         &self.tws_time
     }
 
+    /// Optional capabilities echoed by the server in the handshake, if any.
+    pub fn optional_capabilities(&self) -> Option<&str> {
+        self.optional_capabilities.as_deref()
+    }
+
     /// Current connection state.
     pub fn conn_state(&self) -> ConnState {
         self.conn_state
@@ -292,10 +632,13 @@ impl Transport {
                 reader: self.reader,
                 read_buf: self.read_buf,
                 server_version: self.server_version,
+                wire_tap: self.wire_tap.clone(),
             },
             TransportWriter {
                 writer: self.writer,
                 server_version: self.server_version,
+                limiter: self.max_messages_per_sec.map(RateLimiter::new),
+                wire_tap: self.wire_tap,
             },
         )
     }
@@ -307,12 +650,13 @@ impl Transport {
 
 /// Read half of a split transport.
 ///
-/// Owns the TCP read half and the read buffer. Provides `read_message()`
+/// Owns the stream read half and the read buffer. Provides `read_message()`
 /// for reading complete framed messages from the server.
 pub struct TransportReader {
-    reader: OwnedReadHalf,
+    reader: BoxedRead,
     read_buf: BytesMut,
     server_version: i32,
+    wire_tap: Option<WireTap>,
 }
 
 impl TransportReader {
@@ -324,38 +668,10 @@ impl TransportReader {
     ///
     /// Same logic as `Transport::read_message`.
     pub async fn read_message(&mut self) -> Result<Vec<u8>> {
-        // Read length header
-        while self.read_buf.len() < HEADER_LEN {
-            let n = self.reader.read_buf(&mut self.read_buf).await?;
-            if n == 0 {
-                return Err(IBApiError::Disconnected(
-                    "connection closed while reading message header".into(),
-                ));
-            }
+        let msg = read_frame(&mut self.reader, &mut self.read_buf).await?;
+        if let Some(tap) = &self.wire_tap {
+            tap.record_incoming(&msg);
         }
-
-        let len_bytes: [u8; 4] = self.read_buf[..4].try_into().unwrap();
-        let msg_len = u32::from_be_bytes(len_bytes) as usize;
-
-        if msg_len == 0 || msg_len > MAX_MSG_LEN {
-            return Err(IBApiError::Protocol(format!(
-                "invalid message length: {msg_len}"
-            )));
-        }
-
-        // Read message body
-        let total_needed = HEADER_LEN + msg_len;
-        while self.read_buf.len() < total_needed {
-            let n = self.reader.read_buf(&mut self.read_buf).await?;
-            if n == 0 {
-                return Err(IBApiError::Disconnected(
-                    "connection closed while reading message body".into(),
-                ));
-            }
-        }
-
-        self.read_buf.advance(HEADER_LEN);
-        let msg = self.read_buf.split_to(msg_len).to_vec();
         Ok(msg)
     }
 }
@@ -366,11 +682,13 @@ impl TransportReader {
 
 /// Write half of a split transport.
 ///
-/// Owns the TCP write half. Provides `send_message()` for sending
+/// Owns the stream write half. Provides `send_message()` for sending
 /// pre-encoded framed messages to the server.
 pub struct TransportWriter {
-    writer: OwnedWriteHalf,
+    writer: BoxedWrite,
     server_version: i32,
+    limiter: Option<RateLimiter>,
+    wire_tap: Option<WireTap>,
 }
 
 impl TransportWriter {
@@ -379,10 +697,22 @@ impl TransportWriter {
     }
 
     /// Send a pre-encoded, framed message to the server.
+    ///
+    /// Waits first if the message rate limit (see
+    /// [`TransportConfig::max_messages_per_sec`]) has been reached.
     pub async fn send_message(&mut self, data: &[u8]) -> Result<()> {
+        if let Some(limiter) = &mut self.limiter {
+            limiter.acquire().await;
+        }
         self.writer.write_all(data).await.map_err(|e| {
             IBApiError::Connection(format!("failed to send: {e}"))
         })?;
+        self.writer.flush().await.map_err(|e| {
+            IBApiError::Connection(format!("failed to send: {e}"))
+        })?;
+        if let Some(tap) = &self.wire_tap {
+            tap.record(WireDirection::Outgoing, data);
+        }
         Ok(())
     }
 
@@ -487,33 +817,141 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn connect_redirect() {
+    /// Mock TWS that answers every handshake with a redirect to `target`.
+    /// `None` redirects back to itself.
+    async fn mock_tws_redirect(target: Option<String>) -> u16 {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let port = listener.local_addr().unwrap().port();
+        let target = target.unwrap_or_else(|| format!("127.0.0.1:{port}"));
 
         tokio::spawn(async move {
-            let (mut stream, _) = listener.accept().await.unwrap();
-            let mut buf = vec![0u8; 256];
-            let _ = stream.read(&mut buf).await.unwrap();
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buf = vec![0u8; 256];
+                let _ = stream.read(&mut buf).await.unwrap();
 
-            // Send negative version (redirect)
-            let response = build_framed_response(&["-1", "10.0.0.1:4002"]);
-            stream.write_all(&response).await.unwrap();
+                // Send negative version (redirect)
+                let response = build_framed_response(&["-1", &target]);
+                stream.write_all(&response).await.unwrap();
+            }
         });
 
         tokio::task::yield_now().await;
+        port
+    }
+
+    #[tokio::test]
+    async fn connect_redirect() {
+        let target_port = mock_tws_handshake(176, "20260101 12:00:00 EST").await;
+        let port = mock_tws_redirect(Some(format!("127.0.0.1:{target_port}"))).await;
+
+        let transport = Transport::connect("127.0.0.1", port, None)
+            .await
+            .unwrap();
+        assert_eq!(transport.server_version(), 176);
+        assert_eq!(transport.tws_time(), "20260101 12:00:00 EST");
+        assert!(transport.is_connected());
+    }
+
+    #[tokio::test]
+    async fn connect_redirect_limit() {
+        let port = mock_tws_redirect(None).await;
 
         let result = Transport::connect("127.0.0.1", port, None).await;
         match result {
             Err(e) => assert!(
-                e.to_string().contains("redirect"),
+                e.to_string().contains("limit of 2 redirects"),
                 "unexpected error: {e}"
             ),
-            Ok(_) => panic!("expected redirect error"),
+            Ok(_) => panic!("expected redirect limit error"),
+        }
+
+        let config = TransportConfig { max_redirects: 0, ..TransportConfig::default() };
+        let result = Transport::connect_with_config("127.0.0.1", port, &config).await;
+        assert!(matches!(result, Err(IBApiError::Connection(_))));
+    }
+
+    #[test]
+    fn parse_redirect_targets() {
+        assert_eq!(parse_redirect("10.0.0.1:4002", 7496).unwrap(), ("10.0.0.1".into(), 4002));
+        assert_eq!(
+            parse_redirect("gw.example.com", 7496).unwrap(),
+            ("gw.example.com".into(), 7496)
+        );
+        assert_eq!(parse_redirect(":4002", 7496).unwrap(), ("127.0.0.1".into(), 4002));
+        assert!(parse_redirect("10.0.0.1:port", 7496).is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_handshake_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Accept, read the connect request, and never answer.
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 256];
+            let _ = stream.read(&mut buf).await;
+            tokio::time::sleep(Duration::from_secs(30)).await;
+        });
+
+        let config = TransportConfig {
+            handshake_timeout: Duration::from_millis(100),
+            ..TransportConfig::default()
+        };
+        match Transport::connect_with_config("127.0.0.1", port, &config).await {
+            Err(IBApiError::Timeout(msg)) => assert!(msg.contains("handshake"), "got {msg}"),
+            Err(e) => panic!("expected Timeout, got {e}"),
+            Ok(_) => panic!("expected Timeout, got a connection"),
         }
     }
 
+    #[tokio::test]
+    async fn writer_rate_limits_bursts() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            stream.write_all(&build_framed_response(&["176", "time"])).await.unwrap();
+            // Drain whatever the client sends.
+            while stream.read(&mut buf).await.is_ok_and(|n| n > 0) {}
+        });
+        tokio::task::yield_now().await;
+
+        let transport =
+            Transport::connect_with_config("127.0.0.1", port, &TransportConfig::default())
+                .await
+                .unwrap();
+        let (_reader, mut writer) = transport.into_split();
+
+        // Sleeps in the limiter advance the paused clock instantly.
+        tokio::time::pause();
+        let start = tokio::time::Instant::now();
+        let msg = build_framed_response(&["49", "1"]);
+        for _ in 0..100 {
+            writer.send_message(&msg).await.unwrap();
+        }
+        // 45/sec with a burst of 4: (100 - 4) / 45 = 2.13s.
+        assert!(start.elapsed() >= Duration::from_secs(2), "took {:?}", start.elapsed());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rate_limiter_refills_over_time() {
+        let mut limiter = RateLimiter::new(10);
+        let start = tokio::time::Instant::now();
+        limiter.acquire().await; // burst of 1 at 10/sec
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        limiter.acquire().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        let resumed = tokio::time::Instant::now();
+        limiter.acquire().await; // refilled, but capped at the burst size
+        limiter.acquire().await;
+        assert_eq!(resumed.elapsed(), Duration::from_millis(100));
+    }
+
     #[tokio::test]
     async fn connect_refused() {
         // Port 1 is almost certainly not listening
@@ -660,4 +1098,82 @@ mod tests {
         assert!(!transport.is_connected());
         assert_eq!(transport.conn_state(), ConnState::Disconnected);
     }
+
+    #[cfg(feature = "tls")]
+    #[tokio::test]
+    async fn connect_tls_handshake_and_split() {
+        use std::sync::Arc;
+        use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+        use tokio_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+        use tokio_rustls::TlsAcceptor;
+
+        let _ = tokio_rustls::rustls::crypto::ring::default_provider().install_default();
+
+        // Self-signed certificate for "localhost"
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_der = CertificateDer::from(cert.cert.der().to_vec());
+        let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
+
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert_der.clone()], key_der)
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(server_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let handle = tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut stream = acceptor.accept(tcp).await.unwrap();
+
+            // "API\0" prefix, then the length-prefixed version range
+            let mut prefix = [0u8; 4];
+            stream.read_exact(&mut prefix).await.unwrap();
+            assert_eq!(&prefix, crate::protocol::API_SIGN);
+            let mut len = [0u8; HEADER_LEN];
+            stream.read_exact(&mut len).await.unwrap();
+            let mut body = vec![0u8; u32::from_be_bytes(len) as usize];
+            stream.read_exact(&mut body).await.unwrap();
+
+            let handshake = build_framed_response(&["176", "20260101 12:00:00"]);
+            stream.write_all(&handshake).await.unwrap();
+            stream.flush().await.unwrap();
+
+            // Echo one framed message back
+            stream.read_exact(&mut len).await.unwrap();
+            let mut msg = vec![0u8; u32::from_be_bytes(len) as usize];
+            stream.read_exact(&mut msg).await.unwrap();
+            let mut echo = len.to_vec();
+            echo.extend_from_slice(&msg);
+            stream.write_all(&echo).await.unwrap();
+            stream.flush().await.unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        let mut roots = RootCertStore::empty();
+        roots.add(cert_der).unwrap();
+        let client_config = ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let config = TlsConfig::new(Arc::new(client_config)).with_server_name("localhost");
+
+        let transport = Transport::connect_tls("127.0.0.1", port, config).await.unwrap();
+        assert_eq!(transport.server_version(), 176);
+        assert!(transport.is_connected());
+
+        let (mut reader, mut writer) = transport.into_split();
+        let mut enc = MessageEncoder::new(176);
+        enc.encode_field_i32(49); // REQ_CURRENT_TIME
+        enc.encode_field_i32(1);  // version
+        writer.send_message(&enc.finalize().unwrap()).await.unwrap();
+
+        let msg = reader.read_message().await.unwrap();
+        let mut dec = MessageDecoder::new(&msg, 176);
+        assert_eq!(dec.decode_i32().unwrap(), 49);
+        assert_eq!(dec.decode_i32().unwrap(), 1);
+
+        let version_range = handle.await.unwrap();
+        assert!(version_range.starts_with("v"), "unexpected version range: {version_range}");
+    }
 }
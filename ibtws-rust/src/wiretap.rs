@@ -0,0 +1,182 @@
+//! Capture of the raw framed messages exchanged with TWS.
+//!
+//! A [`WireTap`] set in [`TransportConfig::wire_tap`](crate::TransportConfig)
+//! sees every V100+ frame (`[4-byte BE length][body]`) after the handshake:
+//! outgoing ones as they are written by the transport, incoming ones as the
+//! reader task takes them off the socket. Useful for debugging protocol
+//! issues and for recording regression fixtures.
+//!
+//! There is no C++ equivalent -- the closest is the C++ API's message
+//! logging in TWS itself (`API Settings > Create API message log file`).
+
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Which way a captured frame travelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireDirection {
+    /// Server to client.
+    Incoming,
+    /// Client to server.
+    Outgoing,
+}
+
+type TapFn = dyn Fn(WireDirection, &[u8]) + Send + Sync;
+
+/// Callback invoked with every framed message, header included.
+///
+/// The callback runs inline on the reader task and on the sending task, so
+/// it should be quick. Two taps are equal only if they are clones of the
+/// same tap.
+#[derive(Clone)]
+pub struct WireTap {
+    tap: Arc<TapFn>,
+}
+
+impl WireTap {
+    /// Call `f` with the direction and bytes of each frame.
+    pub fn new(f: impl Fn(WireDirection, &[u8]) + Send + Sync + 'static) -> Self {
+        Self { tap: Arc::new(f) }
+    }
+
+    /// Log each frame as a hex dump at `TRACE` level (target
+    /// `ibtws_rust::wiretap`).
+    pub fn trace() -> Self {
+        Self::new(|direction, frame| {
+            tracing::trace!(?direction, len = frame.len(), hex = %hex(frame), "wire frame");
+        })
+    }
+
+    /// Append every incoming frame to the file at `path`, creating it if
+    /// needed.
+    ///
+    /// The file is a plain concatenation of length-prefixed frames, which
+    /// is what [`replay_stream`](crate::replay_stream) decodes. Outgoing
+    /// frames are not written, so the recording replays as a pure server
+    /// stream; combine with [`trace`](Self::trace) or a custom tap to see
+    /// both directions. Write failures are logged and do not affect the
+    /// connection.
+    pub fn file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let file = Mutex::new(file);
+        Ok(Self::new(move |direction, frame| {
+            if direction == WireDirection::Incoming {
+                let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+                if let Err(e) = file.write_all(frame) {
+                    tracing::warn!(error = %e, "failed to write wire capture");
+                }
+            }
+        }))
+    }
+
+    /// Pass one frame to the callback.
+    pub(crate) fn record(&self, direction: WireDirection, frame: &[u8]) {
+        (self.tap)(direction, frame);
+    }
+
+    /// Pass an incoming message body to the callback, with its length
+    /// header restored.
+    pub(crate) fn record_incoming(&self, body: &[u8]) {
+        let mut frame = Vec::with_capacity(4 + body.len());
+        frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        frame.extend_from_slice(body);
+        self.record(WireDirection::Incoming, &frame);
+    }
+}
+
+impl fmt::Debug for WireTap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WireTap").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for WireTap {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.tap, &other.tap)
+    }
+}
+
+impl Eq for WireTap {}
+
+/// Space-separated lowercase hex, e.g. `"00 00 00 05 34 39"`.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect::<Vec<_>>().join(" ")
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::IBClient;
+    use crate::testutil::{frame, MockTws};
+    use crate::transport::TransportConfig;
+    use crate::wrapper::IBEvent;
+
+    #[tokio::test]
+    async fn wire_tap_captures_both_directions() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink = captured.clone();
+        let config = TransportConfig {
+            wire_tap: Some(WireTap::new(move |direction, frame| {
+                sink.lock().unwrap().push((direction, frame.to_vec()));
+            })),
+            ..TransportConfig::default()
+        };
+
+        let mut tws = MockTws::start(176).await;
+        let (client, mut rx) =
+            IBClient::connect_with_config("127.0.0.1", tws.port(), 7, None, &config)
+                .await
+                .unwrap();
+        client.req_current_time().await.unwrap();
+        tws.expect_message().await;
+        let current_time = frame(&["49", "1", "1767225600"]);
+        tws.push_event(current_time.clone());
+        assert!(matches!(rx.recv().await, Some(IBEvent::CurrentTime { .. })));
+
+        let captured = captured.lock().unwrap();
+        let outgoing: Vec<_> = captured
+            .iter()
+            .filter(|(d, _)| *d == WireDirection::Outgoing)
+            .map(|(_, f)| f.clone())
+            .collect();
+        // START_API (71) and REQ_CURRENT_TIME (49), each with its header.
+        assert_eq!(outgoing.len(), 2);
+        assert_eq!(&outgoing[0][4..7], b"71\0");
+        assert_eq!(outgoing[1], frame(&["49", "1"]));
+        assert!(captured.contains(&(WireDirection::Incoming, current_time)));
+    }
+
+    #[tokio::test]
+    async fn wire_tap_file_records_a_replayable_stream() {
+        let path = std::env::temp_dir()
+            .join(format!("ibtws-wiretap-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = TransportConfig {
+            wire_tap: Some(WireTap::file(&path).unwrap()),
+            ..TransportConfig::default()
+        };
+
+        let tws = MockTws::start(176).await;
+        let (_client, mut rx) =
+            IBClient::connect_with_config("127.0.0.1", tws.port(), 0, None, &config)
+                .await
+                .unwrap();
+        tws.push_event(frame(&["9", "1", "100"])); // NEXT_VALID_ID
+        tws.push_event(frame(&["15", "1", "DU123"])); // MANAGED_ACCTS
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+
+        let events = crate::replay_stream(&std::fs::read(&path).unwrap(), 176);
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(events[0], IBEvent::NextValidId { order_id: 100 }));
+        assert!(matches!(&events[1], IBEvent::ManagedAccounts { accounts } if accounts == "DU123"));
+        assert_eq!(events.len(), 2);
+    }
+}
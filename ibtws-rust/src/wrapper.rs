@@ -9,16 +9,23 @@
 //!
 //! Ported from: `EWrapper.h`, `EWrapper_prototypes.h` (113 virtual methods).
 
+use std::fmt;
+
 use rust_decimal::Decimal;
 
+use crate::errors::{IBApiError, IbErrorCode};
+use crate::models::account::AccountValue;
 use crate::models::bar::{Bar, HistoricalSession, HistoricalTick, HistoricalTickBidAsk, HistoricalTickLast};
 use crate::models::common::{
     FamilyCode, HistogramEntry, NewsProvider, PriceIncrement, SmartComponent, SoftDollarTier,
 };
 use crate::models::contract::{Contract, ContractDescription, ContractDetails, DeltaNeutralContract};
 use crate::models::execution::{CommissionAndFeesReport, Execution};
-use crate::models::market_data::{DepthMktDataDescription, TickAttrib, TickAttribBidAsk, TickAttribLast};
+use crate::models::market_data::{
+    DepthMktDataDescription, SnapshotPermissions, TickAttrib, TickAttribBidAsk, TickAttribLast,
+};
 use crate::models::order::{Order, OrderState};
+use crate::models::scanner::{parse_combo_legs_str, ScannerLeg};
 use crate::protocol::TickType;
 
 // ============================================================================
@@ -26,7 +33,7 @@ use crate::protocol::TickType;
 // ============================================================================
 
 /// A single scanner result entry within a `ScannerData` event.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ScannerDataItem {
     pub rank: i32,
     pub contract_details: ContractDetails,
@@ -36,6 +43,14 @@ pub struct ScannerDataItem {
     pub legs_str: String,
 }
 
+impl ScannerDataItem {
+    /// The legs of a combo hit, parsed from `legs_str` (see
+    /// [`parse_combo_legs_str`](crate::models::scanner::parse_combo_legs_str)).
+    pub fn legs(&self) -> Vec<ScannerLeg> {
+        parse_combo_legs_str(&self.legs_str)
+    }
+}
+
 /// All possible events from the IB TWS/Gateway server.
 ///
 /// Each variant corresponds to one C++ `EWrapper` callback method.
@@ -44,7 +59,7 @@ pub struct ScannerDataItem {
 /// ## Usage
 ///
 /// ```rust,ignore
-/// let (mut client, mut rx) = IBClient::connect("127.0.0.1", 4002, 0, None).await?;
+/// let (client, mut rx) = IBClient::connect("127.0.0.1", 4002, 0, None).await?;
 ///
 /// while let Some(event) = rx.recv().await {
 ///     match event {
@@ -55,7 +70,7 @@ pub struct ScannerDataItem {
 ///     }
 /// }
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[allow(clippy::large_enum_variant)]
 pub enum IBEvent {
     // ========================================================================
@@ -84,10 +99,35 @@ pub enum IBEvent {
         advanced_order_reject_json: String,
     },
 
+    /// Status of the connection between TWS/Gateway and IB's servers,
+    /// decoded from the error codes TWS uses for it: 1100 (lost), 2110
+    /// (broken, restoring automatically), 1101 (restored, market data
+    /// subscriptions were lost and must be re-requested) and 1102
+    /// (restored, data maintained).
+    /// No C++ equivalent -- these arrive through `error(...)`.
+    ConnectivityStatus {
+        connected: bool,
+        data_lost: bool,
+        code: i32,
+        message: String,
+    },
+
     /// Connection to TWS/Gateway has been closed.
     /// C++: `connectionClosed()`
     ConnectionClosed,
 
+    /// Connection restored after an unexpected disconnect (reconnecting
+    /// clients only). Active subscriptions have been re-sent.
+    /// No C++ equivalent.
+    ConnectionReestablished {
+        server_version: i32,
+    },
+
+    /// The keepalive watchdog got no `CurrentTime` answer within two probe
+    /// intervals; the socket may still be open. See `IBClient::start_keepalive`.
+    /// No C++ equivalent.
+    ConnectionLost,
+
     // ========================================================================
     // Market Data (Ticks)
     // ========================================================================
@@ -164,9 +204,17 @@ pub enum IBEvent {
 
     /// Tick request parameters.
     /// C++: `tickReqParams(int, double, const std::string&, int)`
+    ///
+    /// See [`IBEvent::snapshot_permissions`] for the bits of
+    /// `snapshot_permissions`.
     TickReqParams {
         req_id: i32,
         min_tick: f64,
+        /// Key of the SMART components this instrument's quotes come from,
+        /// e.g. `"9c0001"`: an opaque exchange-mapping ID, not an exchange
+        /// name. Pass it unchanged to `IBClient::smart_components` to map the
+        /// exchange letters of tick data to exchange names. Empty when the
+        /// quotes are not aggregated over SMART.
         bbo_exchange: String,
         snapshot_permissions: i32,
     },
@@ -195,8 +243,14 @@ pub enum IBEvent {
 
     /// Tick-by-tick last trade or all-last data.
     /// C++: `tickByTickAllLast(int, int, time_t, double, Decimal, ...)`
+    ///
+    /// C++ reports both request types through this one callback; `tick_type`
+    /// tells them apart.
     TickByTickAllLast {
         req_id: i32,
+        /// `1` for a `"Last"` request, `2` for `"AllLast"`, which adds trade
+        /// types `"Last"` leaves out: combos, derivatives and average price
+        /// trades.
         tick_type: i32,
         time: i64,
         price: f64,
@@ -226,6 +280,14 @@ pub enum IBEvent {
         mid_point: f64,
     },
 
+    /// Tick-by-tick data of a tick type this library does not decode. The
+    /// payload after `time` is skipped. There is no C++ equivalent.
+    TickByTickUnknown {
+        req_id: i32,
+        tick_type: i32,
+        time: i64,
+    },
+
     // ========================================================================
     // Orders
     // ========================================================================
@@ -243,7 +305,8 @@ pub enum IBEvent {
         last_fill_price: f64,
         client_id: i32,
         why_held: String,
-        mkt_cap_price: f64,
+        /// `None` when the server did not send one (C++ `UNSET_DOUBLE`).
+        mkt_cap_price: Option<f64>,
     },
 
     /// Open order details.
@@ -323,7 +386,8 @@ pub enum IBEvent {
         position: Decimal,
         market_price: f64,
         market_value: f64,
-        average_cost: f64,
+        /// A `double` in C++; decoded exactly from the wire string.
+        average_cost: Decimal,
         unrealized_pnl: f64,
         realized_pnl: f64,
         account_name: String,
@@ -363,7 +427,8 @@ pub enum IBEvent {
         account: String,
         contract: Box<Contract>,
         position: Decimal,
-        avg_cost: f64,
+        /// A `double` in C++; decoded exactly from the wire string.
+        avg_cost: Decimal,
     },
 
     /// End of positions list.
@@ -378,7 +443,8 @@ pub enum IBEvent {
         model_code: String,
         contract: Box<Contract>,
         pos: Decimal,
-        avg_cost: f64,
+        /// A `double` in C++; decoded exactly from the wire string.
+        avg_cost: Decimal,
     },
 
     /// End of multi-position list.
@@ -452,6 +518,8 @@ pub enum IBEvent {
         multiplier: String,
         expirations: Vec<String>,
         strikes: Vec<f64>,
+        /// `strikes` as sent by the server (e.g. `"2.50"`), in the same order.
+        strike_strings: Vec<String>,
     },
 
     /// End of security definition optional parameters.
@@ -619,22 +687,28 @@ pub enum IBEvent {
 
     /// P&L update.
     /// C++: `pnl(int, double, double, double)`
+    ///
+    /// `None` where C++ reports `UNSET_DOUBLE`: a value TWS has not computed,
+    /// or one the server version does not send (unrealized P&L before
+    /// `UNREALIZED_PNL`, realized P&L before `REALIZED_PNL`).
     Pnl {
         req_id: i32,
-        daily_pnl: f64,
-        unrealized_pnl: f64,
-        realized_pnl: f64,
+        daily_pnl: Option<f64>,
+        unrealized_pnl: Option<f64>,
+        realized_pnl: Option<f64>,
     },
 
     /// Single-position P&L update.
     /// C++: `pnlSingle(int, Decimal, double, double, double, double)`
+    ///
+    /// Unset values are `None`, as for [`Pnl`](Self::Pnl).
     PnlSingle {
         req_id: i32,
         pos: Decimal,
-        daily_pnl: f64,
-        unrealized_pnl: f64,
-        realized_pnl: f64,
-        value: f64,
+        daily_pnl: Option<f64>,
+        unrealized_pnl: Option<f64>,
+        realized_pnl: Option<f64>,
+        value: Option<f64>,
     },
 
     // ========================================================================
@@ -858,3 +932,680 @@ pub enum IBEvent {
         data: Vec<u8>,
     },
 }
+
+impl IBEvent {
+    /// Event for an `ERR_MSG`: `ConnectivityStatus` for the connectivity
+    /// codes (see [`IbErrorCode::is_connectivity`]), `Error` otherwise.
+    pub(crate) fn from_error(
+        req_id: i32,
+        error_time: i64,
+        code: i32,
+        message: String,
+        advanced_order_reject_json: String,
+    ) -> Self {
+        let (connected, data_lost) = match code {
+            1100 | 2110 => (false, false),
+            1101 => (true, true),
+            1102 => (true, false),
+            _ => {
+                return IBEvent::Error {
+                    req_id,
+                    error_time,
+                    code,
+                    message,
+                    advanced_order_reject_json,
+                }
+            }
+        };
+        IBEvent::ConnectivityStatus { connected, data_lost, code, message }
+    }
+
+    /// The account value carried by an `UpdateAccountValue` event.
+    pub fn account_value(&self) -> Option<AccountValue> {
+        match self {
+            IBEvent::UpdateAccountValue { key, value, currency, account_name } => {
+                Some(AccountValue {
+                    key: key.clone(),
+                    value: value.clone(),
+                    currency: currency.clone(),
+                    account_name: account_name.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The snapshot permissions carried by a `TickReqParams` event.
+    pub fn snapshot_permissions(&self) -> Option<SnapshotPermissions> {
+        match self {
+            IBEvent::TickReqParams { snapshot_permissions, .. } => {
+                Some(SnapshotPermissions(*snapshot_permissions))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is a `TickReqParams` event allowing snapshot market
+    /// data for the instrument.
+    pub fn can_snapshot(&self) -> bool {
+        self.snapshot_permissions().is_some_and(SnapshotPermissions::can_snapshot)
+    }
+
+    /// Whether this is a `TickReqParams` event allowing regulatory
+    /// snapshots for the instrument.
+    pub fn can_regulatory_snapshot(&self) -> bool {
+        self.snapshot_permissions().is_some_and(SnapshotPermissions::can_regulatory_snapshot)
+    }
+
+    /// The request failure this event reports, if it is an `Error`.
+    ///
+    /// Maps the code via [`IBApiError::server`]. Returns `None` for every
+    /// other event and for warnings (see [`IbErrorCode::is_warning`]),
+    /// which do not end the request they mention.
+    pub fn api_error(&self) -> Option<IBApiError> {
+        match self {
+            IBEvent::Error { req_id, code, message, advanced_order_reject_json, .. }
+                if !IbErrorCode(*code).is_warning() =>
+            {
+                Some(IBApiError::server(
+                    *req_id,
+                    *code,
+                    message.clone(),
+                    advanced_order_reject_json.clone(),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Request ID this event responds to, if it carries one.
+    ///
+    /// Used by `PendingRequests` to route responses back to the request
+    /// that produced them. Events keyed by order ID (`OrderStatus`,
+    /// `OpenOrder`, ...) and connection-level events return `None`.
+    pub fn req_id(&self) -> Option<i32> {
+        use IBEvent::*;
+        match self {
+            TickPrice { req_id, .. }
+            | TickSize { req_id, .. }
+            | TickOptionComputation { req_id, .. }
+            | TickGeneric { req_id, .. }
+            | TickString { req_id, .. }
+            | TickEfp { req_id, .. }
+            | TickSnapshotEnd { req_id }
+            | TickReqParams { req_id, .. }
+            | TickNews { req_id, .. }
+            | MarketDataType { req_id, .. }
+            | TickByTickAllLast { req_id, .. }
+            | TickByTickBidAsk { req_id, .. }
+            | TickByTickMidPoint { req_id, .. }
+            | TickByTickUnknown { req_id, .. }
+            | ExecDetails { req_id, .. }
+            | ExecDetailsEnd { req_id }
+            | AccountSummary { req_id, .. }
+            | AccountSummaryEnd { req_id }
+            | PositionMulti { req_id, .. }
+            | PositionMultiEnd { req_id }
+            | AccountUpdateMulti { req_id, .. }
+            | AccountUpdateMultiEnd { req_id }
+            | ContractDetails { req_id, .. }
+            | BondContractDetails { req_id, .. }
+            | ContractDetailsEnd { req_id }
+            | SymbolSamples { req_id, .. }
+            | DeltaNeutralValidation { req_id, .. }
+            | SecurityDefinitionOptionalParameter { req_id, .. }
+            | SecurityDefinitionOptionalParameterEnd { req_id }
+            | UpdateMktDepth { req_id, .. }
+            | UpdateMktDepthL2 { req_id, .. }
+            | HistoricalData { req_id, .. }
+            | HistoricalDataEnd { req_id, .. }
+            | HistoricalDataUpdate { req_id, .. }
+            | HeadTimestamp { req_id, .. }
+            | HistoricalTicks { req_id, .. }
+            | HistoricalTicksBidAsk { req_id, .. }
+            | HistoricalTicksLast { req_id, .. }
+            | HistoricalSchedule { req_id, .. }
+            | RealtimeBar { req_id, .. }
+            | ScannerData { req_id, .. }
+            | ScannerDataEnd { req_id }
+            | FundamentalData { req_id, .. }
+            | Pnl { req_id, .. }
+            | PnlSingle { req_id, .. }
+            | NewsArticle { req_id, .. }
+            | HistoricalNews { req_id, .. }
+            | HistoricalNewsEnd { req_id, .. }
+            | ReplaceFaEnd { req_id, .. }
+            | RerouteMktDataReq { req_id, .. }
+            | RerouteMktDepthReq { req_id, .. }
+            | SmartComponents { req_id, .. }
+            | SoftDollarTiers { req_id, .. }
+            | HistogramData { req_id, .. }
+            | WshMetaData { req_id, .. }
+            | WshEventData { req_id, .. }
+            | UserInfo { req_id, .. }
+            | DisplayGroupList { req_id, .. }
+            | DisplayGroupUpdated { req_id, .. } => Some(*req_id),
+            _ => None,
+        }
+    }
+
+    /// Order ID of an order event: `OpenOrder`, `OrderStatus`,
+    /// `OrderBound` or `ExecDetails`.
+    pub fn order_id(&self) -> Option<i64> {
+        match self {
+            IBEvent::OpenOrder { order_id, .. } | IBEvent::OrderStatus { order_id, .. } => {
+                Some(*order_id)
+            }
+            IBEvent::OrderBound { order_id, .. } => Some(i64::from(*order_id)),
+            IBEvent::ExecDetails { execution, .. } => Some(execution.order_id),
+            _ => None,
+        }
+    }
+
+    /// API client ID that placed the order of an order event: `OpenOrder`,
+    /// `CompletedOrder`, `OrderStatus`, `OrderBound` or `ExecDetails`.
+    pub fn client_id(&self) -> Option<i64> {
+        match self {
+            IBEvent::OpenOrder { order, .. } | IBEvent::CompletedOrder { order, .. } => {
+                Some(order.client_id)
+            }
+            IBEvent::OrderStatus { client_id, .. } | IBEvent::OrderBound { client_id, .. } => {
+                Some(i64::from(*client_id))
+            }
+            IBEvent::ExecDetails { execution, .. } => Some(execution.client_id),
+            _ => None,
+        }
+    }
+
+    /// `order_ref` of an `OpenOrder`, `CompletedOrder` or `ExecDetails`.
+    /// `OrderStatus` does not carry it.
+    pub fn order_ref(&self) -> Option<&str> {
+        match self {
+            IBEvent::OpenOrder { order, .. } | IBEvent::CompletedOrder { order, .. } => {
+                Some(&order.order_ref)
+            }
+            IBEvent::ExecDetails { execution, .. } => Some(&execution.order_ref),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// Display
+// ============================================================================
+
+/// Renders an optional value, or `-` when unset.
+struct Opt<'a, T>(&'a Option<T>);
+
+impl<T: fmt::Display> fmt::Display for Opt<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(v) => v.fmt(f),
+            None => f.write_str("-"),
+        }
+    }
+}
+
+/// Symbol, action, type and status of an order -- enough to identify it in
+/// a log line without dumping the full structs.
+fn write_order(
+    f: &mut fmt::Formatter<'_>,
+    contract: &Contract,
+    order: &Order,
+    order_state: &OrderState,
+) -> fmt::Result {
+    write!(
+        f,
+        "{} {} {} {} {}",
+        contract.symbol,
+        Opt(&order.action),
+        Opt(&order.total_quantity),
+        Opt(&order.order_type),
+        order_state.status
+    )
+}
+
+/// Concise one-line summary for logging, e.g. `TickPrice req=5 BID 150.25 x100`
+/// or `OrderStatus 100 Filled 10/10 @150.5`.
+///
+/// Large payloads (orders, contract details, XML/JSON blobs, bar and tick
+/// batches) are reduced to a symbol, a count or a length. Use `{:?}` for
+/// the full contents.
+impl fmt::Display for IBEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use IBEvent::*;
+        match self {
+            // ----- Connection & Error -----
+            NextValidId { order_id } => write!(f, "NextValidId {order_id}"),
+            ManagedAccounts { accounts } => write!(f, "ManagedAccounts {accounts}"),
+            Error { req_id, code, message, .. } => {
+                write!(f, "Error req={req_id} code={code}: {message}")
+            }
+            ConnectivityStatus { connected, data_lost, code, .. } => {
+                write!(f, "ConnectivityStatus code={code} connected={connected}")?;
+                if *data_lost {
+                    f.write_str(" data_lost")?;
+                }
+                Ok(())
+            }
+            ConnectionClosed => f.write_str("ConnectionClosed"),
+            ConnectionReestablished { server_version } => {
+                write!(f, "ConnectionReestablished sv={server_version}")
+            }
+            ConnectionLost => f.write_str("ConnectionLost"),
+
+            // ----- Market Data (Ticks) -----
+            TickPrice { req_id, tick_type, price, size, .. } => {
+                write!(f, "TickPrice req={req_id} {tick_type} {price} x{size}")
+            }
+            TickSize { req_id, tick_type, size } => {
+                write!(f, "TickSize req={req_id} {tick_type} {size}")
+            }
+            TickOptionComputation { req_id, tick_type, implied_vol, delta, opt_price, .. } => {
+                write!(
+                    f,
+                    "TickOptionComputation req={req_id} {tick_type} iv={} delta={} price={}",
+                    Opt(implied_vol),
+                    Opt(delta),
+                    Opt(opt_price)
+                )
+            }
+            TickGeneric { req_id, tick_type, value } => {
+                write!(f, "TickGeneric req={req_id} {tick_type} {value}")
+            }
+            TickString { req_id, tick_type, value } => {
+                write!(f, "TickString req={req_id} {tick_type} {value}")
+            }
+            TickEfp { req_id, tick_type, formatted_basis_points, .. } => {
+                write!(f, "TickEfp req={req_id} {tick_type} {formatted_basis_points}")
+            }
+            TickSnapshotEnd { req_id } => write!(f, "TickSnapshotEnd req={req_id}"),
+            TickReqParams { req_id, min_tick, bbo_exchange, .. } => {
+                write!(f, "TickReqParams req={req_id} min_tick={min_tick} bbo={bbo_exchange}")
+            }
+            TickNews { req_id, provider_code, headline, .. } => {
+                write!(f, "TickNews req={req_id} {provider_code}: {headline}")
+            }
+            MarketDataType { req_id, market_data_type } => {
+                write!(f, "MarketDataType req={req_id} {market_data_type}")
+            }
+
+            // ----- Tick-by-Tick Data -----
+            TickByTickAllLast { req_id, tick_type, price, size, exchange, .. } => {
+                let kind = if *tick_type == 1 { "Last" } else { "AllLast" };
+                write!(f, "TickByTick{kind} req={req_id} {price} x{size} {exchange}")
+            }
+            TickByTickBidAsk { req_id, bid_price, ask_price, bid_size, ask_size, .. } => {
+                write!(
+                    f,
+                    "TickByTickBidAsk req={req_id} {bid_size}x{bid_price} / {ask_price}x{ask_size}"
+                )
+            }
+            TickByTickMidPoint { req_id, mid_point, .. } => {
+                write!(f, "TickByTickMidPoint req={req_id} {mid_point}")
+            }
+            TickByTickUnknown { req_id, tick_type, time } => {
+                write!(f, "TickByTickUnknown req={req_id} type={tick_type} time={time}")
+            }
+
+            // ----- Orders -----
+            OrderStatus { order_id, status, filled, remaining, avg_fill_price, .. } => {
+                write!(
+                    f,
+                    "OrderStatus {order_id} {status} {filled}/{} @{avg_fill_price}",
+                    filled + remaining
+                )
+            }
+            OpenOrder { order_id, contract, order, order_state } => {
+                write!(f, "OpenOrder {order_id} ")?;
+                write_order(f, contract, order, order_state)
+            }
+            OpenOrderEnd => f.write_str("OpenOrderEnd"),
+            CompletedOrder { contract, order, order_state } => {
+                write!(f, "CompletedOrder {} ", order.perm_id)?;
+                write_order(f, contract, order, order_state)
+            }
+            CompletedOrdersEnd => f.write_str("CompletedOrdersEnd"),
+            OrderBound { perm_id, client_id, order_id } => {
+                write!(f, "OrderBound perm={perm_id} client={client_id} order={order_id}")
+            }
+
+            // ----- Execution -----
+            ExecDetails { req_id, contract, execution } => {
+                write!(
+                    f,
+                    "ExecDetails req={req_id} {} {} {} @{} exec={}",
+                    contract.symbol,
+                    execution.side,
+                    Opt(&execution.shares),
+                    execution.price,
+                    execution.exec_id
+                )
+            }
+            ExecDetailsEnd { req_id } => write!(f, "ExecDetailsEnd req={req_id}"),
+            CommissionReport { report } => {
+                write!(
+                    f,
+                    "CommissionReport exec={} {} {}",
+                    report.exec_id, report.commission_and_fees, report.currency
+                )
+            }
+
+            // ----- Account Data -----
+            UpdateAccountValue { key, value, currency, account_name } => {
+                write!(f, "UpdateAccountValue {account_name} {key}={value} {currency}")
+            }
+            UpdatePortfolio { contract, position, market_price, account_name, .. } => {
+                write!(
+                    f,
+                    "UpdatePortfolio {account_name} {} {position} @{market_price}",
+                    contract.symbol
+                )
+            }
+            UpdateAccountTime { timestamp } => write!(f, "UpdateAccountTime {timestamp}"),
+            AccountDownloadEnd { account } => write!(f, "AccountDownloadEnd {account}"),
+            AccountSummary { req_id, account, tag, value, currency } => {
+                write!(f, "AccountSummary req={req_id} {account} {tag}={value} {currency}")
+            }
+            AccountSummaryEnd { req_id } => write!(f, "AccountSummaryEnd req={req_id}"),
+            Position { account, contract, position, avg_cost } => {
+                write!(f, "Position {account} {} {position} @{avg_cost}", contract.symbol)
+            }
+            PositionEnd => f.write_str("PositionEnd"),
+            PositionMulti { req_id, account, contract, pos, avg_cost, .. } => {
+                write!(
+                    f,
+                    "PositionMulti req={req_id} {account} {} {pos} @{avg_cost}",
+                    contract.symbol
+                )
+            }
+            PositionMultiEnd { req_id } => write!(f, "PositionMultiEnd req={req_id}"),
+            AccountUpdateMulti { req_id, account, key, value, currency, .. } => {
+                write!(f, "AccountUpdateMulti req={req_id} {account} {key}={value} {currency}")
+            }
+            AccountUpdateMultiEnd { req_id } => write!(f, "AccountUpdateMultiEnd req={req_id}"),
+
+            // ----- Contract Information -----
+            ContractDetails { req_id, details } => write!(
+                f,
+                "ContractDetails req={req_id} {} {} con_id={}",
+                details.contract.symbol,
+                Opt(&details.contract.sec_type),
+                details.contract.con_id
+            ),
+            BondContractDetails { req_id, details } => write!(
+                f,
+                "BondContractDetails req={req_id} {} con_id={}",
+                details.contract.symbol, details.contract.con_id
+            ),
+            ContractDetailsEnd { req_id } => write!(f, "ContractDetailsEnd req={req_id}"),
+            SymbolSamples { req_id, descriptions } => {
+                write!(f, "SymbolSamples req={req_id} count={}", descriptions.len())
+            }
+            DeltaNeutralValidation { req_id, delta_neutral_contract } => write!(
+                f,
+                "DeltaNeutralValidation req={req_id} con_id={}",
+                delta_neutral_contract.con_id
+            ),
+            SecurityDefinitionOptionalParameter {
+                req_id, exchange, trading_class, expirations, strikes, ..
+            } => write!(
+                f,
+                "SecurityDefinitionOptionalParameter req={req_id} {exchange} {trading_class} \
+                 expirations={} strikes={}",
+                expirations.len(),
+                strikes.len()
+            ),
+            SecurityDefinitionOptionalParameterEnd { req_id } => {
+                write!(f, "SecurityDefinitionOptionalParameterEnd req={req_id}")
+            }
+
+            // ----- Market Depth -----
+            UpdateMktDepth { req_id, position, operation, side, price, size } => write!(
+                f,
+                "UpdateMktDepth req={req_id} pos={position} op={operation} side={side} \
+                 {price} x{size}"
+            ),
+            UpdateMktDepthL2 { req_id, position, market_maker, operation, side, price, size, .. } => {
+                write!(
+                    f,
+                    "UpdateMktDepthL2 req={req_id} pos={position} mm={market_maker} \
+                     op={operation} side={side} {price} x{size}"
+                )
+            }
+            MktDepthExchanges { descriptions } => {
+                write!(f, "MktDepthExchanges count={}", descriptions.len())
+            }
+
+            // ----- Historical Data -----
+            HistoricalData { req_id, bars } => {
+                write!(f, "HistoricalData req={req_id} bars={}", bars.len())
+            }
+            HistoricalDataEnd { req_id, start, end } => {
+                write!(f, "HistoricalDataEnd req={req_id} {start} - {end}")
+            }
+            HistoricalDataUpdate { req_id, bar } => write!(
+                f,
+                "HistoricalDataUpdate req={req_id} {} O={} H={} L={} C={}",
+                bar.time, bar.open, bar.high, bar.low, bar.close
+            ),
+            HeadTimestamp { req_id, head_timestamp } => {
+                write!(f, "HeadTimestamp req={req_id} {head_timestamp}")
+            }
+            HistoricalTicks { req_id, ticks, done } => {
+                write!(f, "HistoricalTicks req={req_id} ticks={} done={done}", ticks.len())
+            }
+            HistoricalTicksBidAsk { req_id, ticks, done } => {
+                write!(f, "HistoricalTicksBidAsk req={req_id} ticks={} done={done}", ticks.len())
+            }
+            HistoricalTicksLast { req_id, ticks, done } => {
+                write!(f, "HistoricalTicksLast req={req_id} ticks={} done={done}", ticks.len())
+            }
+            HistoricalSchedule { req_id, time_zone, sessions, .. } => write!(
+                f,
+                "HistoricalSchedule req={req_id} {time_zone} sessions={}",
+                sessions.len()
+            ),
+
+            // ----- Real-time Bars -----
+            RealtimeBar { req_id, time, open, high, low, close, volume, .. } => write!(
+                f,
+                "RealtimeBar req={req_id} {time} O={open} H={high} L={low} C={close} V={volume}"
+            ),
+
+            // ----- Scanner -----
+            ScannerData { req_id, items } => {
+                write!(f, "ScannerData req={req_id} rows={}", items.len())
+            }
+            ScannerDataEnd { req_id } => write!(f, "ScannerDataEnd req={req_id}"),
+            ScannerParameters { xml } => write!(f, "ScannerParameters {} bytes", xml.len()),
+
+            // ----- Fundamentals -----
+            FundamentalData { req_id, data } => {
+                write!(f, "FundamentalData req={req_id} {} bytes", data.len())
+            }
+
+            // ----- P&L -----
+            Pnl { req_id, daily_pnl, unrealized_pnl, realized_pnl } => write!(
+                f,
+                "Pnl req={req_id} daily={} unrealized={} realized={}",
+                Opt(daily_pnl),
+                Opt(unrealized_pnl),
+                Opt(realized_pnl)
+            ),
+            PnlSingle { req_id, pos, daily_pnl, unrealized_pnl, realized_pnl, value } => write!(
+                f,
+                "PnlSingle req={req_id} pos={pos} daily={} unrealized={} realized={} value={}",
+                Opt(daily_pnl),
+                Opt(unrealized_pnl),
+                Opt(realized_pnl),
+                Opt(value)
+            ),
+
+            // ----- News -----
+            UpdateNewsBulletin { msg_id, origin_exch, message, .. } => {
+                write!(f, "UpdateNewsBulletin {msg_id} {origin_exch}: {message}")
+            }
+            NewsArticle { req_id, article_type, article_text } => write!(
+                f,
+                "NewsArticle req={req_id} type={article_type} {} bytes",
+                article_text.len()
+            ),
+            HistoricalNews { req_id, time, provider_code, headline, .. } => {
+                write!(f, "HistoricalNews req={req_id} {time} {provider_code}: {headline}")
+            }
+            HistoricalNewsEnd { req_id, has_more } => {
+                write!(f, "HistoricalNewsEnd req={req_id} has_more={has_more}")
+            }
+            NewsProviders { providers } => write!(f, "NewsProviders count={}", providers.len()),
+
+            // ----- Financial Advisor -----
+            ReceiveFa { fa_data_type, xml } => {
+                write!(f, "ReceiveFa type={fa_data_type} {} bytes", xml.len())
+            }
+            ReplaceFaEnd { req_id, text } => write!(f, "ReplaceFaEnd req={req_id} {text}"),
+
+            // ----- Market Rules & Infrastructure -----
+            MarketRule { market_rule_id, price_increments } => write!(
+                f,
+                "MarketRule {market_rule_id} increments={}",
+                price_increments.len()
+            ),
+            RerouteMktDataReq { req_id, con_id, exchange } => {
+                write!(f, "RerouteMktDataReq req={req_id} con_id={con_id} {exchange}")
+            }
+            RerouteMktDepthReq { req_id, con_id, exchange } => {
+                write!(f, "RerouteMktDepthReq req={req_id} con_id={con_id} {exchange}")
+            }
+            SmartComponents { req_id, components } => {
+                write!(f, "SmartComponents req={req_id} count={}", components.len())
+            }
+            FamilyCodes { codes } => write!(f, "FamilyCodes count={}", codes.len()),
+            SoftDollarTiers { req_id, tiers } => {
+                write!(f, "SoftDollarTiers req={req_id} count={}", tiers.len())
+            }
+
+            // ----- Histogram -----
+            HistogramData { req_id, data } => {
+                write!(f, "HistogramData req={req_id} buckets={}", data.len())
+            }
+
+            // ----- Time -----
+            CurrentTime { time } => write!(f, "CurrentTime {time}"),
+            CurrentTimeInMillis { time_in_millis } => {
+                write!(f, "CurrentTimeInMillis {time_in_millis}")
+            }
+
+            // ----- WSH -----
+            WshMetaData { req_id, data_json } => {
+                write!(f, "WshMetaData req={req_id} {} bytes", data_json.len())
+            }
+            WshEventData { req_id, data_json } => {
+                write!(f, "WshEventData req={req_id} {} bytes", data_json.len())
+            }
+
+            // ----- User Info & Display Groups -----
+            UserInfo { req_id, white_branding_id } => {
+                write!(f, "UserInfo req={req_id} {white_branding_id}")
+            }
+            DisplayGroupList { req_id, groups } => {
+                write!(f, "DisplayGroupList req={req_id} {groups}")
+            }
+            DisplayGroupUpdated { req_id, contract_info } => {
+                write!(f, "DisplayGroupUpdated req={req_id} {contract_info}")
+            }
+
+            // ----- Verification -----
+            VerifyMessageApi { .. } => f.write_str("VerifyMessageApi"),
+            VerifyCompleted { is_successful, error_text } => {
+                write!(f, "VerifyCompleted ok={is_successful} {error_text}")
+            }
+            VerifyAndAuthMessageApi { .. } => f.write_str("VerifyAndAuthMessageApi"),
+            VerifyAndAuthCompleted { is_successful, error_text } => {
+                write!(f, "VerifyAndAuthCompleted ok={is_successful} {error_text}")
+            }
+
+            // ----- Unknown -----
+            Unknown { msg_id, data } => write!(f, "Unknown msg_id={msg_id} {} bytes", data.len()),
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::enums::{Action, OrderType};
+
+    #[test]
+    fn tick_req_params_snapshot_permission_bits() {
+        let params = |snapshot_permissions| IBEvent::TickReqParams {
+            req_id: 1,
+            min_tick: 0.01,
+            bbo_exchange: "9c0001".into(),
+            snapshot_permissions,
+        };
+        assert!(!params(0).can_snapshot());
+        assert!(!params(0).can_regulatory_snapshot());
+        assert!(params(1).can_snapshot());
+        assert!(!params(1).can_regulatory_snapshot());
+        assert!(!params(2).can_snapshot());
+        assert!(params(2).can_regulatory_snapshot());
+        assert!(params(3).can_snapshot() && params(3).can_regulatory_snapshot());
+
+        let permissions = params(7).snapshot_permissions().unwrap();
+        assert_eq!(permissions.bits(), 7);
+        assert!(permissions.contains(SnapshotPermissions::SNAPSHOT | 4));
+        assert!(!SnapshotPermissions(1).contains(SnapshotPermissions::SNAPSHOT | 4));
+
+        let other = IBEvent::TickSnapshotEnd { req_id: 1 };
+        assert_eq!(other.snapshot_permissions(), None);
+        assert!(!other.can_snapshot());
+    }
+
+    #[test]
+    fn display_tick_and_order_status() {
+        let tick = IBEvent::TickPrice {
+            req_id: 5,
+            tick_type: TickType::Bid,
+            price: 150.25,
+            size: Decimal::from(100),
+            attrib: TickAttrib::default(),
+        };
+        assert_eq!(tick.to_string(), "TickPrice req=5 BID 150.25 x100");
+
+        let status = IBEvent::OrderStatus {
+            order_id: 100,
+            status: "Filled".into(),
+            filled: Decimal::from(10),
+            remaining: Decimal::ZERO,
+            avg_fill_price: 150.5,
+            perm_id: 0,
+            parent_id: 0,
+            last_fill_price: 150.5,
+            client_id: 0,
+            why_held: String::new(),
+            mkt_cap_price: None,
+        };
+        assert_eq!(status.to_string(), "OrderStatus 100 Filled 10/10 @150.5");
+    }
+
+    #[test]
+    fn display_open_order_is_a_summary() {
+        let event = IBEvent::OpenOrder {
+            order_id: 7,
+            contract: Box::new(Contract::stock("AAPL")),
+            order: Box::new(Order {
+                action: Some(Action::Buy),
+                total_quantity: Some(Decimal::from(10)),
+                order_type: Some(OrderType::Limit),
+                ..Order::default()
+            }),
+            order_state: Box::new(OrderState { status: "Submitted".into(), ..OrderState::default() }),
+        };
+        assert_eq!(event.to_string(), "OpenOrder 7 AAPL BUY 10 LMT Submitted");
+    }
+}
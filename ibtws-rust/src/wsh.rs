@@ -0,0 +1,212 @@
+//! Typed parsing of Wall Street Horizon (WSH) event data.
+//!
+//! `IBEvent::WshEventData` carries the server's JSON payload verbatim in
+//! `data_json`. [`parse_wsh_event`] turns that payload into [`WshEvent`]s;
+//! event types without a dedicated struct are kept as raw JSON in
+//! [`WshEvent::Other`], so nothing in the payload is lost.
+//!
+//! There is no C++ equivalent -- `EWrapper::wshEventData` passes the JSON
+//! string through unchanged. Requires the `serde` feature.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::{IBApiError, Result};
+
+/// WSH `event_type` of an earnings date announcement.
+pub const EARNINGS_DATE: &str = "wshe_ed";
+/// WSH `event_type` of a dividend announcement.
+pub const DIVIDEND: &str = "wshe_div";
+
+// ============================================================================
+// Event structs
+// ============================================================================
+
+/// Earnings date event (`event_type` [`EARNINGS_DATE`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WshEarningsEvent {
+    pub event_key: String,
+    pub con_id: Option<i64>,
+    pub symbol: String,
+    /// YYYYMMDD.
+    pub earnings_date: String,
+    /// e.g. `"BMO"` (before market open) or `"AMC"` (after market close).
+    pub time_of_day: String,
+    pub fiscal_quarter: Option<i32>,
+    pub fiscal_year: Option<i32>,
+}
+
+/// Dividend event (`event_type` [`DIVIDEND`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WshDividendEvent {
+    pub event_key: String,
+    pub con_id: Option<i64>,
+    pub symbol: String,
+    /// YYYYMMDD.
+    pub ex_date: String,
+    /// YYYYMMDD.
+    pub record_date: String,
+    /// YYYYMMDD.
+    pub pay_date: String,
+    pub amount: Option<f64>,
+    pub currency: String,
+    pub frequency: String,
+}
+
+/// One entry of a WSH event data payload.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WshEvent {
+    Earnings(WshEarningsEvent),
+    Dividend(WshDividendEvent),
+    /// Any other event type, with its JSON object as received.
+    Other { event_type: String, raw: Value },
+}
+
+impl WshEvent {
+    /// The WSH `event_type` code of this event.
+    pub fn event_type(&self) -> &str {
+        match self {
+            Self::Earnings(_) => EARNINGS_DATE,
+            Self::Dividend(_) => DIVIDEND,
+            Self::Other { event_type, .. } => event_type,
+        }
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Wire shape: `{"event_type": ..., "data": {...}, ...}`. Fields outside
+/// `data` (event key, con_id, symbol) are merged into the typed struct.
+#[derive(Deserialize)]
+struct RawEvent {
+    #[serde(default)]
+    event_type: String,
+    #[serde(flatten)]
+    rest: serde_json::Map<String, Value>,
+}
+
+/// Parse the `data_json` of a `WshEventData` event.
+///
+/// Accepts either a JSON array of events or a single event object.
+pub fn parse_wsh_event(data_json: &str) -> Result<Vec<WshEvent>> {
+    let value: Value = serde_json::from_str(data_json)
+        .map_err(|e| IBApiError::Decoding(format!("invalid WSH event JSON: {e}")))?;
+    let items = match value {
+        Value::Array(items) => items,
+        object @ Value::Object(_) => vec![object],
+        other => {
+            return Err(IBApiError::Decoding(format!(
+                "WSH event data must be an object or array, got {other}"
+            )))
+        }
+    };
+    items.into_iter().map(parse_one).collect()
+}
+
+fn parse_one(item: Value) -> Result<WshEvent> {
+    let raw: RawEvent = serde_json::from_value(item.clone())
+        .map_err(|e| IBApiError::Decoding(format!("invalid WSH event: {e}")))?;
+    match raw.event_type.as_str() {
+        EARNINGS_DATE => typed(raw.rest).map(WshEvent::Earnings),
+        DIVIDEND => typed(raw.rest).map(WshEvent::Dividend),
+        _ => Ok(WshEvent::Other { event_type: raw.event_type, raw: item }),
+    }
+}
+
+/// Flatten the nested `data` object into the top-level fields and
+/// deserialize the result.
+fn typed<T: serde::de::DeserializeOwned>(mut fields: serde_json::Map<String, Value>) -> Result<T> {
+    if let Some(Value::Object(data)) = fields.remove("data") {
+        fields.extend(data);
+    }
+    serde_json::from_value(Value::Object(fields))
+        .map_err(|e| IBApiError::Decoding(format!("invalid WSH event: {e}")))
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"[
+        {
+            "event_key": "ed-265598-2026Q1",
+            "event_type": "wshe_ed",
+            "con_id": 265598,
+            "symbol": "AAPL",
+            "data": {
+                "earnings_date": "20260129",
+                "time_of_day": "AMC",
+                "fiscal_quarter": 1,
+                "fiscal_year": 2026
+            }
+        },
+        {
+            "event_key": "div-265598-2026-02",
+            "event_type": "wshe_div",
+            "con_id": 265598,
+            "symbol": "AAPL",
+            "data": {
+                "ex_date": "20260209",
+                "record_date": "20260209",
+                "pay_date": "20260212",
+                "amount": 0.26,
+                "currency": "USD",
+                "frequency": "Quarterly"
+            }
+        },
+        {
+            "event_key": "sp-1",
+            "event_type": "wshe_sp",
+            "data": { "ratio": "4:1" }
+        }
+    ]"#;
+
+    #[test]
+    fn parse_sample_payload() {
+        let events = parse_wsh_event(SAMPLE).unwrap();
+        assert_eq!(events.len(), 3);
+
+        match &events[0] {
+            WshEvent::Earnings(e) => {
+                assert_eq!(e.event_key, "ed-265598-2026Q1");
+                assert_eq!(e.con_id, Some(265598));
+                assert_eq!(e.earnings_date, "20260129");
+                assert_eq!(e.time_of_day, "AMC");
+                assert_eq!(e.fiscal_quarter, Some(1));
+                assert_eq!(e.fiscal_year, Some(2026));
+            }
+            other => panic!("expected earnings, got {other:?}"),
+        }
+        match &events[1] {
+            WshEvent::Dividend(d) => {
+                assert_eq!(d.ex_date, "20260209");
+                assert_eq!(d.pay_date, "20260212");
+                assert_eq!(d.amount, Some(0.26));
+                assert_eq!(d.currency, "USD");
+            }
+            other => panic!("expected dividend, got {other:?}"),
+        }
+        assert_eq!(events[2].event_type(), "wshe_sp");
+        match &events[2] {
+            WshEvent::Other { raw, .. } => assert_eq!(raw["data"]["ratio"], "4:1"),
+            other => panic!("expected other, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_single_object_and_errors() {
+        let events = parse_wsh_event(r#"{"event_type": "wshe_ed", "data": {}}"#).unwrap();
+        assert_eq!(events, vec![WshEvent::Earnings(WshEarningsEvent::default())]);
+
+        assert!(matches!(parse_wsh_event("not json"), Err(IBApiError::Decoding(_))));
+        assert!(matches!(parse_wsh_event("42"), Err(IBApiError::Decoding(_))));
+    }
+}
@@ -52,7 +52,7 @@ fn spy_contract() -> Contract {
 #[tokio::test]
 #[ignore]
 async fn test_connect_and_disconnect() {
-    let (mut client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id(), None)
+    let (client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id(), None)
         .await
         .expect("Failed to connect to IB");
 
@@ -112,7 +112,7 @@ async fn test_connect_wrong_port() {
 #[tokio::test]
 #[ignore]
 async fn test_request_market_data() {
-    let (mut client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 1, None)
+    let (client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 1, None)
         .await
         .expect("Failed to connect");
 
@@ -176,7 +176,7 @@ async fn test_request_market_data() {
 #[tokio::test]
 #[ignore]
 async fn test_request_historical_data() {
-    let (mut client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 2, None)
+    let (client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 2, None)
         .await
         .expect("Failed to connect");
 
@@ -254,7 +254,7 @@ async fn test_request_historical_data() {
 #[tokio::test]
 #[ignore]
 async fn test_request_account_summary() {
-    let (mut client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 3, None)
+    let (client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 3, None)
         .await
         .expect("Failed to connect");
 
@@ -313,7 +313,7 @@ async fn test_request_account_summary() {
 #[tokio::test]
 #[ignore]
 async fn test_request_positions() {
-    let (mut client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 4, None)
+    let (client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 4, None)
         .await
         .expect("Failed to connect");
 
@@ -372,28 +372,26 @@ async fn test_request_positions() {
 #[tokio::test]
 #[ignore]
 async fn test_place_and_cancel_limit_order() {
-    let (mut client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 5, None)
+    let (client, mut rx) = IBClient::connect(&ib_host(), ib_port(), ib_client_id() + 5, None)
         .await
         .expect("Failed to connect");
 
     // Wait for NextValidId
-    let mut order_id: i64 = -1;
     let timeout = tokio::time::sleep(Duration::from_secs(5));
     tokio::pin!(timeout);
 
-    loop {
+    let order_id: i64 = loop {
         tokio::select! {
             event = rx.recv() => {
                 if let Some(IBEvent::NextValidId { order_id: id }) = event {
-                    order_id = id;
-                    break;
+                    break id;
                 }
             }
             _ = &mut timeout => {
                 panic!("Timed out waiting for NextValidId");
             }
         }
-    }
+    };
 
     assert!(order_id > 0, "Should have valid order ID");
 
@@ -0,0 +1,46 @@
+//! Replay tests for recorded TWS session captures.
+//!
+//! Each fixture in `tests/fixtures/` is the raw byte stream a TWS/Gateway
+//! sent after the handshake, as captured from the socket. The file name
+//! records the server version to decode it with.
+
+use ibtws_rust::{replay_stream, IBEvent};
+
+/// `reqContractDetails(7, AAPL)` right after connecting: managed accounts,
+/// next valid ID, a farm status notice, then the contract and its end marker.
+const CONTRACT_DETAILS: &[u8] = include_bytes!("fixtures/contract_details_sv176.bin");
+
+#[test]
+fn replay_contract_details_capture() {
+    let events = replay_stream(CONTRACT_DETAILS, 176);
+    assert_eq!(events.len(), 5, "{events:#?}");
+
+    assert!(matches!(&events[0], IBEvent::ManagedAccounts { accounts } if accounts == "DU123456"));
+    assert!(matches!(events[1], IBEvent::NextValidId { order_id: 1 }));
+    assert!(matches!(events[2], IBEvent::Error { req_id: -1, code: 2104, .. }));
+    match &events[3] {
+        IBEvent::ContractDetails { req_id, details } => {
+            assert_eq!(*req_id, 7);
+            assert_eq!(details.contract.symbol, "AAPL");
+            assert_eq!(details.contract.con_id, 265598);
+            assert_eq!(details.long_name, "APPLE INC");
+            assert_eq!(details.contract.primary_exchange, "NASDAQ");
+        }
+        other => panic!("expected ContractDetails, got {other:?}"),
+    }
+    assert!(matches!(events[4], IBEvent::ContractDetailsEnd { req_id: 7 }));
+}
+
+#[test]
+fn replay_truncated_capture_ends_with_unknown() {
+    let cut = CONTRACT_DETAILS.len() - 3;
+    let events = replay_stream(&CONTRACT_DETAILS[..cut], 176);
+    assert_eq!(events.len(), 5);
+    assert!(matches!(events[3], IBEvent::ContractDetails { .. }));
+    match &events[4] {
+        IBEvent::Unknown { msg_id: -1, data } => assert_eq!(data.len(), 4 + 7 - 3),
+        other => panic!("expected Unknown, got {other:?}"),
+    }
+
+    assert!(replay_stream(&[], 176).is_empty());
+}
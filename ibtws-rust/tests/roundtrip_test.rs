@@ -0,0 +1,144 @@
+//! Property tests for the field encoding shared by `MessageEncoder` and
+//! `MessageDecoder`.
+//!
+//! Every value written with an `encode_field_*` method must decode to the
+//! same value with the matching `decode_*` method. Strings are the one
+//! restriction: a NUL byte would end the field early, so generated strings
+//! never contain one.
+
+use ibtws_rust::{MessageDecoder, MessageEncoder};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+const SV: i32 = 176;
+
+/// Encode with `write`, then decode the message body with `read`.
+fn roundtrip<T>(
+    write: impl FnOnce(&mut MessageEncoder),
+    read: impl FnOnce(&mut MessageDecoder) -> ibtws_rust::errors::Result<T>,
+) -> T {
+    let mut enc = MessageEncoder::new(SV);
+    write(&mut enc);
+    let framed = enc.finalize().unwrap();
+    let body = &framed[4..];
+    let mut dec = MessageDecoder::new(body, SV);
+    let value = read(&mut dec).unwrap();
+    assert!(!dec.has_remaining(), "field not fully consumed");
+    value
+}
+
+/// Any `Decimal`: a 96-bit mantissa at any scale, including the extremes.
+fn decimal() -> impl Strategy<Value = Decimal> {
+    prop_oneof![
+        (-(1i128 << 96) + 1..(1i128 << 96), 0u32..=28)
+            .prop_map(|(mantissa, scale)| Decimal::from_i128_with_scale(mantissa, scale)),
+        Just(Decimal::MAX),
+        Just(Decimal::MIN),
+        Just(Decimal::ZERO),
+    ]
+}
+
+/// `f64`s are equal if they have the same bits, or are both NaN.
+fn same_f64(a: f64, b: f64) -> bool {
+    a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+}
+
+proptest! {
+    #[test]
+    fn i32_roundtrips(v in any::<i32>()) {
+        prop_assert_eq!(roundtrip(|e| { e.encode_field_i32(v); }, |d| d.decode_i32()), v);
+    }
+
+    #[test]
+    fn i64_roundtrips(v in any::<i64>()) {
+        prop_assert_eq!(roundtrip(|e| { e.encode_field_i64(v); }, |d| d.decode_i64()), v);
+    }
+
+    #[test]
+    fn bool_roundtrips(v in any::<bool>()) {
+        prop_assert_eq!(roundtrip(|e| { e.encode_field_bool(v); }, |d| d.decode_bool()), v);
+    }
+
+    /// Includes NaN, both infinities (`+inf` travels as `"Infinity"`),
+    /// negative zero and subnormals.
+    #[test]
+    fn f64_roundtrips(v in any::<f64>()) {
+        let decoded = roundtrip(|e| { e.encode_field_f64(v); }, |d| d.decode_f64());
+        prop_assert!(same_f64(decoded, v), "{v:?} decoded as {decoded:?}");
+    }
+
+    /// Value and scale survive, e.g. `1.50` stays `1.50`.
+    #[test]
+    fn decimal_roundtrips(v in decimal()) {
+        let decoded = roundtrip(|e| { e.encode_field_decimal(&v); }, |d| d.decode_decimal());
+        prop_assert_eq!(decoded, v);
+        prop_assert_eq!(decoded.scale(), v.scale());
+    }
+
+    /// Any text without NUL, including non-ASCII (the encoder only warns).
+    #[test]
+    fn string_roundtrips(v in "[^\u{0}]*") {
+        let decoded = roundtrip(|e| { e.encode_field_str(&v); }, |d| d.decode_string());
+        prop_assert_eq!(decoded, v);
+    }
+
+    #[test]
+    fn max_fields_roundtrip(
+        i in proptest::option::of(any::<i32>()),
+        l in proptest::option::of(any::<i64>()),
+        f in proptest::option::of(any::<f64>()),
+        d in proptest::option::of(decimal()),
+    ) {
+        let decoded = roundtrip(
+            |e| {
+                e.encode_field_max_i32(i)
+                    .encode_field_max_i64(l)
+                    .encode_field_max_f64(f)
+                    .encode_field_max_decimal(d.as_ref());
+            },
+            |dec| {
+                Ok((
+                    dec.decode_i32_max()?,
+                    dec.decode_i64_max()?,
+                    dec.decode_f64_max()?,
+                    dec.decode_decimal_max()?,
+                ))
+            },
+        );
+        prop_assert_eq!(decoded.0, i);
+        prop_assert_eq!(decoded.1, l);
+        match (decoded.2, f) {
+            (Some(a), Some(b)) => prop_assert!(same_f64(a, b), "{b:?} decoded as {a:?}"),
+            (a, b) => prop_assert_eq!(a, b),
+        }
+        prop_assert_eq!(decoded.3, d);
+    }
+
+    /// Fields stay aligned: a sequence of mixed fields decodes in order.
+    #[test]
+    fn mixed_fields_roundtrip(s in "[^\u{0}]*", i in any::<i32>(), d in decimal()) {
+        let decoded = roundtrip(
+            |e| {
+                e.encode_field_str(&s).encode_field_i32(i).encode_field_decimal(&d);
+            },
+            |dec| Ok((dec.decode_string()?, dec.decode_i32()?, dec.decode_decimal()?)),
+        );
+        prop_assert_eq!(decoded, (s, i, d));
+    }
+}
+
+/// Empty fields decode as zero (C++ `atoi("")`/`atof("")`), and as `None`
+/// for the "max" decoders.
+#[test]
+fn empty_fields_decode_as_zero_or_none() {
+    let body = b"\0\0\0\0\0\0\0\0";
+    let mut dec = MessageDecoder::new(body, SV);
+    assert_eq!(dec.decode_i32().unwrap(), 0);
+    assert_eq!(dec.decode_i64().unwrap(), 0);
+    assert_eq!(dec.decode_f64().unwrap(), 0.0);
+    assert_eq!(dec.decode_decimal().unwrap(), Decimal::ZERO);
+    assert_eq!(dec.decode_i32_max().unwrap(), None);
+    assert_eq!(dec.decode_i64_max().unwrap(), None);
+    assert_eq!(dec.decode_f64_max().unwrap(), None);
+    assert_eq!(dec.decode_decimal_max().unwrap(), None);
+}
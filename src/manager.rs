@@ -304,6 +304,7 @@ impl VaultWolfManager {
         }
     }
 
+    #[allow(dead_code)]
     pub async fn get_historical_data(&self, req_id: i64) -> Option<models::HistoricalData> {
         self.historical_data_cache
             .lock()
@@ -603,6 +604,7 @@ impl VaultWolfManager {
         self.state.managed_accounts.lock().await.clone()
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn store_order(
         &self,
         order_id: i64,
@@ -885,7 +887,7 @@ async fn process_event(
                     currency: contract.currency.clone(),
                     exchange: contract.exchange.clone(),
                     position: position.to_f64().unwrap_or(0.0),
-                    avg_cost,
+                    avg_cost: avg_cost.to_f64().unwrap_or(0.0),
                     ..Default::default()
                 });
             }
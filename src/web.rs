@@ -123,6 +123,8 @@ pub struct CancelOrderBody {
     pub order_id: Option<i64>,
 }
 
+// Only `order_id` is consumed until order modification is implemented.
+#[allow(dead_code)]
 #[derive(Debug, Deserialize)]
 pub struct ModifyOrderBody {
     pub order_id: Option<i64>,